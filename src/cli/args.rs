@@ -1,6 +1,12 @@
+use anyhow::Result;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::model::{ConfigFormat, Settings, TmuxpLocation, WindowLayout};
+use crate::ops::presets::Preset;
+use crate::ops::sync::SyncTarget;
+use crate::ops::validate;
+
 #[derive(Parser, Debug)]
 #[command(name = "tmuxify")]
 #[command(about = "Interactive tmuxp configuration generator", long_about = None)]
@@ -21,9 +27,37 @@ pub struct Args {
     #[arg(long, global = true)]
     pub project: Option<PathBuf>,
 
-    /// Where to store the tmuxp file (home or project)
-    #[arg(long, global = true, value_name = "LOCATION")]
-    pub tmuxp_location: Option<String>,
+    /// Where to store the tmuxp file
+    #[arg(
+        long,
+        global = true,
+        value_name = "LOCATION",
+        value_enum,
+        ignore_case = true
+    )]
+    pub tmuxp_location: Option<TmuxpLocation>,
+
+    /// Format of written tmuxp configs (toml only with --dry-run)
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        value_enum,
+        ignore_case = true
+    )]
+    pub format: Option<ConfigFormat>,
+
+    /// Open the generated YAML in $EDITOR and validate the edit before writing
+    #[arg(long, global = true)]
+    pub edit_preview: bool,
+
+    /// Layout the wizard preselects for new windows
+    #[arg(long, value_enum, ignore_case = true)]
+    pub layout: Option<WindowLayout>,
+
+    /// Load and attach to the session once the wizard has written its files
+    #[arg(long)]
+    pub attach: bool,
 
     /// Session name (defaults to directory name)
     #[arg(long, global = true)]
@@ -32,10 +66,485 @@ pub struct Args {
     /// Override start_directory in tmuxp config
     #[arg(long, global = true)]
     pub start_dir: Option<PathBuf>,
+
+    /// Set a session environment variable (repeatable; before the subcommand,
+    /// since `add-window --env` sets the new window's)
+    #[arg(
+        long = "env",
+        value_name = "KEY=VALUE",
+        value_parser = crate::ops::interactive::parse_env_assignment
+    )]
+    pub env: Vec<(String, String)>,
+
+    /// Start the wizard from an existing config (file, project directory, or session name)
+    #[arg(long, global = true, value_name = "PATH|NAME")]
+    pub from: Option<String>,
+
+    /// Start from a built-in preset instead of asking
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        ignore_case = true,
+        conflicts_with = "from"
+    )]
+    pub preset: Option<Preset>,
+
+    /// Disable every feature that uses the network (self-update, share, import --url)
+    #[arg(long, global = true)]
+    pub offline: bool,
+}
+
+impl Args {
+    /// Resolve the project directory (`--project` or the current directory)
+    pub fn project_dir(&self) -> Result<PathBuf> {
+        match &self.project {
+            Some(dir) => Ok(validate::resolve_user_path(dir)),
+            None => Ok(std::env::current_dir()?),
+        }
+    }
+
+    /// Whether network access is disabled by `--offline`, the `offline`
+    /// setting, or `TMUXIFY_OFFLINE=1`
+    pub fn is_offline(&self) -> bool {
+        self.offline
+            || std::env::var("TMUXIFY_OFFLINE").is_ok_and(|v| !v.is_empty() && v != "0")
+            || Settings::load().is_ok_and(|s| s.offline)
+    }
+
+    /// Fail before `feature` touches the network in offline mode
+    pub fn require_network(&self, feature: &str) -> Result<()> {
+        if self.is_offline() {
+            anyhow::bail!(
+                "{} needs the network, which offline mode disables \
+                 (--offline, TMUXIFY_OFFLINE, or the offline setting)",
+                feature
+            );
+        }
+        Ok(())
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Run diagnostics to check dependencies and shell hooks
-    Doctor,
+    Doctor {
+        /// Also write a shareable Markdown report to this file
+        #[arg(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+
+        /// Offer to install missing dependencies and add the direnv hook
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Rename the project's session in its config, file name, and .envrc
+    Rename {
+        /// New session name
+        new_name: String,
+    },
+    /// Copy a config into another project, renamed after it and pointed at
+    /// its directory
+    Clone {
+        /// Config to copy: a project directory, a config file, or a session name
+        #[arg(value_name = "PATH|NAME")]
+        source: String,
+
+        /// Project directory to write it for (default: --project or the
+        /// current directory)
+        #[arg(value_name = "DIR")]
+        target: Option<PathBuf>,
+    },
+    /// Append a window to the project's existing config
+    AddWindow {
+        /// Window name
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Window layout (tiled, even-horizontal, even-vertical, main-horizontal,
+        /// main-vertical) or a split description such as h70[v50,v50]
+        #[arg(long)]
+        layout: Option<String>,
+
+        /// Command for a pane (repeat for multiple panes)
+        #[arg(long = "pane", value_name = "CMD")]
+        panes: Vec<String>,
+
+        /// Explicit tmux window index
+        #[arg(long)]
+        index: Option<u32>,
+
+        /// Environment variable for every pane in the window (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+    },
+    /// Explain a concept (layouts, location, direnv, enter)
+    Explain {
+        /// Topic to explain; lists topics when omitted
+        topic: Option<String>,
+    },
+    /// Preview the built-in layouts for a number of panes
+    Layouts {
+        /// Number of panes to lay out
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..=10))]
+        panes: u8,
+
+        /// Also open a throwaway tmux session showing each layout live
+        #[arg(long)]
+        demo: bool,
+    },
+    /// Update tmuxify to the latest GitHub release
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+    /// Show version and build information
+    Version {
+        /// Print as JSON (for bug reports and tooling)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a shell completion script with runtime session-name completion
+    Completions {
+        /// Shell to generate the script for
+        #[arg(value_parser = ["bash", "zsh", "fish"])]
+        shell: String,
+    },
+    /// Print completion candidates (used by the completion scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// What to complete: sessions, projects, templates, windows, or backups
+        kind: String,
+    },
+    /// List every tmuxify-managed file and flag orphans
+    Audit,
+    /// Delete ~/.tmuxp configs whose project is gone or no longer loads them
+    Prune,
+    /// Show recorded config writes (filter with --project)
+    History {
+        /// Number of most recent entries to show
+        #[arg(long, default_value_t = crate::ops::history::DEFAULT_LIMIT)]
+        limit: usize,
+    },
+    /// Pretty-print a config (defaults to the current project's)
+    Show {
+        /// Config file, project directory, or session name
+        #[arg(value_name = "PATH|NAME")]
+        target: Option<String>,
+
+        /// Print raw YAML instead of a tree
+        #[arg(long, conflicts_with_all = ["json", "toml", "table"])]
+        yaml: bool,
+
+        /// Print JSON instead of a tree
+        #[arg(long, conflicts_with_all = ["table", "toml"])]
+        json: bool,
+
+        /// Print TOML instead of a tree
+        #[arg(long, conflicts_with = "table")]
+        toml: bool,
+
+        /// Print one row per window instead of a tree
+        #[arg(long)]
+        table: bool,
+    },
+    /// Start or attach to a project's session (path, project name, or zoxide query)
+    #[command(visible_alias = "load")]
+    Open {
+        /// Project directory, registered project name, or zoxide keywords
+        #[arg(value_name = "QUERY")]
+        query: Option<String>,
+    },
+    /// Install a shell integration hook (direnv: the `layout tmux` library)
+    Hook {
+        /// What to install the hook for
+        #[arg(value_parser = crate::ops::hook::TARGETS.to_vec())]
+        target: String,
+    },
+    /// Print the shell lines that activate tmuxify (e.g. `tmuxify hooks zsh >> ~/.zshrc`)
+    Hooks {
+        /// Shell to print the snippet for (defaults to the current shell)
+        #[arg(value_parser = ["bash", "zsh", "fish", "nu", "nushell"])]
+        shell: Option<String>,
+
+        /// Print tmuxify's own auto-attach function instead of the direnv hook
+        #[arg(long)]
+        native: bool,
+    },
+    /// Merge .tmuxp.local.yaml over a config and print the merged file's path
+    Overlay {
+        /// Config file, project directory, or session name
+        #[arg(value_name = "PATH|NAME")]
+        config: String,
+    },
+    /// Check tmux-resurrect/continuum and keep tmuxify sessions out of their saves
+    Resurrect {
+        /// Add the exclusion hook to your tmux config
+        #[arg(long)]
+        write: bool,
+    },
+    /// Strip tmuxify sessions from a resurrect save file (resurrect's post-save hook)
+    #[command(name = "__resurrect-filter", hide = true)]
+    ResurrectFilter {
+        /// Save file passed by tmux-resurrect
+        file: PathBuf,
+    },
+    /// Export the project's config for another tool
+    Export {
+        /// Target format: vscode-tasks (.vscode/tasks.json), systemd (a user service),
+        /// or aliases (shell functions for every registered project)
+        #[arg(long, value_parser = crate::ops::export::FORMATS.to_vec())]
+        to: String,
+    },
+    /// Convert another tool's project file into a tmuxp config
+    Convert {
+        /// Format of the project file
+        #[arg(long, value_parser = crate::ops::convert::FORMATS.to_vec())]
+        from: String,
+
+        /// Project file, or a project name in the tool's config directory
+        #[arg(value_name = "PATH|NAME")]
+        path: String,
+    },
+    /// Write the project's config and .envrc from flags or a spec file, without prompting
+    Generate {
+        /// Window as NAME[:LAYOUT][=CMD] (repeat for multiple windows)
+        #[arg(long = "window", value_name = "NAME[:LAYOUT][=CMD]")]
+        windows: Vec<String>,
+
+        /// Another pane for a window, as WINDOW=CMD (repeatable)
+        #[arg(long = "pane", value_name = "WINDOW=CMD")]
+        panes: Vec<String>,
+
+        /// YAML spec with a tmuxp `windows` list (`-` reads stdin)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["windows", "panes"])]
+        spec: Option<PathBuf>,
+    },
+    /// Build or edit the project's config in a full-screen editor
+    Tui,
+    /// Start the project's session with tmux directly, without tmuxp
+    Up {
+        /// Config to load (defaults to the project's)
+        config: Option<PathBuf>,
+
+        /// Start the session without attaching to it
+        #[arg(short, long)]
+        detach: bool,
+    },
+    /// Snapshot running tmux sessions into tmuxp configs
+    Freeze {
+        /// Session to freeze (defaults to the current one)
+        #[arg(conflicts_with = "all")]
+        session: Option<String>,
+
+        /// Freeze every session on the server into ~/.tmuxp/<session>.yaml
+        #[arg(long)]
+        all: bool,
+    },
+    /// Upload the config (secrets redacted) as a gist or to a paste service
+    Share {
+        /// Config file, project directory, or session name
+        #[arg(value_name = "PATH|NAME")]
+        target: Option<String>,
+
+        /// Upload without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Adopt an existing tmuxp config, or one shared with `tmuxify share`,
+    /// for the current project
+    Import {
+        /// Hand-written tmuxp config to import
+        #[arg(required_unless_present = "url", conflicts_with = "url")]
+        path: Option<PathBuf>,
+
+        /// Gist or paste URL
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Write without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Interactively edit the project's config: add, remove, rename, or
+    /// change windows
+    Edit {
+        /// Edit only this window, by name or 1-based index
+        #[arg(long, value_name = "NAME|INDEX")]
+        window: Option<String>,
+    },
+    /// Check that a config is valid (defaults to the current project's)
+    Validate {
+        /// Config file, project directory, or session name
+        #[arg(value_name = "PATH|NAME")]
+        target: Option<String>,
+
+        /// Also load it with tmuxp on a throwaway tmux server, then tear that
+        /// down. The config's commands run during the check.
+        #[arg(long)]
+        load_check: bool,
+
+        /// Run the load check without asking for confirmation
+        #[arg(long, short, requires = "load_check")]
+        yes: bool,
+    },
+    /// Regenerate the project's files, run direnv allow, and load the session
+    Apply {
+        /// Don't regenerate the tmuxp config and .envrc
+        #[arg(long)]
+        no_write: bool,
+
+        /// Don't run direnv allow
+        #[arg(long)]
+        no_allow: bool,
+
+        /// Don't load the session
+        #[arg(long)]
+        no_load: bool,
+
+        /// Load the session in the background instead of attaching
+        #[arg(long, conflicts_with = "no_load")]
+        detach: bool,
+    },
+    /// Show how regenerating the project's files would change them, without writing
+    Diff,
+    /// Remove a window (or one of its panes) from the project's config
+    RmWindow {
+        /// Window name or 1-based index
+        #[arg(value_name = "NAME|INDEX")]
+        target: String,
+
+        /// Remove only this pane (1-based) instead of the whole window
+        #[arg(long)]
+        pane: Option<usize>,
+    },
+    /// Remove the project's generated config copies and .envrc block (backed up first)
+    Delete {
+        /// Delete without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// List every tmuxp config tmuxify knows about and whether it's running
+    List {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the project's .tmuxp.yaml with its ~/.tmuxp copy and copy or
+    /// link one over the other
+    Sync {
+        /// Copy to replace (default: ask)
+        #[arg(long, value_enum, ignore_case = true, value_name = "COPY")]
+        to: Option<SyncTarget>,
+
+        /// Replace it with a symlink to the other copy instead
+        #[arg(long, requires = "to")]
+        link: bool,
+    },
+    /// List, prune, or restore the `.backup.` copies tmuxify leaves behind
+    Backups {
+        #[command(subcommand)]
+        action: BackupsAction,
+    },
+    /// Save, list, apply, or delete reusable session templates
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+}
+
+/// `tmuxify template` actions
+#[derive(Subcommand, Debug)]
+pub enum TemplateAction {
+    /// Save the project's config as a template
+    Save { name: String },
+    /// List saved templates
+    List,
+    /// Write a template for the current project
+    Apply { name: String },
+    /// Delete a template
+    Delete { name: String },
+}
+
+/// `tmuxify backups` actions
+#[derive(Subcommand, Debug)]
+pub enum BackupsAction {
+    /// List backups in the project and ~/.tmuxp, oldest first
+    List,
+    /// Delete old backups (default: all but the newest 3 of each file)
+    Prune {
+        /// Delete backups older than this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u64>,
+
+        /// Keep only the newest N backups of each file
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+
+        /// Delete without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Put a backup back in place of the file it was taken from
+    Restore {
+        /// Backup file name or path (asks when omitted)
+        backup: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_flag_blocks_network_features() {
+        let args = Args::parse_from(["tmuxify", "self-update", "--offline"]);
+        let err = args.require_network("Checking for updates").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Checking for updates needs the network")
+        );
+    }
+
+    #[test]
+    fn env_is_per_window_for_add_window_and_per_session_before_it() {
+        let args = Args::try_parse_from([
+            "tmuxify",
+            "add-window",
+            "--name",
+            "x",
+            "--pane",
+            "ls",
+            "--env",
+            "FOO=bar",
+        ])
+        .unwrap();
+        assert!(args.env.is_empty());
+        match args.command {
+            Some(Commands::AddWindow { env, .. }) => assert_eq!(env, ["FOO=bar"]),
+            other => panic!("expected add-window, got {:?}", other),
+        }
+
+        let args = Args::try_parse_from(["tmuxify", "--env", "PORT=3000", "generate"]).unwrap();
+        assert_eq!(args.env, [("PORT".to_string(), "3000".to_string())]);
+    }
+
+    #[test]
+    fn typed_values_ignore_case() {
+        let args = Args::try_parse_from([
+            "tmuxify",
+            "--tmuxp-location",
+            "Project",
+            "--format",
+            "JSON",
+            "--layout",
+            "Main-Vertical",
+            "generate",
+        ])
+        .unwrap();
+        assert_eq!(args.tmuxp_location, Some(TmuxpLocation::Project));
+        assert_eq!(args.format, Some(ConfigFormat::Json));
+        assert_eq!(args.layout, Some(WindowLayout::MainVertical));
+        assert!(Args::try_parse_from(["tmuxify", "--tmuxp-location", "attic"]).is_err());
+    }
 }
@@ -1,13 +1,182 @@
 use anyhow::Result;
 
-use super::{Args, Commands};
+use super::{Args, BackupsAction, Commands, TemplateAction};
 use crate::ops;
+use crate::ops::show::ShowFormat;
 
 /// Execute the appropriate command based on CLI arguments
-pub fn run_command(args: Args) -> Result<()> {
-    match args.command {
-        Some(Commands::Doctor) => {
-            ops::doctor::run()?;
+pub fn run_command(mut args: Args) -> Result<()> {
+    match args.command.take() {
+        Some(Commands::Doctor { report, fix }) => {
+            ops::doctor::run(report.as_deref(), fix, args.dry_run)?;
+        }
+        Some(Commands::Rename { new_name }) => {
+            ops::rename::run(&args, &new_name)?;
+        }
+        Some(Commands::Clone { source, target }) => {
+            ops::clone::run(&args, &source, target.as_deref())?;
+        }
+        Some(Commands::AddWindow {
+            name,
+            layout,
+            panes,
+            index,
+            env,
+        }) => {
+            ops::edit::add_window(&args, name, layout, panes, index, env)?;
+        }
+        Some(Commands::Apply {
+            no_write,
+            no_allow,
+            no_load,
+            detach,
+        }) => {
+            let steps = ops::apply::Steps {
+                write: !no_write,
+                allow: !no_allow,
+                load: !no_load,
+                detach,
+            };
+            ops::apply::run(&args, steps)?;
+        }
+        Some(Commands::Diff) => {
+            ops::diff::run(&args)?;
+        }
+        Some(Commands::RmWindow { target, pane }) => {
+            ops::edit::rm_window(&args, &target, pane)?;
+        }
+        Some(Commands::Convert { from, path }) => {
+            ops::convert::run(&args, &from, &path)?;
+        }
+        Some(Commands::Generate {
+            windows,
+            panes,
+            spec,
+        }) => {
+            ops::generate::run(&args, &windows, &panes, spec.as_deref())?;
+        }
+        Some(Commands::Tui) => {
+            ops::tui::run(&args)?;
+        }
+        Some(Commands::Up { config, detach }) => {
+            ops::open::up(&args, config.as_deref(), detach)?;
+        }
+        Some(Commands::Delete { yes }) => {
+            ops::delete::run(&args, yes)?;
+        }
+        Some(Commands::List { json }) => {
+            ops::list::run(json)?;
+        }
+        Some(Commands::Sync { to, link }) => {
+            ops::sync::run(&args, to, link)?;
+        }
+        Some(Commands::Backups { action }) => match action {
+            BackupsAction::List => ops::backups::list(&args)?,
+            BackupsAction::Prune {
+                older_than,
+                keep,
+                yes,
+            } => ops::backups::prune(&args, older_than, keep, yes)?,
+            BackupsAction::Restore { backup } => ops::backups::restore(&args, backup.as_deref())?,
+        },
+        Some(Commands::Template { action }) => match action {
+            TemplateAction::Save { name } => ops::template::save(&args, &name)?,
+            TemplateAction::List => ops::template::list()?,
+            TemplateAction::Apply { name } => ops::template::apply(&args, &name)?,
+            TemplateAction::Delete { name } => ops::template::delete(&args, &name)?,
+        },
+        Some(Commands::Edit { window }) => {
+            ops::edit::edit(&args, window.as_deref())?;
+        }
+        Some(Commands::Explain { topic }) => {
+            ops::explain::run(topic.as_deref())?;
+        }
+        Some(Commands::Layouts { panes, demo }) => {
+            ops::layouts::run(&args, usize::from(panes), demo)?;
+        }
+        Some(Commands::SelfUpdate { check }) => {
+            ops::update::run(&args, check)?;
+        }
+        Some(Commands::Version { json }) => {
+            ops::version::run(json)?;
+        }
+        Some(Commands::Completions { shell }) => {
+            ops::complete::script(&shell)?;
+        }
+        Some(Commands::Complete { kind }) => {
+            ops::complete::run(&args, &kind)?;
+        }
+        Some(Commands::Audit) => {
+            ops::audit::run()?;
+        }
+        Some(Commands::Prune) => {
+            ops::prune::run(&args)?;
+        }
+        Some(Commands::History { limit }) => {
+            ops::history::run(&args, limit)?;
+        }
+        Some(Commands::Show {
+            target,
+            yaml,
+            json,
+            toml,
+            table,
+        }) => {
+            let format = if yaml {
+                ShowFormat::Yaml
+            } else if json {
+                ShowFormat::Json
+            } else if toml {
+                ShowFormat::Toml
+            } else if table {
+                ShowFormat::Table
+            } else {
+                ShowFormat::Tree
+            };
+            ops::show::run(&args, target.as_deref(), format)?;
+        }
+        Some(Commands::Export { to }) => {
+            ops::export::run(&args, &to)?;
+        }
+        Some(Commands::Open { query }) => {
+            ops::open::run(&args, query.as_deref())?;
+        }
+        Some(Commands::Hook { target }) => {
+            ops::hook::run(&args, &target)?;
+        }
+        Some(Commands::Hooks { shell, native }) => {
+            ops::hook::print_snippet(shell.as_deref(), native)?;
+        }
+        Some(Commands::Overlay { config }) => {
+            ops::overlay::run(&args, &config)?;
+        }
+        Some(Commands::Resurrect { write }) => {
+            ops::resurrect::run(&args, write)?;
+        }
+        Some(Commands::ResurrectFilter { file }) => {
+            ops::resurrect::filter(&file)?;
+        }
+        Some(Commands::Freeze { session, all }) => {
+            if all {
+                ops::freeze::run_all(&args)?;
+            } else {
+                ops::freeze::run(&args, session.as_deref())?;
+            }
+        }
+        Some(Commands::Share { target, yes }) => {
+            ops::share::run(&args, target.as_deref(), yes)?;
+        }
+        Some(Commands::Import { path, url, yes }) => match (path, url) {
+            (_, Some(url)) => ops::import::from_url(&args, &url, yes)?,
+            (Some(path), None) => ops::import::from_path(&args, &path, yes)?,
+            (None, None) => unreachable!("clap requires a path or --url"),
+        },
+        Some(Commands::Validate {
+            target,
+            load_check,
+            yes,
+        }) => {
+            ops::lint::run(&args, target.as_deref(), load_check, yes)?;
         }
         None => {
             // Default: run interactive configuration
@@ -1,5 +1,5 @@
 mod args;
 mod commands;
 
-pub use args::{Args, Commands};
+pub use args::{Args, BackupsAction, Commands, TemplateAction};
 pub use commands::run_command;
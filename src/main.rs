@@ -2,11 +2,34 @@ mod cli;
 mod model;
 mod ops;
 
-use anyhow::Result;
 use clap::Parser;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
+use crate::ops::log;
+
+fn main() -> ExitCode {
     let args = cli::Args::parse();
-    cli::run_command(args)?;
-    Ok(())
+    // Completion and the resurrect hook run often and would rotate away useful logs
+    if !matches!(
+        args.command,
+        Some(cli::Commands::Complete { .. } | cli::Commands::ResurrectFilter { .. })
+    ) {
+        log::init();
+    }
+
+    match cli::run_command(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            log::debug(format!("error: {:#}", e));
+            // doctor already printed its findings
+            if let Some(ops::doctor::ChecksFailed(category)) = e.downcast_ref() {
+                return ExitCode::from(category.exit_code());
+            }
+            eprintln!("Error: {:?}", e);
+            if let Some(path) = log::path() {
+                eprintln!("\nSee log at {}", path.display());
+            }
+            ExitCode::FAILURE
+        }
+    }
 }
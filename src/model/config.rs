@@ -1,10 +1,13 @@
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use super::Window;
 
 /// Where to store the tmuxp configuration file
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum TmuxpLocation {
     /// Store in ~/.tmuxp/<session>.yaml
     Home,
@@ -12,12 +15,74 @@ pub enum TmuxpLocation {
     Project,
 }
 
-impl TmuxpLocation {
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "home" => Some(TmuxpLocation::Home),
-            "project" => Some(TmuxpLocation::Project),
-            _ => None,
+/// File format a tmuxp config is written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfigFormat {
+    /// YAML, tmuxp's usual format
+    #[default]
+    Yaml,
+    /// JSON, which tmuxp also loads
+    Json,
+    /// TOML, for other tooling; tmuxp can't load it
+    Toml,
+}
+
+impl ConfigFormat {
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+
+    /// The format a file's extension names, YAML for anything else
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => ConfigFormat::Json,
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+
+    /// `path` with this format's extension
+    pub fn apply(&self, path: &Path) -> PathBuf {
+        path.with_extension(self.extension())
+    }
+}
+
+/// Options controlling the generated .envrc
+#[derive(Debug, Clone)]
+pub struct EnvrcOptions {
+    /// Command used to invoke tmuxp (e.g. `tmuxp` or `python3 -m tmuxp`)
+    pub tmuxp_command: String,
+    /// Load paths of additional sessions for the project, mentioned but not auto-loaded
+    pub extra_sessions: Vec<String>,
+    /// Emit a single `layout tmux` line that calls the direnv library
+    /// function installed by `tmuxify hook direnv`
+    pub use_layout: bool,
+    /// Extra arguments for `tmuxp load` (e.g. `-d`, `--log-file FILE`)
+    pub load_args: Vec<String>,
+    /// Load the session detached and print how to attach, instead of
+    /// attaching the terminal that entered the project
+    pub detach: bool,
+    /// Load with `tmuxify up` (the native driver) instead of tmuxp
+    pub native: bool,
+    /// Format of the config files the `.envrc` loads
+    pub format: ConfigFormat,
+}
+
+impl Default for EnvrcOptions {
+    fn default() -> Self {
+        Self {
+            tmuxp_command: "tmuxp".to_string(),
+            extra_sessions: Vec::new(),
+            use_layout: false,
+            load_args: Vec::new(),
+            detach: false,
+            native: false,
+            format: ConfigFormat::Yaml,
         }
     }
 }
@@ -26,17 +91,154 @@ impl TmuxpLocation {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub session_name: String,
-    pub start_directory: String,
+    /// Project root; bytes that aren't UTF-8 are written as `\xNN` escapes
+    #[serde(
+        default,
+        serialize_with = "serialize_start_directory",
+        deserialize_with = "deserialize_start_directory"
+    )]
+    pub start_directory: PathBuf,
+    /// Keep pane commands out of shell history (tmuxp defaults to true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppress_history: Option<bool>,
+    /// Environment variables set for every window of the session
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_environment"
+    )]
+    pub environment: BTreeMap<String, String>,
     pub windows: Vec<Window>,
+    /// Keys tmuxify doesn't model, passed through to tmuxp unchanged
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// Write a path as text, escaping bytes that aren't UTF-8
+fn serialize_start_directory<S>(path: &Path, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&escape_path(path))
+}
+
+/// Read a path written by [`serialize_start_directory`]
+fn deserialize_start_directory<'de, D>(deserializer: D) -> Result<PathBuf, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(unescape_path(&String::deserialize(deserializer)?))
+}
+
+/// Text form of `path`. A UTF-8 path is returned unchanged so tmuxp can use
+/// it; otherwise each invalid byte becomes `\xNN` and `\` becomes `\\`.
+pub fn escape_path(path: &Path) -> String {
+    if let Some(text) = path.to_str() {
+        return text.to_string();
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut text = String::new();
+        for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+            text.push_str(&chunk.valid().replace('\\', "\\\\"));
+            for byte in chunk.invalid() {
+                text.push_str(&format!("\\x{:02x}", byte));
+            }
+        }
+        text
+    }
+    #[cfg(not(unix))]
+    path.to_string_lossy().into_owned()
+}
+
+/// Inverse of [`escape_path`]. Escapes are only decoded when they produce a
+/// path that isn't UTF-8, so ordinary paths containing `\` read back as-is.
+pub fn unescape_path(text: &str) -> PathBuf {
+    #[cfg(unix)]
+    if text.contains("\\x") {
+        use std::os::unix::ffi::OsStringExt;
+
+        if let Some(bytes) = unescape_bytes(text).filter(|b| std::str::from_utf8(b).is_err()) {
+            return PathBuf::from(std::ffi::OsString::from_vec(bytes));
+        }
+    }
+    PathBuf::from(text)
+}
+
+/// Decode `\\` and `\xNN`; `None` when `text` has any other escape
+#[cfg(unix)]
+fn unescape_bytes(text: &str) -> Option<Vec<u8>> {
+    let hex = |digit: &u8| (*digit as char).to_digit(16);
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = match (byte, tail) {
+            (b'\\', [b'\\', tail @ ..]) => {
+                bytes.push(b'\\');
+                tail
+            }
+            (b'\\', [b'x', high, low, tail @ ..]) => {
+                bytes.push((hex(high)? * 16 + hex(low)?) as u8);
+                tail
+            }
+            (b'\\', _) => return None,
+            _ => {
+                bytes.push(byte);
+                tail
+            }
+        };
+    }
+    Some(bytes)
+}
+
+/// Read an `environment:` mapping, accepting unquoted numbers and booleans
+/// (`PORT: 3000`) as tmuxp does
+fn deserialize_environment<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let map = match Value::deserialize(deserializer)? {
+        Value::Null => return Ok(BTreeMap::new()),
+        Value::Mapping(map) => map,
+        _ => {
+            return Err(D::Error::custom(
+                "environment must be a mapping of KEY: value",
+            ));
+        }
+    };
+    map.into_iter()
+        .map(|(key, value)| {
+            let key = key
+                .as_str()
+                .ok_or_else(|| D::Error::custom("environment keys must be strings"))?;
+            let value = match value {
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => {
+                    return Err(D::Error::custom(format!(
+                        "environment variable {} must be a string",
+                        key
+                    )));
+                }
+            };
+            Ok((key.to_string(), value))
+        })
+        .collect()
 }
 
 impl Config {
     /// Create a new tmuxp configuration
-    pub fn new(session_name: String, start_directory: String, windows: Vec<Window>) -> Self {
+    pub fn new(session_name: String, start_directory: PathBuf, windows: Vec<Window>) -> Self {
         Self {
             session_name,
             start_directory,
+            suppress_history: None,
+            environment: BTreeMap::new(),
             windows,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -45,18 +247,127 @@ impl Config {
         Ok(serde_yaml::to_string(self)?)
     }
 
+    /// Serialize to a pretty-printed JSON string
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        crate::ops::json::to_json_pretty(self)
+    }
+
+    /// Serialize to a TOML string
+    pub fn to_toml(&self) -> anyhow::Result<String> {
+        crate::ops::toml::to_toml(self)
+    }
+
+    /// Serialize in `format`
+    pub fn render(&self, format: ConfigFormat) -> anyhow::Result<String> {
+        match format {
+            ConfigFormat::Yaml => self.to_yaml(),
+            ConfigFormat::Json => self.to_json(),
+            ConfigFormat::Toml => self.to_toml(),
+        }
+    }
+
+    /// Parse a configuration from a YAML string
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Focus the first window and its first pane when nothing is focused
+    /// explicitly, instead of tmux's default of the last-created window.
+    pub fn apply_default_focus(&mut self) {
+        let has_focus = self
+            .windows
+            .iter()
+            .any(|w| w.focus == Some(true) || w.panes.iter().any(|p| p.focus == Some(true)));
+        if has_focus {
+            return;
+        }
+
+        if let Some(window) = self.windows.first_mut() {
+            window.focus = Some(true);
+            if let Some(pane) = window.panes.first_mut() {
+                pane.focus = Some(true);
+            }
+        }
+    }
+
+    /// Check explicit window indexes for duplicates and values below tmux's
+    /// `base-index`. `base_index` is only called when a window sets an
+    /// index, since asking tmux costs a process.
+    pub fn validate_window_indexes(&self, base_index: impl FnOnce() -> u32) -> anyhow::Result<()> {
+        let mut indexes = self
+            .windows
+            .iter()
+            .filter_map(|window| window.window_index.map(|index| (window, index)))
+            .peekable();
+        if indexes.peek().is_none() {
+            return Ok(());
+        }
+        let base_index = base_index();
+        let mut seen = std::collections::BTreeSet::new();
+        for (window, index) in indexes {
+            let name = window.window_name.as_deref().unwrap_or("unnamed");
+            if index < base_index {
+                anyhow::bail!(
+                    "Window '{}' uses index {} but tmux base-index is {}",
+                    name,
+                    index,
+                    base_index
+                );
+            }
+            if !seen.insert(index) {
+                anyhow::bail!("Window index {} is used more than once", index);
+            }
+        }
+        Ok(())
+    }
+
     /// Get the tmuxp file path based on location preference
     pub fn get_file_path(
         &self,
         location: TmuxpLocation,
         project_dir: Option<&Path>,
+    ) -> anyhow::Result<PathBuf> {
+        Self::file_path_for(&self.session_name, location, project_dir)
+    }
+
+    /// Get the file path for an additional (non-primary) session of a project.
+    /// Home configs are already named per session; project-local ones become
+    /// `.tmuxp.<session>.yaml` next to the primary `.tmuxp.yaml`.
+    pub fn secondary_file_path(
+        &self,
+        location: TmuxpLocation,
+        project_dir: Option<&Path>,
+    ) -> anyhow::Result<PathBuf> {
+        match location {
+            TmuxpLocation::Home => self.get_file_path(location, project_dir),
+            TmuxpLocation::Project => {
+                let file_name = format!(".tmuxp.{}.yaml", self.session_name);
+                Ok(project_dir.map_or_else(|| PathBuf::from(&file_name), |d| d.join(&file_name)))
+            }
+        }
+    }
+
+    /// Path used by `.envrc` to load this config as an additional session
+    pub fn secondary_load_path(&self, location: TmuxpLocation, format: ConfigFormat) -> String {
+        let ext = format.extension();
+        match location {
+            TmuxpLocation::Home => format!("~/.tmuxp/{}.{}", self.session_name, ext),
+            TmuxpLocation::Project => format!("./.tmuxp.{}.{}", self.session_name, ext),
+        }
+    }
+
+    /// Get the tmuxp file path for a session before a config has been built
+    pub fn file_path_for(
+        session_name: &str,
+        location: TmuxpLocation,
+        project_dir: Option<&Path>,
     ) -> anyhow::Result<PathBuf> {
         match location {
             TmuxpLocation::Home => {
                 let home = dirs::home_dir()
                     .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
                 let tmuxp_dir = home.join(".tmuxp");
-                Ok(tmuxp_dir.join(format!("{}.yaml", self.session_name)))
+                Ok(tmuxp_dir.join(format!("{}.yaml", session_name)))
             }
             TmuxpLocation::Project => {
                 if let Some(dir) = project_dir {
@@ -69,33 +380,147 @@ impl Config {
     }
 
     /// Generate the .envrc content for this configuration
-    pub fn generate_envrc(&self, location: TmuxpLocation) -> String {
+    ///
+    /// The `.envrc` sources an optional, uncommitted `.envrc.local` and loads
+    /// `.tmuxp.local.yaml` merged over the config when it exists.
+    ///
+    /// Auto-loading is skipped inside remote-development environments
+    /// (Codespaces, Gitpod, JetBrains Gateway) unless `TMUXIFY_REMOTE_ATTACH`
+    /// is set, since those already multiplex their terminals. With
+    /// `use_layout`, that logic lives in the shared `layout_tmux` function.
+    ///
+    /// With `detach`, the session loads in the background and the `.envrc`
+    /// prints how to attach instead of taking over the terminal.
+    pub fn generate_envrc(&self, location: TmuxpLocation, options: &EnvrcOptions) -> String {
+        let ext = options.format.extension();
         let load_path = match location {
-            TmuxpLocation::Home => format!("~/.tmuxp/{}.yaml", self.session_name),
-            TmuxpLocation::Project => "./.tmuxp.yaml".to_string(),
+            TmuxpLocation::Home => format!("~/.tmuxp/{}.{}", self.session_name, ext),
+            TmuxpLocation::Project => format!("./.tmuxp.{}", ext),
         };
 
-        format!(
-            r#"if [ -z "$TMUX" ]; then
-  tmuxp load {}
+        let load = match options.native {
+            true => "tmuxify up".to_string(),
+            false => format!("{} load", options.tmuxp_command),
+        };
+
+        let mut envrc = if options.use_layout && !options.native {
+            // layout_tmux defaults to ./.tmuxp.yaml and plain `tmuxp`
+            let mut line = String::from("layout tmux");
+            let custom_command = options.tmuxp_command != "tmuxp";
+            let default_path =
+                location == TmuxpLocation::Project && options.format == ConfigFormat::Yaml;
+            if !default_path || custom_command {
+                line.push(' ');
+                line.push_str(&load_path);
+            }
+            if custom_command {
+                line.push(' ');
+                line.push_str(&options.tmuxp_command);
+            }
+            line.push('\n');
+            if options.detach {
+                // layout_tmux loads detached and prints a hint for this session
+                line.insert_str(
+                    0,
+                    &format!(
+                        "TMUXIFY_DETACHED_SESSION={}\n",
+                        shell_word(&self.session_name)
+                    ),
+                );
+            }
+            if !options.load_args.is_empty() {
+                // layout_tmux word-splits this into `tmuxp load` arguments
+                line.insert_str(
+                    0,
+                    &format!(
+                        "TMUXIFY_TMUXP_ARGS={}\n",
+                        shell_word(&options.load_args.join(" "))
+                    ),
+                );
+            }
+            line
+        } else {
+            // tmuxp load arguments don't apply to `tmuxify up`
+            let load_args = match options.native {
+                true => &[][..],
+                false => &options.load_args[..],
+            };
+            let mut args: String = load_args
+                .iter()
+                .map(|arg| format!(" {}", shell_word(arg)))
+                .collect();
+            // Detached: a running session only gets the hint, and a load ends with it
+            let (running, announce) = match self.detach_hint(options) {
+                Some(hint) => {
+                    args.insert_str(0, " -d");
+                    (
+                        format!(
+                            "  elif tmux has-session -t {} 2>/dev/null; then\n    echo {} >&2\n",
+                            shell_word(&format!("={}", self.session_name)),
+                            hint
+                        ),
+                        format!(" && echo {} >&2", hint),
+                    )
+                }
+                None => (String::new(), String::new()),
+            };
+            format!(
+                r#"source_env_if_exists .envrc.local
+if [ -z "$TMUX" ]; then
+  if [ -n "$CODESPACES$GITPOD_WORKSPACE_ID$JETBRAINS_REMOTE_RUN" ] && [ -z "$TMUXIFY_REMOTE_ATTACH" ]; then
+    : # remote development environment; export TMUXIFY_REMOTE_ATTACH=1 to auto-load
+{running}  elif [ ! -f .tmuxp.local.yaml ] || ! command -v tmuxify >/dev/null 2>&1; then
+    {load}{args} {path}{announce}
+  else
+    # personal overrides in .tmuxp.local.yaml, merged over the shared config
+    {load}{args} "$(tmuxify overlay {path} || echo {path})"{announce}
+  fi
 fi
 "#,
-            load_path
-        )
+                path = load_path
+            )
+        };
+
+        if !options.extra_sessions.is_empty() {
+            envrc.push_str("\n# Other sessions for this project:\n");
+            for path in &options.extra_sessions {
+                envrc.push_str(&format!("#   {} {}\n", load, path));
+            }
+        }
+
+        envrc
+    }
+
+    /// The quoted `echo` argument a detached `.envrc` prints, if detached
+    fn detach_hint(&self, options: &EnvrcOptions) -> Option<String> {
+        options.detach.then(|| {
+            shell_word(&format!(
+                "tmuxify: session '{}' ready - run tmux attach -t {}",
+                self.session_name,
+                shell_word(&self.session_name)
+            ))
+        })
+    }
+}
+
+/// Quote a word for POSIX shells unless it is safe as is
+fn shell_word(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::Pane;
     use std::path::PathBuf;
 
     fn sample_config() -> Config {
-        Config::new(
-            "myapp".to_string(),
-            "/tmp/myapp".to_string(),
-            vec![],
-        )
+        Config::new("myapp".to_string(), "/tmp/myapp".into(), vec![])
     }
 
     #[test]
@@ -107,20 +532,316 @@ mod tests {
         assert!(y.contains("start_directory"));
     }
 
+    #[test]
+    fn session_environment_round_trips() {
+        let cfg = Config::from_yaml(
+            "session_name: app\nenvironment:\n  PORT: 3000\n  DEBUG: true\nwindows: []\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.environment["PORT"], "3000");
+        assert_eq!(cfg.environment["DEBUG"], "true");
+        assert!(cfg.extra.is_empty());
+
+        let y = cfg.to_yaml().unwrap();
+        assert!(y.contains("environment:\n  DEBUG: 'true'\n  PORT: '3000'\n"));
+        assert!(!sample_config().to_yaml().unwrap().contains("environment"));
+        assert!(Config::from_yaml("session_name: a\nenvironment: [A]\nwindows: []\n").is_err());
+    }
+
+    #[test]
+    fn yaml_round_trips() {
+        let cfg = Config::new(
+            "myapp".to_string(),
+            "/tmp/myapp".into(),
+            vec![Window::simple()],
+        );
+        let parsed = Config::from_yaml(&cfg.to_yaml().unwrap()).unwrap();
+        assert_eq!(parsed.session_name, "myapp");
+        assert_eq!(parsed.windows.len(), 1);
+    }
+
+    #[test]
+    fn pane_start_directory_serialized_only_when_set() {
+        let cfg = Config::new(
+            "myapp".to_string(),
+            "/tmp/myapp".into(),
+            vec![Window::new(
+                None,
+                None,
+                vec![
+                    Pane::new(vec!["ls".to_string()]).with_start_directory(Some("api".into())),
+                    Pane::new(vec![]),
+                ],
+            )],
+        );
+        let y = cfg.to_yaml().unwrap();
+        assert_eq!(y.matches("start_directory").count(), 2);
+        assert!(y.contains("start_directory: api"));
+    }
+
+    #[test]
+    fn suppress_history_serialized_only_when_set() {
+        let mut cfg = sample_config();
+        assert!(!cfg.to_yaml().unwrap().contains("suppress_history"));
+
+        cfg.suppress_history = Some(false);
+        assert!(cfg.to_yaml().unwrap().contains("suppress_history: false"));
+    }
+
+    #[test]
+    fn window_indexes_reject_duplicates_and_below_base_index() {
+        let indexed = |idx: u32| {
+            let mut w = Window::simple();
+            w.window_index = Some(idx);
+            w
+        };
+
+        let cfg = Config::new("s".into(), "/tmp".into(), vec![indexed(0), indexed(9)]);
+        assert!(cfg.validate_window_indexes(|| 0).is_ok());
+        assert!(cfg.validate_window_indexes(|| 1).is_err());
+
+        let dup = Config::new(
+            "s".into(),
+            "/tmp".into(),
+            vec![indexed(2), Window::simple(), indexed(2)],
+        );
+        assert!(dup.validate_window_indexes(|| 0).is_err());
+
+        let plain = Config::new("s".into(), "/tmp".into(), vec![Window::simple()]);
+        assert!(
+            plain
+                .validate_window_indexes(|| unreachable!("no window sets an index"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn default_focus_targets_first_window_and_pane() {
+        let mut cfg = Config::new(
+            "s".into(),
+            "/tmp".into(),
+            vec![Window::simple(), Window::simple()],
+        );
+        cfg.apply_default_focus();
+        assert_eq!(cfg.windows[0].focus, Some(true));
+        assert_eq!(cfg.windows[0].panes[0].focus, Some(true));
+        assert_eq!(cfg.windows[1].focus, None);
+    }
+
+    #[test]
+    fn default_focus_respects_explicit_focus() {
+        let mut cfg = Config::new(
+            "s".into(),
+            "/tmp".into(),
+            vec![Window::simple(), Window::simple()],
+        );
+        cfg.windows[1].panes[0].focus = Some(true);
+        cfg.apply_default_focus();
+        assert_eq!(cfg.windows[0].focus, None);
+    }
+
+    #[test]
+    fn unmodeled_keys_pass_through_at_every_level() {
+        let yaml = r#"
+session_name: myapp
+before_script: ./bootstrap.sh
+windows:
+  - window_name: editor
+    shell_command_before:
+      - source .venv/bin/activate
+    panes:
+      - shell_command:
+          - nvim
+        enter: false
+"#;
+        let cfg = Config::from_yaml(yaml).unwrap();
+        assert!(cfg.extra.contains_key("before_script"));
+        assert!(cfg.windows[0].extra.contains_key("shell_command_before"));
+        assert!(cfg.windows[0].panes[0].extra.contains_key("enter"));
+
+        let out = cfg.to_yaml().unwrap();
+        assert!(out.contains("before_script: ./bootstrap.sh"));
+        assert!(out.contains("shell_command_before"));
+        assert!(out.contains("enter: false"));
+    }
+
+    #[test]
+    fn hand_written_configs_parse_pane_shorthands() {
+        let yaml = "session_name: legacy\n\
+                    windows:\n\
+                    - window_name: editor\n  panes:\n  - vim\n  - pane\n";
+        let cfg = Config::from_yaml(yaml).unwrap();
+        assert_eq!(cfg.windows[0].panes.len(), 2);
+        assert_eq!(cfg.windows[0].panes[0].shell_command, ["vim"]);
+        assert!(cfg.windows[0].panes[1].shell_command.is_empty());
+    }
+
     #[test]
     fn envrc_home_points_to_home_tmuxp() {
         let cfg = sample_config();
-        let envrc = cfg.generate_envrc(TmuxpLocation::Home);
+        let envrc = cfg.generate_envrc(TmuxpLocation::Home, &EnvrcOptions::default());
         assert!(envrc.contains("~/.tmuxp/myapp.yaml"));
     }
 
     #[test]
     fn envrc_project_points_to_local_file() {
         let cfg = sample_config();
-        let envrc = cfg.generate_envrc(TmuxpLocation::Project);
+        let envrc = cfg.generate_envrc(TmuxpLocation::Project, &EnvrcOptions::default());
         assert!(envrc.contains("./.tmuxp.yaml"));
     }
 
+    #[test]
+    fn envrc_skips_remote_environments_unless_opted_in() {
+        let cfg = sample_config();
+        let envrc = cfg.generate_envrc(TmuxpLocation::Project, &EnvrcOptions::default());
+        assert!(envrc.contains("$CODESPACES$GITPOD_WORKSPACE_ID$JETBRAINS_REMOTE_RUN"));
+        assert!(envrc.contains("TMUXIFY_REMOTE_ATTACH"));
+    }
+
+    #[test]
+    fn envrc_uses_resolved_tmuxp_command() {
+        let cfg = sample_config();
+        let opts = EnvrcOptions {
+            tmuxp_command: "python3 -m tmuxp".to_string(),
+            ..Default::default()
+        };
+        let envrc = cfg.generate_envrc(TmuxpLocation::Project, &opts);
+        assert!(envrc.contains("python3 -m tmuxp load ./.tmuxp.yaml"));
+    }
+
+    #[test]
+    fn envrc_uses_tmuxify_up_with_the_native_driver() {
+        let cfg = sample_config();
+        let opts = EnvrcOptions {
+            native: true,
+            use_layout: true,
+            load_args: vec!["--log-file".into(), "x.log".into()],
+            detach: true,
+            ..Default::default()
+        };
+        let envrc = cfg.generate_envrc(TmuxpLocation::Project, &opts);
+        assert!(envrc.contains("    tmuxify up -d ./.tmuxp.yaml && echo"));
+        assert!(!envrc.contains("tmuxp load"));
+        assert!(!envrc.contains("layout tmux"));
+    }
+
+    #[test]
+    fn envrc_passes_extra_load_args() {
+        let cfg = sample_config();
+        let opts = EnvrcOptions {
+            load_args: vec!["-d".into(), "--log-file".into(), "my log.txt".into()],
+            ..Default::default()
+        };
+        let envrc = cfg.generate_envrc(TmuxpLocation::Project, &opts);
+        assert!(envrc.contains("    tmuxp load -d --log-file 'my log.txt' ./.tmuxp.yaml\n"));
+
+        let layout = EnvrcOptions {
+            use_layout: true,
+            ..opts
+        };
+        assert_eq!(
+            cfg.generate_envrc(TmuxpLocation::Project, &layout),
+            "TMUXIFY_TMUXP_ARGS='-d --log-file my log.txt'\nlayout tmux\n"
+        );
+    }
+
+    #[test]
+    fn detached_envrc_loads_in_the_background_and_hints() {
+        let cfg = sample_config();
+        let opts = EnvrcOptions {
+            detach: true,
+            ..Default::default()
+        };
+        let envrc = cfg.generate_envrc(TmuxpLocation::Project, &opts);
+        let hint = "echo 'tmuxify: session '\\''myapp'\\'' ready - run tmux attach -t myapp' >&2";
+        assert!(envrc.contains(&format!(
+            "  elif tmux has-session -t =myapp 2>/dev/null; then\n    {}\n",
+            hint
+        )));
+        assert!(envrc.contains(&format!("    tmuxp load -d ./.tmuxp.yaml && {}\n", hint)));
+        let syntax = std::process::Command::new("sh")
+            .args(["-n", "-c", &envrc])
+            .status()
+            .unwrap();
+        assert!(syntax.success());
+
+        let layout = EnvrcOptions {
+            use_layout: true,
+            ..opts
+        };
+        assert_eq!(
+            cfg.generate_envrc(TmuxpLocation::Project, &layout),
+            "TMUXIFY_DETACHED_SESSION=myapp\nlayout tmux\n"
+        );
+    }
+
+    #[test]
+    fn envrc_loads_local_overrides() {
+        let envrc = sample_config().generate_envrc(TmuxpLocation::Home, &EnvrcOptions::default());
+        assert!(envrc.starts_with("source_env_if_exists .envrc.local\n"));
+        assert!(envrc.contains(
+            "tmuxp load \"$(tmuxify overlay ~/.tmuxp/myapp.yaml || echo ~/.tmuxp/myapp.yaml)\""
+        ));
+    }
+
+    #[test]
+    fn layout_envrc_is_a_single_line() {
+        let cfg = sample_config();
+        let layout = EnvrcOptions {
+            use_layout: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            cfg.generate_envrc(TmuxpLocation::Project, &layout),
+            "layout tmux\n"
+        );
+        assert_eq!(
+            cfg.generate_envrc(TmuxpLocation::Home, &layout),
+            "layout tmux ~/.tmuxp/myapp.yaml\n"
+        );
+
+        let venv = EnvrcOptions {
+            tmuxp_command: "./.venv/bin/tmuxp".into(),
+            ..layout
+        };
+        assert_eq!(
+            cfg.generate_envrc(TmuxpLocation::Project, &venv),
+            "layout tmux ./.tmuxp.yaml ./.venv/bin/tmuxp\n"
+        );
+
+        // layout_tmux only defaults to ./.tmuxp.yaml, so JSON names its file
+        let json = EnvrcOptions {
+            use_layout: true,
+            format: ConfigFormat::Json,
+            ..Default::default()
+        };
+        assert_eq!(
+            cfg.generate_envrc(TmuxpLocation::Project, &json),
+            "layout tmux ./.tmuxp.json\n"
+        );
+    }
+
+    #[test]
+    fn envrc_mentions_extra_sessions_without_loading_them() {
+        let cfg = sample_config();
+        let opts = EnvrcOptions {
+            extra_sessions: vec!["./.tmuxp.myapp-infra.yaml".to_string()],
+            ..Default::default()
+        };
+        let envrc = cfg.generate_envrc(TmuxpLocation::Project, &opts);
+        assert!(envrc.contains("#   tmuxp load ./.tmuxp.myapp-infra.yaml"));
+        assert_eq!(envrc.matches("\n    tmuxp load ./").count(), 1);
+    }
+
+    #[test]
+    fn secondary_project_path_is_named_after_session() {
+        let cfg = sample_config();
+        let p = cfg
+            .secondary_file_path(TmuxpLocation::Project, Some(Path::new("/work/proj")))
+            .unwrap();
+        assert_eq!(p, PathBuf::from("/work/proj/.tmuxp.myapp.yaml"));
+    }
+
     #[test]
     fn get_path_home_includes_session() {
         let cfg = sample_config();
@@ -136,4 +857,26 @@ mod tests {
             .unwrap();
         assert_eq!(p, PathBuf::from("/work/proj/.tmuxp.yaml"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_start_directory_round_trips() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = PathBuf::from(OsStr::from_bytes(b"/tmp/a\\b/caf\xe9"));
+        let cfg = Config::new("s".into(), dir.clone(), vec![]);
+        let yaml = cfg.to_yaml().unwrap();
+        assert!(yaml.contains(r"/tmp/a\\b/caf\xe9"), "{}", yaml);
+        assert_eq!(Config::from_yaml(&yaml).unwrap().start_directory, dir);
+        let json = cfg.to_json().unwrap();
+        assert_eq!(Config::from_yaml(&json).unwrap().start_directory, dir);
+
+        let plain = Config::new("s".into(), PathBuf::from(r"/tmp/a\b"), vec![]);
+        let yaml = plain.to_yaml().unwrap();
+        assert_eq!(
+            Config::from_yaml(&yaml).unwrap().start_directory,
+            plain.start_directory
+        );
+    }
 }
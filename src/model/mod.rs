@@ -1,7 +1,10 @@
 mod config;
 mod pane;
+mod settings;
+mod split;
 mod window;
 
-pub use config::{Config, TmuxpLocation};
+pub use config::{Config, ConfigFormat, EnvrcOptions, TmuxpLocation};
 pub use pane::Pane;
-pub use window::{Window, WindowLayout};
+pub use settings::{Driver, Settings};
+pub use window::{DEFAULT_TERMINAL_SIZE, Window, WindowLayout, is_valid_pane_size};
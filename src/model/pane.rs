@@ -1,25 +1,309 @@
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Deref;
+
+/// One `shell_command` entry: the command and any per-command options
+/// (`enter`, `sleep_before`, ...) tmuxp accepts in its `{cmd: ...}` form
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaneCommand {
+    pub cmd: String,
+    /// Options kept as written, e.g. `enter: false`
+    pub options: BTreeMap<String, Value>,
+}
+
+impl PaneCommand {
+    /// The command's own `enter` option: whether to press Enter after
+    /// typing it, when set
+    pub fn enter(&self) -> Option<bool> {
+        self.options.get("enter").and_then(Value::as_bool)
+    }
+}
+
+impl From<String> for PaneCommand {
+    fn from(cmd: String) -> Self {
+        Self {
+            cmd,
+            options: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<&str> for PaneCommand {
+    fn from(cmd: &str) -> Self {
+        cmd.to_string().into()
+    }
+}
+
+impl Deref for PaneCommand {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.cmd
+    }
+}
+
+impl fmt::Display for PaneCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.cmd)
+    }
+}
+
+impl PartialEq<&str> for PaneCommand {
+    fn eq(&self, other: &&str) -> bool {
+        self.cmd == *other
+    }
+}
+
+impl PartialEq<String> for PaneCommand {
+    fn eq(&self, other: &String) -> bool {
+        self.cmd == *other
+    }
+}
+
+impl PartialEq<str> for PaneCommand {
+    fn eq(&self, other: &str) -> bool {
+        self.cmd == other
+    }
+}
+
+/// A plain string, or a `{cmd: ..., <option>: ...}` mapping when it has options
+impl Serialize for PaneCommand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.options.is_empty() {
+            return serializer.serialize_str(&self.cmd);
+        }
+        let mut map = Mapping::new();
+        map.insert("cmd".into(), self.cmd.clone().into());
+        for (key, value) in &self.options {
+            map.insert(key.clone().into(), value.clone());
+        }
+        map.serialize(serializer)
+    }
+}
 
 /// Represents a single tmux pane with optional shell commands
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pane {
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub shell_command: Vec<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_commands"
+    )]
+    pub shell_command: Vec<PaneCommand>,
+    /// Working directory for this pane, relative to the window/session start directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_directory: Option<String>,
+    /// Environment variables set for this pane (tmuxp pane-level `environment`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment: BTreeMap<String, String>,
+    /// Override the session's `suppress_history` for this pane's commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppress_history: Option<bool>,
+    /// Select this pane within its window when the session is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<bool>,
+    /// Keys tmuxify doesn't model, passed through to tmuxp unchanged
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 impl Pane {
     /// Create a new pane with the given commands
     pub fn new(commands: Vec<String>) -> Self {
         Self {
-            shell_command: commands,
+            shell_command: commands.into_iter().map(PaneCommand::from).collect(),
+            start_directory: None,
+            environment: BTreeMap::new(),
+            suppress_history: None,
+            focus: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Set the pane's working directory
+    pub fn with_start_directory(mut self, dir: Option<String>) -> Self {
+        self.start_directory = dir;
+        self
+    }
+
+    /// Set the pane's environment variables
+    pub fn with_environment(mut self, environment: BTreeMap<String, String>) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Delay the pane's commands by `seconds`, so it can wait for a service
+    /// started in another pane. Emitted as a `sleep N && ` prefix on the first
+    /// command since later commands already run after it.
+    pub fn with_delay(mut self, seconds: u32) -> Self {
+        if seconds == 0 {
+            return self;
+        }
+        if let Some(first) = self.shell_command.first_mut() {
+            first.cmd = format!("sleep {} && {}", seconds, first.cmd);
         }
+        self
+    }
+
+    /// The pane's commands as text, without their options
+    pub fn commands(&self) -> Vec<String> {
+        self.shell_command.iter().map(|c| c.cmd.clone()).collect()
+    }
+
+    /// Replace the pane's commands, keeping the options of each one whose
+    /// text is unchanged
+    pub fn set_commands(&mut self, commands: Vec<String>) {
+        let mut previous = std::mem::take(&mut self.shell_command);
+        self.shell_command = commands
+            .into_iter()
+            .map(|cmd| match previous.iter().position(|c| c.cmd == cmd) {
+                Some(i) => previous.remove(i),
+                None => cmd.into(),
+            })
+            .collect();
     }
 
     /// Create an empty pane (no commands)
-    #[allow(dead_code)]
     pub fn empty() -> Self {
         Self {
             shell_command: Vec::new(),
+            start_directory: None,
+            environment: BTreeMap::new(),
+            suppress_history: None,
+            focus: None,
+            extra: BTreeMap::new(),
         }
     }
 }
+
+/// Parse a window's `panes` in any form tmuxp accepts: a mapping, a single
+/// command (`- vim`), or a blank pane (`- null`, `- blank`, `- pane`)
+pub fn deserialize_panes<'de, D>(deserializer: D) -> Result<Vec<Pane>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = Vec::<Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::Null => Ok(Pane::empty()),
+            Value::String(s) if s == "blank" || s == "pane" => Ok(Pane::empty()),
+            Value::String(command) => Ok(Pane::new(vec![command])),
+            value => serde_yaml::from_value(value).map_err(D::Error::custom),
+        })
+        .collect()
+}
+
+/// Parse `shell_command` as a single command or a list, where each entry may
+/// also be a `{cmd: ...}` mapping with per-command options
+fn deserialize_commands<'de, D>(deserializer: D) -> Result<Vec<PaneCommand>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let entries = match Value::deserialize(deserializer)? {
+        Value::Null => Vec::new(),
+        Value::Sequence(entries) => entries,
+        entry => vec![entry],
+    };
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            Value::String(command) => Ok(command.into()),
+            Value::Mapping(map) => {
+                let mut cmd = None;
+                let mut options = BTreeMap::new();
+                for (key, value) in map {
+                    match (key.as_str(), value) {
+                        (Some("cmd"), Value::String(command)) => cmd = Some(command),
+                        (Some(key), value) => {
+                            options.insert(key.to_string(), value);
+                        }
+                        (None, _) => return Err(D::Error::custom("option names must be strings")),
+                    }
+                }
+                let cmd =
+                    cmd.ok_or_else(|| D::Error::custom("shell_command entries need a `cmd` key"))?;
+                Ok(PaneCommand { cmd, options })
+            }
+            other => serde_yaml::from_value::<String>(other)
+                .map(PaneCommand::from)
+                .map_err(D::Error::custom),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_prefixes_only_first_command() {
+        let pane = Pane::new(vec!["curl localhost:3000".into(), "echo done".into()]).with_delay(3);
+        assert_eq!(
+            pane.shell_command,
+            vec![
+                "sleep 3 && curl localhost:3000".to_string(),
+                "echo done".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_delay_or_no_commands_is_a_no_op() {
+        assert_eq!(
+            Pane::new(vec!["ls".into()]).with_delay(0).shell_command,
+            vec!["ls"]
+        );
+        assert!(Pane::empty().with_delay(5).shell_command.is_empty());
+    }
+
+    #[test]
+    fn tmuxp_pane_shorthands_parse() {
+        #[derive(Deserialize)]
+        struct Panes {
+            #[serde(deserialize_with = "deserialize_panes")]
+            panes: Vec<Pane>,
+        }
+        let yaml = "panes:\n\
+                    - vim\n\
+                    - null\n\
+                    - blank\n\
+                    - shell_command: npm run dev\n\
+                    - shell_command:\n  - cmd: cd api\n  - cargo watch\n";
+        let panes: Vec<Vec<String>> = serde_yaml::from_str::<Panes>(yaml)
+            .unwrap()
+            .panes
+            .iter()
+            .map(Pane::commands)
+            .collect();
+        assert_eq!(
+            panes,
+            [
+                vec!["vim"],
+                vec![],
+                vec![],
+                vec!["npm run dev"],
+                vec!["cd api", "cargo watch"]
+            ]
+        );
+
+        let missing_cmd = "panes:\n- shell_command:\n  - enter: false\n";
+        assert!(serde_yaml::from_str::<Panes>(missing_cmd).is_err());
+    }
+
+    #[test]
+    fn per_command_options_round_trip() {
+        let yaml = "shell_command:\n- cmd: make deploy\n  enter: false\n- make test\n";
+        let mut pane: Pane = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(pane.shell_command[0].enter(), Some(false));
+        assert_eq!(pane.shell_command[1].enter(), None);
+        assert_eq!(serde_yaml::to_string(&pane).unwrap(), yaml);
+
+        pane.set_commands(vec!["make lint".into(), "make deploy".into()]);
+        assert!(pane.shell_command[0].options.is_empty());
+        assert_eq!(pane.shell_command[1].enter(), Some(false));
+    }
+}
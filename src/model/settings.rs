@@ -0,0 +1,249 @@
+use anyhow::Context;
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Once;
+
+use super::WindowLayout;
+
+/// User preferences read from `~/.config/tmuxify/config.yaml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Default layout by pane count. The entry with the largest count not
+    /// exceeding a window's pane count applies; `none` means no layout.
+    pub default_layouts: BTreeMap<usize, String>,
+    /// External fuzzy finder for long lists: `fzf`, `sk`, or `auto` for
+    /// whichever is installed. Unset keeps the built-in prompts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzzy_finder: Option<String>,
+    /// Generate `.envrc` files as a single `layout tmux` line backed by the
+    /// direnv library from `tmuxify hook direnv`
+    pub direnv_layout: bool,
+    /// Generate `.envrc` files that load the session detached and print how
+    /// to attach, instead of attaching every terminal that enters the project
+    pub envrc_detach: bool,
+    /// Extra arguments for every `tmuxp load` tmuxify writes or runs
+    /// (e.g. `["-d"]` or `["--log-file", "/tmp/tmuxp.log"]`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tmuxp_load_args: Vec<String>,
+    /// Paste service `tmuxify share` POSTs configs to (e.g. `https://paste.rs`).
+    /// Unset shares as a secret GitHub gist via `gh`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_endpoint: Option<String>,
+    /// Prompt theme: `colorful` (the default) or `simple` for plain text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Answers the wizard preselects
+    pub wizard: WizardDefaults,
+    /// Disable network features (self-update, share, import --url), as `--offline` does
+    pub offline: bool,
+    /// How sessions are started: with `tmuxp load`, or built directly with tmux
+    pub driver: Driver,
+}
+
+/// What starts sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Driver {
+    /// `tmuxp load`, in `open` and in generated `.envrc` files
+    #[default]
+    Tmuxp,
+    /// tmuxify's own tmux commands (`tmuxify up`), so tmuxp isn't needed
+    Native,
+}
+
+/// Wizard answers to preselect, for prompts answered the same way every time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WizardDefaults {
+    /// Layout preselected for new windows: `auto`, a built-in layout, or `split`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    /// Pane count offered for new windows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panes: Option<usize>,
+    /// Preselect "Add another window" instead of "Done" after each window
+    pub add_another_window: bool,
+    /// Answer to "Run 'direnv allow' now?"; when set, the question is skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direnv_allow: Option<bool>,
+    /// Answer to "Load the session now?"; when set, the question is skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_session: Option<bool>,
+}
+
+impl Settings {
+    /// Path of the user settings file
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tmuxify").join("config.yaml"))
+    }
+
+    /// Load user settings, falling back to defaults when no file exists
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = Self::path().filter(|p| p.is_file()) else {
+            return Ok(Self::default());
+        };
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let settings: Self = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        // Settings are loaded by many steps of one run; warn only once
+        static WARNED: Once = Once::new();
+        WARNED.call_once(|| {
+            for warning in settings.warnings() {
+                eprintln!(
+                    "{} {}: {}",
+                    style("⚠").yellow().bold(),
+                    path.display(),
+                    warning
+                );
+            }
+        });
+        Ok(settings)
+    }
+
+    /// Values tmuxify doesn't understand and ignores
+    pub fn warnings(&self) -> Vec<String> {
+        let layout_names = || {
+            WindowLayout::all()
+                .iter()
+                .map(|l| l.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let wizard_layout = self
+            .wizard
+            .layout
+            .as_deref()
+            .filter(|name| !is_wizard_layout(name))
+            .map(|name| {
+                format!(
+                    "wizard.layout: unknown layout '{}' (use auto, split, or one of {}); \
+                     auto applies",
+                    name,
+                    layout_names()
+                )
+            });
+        self.default_layouts
+            .iter()
+            .filter(|(_, name)| parse_layout(name).is_none())
+            .map(|(count, name)| {
+                format!(
+                    "default_layouts.{}: unknown layout '{}' (use none, one of {}, or a tmux \
+                     layout string); the built-in default applies",
+                    count,
+                    name,
+                    layout_names()
+                )
+            })
+            .chain(wizard_layout)
+            .collect()
+    }
+
+    /// Default layout for a window with `pane_count` panes, honoring user
+    /// overrides; an unknown override falls back to the built-in default
+    pub fn default_layout_for(&self, pane_count: usize) -> Option<WindowLayout> {
+        self.default_layouts
+            .range(..=pane_count)
+            .next_back()
+            .and_then(|(_, name)| parse_layout(name))
+            .unwrap_or_else(|| WindowLayout::default_for_panes(pane_count))
+    }
+}
+
+/// Whether `name` is a `wizard.layout` value the wizard understands
+pub fn is_wizard_layout(name: &str) -> bool {
+    matches!(name, "auto" | "split") || WindowLayout::all().iter().any(|l| l.to_string() == name)
+}
+
+/// A `default_layouts` value: `Some(None)` for `none`, `None` when unknown
+fn parse_layout(name: &str) -> Option<Option<WindowLayout>> {
+    if name.eq_ignore_ascii_case("none") {
+        return Some(None);
+    }
+    WindowLayout::from_str(name)
+        .or_else(|| Some(WindowLayout::Custom(name.to_string())).filter(WindowLayout::is_known))
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_heuristic_applies_without_overrides() {
+        let settings = Settings::default();
+        assert_eq!(settings.default_layout_for(1), None);
+        assert_eq!(
+            settings.default_layout_for(2),
+            Some(WindowLayout::EvenHorizontal)
+        );
+        assert_eq!(
+            settings.default_layout_for(3),
+            Some(WindowLayout::MainVertical)
+        );
+        assert_eq!(settings.default_layout_for(6), Some(WindowLayout::Tiled));
+    }
+
+    #[test]
+    fn wizard_defaults_are_optional() {
+        let settings: Settings = serde_yaml::from_str("theme: simple\n").unwrap();
+        assert_eq!(settings.theme.as_deref(), Some("simple"));
+        assert_eq!(settings.wizard.direnv_allow, None);
+
+        let yaml = "wizard:\n  layout: tiled\n  panes: 2\n  direnv_allow: true\n";
+        let settings: Settings = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(settings.wizard.layout.as_deref(), Some("tiled"));
+        assert_eq!(settings.wizard.panes, Some(2));
+        assert!(!settings.wizard.add_another_window);
+        assert_eq!(settings.wizard.direnv_allow, Some(true));
+    }
+
+    #[test]
+    fn user_overrides_use_largest_matching_count() {
+        let yaml = "default_layouts:\n  1: none\n  2: even-vertical\n  5: main-horizontal\n";
+        let settings: Settings = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(settings.default_layout_for(1), None);
+        assert_eq!(
+            settings.default_layout_for(4),
+            Some(WindowLayout::EvenVertical)
+        );
+        assert_eq!(
+            settings.default_layout_for(8),
+            Some(WindowLayout::MainHorizontal)
+        );
+        assert!(settings.warnings().is_empty());
+    }
+
+    #[test]
+    fn unknown_layouts_warn_and_fall_back() {
+        let (custom, _) = WindowLayout::from_split("h70").unwrap();
+        let WindowLayout::Custom(layout) = &custom else {
+            unreachable!()
+        };
+        let yaml = format!("default_layouts:\n  2: sideways\n  3: '{}'\n", layout);
+        let settings: Settings = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(
+            settings.default_layout_for(2),
+            Some(WindowLayout::EvenHorizontal)
+        );
+        assert_eq!(settings.default_layout_for(3), Some(custom));
+        let warnings = settings.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("default_layouts.2: unknown layout 'sideways'"));
+    }
+
+    #[test]
+    fn unknown_wizard_layout_warns() {
+        let settings: Settings = serde_yaml::from_str("wizard:\n  layout: split\n").unwrap();
+        assert!(settings.warnings().is_empty());
+        let settings: Settings = serde_yaml::from_str("wizard:\n  layout: grid\n").unwrap();
+        let warnings = settings.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("wizard.layout: unknown layout 'grid'"));
+    }
+}
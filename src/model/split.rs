@@ -0,0 +1,335 @@
+//! A small split-description DSL compiled into tmux layout strings.
+//!
+//! - `h70` splits left/right: a 70% pane on the left, the rest on the right.
+//! - `v30` splits top/bottom: a 30% pane on top, the rest below.
+//! - `h70[v50,v50]`: 70% left pane; the remaining area is described by the
+//!   expression in brackets (here, two stacked 50% panes).
+//! - `h33,h33,h34`: a comma list of panes side by side (all the same
+//!   direction). When the sizes add up to less than 100 the rest becomes one
+//!   more pane.
+
+use anyhow::{Result, anyhow, bail};
+
+/// Window size the layout is computed for; tmux scales it to the real window
+const LAYOUT_WIDTH: u32 = 200;
+const LAYOUT_HEIGHT: u32 = 50;
+
+/// Direction in which a cell is split
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// Left/right (`h`), `{}` in tmux layout strings
+    Horizontal,
+    /// Top/bottom (`v`), `[]` in tmux layout strings
+    Vertical,
+}
+
+/// Parsed layout tree
+#[derive(Debug, Clone)]
+enum Cell {
+    Pane,
+    Split {
+        direction: Direction,
+        children: Vec<(f64, Cell)>,
+    },
+}
+
+/// A DSL expression compiled into a tmux layout string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledLayout {
+    /// Full tmux layout string including checksum
+    pub layout: String,
+    /// Number of panes the layout expects
+    pub panes: usize,
+}
+
+/// Compile a split description into a tmux layout string
+pub fn compile(dsl: &str) -> Result<CompiledLayout> {
+    let mut parser = Parser {
+        input: dsl.trim().as_bytes(),
+        pos: 0,
+    };
+    let cell = parser.parse_node()?;
+    if parser.pos != parser.input.len() {
+        bail!(
+            "Unexpected '{}' at position {} in split '{}'",
+            parser.input[parser.pos] as char,
+            parser.pos + 1,
+            dsl
+        );
+    }
+
+    let mut body = String::new();
+    let mut next_pane = 0;
+    render(
+        &cell,
+        LAYOUT_WIDTH,
+        LAYOUT_HEIGHT,
+        0,
+        0,
+        &mut next_pane,
+        &mut body,
+    )?;
+
+    Ok(CompiledLayout {
+        layout: format!("{:04x},{}", checksum(&body), body),
+        panes: next_pane,
+    })
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    /// node := dir size "[" node "]" | dir size ("," dir size)*
+    fn parse_node(&mut self) -> Result<Cell> {
+        let (direction, size) = self.parse_segment()?;
+
+        if self.peek() == Some(b'[') {
+            self.pos += 1;
+            let rest = self.parse_node()?;
+            if self.peek() != Some(b']') {
+                bail!("Expected ']' at position {}", self.pos + 1);
+            }
+            self.pos += 1;
+
+            let remainder = 100.0 - size;
+            let mut children = vec![(size, Cell::Pane)];
+            match rest {
+                // Same-direction remainder merges into this split
+                Cell::Split {
+                    direction: d,
+                    children: inner,
+                } if d == direction => {
+                    children.extend(inner.into_iter().map(|(w, c)| (w * remainder / 100.0, c)));
+                }
+                other => children.push((remainder, other)),
+            }
+            return Ok(Cell::Split {
+                direction,
+                children,
+            });
+        }
+
+        // Comma list of panes in one direction
+        let mut sizes = vec![size];
+        while self.peek() == Some(b',') {
+            self.pos += 1;
+            let (d, s) = self.parse_segment()?;
+            if d != direction {
+                bail!("Mixed directions in list at position {}", self.pos);
+            }
+            sizes.push(s);
+        }
+
+        let total: f64 = sizes.iter().sum();
+        if total > 100.0 {
+            bail!("Pane sizes add up to {}%, more than 100%", total);
+        }
+
+        let mut children: Vec<(f64, Cell)> = sizes.into_iter().map(|s| (s, Cell::Pane)).collect();
+        if total < 100.0 {
+            children.push((100.0 - total, Cell::Pane));
+        }
+
+        if children.len() == 1 {
+            Ok(Cell::Pane)
+        } else {
+            Ok(Cell::Split {
+                direction,
+                children,
+            })
+        }
+    }
+
+    /// segment := ("h" | "v") percentage
+    fn parse_segment(&mut self) -> Result<(Direction, f64)> {
+        let direction = match self.peek() {
+            Some(b'h') | Some(b'H') => Direction::Horizontal,
+            Some(b'v') | Some(b'V') => Direction::Vertical,
+            Some(c) => bail!(
+                "Expected 'h' or 'v' at position {}, found '{}'",
+                self.pos + 1,
+                c as char
+            ),
+            None => bail!("Unexpected end of split description"),
+        };
+        self.pos += 1;
+
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits = std::str::from_utf8(&self.input[start..self.pos])?;
+        let size: u32 = digits
+            .parse()
+            .map_err(|_| anyhow!("Expected a percentage at position {}", start + 1))?;
+        if !(1..=100).contains(&size) {
+            bail!(
+                "Percentage {} at position {} must be between 1 and 100",
+                size,
+                start + 1
+            );
+        }
+
+        Ok((direction, f64::from(size)))
+    }
+}
+
+/// Render a cell as `WxH,X,Y` followed by its pane id or children
+fn render(
+    cell: &Cell,
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    next_pane: &mut usize,
+    out: &mut String,
+) -> Result<()> {
+    out.push_str(&format!("{}x{},{},{}", width, height, x, y));
+
+    let Cell::Split {
+        direction,
+        children,
+    } = cell
+    else {
+        out.push_str(&format!(",{}", next_pane));
+        *next_pane += 1;
+        return Ok(());
+    };
+
+    // One-cell borders between siblings
+    let length = match direction {
+        Direction::Horizontal => width,
+        Direction::Vertical => height,
+    };
+    let borders = children.len() as u32 - 1;
+    let available = length
+        .checked_sub(borders)
+        .ok_or_else(|| anyhow!("Too many panes to fit the layout"))?;
+
+    let total: f64 = children.iter().map(|(w, _)| w).sum();
+    let mut sizes: Vec<u32> = children
+        .iter()
+        .map(|(w, _)| (f64::from(available) * w / total).floor() as u32)
+        .collect();
+    let used: u32 = sizes.iter().sum();
+    if let Some(last) = sizes.last_mut() {
+        *last += available - used;
+    }
+    if sizes.contains(&0) {
+        bail!("A pane in the split is too small to fit the layout");
+    }
+
+    let (open, close) = match direction {
+        Direction::Horizontal => ('{', '}'),
+        Direction::Vertical => ('[', ']'),
+    };
+    out.push(open);
+
+    let mut offset = 0;
+    for (i, ((_, child), size)) in children.iter().zip(&sizes).enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        match direction {
+            Direction::Horizontal => render(child, *size, height, x + offset, y, next_pane, out)?,
+            Direction::Vertical => render(child, width, *size, x, y + offset, next_pane, out)?,
+        }
+        offset += size + 1;
+    }
+
+    out.push(close);
+    Ok(())
+}
+
+/// Whether `layout` is a tmux layout string (`csum,WxH,...`) whose checksum
+/// matches its body, as `select-layout` requires
+pub fn is_layout_string(layout: &str) -> bool {
+    let Some((sum, body)) = layout.split_once(',') else {
+        return false;
+    };
+    let size = body.split(',').next().unwrap_or_default();
+    sum.len() == 4
+        && u16::from_str_radix(sum, 16).is_ok_and(|sum| sum == checksum(body))
+        && size
+            .split_once('x')
+            .is_some_and(|(w, h)| w.parse::<u32>().is_ok() && h.parse::<u32>().is_ok())
+}
+
+/// How many panes a tmux layout string describes: every `WxH` cell that
+/// isn't split further, or None when it isn't a layout string
+pub fn layout_panes(layout: &str) -> Option<usize> {
+    if !is_layout_string(layout) {
+        return None;
+    }
+    let cells = layout.matches('x').count();
+    let splits = layout.matches(['{', '[']).count();
+    Some(cells - splits)
+}
+
+/// tmux's layout checksum (see `layout_checksum` in tmux's layout-custom.c)
+fn checksum(layout: &str) -> u16 {
+    layout.bytes().fold(0u16, |csum, b| {
+        let rotated = (csum >> 1) | ((csum & 1) << 15);
+        rotated.wrapping_add(u16::from(b))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiles_left_pane_with_split_right_column() {
+        let compiled = compile("h70[v50,v50]").unwrap();
+        assert_eq!(compiled.panes, 3);
+
+        let (_, body) = compiled.layout.split_once(',').unwrap();
+        assert_eq!(
+            body,
+            "200x50,0,0{139x50,0,0,0,60x50,140,0[60x24,140,0,1,60x25,140,25,2]}"
+        );
+    }
+
+    #[test]
+    fn counts_the_panes_of_a_layout_string() {
+        for (dsl, panes) in [("h70[v50,v50]", 3), ("h33,h33,h34", 3), ("v30", 2)] {
+            let compiled = compile(dsl).unwrap();
+            assert_eq!(layout_panes(&compiled.layout), Some(panes), "{}", dsl);
+        }
+        assert_eq!(layout_panes("tiled"), None);
+    }
+
+    #[test]
+    fn list_sizes_below_100_get_a_remainder_pane() {
+        assert_eq!(compile("h33,h33").unwrap().panes, 3);
+        assert_eq!(compile("v30").unwrap().panes, 2);
+        assert_eq!(compile("h100").unwrap().panes, 1);
+    }
+
+    #[test]
+    fn checksum_matches_tmux() {
+        // Example from the tmux manual page (select-layout)
+        assert_eq!(checksum("159x48,0,0{79x48,0,0,79x48,80,0}"), 0xbb62);
+        assert!(is_layout_string("bb62,159x48,0,0{79x48,0,0,79x48,80,0}"));
+        assert!(!is_layout_string("bb63,159x48,0,0{79x48,0,0,79x48,80,0}"));
+        assert!(!is_layout_string("main-verticle"));
+    }
+
+    #[test]
+    fn rejects_invalid_descriptions() {
+        assert!(compile("").is_err());
+        assert!(compile("x50").is_err());
+        assert!(compile("h60,h60").is_err());
+        assert!(compile("h50,v50").is_err());
+        assert!(compile("h70[v50").is_err());
+        assert!(compile("h0").is_err());
+        assert!(compile("h50]").is_err());
+    }
+}
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use super::Pane;
+use super::split;
 
 /// Available tmux window layouts
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum WindowLayout {
     Tiled,
@@ -11,18 +13,178 @@ pub enum WindowLayout {
     EvenVertical,
     MainHorizontal,
     MainVertical,
+    /// A raw tmux layout string (e.g. compiled from the split DSL)
+    #[serde(untagged)]
+    Custom(String),
 }
 
+/// Size tmux gives sessions started without a client (its `default-size`)
+pub const DEFAULT_TERMINAL_SIZE: (usize, usize) = (80, 24);
+
+/// Narrowest pane, in columns, that still fits a prompt and some output
+const MIN_PANE_WIDTH: usize = 20;
+/// Shortest pane, in lines, that still shows a few lines of output
+const MIN_PANE_HEIGHT: usize = 4;
+
+/// A layout that is a poor fit for its window's pane count
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutWarning {
+    pub message: String,
+    /// A built-in layout that fits, if any does
+    pub alternative: Option<WindowLayout>,
+}
+
+/// Built-in layouts, in the order offered by the wizard
+const BUILTIN_LAYOUTS: &[WindowLayout] = &[
+    WindowLayout::Tiled,
+    WindowLayout::EvenHorizontal,
+    WindowLayout::EvenVertical,
+    WindowLayout::MainHorizontal,
+    WindowLayout::MainVertical,
+];
+
 impl WindowLayout {
     /// Get all available layouts as a list
     pub fn all() -> &'static [WindowLayout] {
-        &[
-            WindowLayout::Tiled,
-            WindowLayout::EvenHorizontal,
-            WindowLayout::EvenVertical,
-            WindowLayout::MainHorizontal,
-            WindowLayout::MainVertical,
-        ]
+        BUILTIN_LAYOUTS
+    }
+
+    /// The tmux option that sizes the main pane for this layout, if it has one
+    pub fn main_pane_option(&self) -> Option<&'static str> {
+        match self {
+            WindowLayout::MainHorizontal => Some("main-pane-height"),
+            WindowLayout::MainVertical => Some("main-pane-width"),
+            _ => None,
+        }
+    }
+
+    /// Sensible default layout for a pane count: none for a single pane,
+    /// side by side for two, a main pane for three, tiled beyond that
+    pub fn default_for_panes(pane_count: usize) -> Option<Self> {
+        match pane_count {
+            0 | 1 => None,
+            2 => Some(WindowLayout::EvenHorizontal),
+            3 => Some(WindowLayout::MainVertical),
+            _ => Some(WindowLayout::Tiled),
+        }
+    }
+
+    /// Compile a split description (e.g. `h70[v50,v50]`) into a custom layout,
+    /// returning it with the number of panes it expects
+    pub fn from_split(dsl: &str) -> anyhow::Result<(Self, usize)> {
+        let compiled = split::compile(dsl)?;
+        Ok((WindowLayout::Custom(compiled.layout), compiled.panes))
+    }
+
+    /// How many panes a custom layout was made for; None for built-in
+    /// layouts, which fit any count
+    pub fn custom_panes(&self) -> Option<usize> {
+        match self {
+            WindowLayout::Custom(layout) => split::layout_panes(layout),
+            _ => None,
+        }
+    }
+
+    /// Approximate size of the smallest pane when `panes` panes share a
+    /// `width`x`height` terminal, or None for custom layouts
+    fn smallest_pane(
+        &self,
+        panes: usize,
+        (width, height): (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let n = panes.max(1);
+        let others = (n - 1).max(1);
+        match self {
+            WindowLayout::EvenHorizontal => Some((width / n, height)),
+            WindowLayout::EvenVertical => Some((width, height / n)),
+            WindowLayout::MainVertical => Some((width / 2, height / others)),
+            WindowLayout::MainHorizontal => Some((width / others, height / 2)),
+            WindowLayout::Tiled => {
+                let columns = (1..=n).find(|c| c * c >= n).unwrap_or(n);
+                Some((width / columns, height / n.div_ceil(columns)))
+            }
+            WindowLayout::Custom(_) => None,
+        }
+    }
+
+    /// Why this layout is degenerate for `panes` panes in a terminal of
+    /// `size` (columns, lines), with a built-in alternative that fits
+    pub fn warning(&self, panes: usize, size: (usize, usize)) -> Option<LayoutWarning> {
+        if matches!(self, WindowLayout::Custom(_)) {
+            return None;
+        }
+        if panes <= 1 {
+            return Some(LayoutWarning {
+                message: format!(
+                    "{} has no effect with a single pane; leave the layout unset",
+                    self
+                ),
+                alternative: None,
+            });
+        }
+        let fits = |layout: &WindowLayout| {
+            layout
+                .smallest_pane(panes, size)
+                .is_some_and(|(w, h)| w >= MIN_PANE_WIDTH && h >= MIN_PANE_HEIGHT)
+        };
+        if fits(self) {
+            return None;
+        }
+
+        let (width, height) = self.smallest_pane(panes, size)?;
+        let alternative = Self::all()
+            .iter()
+            .find(|layout| *layout != self && fits(layout))
+            .cloned();
+        let mut message = format!(
+            "{} squeezes {} panes to about {}x{} at {}x{}",
+            self, panes, width, height, size.0, size.1
+        );
+        match &alternative {
+            Some(layout) => message.push_str(&format!("; {} fits better", layout)),
+            None => message.push_str("; consider splitting them across windows"),
+        }
+        Some(LayoutWarning {
+            message,
+            alternative,
+        })
+    }
+
+    /// Whether tmux accepts this layout: a built-in name, or a layout string
+    /// with a valid checksum
+    pub fn is_known(&self) -> bool {
+        match self {
+            WindowLayout::Custom(layout) => split::is_layout_string(layout),
+            _ => true,
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .find(|layout| layout.to_string() == s.to_lowercase())
+            .cloned()
+    }
+}
+
+/// The built-in layouts as command-line values; custom layout strings
+/// aren't offered
+impl clap::ValueEnum for WindowLayout {
+    fn value_variants<'a>() -> &'a [Self] {
+        BUILTIN_LAYOUTS
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            WindowLayout::Custom(_) => None,
+            layout => Some(clap::builder::PossibleValue::new(match layout {
+                WindowLayout::Tiled => "tiled",
+                WindowLayout::EvenHorizontal => "even-horizontal",
+                WindowLayout::EvenVertical => "even-vertical",
+                WindowLayout::MainHorizontal => "main-horizontal",
+                _ => "main-vertical",
+            })),
+        }
     }
 }
 
@@ -34,6 +196,7 @@ impl std::fmt::Display for WindowLayout {
             WindowLayout::EvenVertical => write!(f, "even-vertical"),
             WindowLayout::MainHorizontal => write!(f, "main-horizontal"),
             WindowLayout::MainVertical => write!(f, "main-vertical"),
+            WindowLayout::Custom(layout) => write!(f, "{}", layout),
         }
     }
 }
@@ -45,7 +208,23 @@ pub struct Window {
     pub window_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<WindowLayout>,
+    /// Explicit tmux window index (tmuxp `window_index`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_index: Option<u32>,
+    /// Environment variables exported in every pane of this window
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment: BTreeMap<String, String>,
+    /// Select this window when the session is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<bool>,
+    /// tmux window options applied by tmuxp (e.g. `main-pane-height`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
+    #[serde(deserialize_with = "super::pane::deserialize_panes")]
     pub panes: Vec<Pane>,
+    /// Keys tmuxify doesn't model, passed through to tmuxp unchanged
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 impl Window {
@@ -54,17 +233,142 @@ impl Window {
         Self {
             window_name: name,
             layout,
+            window_index: None,
+            environment: BTreeMap::new(),
+            focus: None,
+            options: BTreeMap::new(),
             panes,
+            extra: BTreeMap::new(),
         }
     }
 
+    /// Size the main pane of a main-horizontal/main-vertical layout.
+    /// `size` is a cell count (`40`) or a percentage (`70%`).
+    pub fn set_main_pane_size(&mut self, size: &str) -> anyhow::Result<()> {
+        let option = self
+            .layout
+            .as_ref()
+            .and_then(|l| l.main_pane_option())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Main pane size only applies to main-horizontal/main-vertical layouts"
+                )
+            })?;
+
+        if !is_valid_pane_size(size) {
+            anyhow::bail!(
+                "Invalid main pane size '{}': use cells (40) or a percentage (70%)",
+                size
+            );
+        }
+
+        self.options.insert(option.to_string(), size.to_string());
+        Ok(())
+    }
+
+    /// Layout warning for this window in a terminal of `size` (columns, lines)
+    pub fn layout_warning(&self, size: (usize, usize)) -> Option<LayoutWarning> {
+        self.layout.as_ref()?.warning(self.panes.len(), size)
+    }
+
     /// Create a simple window with a single empty pane
     #[allow(dead_code)]
     pub fn simple() -> Self {
         Self {
             window_name: None,
             layout: None,
+            window_index: None,
+            environment: BTreeMap::new(),
+            focus: None,
+            options: BTreeMap::new(),
             panes: vec![Pane::empty()],
+            extra: BTreeMap::new(),
         }
     }
 }
+
+/// Check a pane size is a cell count or a percentage between 1% and 99%
+pub fn is_valid_pane_size(size: &str) -> bool {
+    match size.strip_suffix('%') {
+        Some(pct) => pct.parse::<u8>().is_ok_and(|p| (1..=99).contains(&p)),
+        None => size.parse::<u32>().is_ok_and(|cells| cells > 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degenerate_layouts_warn_with_a_fitting_alternative() {
+        let size = DEFAULT_TERMINAL_SIZE;
+        let single = WindowLayout::MainVertical.warning(1, size).unwrap();
+        assert_eq!(single.alternative, None);
+        assert!(single.message.contains("single pane"));
+
+        let narrow = WindowLayout::EvenHorizontal.warning(8, size).unwrap();
+        assert_eq!(narrow.alternative, Some(WindowLayout::Tiled));
+        assert!(narrow.message.contains("about 10x24"));
+
+        assert_eq!(WindowLayout::EvenHorizontal.warning(8, (240, 60)), None);
+        assert_eq!(WindowLayout::Tiled.warning(4, size), None);
+        assert_eq!(WindowLayout::MainVertical.warning(3, size), None);
+        assert!(
+            WindowLayout::Tiled
+                .warning(40, size)
+                .unwrap()
+                .alternative
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn custom_layout_serializes_as_plain_string() {
+        let (layout, panes) = WindowLayout::from_split("h50").unwrap();
+        assert_eq!(panes, 2);
+
+        let w = Window::new(
+            None,
+            Some(layout.clone()),
+            vec![Pane::empty(), Pane::empty()],
+        );
+        let yaml = serde_yaml::to_string(&w).unwrap();
+        let parsed: Window = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.layout, Some(layout));
+
+        let builtin: Window = serde_yaml::from_str("layout: main-vertical\npanes: []\n").unwrap();
+        assert_eq!(builtin.layout, Some(WindowLayout::MainVertical));
+    }
+
+    #[test]
+    fn main_pane_size_uses_layout_specific_option() {
+        let mut w = Window::new(None, Some(WindowLayout::MainVertical), vec![Pane::empty()]);
+        w.set_main_pane_size("70%").unwrap();
+        assert_eq!(
+            w.options.get("main-pane-width").map(String::as_str),
+            Some("70%")
+        );
+
+        let mut w = Window::new(
+            None,
+            Some(WindowLayout::MainHorizontal),
+            vec![Pane::empty()],
+        );
+        w.set_main_pane_size("30").unwrap();
+        assert_eq!(
+            w.options.get("main-pane-height").map(String::as_str),
+            Some("30")
+        );
+    }
+
+    #[test]
+    fn main_pane_size_rejected_for_other_layouts_and_bad_values() {
+        let mut tiled = Window::new(None, Some(WindowLayout::Tiled), vec![Pane::empty()]);
+        assert!(tiled.set_main_pane_size("50%").is_err());
+
+        let mut w = Window::new(None, Some(WindowLayout::MainVertical), vec![Pane::empty()]);
+        assert!(w.set_main_pane_size("150%").is_err());
+        assert!(w.set_main_pane_size("wide").is_err());
+        assert!(w.set_main_pane_size("0").is_err());
+    }
+}
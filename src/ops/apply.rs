@@ -0,0 +1,119 @@
+//! `tmuxify apply`: regenerate a project's files, trust them with
+//! `direnv allow`, and load the session, reporting each step.
+
+use anyhow::Result;
+use console::style;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::Args;
+use crate::model::Settings;
+use crate::ops::process::SystemRunner;
+use crate::ops::{load, open, write};
+
+/// Which steps of `apply` to run
+#[derive(Debug, Clone, Copy)]
+pub struct Steps {
+    pub write: bool,
+    pub allow: bool,
+    pub load: bool,
+    /// Load the session without attaching to it
+    pub detach: bool,
+}
+
+/// Write, allow, and load the project's session in one go
+pub fn run(args: &Args, steps: Steps) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let (mut config_path, location) =
+        load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let config = load::load_config(&config_path)?;
+    let runner = SystemRunner::new(args.dry_run);
+
+    if steps.write {
+        let options = write::WriteOptions {
+            dry_run: args.dry_run,
+            force: args.force,
+            envrc: write::envrc_options(&project_dir, &Settings::load()?),
+            format: args.format,
+            ..Default::default()
+        };
+        let files = write::render_config(&config, location, &project_dir, &options)?;
+        if up_to_date(&files) {
+            done("Files are up to date");
+        } else {
+            let result = write::write_config(&config, location, &project_dir, &options)?;
+            if !args.dry_run {
+                done(&format!(
+                    "Wrote {} and {}",
+                    result.tmuxp_path.display(),
+                    result.envrc_path.display()
+                ));
+            }
+            config_path = result.tmuxp_path;
+        }
+    } else {
+        skipped("Write files", "--no-write");
+    }
+
+    if steps.allow {
+        write::run_direnv_allow(&runner, &project_dir)?;
+        if !args.dry_run {
+            done("Trusted .envrc with direnv allow");
+        }
+    } else {
+        skipped("direnv allow", "--no-allow");
+    }
+
+    if steps.load {
+        open::start_session(&runner, &project_dir, &config, &config_path, steps.detach)?;
+        if steps.detach && !args.dry_run {
+            done(&format!(
+                "Session {} is running; attach with tmuxify open",
+                config.session_name
+            ));
+        }
+    } else {
+        skipped("Load session", "--no-load");
+    }
+    Ok(())
+}
+
+/// Whether every rendered file already has this content on disk
+fn up_to_date(files: &[(PathBuf, String)]) -> bool {
+    files
+        .iter()
+        .all(|(path, content)| fs::read_to_string(path).is_ok_and(|c| &c == content))
+}
+
+fn done(message: &str) {
+    println!("{} {}", style("✓").green().bold(), message);
+}
+
+fn skipped(step: &str, flag: &str) {
+    println!(
+        "{} {} {}",
+        style("○").dim(),
+        step,
+        style(format!("(skipped, {})", flag)).dim()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn files_matching_disk_are_up_to_date() {
+        let dir = tempdir().unwrap();
+        let envrc = dir.path().join(".envrc");
+        let files = vec![(envrc.clone(), "layout tmux\n".to_string())];
+        assert!(!up_to_date(&files));
+
+        fs::write(&envrc, "layout tmux\n").unwrap();
+        assert!(up_to_date(&files));
+
+        fs::write(&envrc, "tmuxp load .\n").unwrap();
+        assert!(!up_to_date(&files));
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use crate::model::{Pane, Window, WindowLayout};
+use crate::ops::detect::{self, ProjectType};
+
+/// Pre-built window shapes offered as quick picks in the wizard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Archetype {
+    Editor,
+    ServerLogs,
+    TestsWatcher,
+    /// The project's docker compose stack and its logs
+    Services,
+    Scratch,
+}
+
+impl Archetype {
+    /// Get all available archetypes as a list
+    pub fn all() -> &'static [Archetype] {
+        &[
+            Archetype::Editor,
+            Archetype::ServerLogs,
+            Archetype::TestsWatcher,
+            Archetype::Services,
+            Archetype::Scratch,
+        ]
+    }
+
+    /// Archetypes that make sense for the project: `Services` only with a
+    /// docker compose file
+    pub fn available(project_dir: &Path) -> Vec<Archetype> {
+        let has_compose = detect::compose_file(project_dir).is_some();
+        Archetype::all()
+            .iter()
+            .copied()
+            .filter(|a| *a != Archetype::Services || has_compose)
+            .collect()
+    }
+
+    /// Label for the wizard's quick pick, naming the command it will run
+    pub fn describe(&self, project_dir: &Path, project_type: ProjectType) -> String {
+        let window = self.build(project_dir, project_type);
+        match window.panes.iter().find_map(|p| p.shell_command.first()) {
+            Some(command) if *self != Archetype::Editor => format!("{} ({})", self, command),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Build a window for this archetype, tailored to the project type
+    pub fn build(&self, project_dir: &Path, project_type: ProjectType) -> Window {
+        match self {
+            Archetype::Editor => Window::new(
+                Some("editor".to_string()),
+                Some(WindowLayout::MainVertical),
+                vec![
+                    Pane::new(vec!["${EDITOR:-vim} .".to_string()]),
+                    Pane::new(vec!["git status".to_string()]),
+                ],
+            ),
+            Archetype::ServerLogs => {
+                let server = server_command(project_dir, project_type);
+                Window::new(
+                    Some("server".to_string()),
+                    Some(WindowLayout::MainHorizontal),
+                    vec![Pane::new(server.into_iter().collect()), Pane::empty()],
+                )
+            }
+            Archetype::TestsWatcher => {
+                let watcher = test_watch_command(project_dir, project_type);
+                Window::new(
+                    Some("tests".to_string()),
+                    None,
+                    vec![Pane::new(watcher.into_iter().collect())],
+                )
+            }
+            Archetype::Services => Window::new(
+                Some("services".to_string()),
+                Some(WindowLayout::MainHorizontal),
+                vec![
+                    Pane::new(vec!["docker compose up".to_string()]),
+                    Pane::new(vec!["docker compose logs -f".to_string()]),
+                ],
+            ),
+            Archetype::Scratch => {
+                Window::new(Some("scratch".to_string()), None, vec![Pane::empty()])
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Archetype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Archetype::Editor => write!(f, "editor"),
+            Archetype::ServerLogs => write!(f, "server + logs"),
+            Archetype::TestsWatcher => write!(f, "tests watcher"),
+            Archetype::Services => write!(f, "docker compose"),
+            Archetype::Scratch => write!(f, "scratch shell"),
+        }
+    }
+}
+
+/// Commands offered first when entering a pane's commands: the dev server,
+/// the test watcher, and `docker compose up` when there is a compose file
+pub(crate) fn suggested_commands(project_dir: &Path, project_type: ProjectType) -> Vec<String> {
+    let mut commands: Vec<String> = server_command(project_dir, project_type)
+        .into_iter()
+        .chain(test_watch_command(project_dir, project_type))
+        .collect();
+    if detect::compose_file(project_dir).is_some() {
+        commands.push("docker compose up".to_string());
+    }
+    commands
+}
+
+/// Command that starts a development server for the project type
+pub(crate) fn server_command(project_dir: &Path, project_type: ProjectType) -> Option<String> {
+    match project_type {
+        ProjectType::Rust => Some("cargo run".to_string()),
+        ProjectType::Node => Some(format!("{} dev", detect::package_runner(project_dir))),
+        ProjectType::Python => Some("python -m app".to_string()),
+        ProjectType::Go => Some("go run .".to_string()),
+        ProjectType::Unknown => None,
+    }
+}
+
+/// Command that re-runs tests on change for the project type
+pub(crate) fn test_watch_command(project_dir: &Path, project_type: ProjectType) -> Option<String> {
+    match project_type {
+        ProjectType::Rust => Some("cargo watch -x test".to_string()),
+        ProjectType::Node => Some(format!(
+            "{} test -- --watch",
+            detect::package_runner(project_dir)
+        )),
+        ProjectType::Python => Some("ptw".to_string()),
+        ProjectType::Go => Some("gotestsum --watch".to_string()),
+        ProjectType::Unknown => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_watcher_uses_project_type() {
+        let w = Archetype::TestsWatcher.build(Path::new("/nonexistent"), ProjectType::Rust);
+        assert_eq!(
+            w.panes[0].shell_command,
+            vec!["cargo watch -x test".to_string()]
+        );
+    }
+
+    #[test]
+    fn compose_file_adds_services_and_suggestions() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!Archetype::available(dir.path()).contains(&Archetype::Services));
+        assert_eq!(
+            suggested_commands(dir.path(), ProjectType::Rust),
+            ["cargo run", "cargo watch -x test"]
+        );
+
+        std::fs::write(dir.path().join("compose.yaml"), "services: {}\n").unwrap();
+        assert!(Archetype::available(dir.path()).contains(&Archetype::Services));
+        assert_eq!(
+            suggested_commands(dir.path(), ProjectType::Unknown),
+            ["docker compose up"]
+        );
+        assert_eq!(
+            Archetype::ServerLogs.describe(dir.path(), ProjectType::Rust),
+            "server + logs (cargo run)"
+        );
+        assert_eq!(
+            Archetype::Editor.describe(dir.path(), ProjectType::Rust),
+            "editor"
+        );
+    }
+
+    #[test]
+    fn unknown_project_gets_empty_panes() {
+        let w = Archetype::ServerLogs.build(Path::new("/nonexistent"), ProjectType::Unknown);
+        assert_eq!(w.panes.len(), 2);
+        assert!(w.panes.iter().all(|p| p.shell_command.is_empty()));
+    }
+}
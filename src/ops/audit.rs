@@ -0,0 +1,317 @@
+use anyhow::Result;
+use console::style;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::model::{Config, Settings};
+use crate::ops::{history, load};
+
+/// Backups older than this are reported as stale
+const STALE_AFTER_DAYS: u64 = 30;
+
+/// Kind of file tmuxify creates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ItemKind {
+    HomeConfig,
+    ProjectConfig,
+    Envrc,
+    Backup,
+    State,
+}
+
+impl ItemKind {
+    fn heading(self) -> &'static str {
+        match self {
+            ItemKind::HomeConfig => "tmuxp configs (~/.tmuxp)",
+            ItemKind::ProjectConfig => "Project tmuxp configs",
+            ItemKind::Envrc => ".envrc files",
+            ItemKind::Backup => "Backups",
+            ItemKind::State => "State files",
+        }
+    }
+}
+
+/// A file found by the audit
+#[derive(Debug, Clone)]
+struct Item {
+    kind: ItemKind,
+    path: PathBuf,
+    age_days: Option<u64>,
+    /// Why the file looks orphaned, if it does
+    orphan: Option<String>,
+}
+
+impl Item {
+    fn new(kind: ItemKind, path: PathBuf) -> Self {
+        let age_days = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .map(|age| age.as_secs() / 86_400);
+        Self {
+            kind,
+            path,
+            age_days,
+            orphan: None,
+        }
+    }
+
+    fn stale(&self) -> bool {
+        self.kind == ItemKind::Backup && self.age_days.is_some_and(|d| d > STALE_AFTER_DAYS)
+    }
+}
+
+/// List every tmuxify-managed file found on this machine
+pub fn run() -> Result<()> {
+    let items = collect()?;
+
+    if items.is_empty() {
+        println!("No tmuxify-managed files found.");
+        return Ok(());
+    }
+
+    for kind in [
+        ItemKind::HomeConfig,
+        ItemKind::ProjectConfig,
+        ItemKind::Envrc,
+        ItemKind::Backup,
+        ItemKind::State,
+    ] {
+        let group: Vec<&Item> = items.iter().filter(|i| i.kind == kind).collect();
+        if group.is_empty() {
+            continue;
+        }
+        println!("{}", style(format!("{}:", kind.heading())).bold());
+        for item in group {
+            let marker = if item.orphan.is_some() {
+                style("✗").red().bold()
+            } else if item.stale() {
+                style("⚠").yellow().bold()
+            } else {
+                style("✓").green().bold()
+            };
+            println!(
+                "  {} {} {}",
+                marker,
+                item.path.display(),
+                style(format_age(item.age_days)).dim()
+            );
+            if let Some(reason) = &item.orphan {
+                println!("    {}", style(reason).red());
+            } else if item.stale() {
+                println!("    {}", style("stale backup").yellow());
+            }
+        }
+        println!();
+    }
+
+    let orphans = items.iter().filter(|i| i.orphan.is_some()).count();
+    let stale = items.iter().filter(|i| i.stale()).count();
+    println!(
+        "{} file(s): {} orphaned, {} stale backup(s)",
+        items.len(),
+        orphans,
+        stale
+    );
+
+    Ok(())
+}
+
+/// Gather files from `~/.tmuxp`, projects in the history log, and tmuxify's own state
+fn collect() -> Result<Vec<Item>> {
+    let mut items = Vec::new();
+    let mut projects: Vec<PathBuf> = history::projects()?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+
+    if let Some(tmuxp_dir) = dirs::home_dir().map(|h| h.join(".tmuxp")) {
+        for path in files_in(&tmuxp_dir) {
+            if is_backup(&path) {
+                items.push(Item::new(ItemKind::Backup, path));
+                continue;
+            }
+            if !is_config(&path) {
+                continue;
+            }
+            let mut item = Item::new(ItemKind::HomeConfig, path);
+            match load::load_config(&item.path) {
+                Ok(config) => {
+                    item.orphan = orphan_reason(&item.path, &config);
+                    let start = project_dir(&config);
+                    if let Some(start) = start.filter(|s| s.is_dir() && !projects.contains(s)) {
+                        projects.push(start);
+                    }
+                }
+                Err(e) => item.orphan = Some(format!("unreadable: {}", e)),
+            }
+            items.push(item);
+        }
+    }
+
+    for project in &projects {
+        if !project.is_dir() {
+            continue;
+        }
+        for path in files_in(project) {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if is_backup(&path) && (name.starts_with(".tmuxp") || name.starts_with(".envrc")) {
+                items.push(Item::new(ItemKind::Backup, path));
+            } else if name.starts_with(".tmuxp") && is_config(&path) {
+                items.push(Item::new(ItemKind::ProjectConfig, path));
+            } else if name == ".envrc" && loads_tmuxp(&path) {
+                items.push(Item::new(ItemKind::Envrc, path));
+            }
+        }
+    }
+
+    let state = [history::path(), Settings::path()];
+    for path in state.into_iter().flatten().filter(|p| p.is_file()) {
+        items.push(Item::new(ItemKind::State, path));
+    }
+
+    // History entries for projects that were removed
+    for project in projects.iter().filter(|p| !p.is_dir()) {
+        let mut item = Item::new(ItemKind::State, project.clone());
+        item.orphan = Some("project in history no longer exists".to_string());
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+/// A home config's project directory (its `start_directory`), with `~` expanded
+pub(crate) fn project_dir(config: &Config) -> Option<PathBuf> {
+    let start = &config.start_directory;
+    if start.as_os_str().is_empty() {
+        return None;
+    }
+    match start.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().map(|home| home.join(rest)),
+        Err(_) => Some(start.clone()),
+    }
+}
+
+/// Why a home config looks orphaned: its project is gone, or the project's
+/// tmuxify-generated `.envrc` no longer loads it
+pub(crate) fn orphan_reason(path: &Path, config: &Config) -> Option<String> {
+    let project = project_dir(config)?;
+    if !project.is_dir() {
+        return Some(format!("project {} no longer exists", project.display()));
+    }
+
+    // Only judge .envrc files tmuxify generated; hand-written configs are left alone
+    let envrc = fs::read_to_string(project.join(".envrc")).ok()?;
+    if !load::envrc_loads_tmuxp(&envrc) {
+        return None;
+    }
+    let file_name = path.file_name()?.to_str()?;
+    let referenced = envrc.contains(&format!("~/.tmuxp/{}", file_name))
+        || envrc.contains(&path.display().to_string());
+    (!referenced).then(|| format!("{} no longer loads it", project.join(".envrc").display()))
+}
+
+pub(crate) fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    paths.sort();
+    paths
+}
+
+pub(crate) fn is_yaml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml")
+}
+
+/// Whether a file could be a tmuxp config: YAML, or the JSON `--format json` writes
+pub(crate) fn is_config(path: &Path) -> bool {
+    is_yaml(path) || path.extension().is_some_and(|ext| ext == "json")
+}
+
+pub(crate) fn is_backup(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.contains(".backup."))
+}
+
+/// Whether an `.envrc` contains a tmuxp load line (i.e. was generated by tmuxify)
+fn loads_tmuxp(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| load::envrc_loads_tmuxp(&content))
+}
+
+fn format_age(days: Option<u64>) -> String {
+    match days {
+        None => "(age unknown)".to_string(),
+        Some(0) => "(today)".to_string(),
+        Some(1) => "(1 day old)".to_string(),
+        Some(d) => format!("({} days old)", d),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn classifies_files() {
+        let dir = tempdir().unwrap();
+        let envrc = dir.path().join(".envrc");
+        fs::write(&envrc, "tmuxp load ./.tmuxp.yaml\n").unwrap();
+        assert!(loads_tmuxp(&envrc));
+        fs::write(&envrc, "export FOO=1\n").unwrap();
+        assert!(!loads_tmuxp(&envrc));
+
+        assert!(is_backup(Path::new("/p/.envrc.backup.20250101_000000")));
+        assert!(!is_backup(Path::new("/p/.envrc")));
+        assert!(is_yaml(Path::new("/p/.tmuxp.yml")));
+    }
+
+    #[test]
+    fn detects_orphaned_home_configs() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("app");
+        fs::create_dir(&project).unwrap();
+        let home_cfg = Path::new("/home/u/.tmuxp/app.yaml");
+
+        let gone = Config::new("app".into(), dir.path().join("gone"), vec![]);
+        assert!(
+            orphan_reason(home_cfg, &gone)
+                .unwrap()
+                .contains("no longer exists")
+        );
+
+        let cfg = Config::new("app".into(), project.clone(), vec![]);
+        // No .envrc: can't tell, so not an orphan
+        assert_eq!(orphan_reason(home_cfg, &cfg), None);
+
+        fs::write(project.join(".envrc"), "tmuxp load ~/.tmuxp/app.yaml\n").unwrap();
+        assert_eq!(orphan_reason(home_cfg, &cfg), None);
+
+        fs::write(project.join(".envrc"), "tmuxp load ./.tmuxp.yaml\n").unwrap();
+        assert!(
+            orphan_reason(home_cfg, &cfg)
+                .unwrap()
+                .contains("no longer loads it")
+        );
+    }
+
+    #[test]
+    fn old_backups_are_stale() {
+        let mut item = Item::new(ItemKind::Backup, PathBuf::from("/nonexistent"));
+        assert!(!item.stale());
+        item.age_days = Some(STALE_AFTER_DAYS + 1);
+        assert!(item.stale());
+        item.kind = ItemKind::HomeConfig;
+        assert!(!item.stale());
+    }
+}
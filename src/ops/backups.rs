@@ -0,0 +1,352 @@
+//! The timestamped `<file>.backup.<timestamp>` copies left by config writes,
+//! and `tmuxify backups` to list, prune, and restore them.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use console::style;
+use dialoguer::Confirm;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::ops::{audit, history, picker, table, theme, validate, write};
+
+/// Format of the timestamp `write::backup_file` appends
+const TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// Backups of one file kept when old ones are deleted
+pub const KEEP_PER_FILE: usize = 3;
+
+/// A backup made by `write::backup_file`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// File name of the file it backs up (`.envrc`, `.tmuxp.yaml`, ...)
+    pub original: String,
+    /// `%Y%m%d_%H%M%S`, so sorting by it sorts by age, with a `_<n>`
+    /// counter when several backups were taken in the same second
+    pub timestamp: String,
+}
+
+impl Backup {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let (original, timestamp) = name.rsplit_once(".backup.")?;
+        Some(Self {
+            original: original.to_string(),
+            timestamp: timestamp.to_string(),
+            path,
+        })
+    }
+
+    /// The file this is a backup of, next to the backup
+    pub fn original_path(&self) -> PathBuf {
+        self.path.with_file_name(&self.original)
+    }
+
+    /// When the backup was made, from its timestamp
+    pub fn taken(&self) -> Option<NaiveDateTime> {
+        let stamp = self.timestamp.get(..15).unwrap_or(&self.timestamp);
+        NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).ok()
+    }
+
+    /// Whole days between the backup and `now`
+    fn age_days(&self, now: NaiveDateTime) -> Option<i64> {
+        self.taken().map(|taken| (now - taken).num_days())
+    }
+}
+
+/// Backups of tmuxify's files in `dir`, oldest first. In a project only
+/// backups of `.envrc` and `.tmuxp*` count, as other tools use the same suffix.
+pub fn in_dir(dir: &Path) -> Vec<Backup> {
+    let is_project = dirs::home_dir().is_none_or(|home| dir != home.join(".tmuxp"));
+    let mut backups: Vec<Backup> = audit::files_in(dir)
+        .into_iter()
+        .filter(|path| audit::is_backup(path))
+        .filter_map(Backup::from_path)
+        .filter(|b| !is_project || b.original == ".envrc" || b.original.starts_with(".tmuxp"))
+        .collect();
+    backups.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    backups
+}
+
+/// Backups beyond the newest `keep` of each original file
+pub fn excess(backups: &[Backup], keep: usize) -> Vec<&Backup> {
+    backups
+        .iter()
+        .filter(|backup| {
+            let newer = backups
+                .iter()
+                .filter(|other| {
+                    other.original == backup.original && other.timestamp > backup.timestamp
+                })
+                .count();
+            newer >= keep
+        })
+        .collect()
+}
+
+/// Backups taken more than `days` days before `now`
+pub fn older_than(backups: &[Backup], days: u64, now: NaiveDateTime) -> Vec<&Backup> {
+    backups
+        .iter()
+        .filter(|b| b.age_days(now).is_some_and(|age| age > days as i64))
+        .collect()
+}
+
+/// Delete backups, returning how many were removed
+pub fn remove(backups: &[&Backup]) -> Result<usize> {
+    for backup in backups {
+        fs::remove_file(&backup.path)
+            .with_context(|| format!("Failed to delete {}", backup.path.display()))?;
+    }
+    Ok(backups.len())
+}
+
+/// The project's directory and `~/.tmuxp`, where tmuxify writes files
+fn backup_dirs(project_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs_to_check = vec![project_dir.to_path_buf()];
+    if let Some(tmuxp_dir) = dirs::home_dir().map(|home| home.join(".tmuxp"))
+        && !dirs_to_check.contains(&tmuxp_dir)
+    {
+        dirs_to_check.push(tmuxp_dir);
+    }
+    dirs_to_check
+}
+
+/// Backups in the project and `~/.tmuxp`, oldest first
+fn all(args: &Args) -> Result<Vec<Backup>> {
+    let mut backups: Vec<Backup> = backup_dirs(&args.project_dir()?)
+        .iter()
+        .flat_map(|dir| in_dir(dir))
+        .collect();
+    backups.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    Ok(backups)
+}
+
+fn describe_age(backup: &Backup, now: NaiveDateTime) -> String {
+    match backup.age_days(now) {
+        None => "unknown".to_string(),
+        Some(days) if days <= 0 => "today".to_string(),
+        Some(1) => "1 day".to_string(),
+        Some(days) => format!("{} days", days),
+    }
+}
+
+/// List the project's and `~/.tmuxp`'s backups, oldest first
+pub fn list(args: &Args) -> Result<()> {
+    let backups = all(args)?;
+    if backups.is_empty() {
+        println!("No backups in {}", args.project_dir()?.display());
+        return Ok(());
+    }
+    let now = chrono::Local::now().naive_local();
+    let mut table = table::Table::new(&["BACKUP", "OF", "TAKEN", "AGE"]);
+    for backup in &backups {
+        table.add_row(vec![
+            backup.path.display().to_string(),
+            backup.original.clone(),
+            backup
+                .taken()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| backup.timestamp.clone()),
+            describe_age(backup, now),
+        ]);
+    }
+    print!("{}", table.render());
+    Ok(())
+}
+
+/// Delete backups older than `older_than` days or beyond the newest `keep`
+/// of each file. With neither limit, keeps the newest `KEEP_PER_FILE`.
+pub fn prune(
+    args: &Args,
+    older_than_days: Option<u64>,
+    keep: Option<usize>,
+    yes: bool,
+) -> Result<()> {
+    let backups = all(args)?;
+    let keep = keep.or(older_than_days.is_none().then_some(KEEP_PER_FILE));
+    let now = chrono::Local::now().naive_local();
+    let mut doomed: Vec<&Backup> = older_than_days
+        .map(|days| older_than(&backups, days, now))
+        .unwrap_or_default();
+    if let Some(keep) = keep {
+        for backup in excess(&backups, keep) {
+            if !doomed.contains(&backup) {
+                doomed.push(backup);
+            }
+        }
+    }
+
+    if doomed.is_empty() {
+        println!("{} No backups to prune", style("✓").green().bold());
+        return Ok(());
+    }
+    if args.dry_run {
+        println!("[DRY RUN] Would delete:");
+        for backup in &doomed {
+            println!("  {}", backup.path.display());
+        }
+        return Ok(());
+    }
+    if !yes {
+        validate::require_terminal("Confirming the prune")?;
+        for backup in &doomed {
+            println!("  {}", backup.path.display());
+        }
+        let confirmed = Confirm::with_theme(theme::get())
+            .with_prompt(format!("Delete {} backup(s)?", doomed.len()))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Nothing deleted.");
+            return Ok(());
+        }
+    }
+    let removed = remove(&doomed)?;
+    println!(
+        "{} Deleted {} backup(s)",
+        style("✓").green().bold(),
+        removed
+    );
+    Ok(())
+}
+
+/// The backup `spec` names: a path, or a file name in the project or
+/// `~/.tmuxp`
+fn find<'a>(backups: &'a [Backup], spec: &str) -> Result<&'a Backup> {
+    let path = validate::resolve_user_path(Path::new(spec));
+    backups
+        .iter()
+        .find(|b| b.path == path)
+        .or_else(|| {
+            backups
+                .iter()
+                .find(|b| b.path.file_name().is_some_and(|name| name == spec))
+        })
+        .ok_or_else(|| anyhow::anyhow!("No backup named '{}'; see tmuxify backups list", spec))
+}
+
+/// Copy a backup over the file it was taken from, backing up the current
+/// contents first (unless `--force`). Without `spec`, asks which one.
+pub fn restore(args: &Args, spec: Option<&str>) -> Result<()> {
+    let backups = all(args)?;
+    let backup = match spec {
+        Some(spec) => find(&backups, spec)?,
+        None => {
+            if backups.is_empty() {
+                anyhow::bail!("No backups in {}", args.project_dir()?.display());
+            }
+            validate::require_terminal("Choosing a backup to restore")?;
+            // Newest first, as that's usually the one wanted
+            let newest: Vec<&Backup> = backups.iter().rev().collect();
+            let items: Vec<picker::Item> = newest
+                .iter()
+                .map(|b| {
+                    picker::Item::new(b.path.display().to_string()).with_preview(b.path.clone())
+                })
+                .collect();
+            newest[picker::select("Restore which backup?", &items)?]
+        }
+    };
+
+    let target = backup.original_path();
+    let content = fs::read_to_string(&backup.path)
+        .with_context(|| format!("Failed to read {}", backup.path.display()))?;
+    if args.dry_run {
+        println!(
+            "[DRY RUN] Would restore {} from {}",
+            target.display(),
+            backup.path.display()
+        );
+        return Ok(());
+    }
+
+    let previous = fs::read_to_string(&target).ok();
+    let replaced = write::backup_file(&target, args.force)?;
+    fs::write(&target, &content)
+        .with_context(|| format!("Failed to write {}", target.display()))?;
+    let change =
+        history::FileChange::new(&target, previous.as_deref(), &content, replaced.as_deref());
+    if let Err(e) = history::record(&args.project_dir()?, vec![change]) {
+        eprintln!(
+            "{} Could not record history: {}",
+            style("⚠").yellow().bold(),
+            e
+        );
+    }
+
+    println!(
+        "{} Restored {} from {}",
+        style("✓").green().bold(),
+        target.display(),
+        backup.path.display()
+    );
+    if let Some(replaced) = replaced {
+        println!("  Previous contents saved to {}", replaced.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn only_the_newest_backups_of_each_file_are_kept() {
+        let dir = tempdir().unwrap();
+        for name in [
+            ".envrc.backup.20250101_000000",
+            ".envrc.backup.20250102_000000",
+            ".envrc.backup.20250103_000000",
+            ".tmuxp.yaml.backup.20250101_000000",
+            "notes.txt.backup.20250101_000000",
+            ".envrc",
+        ] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let backups = in_dir(dir.path());
+        assert_eq!(backups.len(), 4);
+        assert_eq!(backups[0].original, ".envrc");
+
+        let old: Vec<&str> = excess(&backups, 2)
+            .iter()
+            .map(|b| b.timestamp.as_str())
+            .collect();
+        assert_eq!(old, ["20250101_000000"]);
+        assert_eq!(excess(&backups, 2)[0].original, ".envrc");
+
+        assert_eq!(remove(&excess(&backups, 1)).unwrap(), 2);
+        assert_eq!(in_dir(dir.path()).len(), 2);
+    }
+
+    #[test]
+    fn backups_are_aged_by_timestamp_and_found_by_name() {
+        let dir = tempdir().unwrap();
+        for name in [
+            ".envrc.backup.20250101_000000",
+            ".envrc.backup.20250101_000000_1",
+            ".envrc.backup.20250110_120000",
+            ".tmuxp.yaml.backup.garbage",
+        ] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let backups = in_dir(dir.path());
+        let now = NaiveDateTime::parse_from_str("20250111_000000", TIMESTAMP_FORMAT).unwrap();
+
+        let old: Vec<&str> = older_than(&backups, 5, now)
+            .iter()
+            .map(|b| b.timestamp.as_str())
+            .collect();
+        assert_eq!(old, ["20250101_000000", "20250101_000000_1"]);
+        assert_eq!(describe_age(&backups[2], now), "today");
+        assert_eq!(describe_age(&backups[3], now), "unknown");
+
+        let found = find(&backups, ".envrc.backup.20250110_120000").unwrap();
+        assert_eq!(found.original_path(), dir.path().join(".envrc"));
+        assert!(find(&backups, ".envrc.backup.nope").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! `tmuxify clone`: copy a generated config into another project, pointed at
+//! the new directory.
+
+use anyhow::Result;
+use console::style;
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::model::{Config, ConfigFormat, Settings, TmuxpLocation};
+use crate::ops::{edit, load, show, validate, write};
+
+/// The config and where it lives for a `PATH|NAME` source: a project
+/// directory's generated config, a config file, or a session name
+fn source_config(spec: &str) -> Result<(PathBuf, TmuxpLocation)> {
+    let path = validate::resolve_user_path(Path::new(spec));
+    if path.is_dir() {
+        return load::locate_project_config(&path, None);
+    }
+    let path = load::resolve_config_path(spec)?;
+    let location = if path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with(".tmuxp."))
+    {
+        TmuxpLocation::Project
+    } else {
+        TmuxpLocation::Home
+    };
+    Ok((path, location))
+}
+
+/// `dir` moved from `old_root` to `new_root`, if it is an absolute path
+/// inside `old_root`
+fn rebase(dir: &str, old_root: &Path, new_root: &Path) -> Option<String> {
+    let rel = Path::new(dir).strip_prefix(old_root).ok()?;
+    let moved = if rel.as_os_str().is_empty() {
+        new_root.to_path_buf()
+    } else {
+        new_root.join(rel)
+    };
+    Some(moved.display().to_string())
+}
+
+/// Point `config` at `start_dir` as session `session_name`, moving window
+/// and pane directories inside the old project along with it
+fn retarget(config: &Config, session_name: &str, start_dir: &Path) -> Config {
+    let old_root = config.start_directory.clone();
+    let mut cloned = config.clone();
+    cloned.session_name = session_name.to_string();
+    cloned.start_directory = start_dir.to_path_buf();
+    for window in &mut cloned.windows {
+        if let Some(Value::String(dir)) = window.extra.get_mut("start_directory")
+            && let Some(moved) = rebase(dir, &old_root, start_dir)
+        {
+            *dir = moved;
+        }
+        for pane in &mut window.panes {
+            if let Some(moved) = pane
+                .start_directory
+                .as_deref()
+                .and_then(|dir| rebase(dir, &old_root, start_dir))
+            {
+                pane.start_directory = Some(moved);
+            }
+        }
+    }
+    cloned
+}
+
+/// Copy the config `source` names into `target` (or the current project),
+/// renamed after it, and write its `.envrc`
+pub fn run(args: &Args, source: &str, target: Option<&Path>) -> Result<()> {
+    let project_dir = match target {
+        Some(dir) => validate::resolve_user_path(dir),
+        None => args.project_dir()?,
+    };
+    if !project_dir.is_dir() {
+        anyhow::bail!("{} is not a directory", project_dir.display());
+    }
+    let (source_path, source_location) = source_config(source)?;
+    let config = load::load_config(&source_path)?;
+
+    let session_name = match &args.session {
+        Some(session) => session.clone(),
+        None => project_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().replace('\u{FFFD}', "_"))
+            .unwrap_or_else(|| "my-session".to_string()),
+    };
+    let start_dir = match &args.start_dir {
+        Some(dir) => validate::resolve_user_path(dir),
+        None => project_dir.clone(),
+    };
+    if session_name == config.session_name && start_dir == config.start_directory {
+        anyhow::bail!(
+            "{} is already session {}'s project; pass a different directory",
+            project_dir.display(),
+            session_name
+        );
+    }
+    let mut cloned = retarget(&config, &session_name, &start_dir);
+    cloned.environment.extend(args.env.iter().cloned());
+    if args.edit_preview {
+        match edit::edit_yaml(&cloned)? {
+            Some(edited) => cloned = edited,
+            None => {
+                println!("Aborted.");
+                return Ok(());
+            }
+        }
+    }
+
+    let location = args.tmuxp_location.unwrap_or(source_location);
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        envrc: write::envrc_options(&project_dir, &Settings::load()?),
+        format: args.format.or(Some(ConfigFormat::from_path(&source_path))),
+        ..Default::default()
+    };
+
+    // Home configs are named after the session; don't replace another project's
+    if location == TmuxpLocation::Home && !args.force {
+        let format = options.format.unwrap_or_default();
+        let target = format.apply(&cloned.get_file_path(location, Some(&project_dir))?);
+        if load::load_config(&target).is_ok_and(|c| c.start_directory != start_dir) {
+            anyhow::bail!(
+                "{} already exists for another project; pass --session or --force",
+                target.display()
+            );
+        }
+    }
+
+    println!(
+        "Cloning session {} -> {} in {}",
+        style(&config.session_name).cyan(),
+        style(&session_name).cyan(),
+        project_dir.display()
+    );
+    let result = write::write_config(&cloned, location, &project_dir, &options)?;
+    if !args.dry_run {
+        show::print_summary(&cloned);
+        result.print_summary();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Pane, Window};
+
+    #[test]
+    fn retarget_moves_directories_inside_the_project() {
+        let mut window = Window::new(
+            Some("dev".into()),
+            None,
+            vec![
+                Pane::empty().with_start_directory(Some("/src/api/web".into())),
+                Pane::empty().with_start_directory(Some("/var/log".into())),
+                Pane::empty().with_start_directory(Some("docs".into())),
+            ],
+        );
+        window
+            .extra
+            .insert("start_directory".into(), Value::from("/src/api"));
+        let config = Config::new("api".into(), "/src/api".into(), vec![window]);
+
+        let cloned = retarget(&config, "api-v2", Path::new("/src/api-v2"));
+        assert_eq!(cloned.session_name, "api-v2");
+        assert_eq!(cloned.start_directory, Path::new("/src/api-v2"));
+        let dirs: Vec<Option<&str>> = cloned.windows[0]
+            .panes
+            .iter()
+            .map(|p| p.start_directory.as_deref())
+            .collect();
+        assert_eq!(
+            dirs,
+            [Some("/src/api-v2/web"), Some("/var/log"), Some("docs")]
+        );
+        assert_eq!(
+            cloned.windows[0].extra["start_directory"],
+            Value::from("/src/api-v2")
+        );
+    }
+
+    #[test]
+    fn source_config_finds_a_project_directory_config() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".tmuxp.yaml"),
+            "session_name: api\nstart_directory: /src/api\nwindows: []\n",
+        )
+        .unwrap();
+        let (path, location) = source_config(&dir.path().display().to_string()).unwrap();
+        assert_eq!(path, dir.path().join(".tmuxp.yaml"));
+        assert_eq!(location, TmuxpLocation::Project);
+
+        let (_, location) =
+            source_config(&dir.path().join(".tmuxp.yaml").display().to_string()).unwrap();
+        assert_eq!(location, TmuxpLocation::Project);
+    }
+}
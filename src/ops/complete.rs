@@ -0,0 +1,309 @@
+use anyhow::Result;
+use clap::CommandFactory;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::Args;
+use crate::ops::{discovery, history, load, template, tmux};
+
+/// Print runtime completion candidates for `kind`, one per line
+pub fn run(args: &Args, kind: &str) -> Result<()> {
+    let candidates = match kind {
+        "sessions" => session_names(),
+        // `open` also takes registered projects by directory name
+        "projects" => {
+            let mut names = session_names();
+            names.extend(
+                history::projects()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|dir| {
+                        Path::new(dir)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                    }),
+            );
+            names.sort();
+            names.dedup();
+            names
+        }
+        "templates" => template::names(),
+        // `edit --window` takes the project's window names
+        "windows" => load::locate_project_config(&args.project_dir()?, None)
+            .and_then(|(path, _)| load::load_config(&path))
+            .map(|config| {
+                config
+                    .windows
+                    .into_iter()
+                    .filter_map(|window| window.window_name)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        "backups" => {
+            let mut names = backup_names(&args.project_dir()?);
+            if let Some(home) = dirs::home_dir() {
+                names.extend(backup_names(&home.join(".tmuxp")));
+            }
+            names
+        }
+        other => anyhow::bail!(
+            "Unknown completion kind: {} (expected sessions, projects, templates, windows, \
+             or backups)",
+            other
+        ),
+    };
+
+    for candidate in candidates {
+        println!("{}", candidate);
+    }
+    Ok(())
+}
+
+/// Print a completion script for `shell` that queries `tmuxify __complete` at runtime
+pub fn script(shell: &str) -> Result<()> {
+    let command = Args::command();
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .filter(|c| !c.is_hide_set())
+        .map(|c| c.get_name().to_string())
+        .collect();
+    let flags: Vec<String> = command
+        .get_arguments()
+        .filter_map(|a| a.get_long())
+        .map(|long| format!("--{}", long))
+        .chain(["--help".to_string(), "--version".to_string()])
+        .collect();
+
+    // Flags with a fixed set of values (--tmuxp-location, --layout)
+    let choices: Vec<(String, Vec<String>)> = command
+        .get_arguments()
+        .filter(|a| a.get_action().takes_values())
+        .filter_map(|a| {
+            let values: Vec<String> = a
+                .get_possible_values()
+                .iter()
+                .map(|v| v.get_name().to_string())
+                .collect();
+            Some((a.get_long()?.to_string(), values)).filter(|(_, v)| !v.is_empty())
+        })
+        .collect();
+
+    let script = match shell {
+        "bash" => bash_script(&subcommands, &flags, &choices),
+        "zsh" => format!(
+            "autoload -U +X bashcompinit && bashcompinit\n{}",
+            bash_script(&subcommands, &flags, &choices)
+        ),
+        "fish" => fish_script(&subcommands, &choices),
+        other => anyhow::bail!("Unsupported shell: {} (expected bash, zsh, or fish)", other),
+    };
+    print!("{}", script);
+    Ok(())
+}
+
+fn bash_script(
+    subcommands: &[String],
+    flags: &[String],
+    choices: &[(String, Vec<String>)],
+) -> String {
+    let choice_arms: String = choices
+        .iter()
+        .map(|(long, values)| {
+            format!(
+                "        --{})\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            return ;;\n",
+                long,
+                values.join(" ")
+            )
+        })
+        .collect();
+    format!(
+        r#"_tmuxify() {{
+    local cur prev
+    cur="${{COMP_WORDS[COMP_CWORD]}}"
+    prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$prev" in
+        --session|--from|show|clone|validate)
+            COMPREPLY=($(compgen -W "$(tmuxify __complete sessions 2>/dev/null)" -- "$cur"))
+            return ;;
+        open)
+            COMPREPLY=($(compgen -W "$(tmuxify __complete projects 2>/dev/null)" -- "$cur"))
+            return ;;
+        --window)
+            COMPREPLY=($(compgen -W "$(tmuxify __complete windows 2>/dev/null)" -- "$cur"))
+            return ;;
+        template)
+            COMPREPLY=($(compgen -W "save list apply delete" -- "$cur"))
+            return ;;
+        apply|delete)
+            if [[ "${{COMP_WORDS[COMP_CWORD-2]}}" == template ]]; then
+                COMPREPLY=($(compgen -W "$(tmuxify __complete templates 2>/dev/null)" -- "$cur"))
+                return
+            fi ;;
+        backups)
+            COMPREPLY=($(compgen -W "list prune restore" -- "$cur"))
+            return ;;
+        restore)
+            COMPREPLY=($(compgen -W "$(tmuxify __complete backups 2>/dev/null)" -- "$cur"))
+            return ;;
+{choice_arms}        --project|--start-dir)
+            COMPREPLY=($(compgen -d -- "$cur"))
+            return ;;
+    esac
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "{flags}" -- "$cur"))
+    elif [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{subcommands}" -- "$cur"))
+    fi
+}}
+complete -F _tmuxify tmuxify
+"#,
+        flags = flags.join(" "),
+        subcommands = subcommands.join(" "),
+        choice_arms = choice_arms,
+    )
+}
+
+fn fish_script(subcommands: &[String], choices: &[(String, Vec<String>)]) -> String {
+    let sessions = "(tmuxify __complete sessions 2>/dev/null)";
+    let mut out = String::from("complete -c tmuxify -f\n");
+    out.push_str(&format!(
+        "complete -c tmuxify -n __fish_use_subcommand -a \"{}\"\n",
+        subcommands.join(" ")
+    ));
+    out.push_str(&format!(
+        "complete -c tmuxify -l session -xa \"{}\"\n",
+        sessions
+    ));
+    out.push_str(&format!(
+        "complete -c tmuxify -l from -xa \"{}\"\n",
+        sessions
+    ));
+    out.push_str(&format!(
+        "complete -c tmuxify -n \"__fish_seen_subcommand_from show clone validate\" -xa \"{}\"\n",
+        sessions
+    ));
+    out.push_str(
+        "complete -c tmuxify -n \"__fish_seen_subcommand_from open\" \
+         -xa \"(tmuxify __complete projects 2>/dev/null)\"\n",
+    );
+    out.push_str(
+        "complete -c tmuxify -n \"__fish_seen_subcommand_from edit\" -l window \
+         -xa \"(tmuxify __complete windows 2>/dev/null)\"\n",
+    );
+    out.push_str(
+        "complete -c tmuxify -n \"__fish_seen_subcommand_from template; \
+         and not __fish_seen_subcommand_from save list apply delete\" \
+         -xa \"save list apply delete\"\n",
+    );
+    out.push_str(
+        "complete -c tmuxify -n \"__fish_seen_subcommand_from template; \
+         and __fish_seen_subcommand_from apply delete\" \
+         -xa \"(tmuxify __complete templates 2>/dev/null)\"\n",
+    );
+    out.push_str(
+        "complete -c tmuxify -n \"__fish_seen_subcommand_from backups\" -xa \"list prune restore\"\n",
+    );
+    out.push_str(
+        "complete -c tmuxify -n \"__fish_seen_subcommand_from restore\" \
+         -xa \"(tmuxify __complete backups 2>/dev/null)\"\n",
+    );
+    for (long, values) in choices {
+        out.push_str(&format!(
+            "complete -c tmuxify -l {} -xa \"{}\"\n",
+            long,
+            values.join(" ")
+        ));
+    }
+    out.push_str("complete -c tmuxify -l project -xa \"(__fish_complete_directories)\"\n");
+    out.push_str("complete -c tmuxify -l start-dir -xa \"(__fish_complete_directories)\"\n");
+    out
+}
+
+/// Running tmux sessions plus discovered configs, by file stem and session
+/// name (both resolve as a `PATH|NAME` argument)
+fn session_names() -> Vec<String> {
+    let mut names = tmux::list_sessions();
+    for config in discovery::discover() {
+        names.push(config.stem());
+        names.push(config.session_name);
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Names of the `<file>.backup.<timestamp>` copies in a directory
+fn backup_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.contains(".backup."))
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn lists_backups() {
+        let dir = tempdir().unwrap();
+        for name in [
+            "api.yaml",
+            "web.yml",
+            "notes.txt",
+            "api.yaml.backup.20250101_120000",
+        ] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        assert_eq!(
+            backup_names(dir.path()),
+            vec!["api.yaml.backup.20250101_120000"]
+        );
+    }
+
+    #[test]
+    fn bash_script_lists_subcommands_and_hides_internal_ones() {
+        let script = bash_script(
+            &["doctor".into(), "show".into()],
+            &["--session".into()],
+            &[(
+                "tmuxp-location".into(),
+                vec!["home".into(), "project".into()],
+            )],
+        );
+        assert!(script.contains("compgen -W \"doctor show\""));
+        assert!(script.contains("tmuxify __complete sessions"));
+        assert!(script.contains("tmuxify __complete backups"));
+        assert!(script.contains(
+            "open)\n            COMPREPLY=($(compgen -W \"$(tmuxify __complete projects"
+        ));
+        assert!(script.contains("tmuxify __complete templates"));
+        assert!(script.contains("tmuxify __complete windows"));
+        assert!(
+            script.contains(
+                "--tmuxp-location)\n            COMPREPLY=($(compgen -W \"home project\""
+            )
+        );
+        assert!(script.ends_with("complete -F _tmuxify tmuxify\n"));
+    }
+
+    #[test]
+    fn fish_script_completes_open_validate_and_templates() {
+        let script = fish_script(&["open".into()], &[]);
+        assert!(
+            script
+                .contains("__fish_seen_subcommand_from open\" -xa \"(tmuxify __complete projects")
+        );
+        assert!(script.contains("show clone validate\" -xa \"(tmuxify __complete sessions"));
+        assert!(script.contains("-xa \"(tmuxify __complete templates 2>/dev/null)\""));
+        assert!(script.contains("-l window -xa \"(tmuxify __complete windows"));
+    }
+}
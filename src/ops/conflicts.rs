@@ -0,0 +1,288 @@
+//! Detection of other mechanisms that start or attach tmux sessions, which
+//! compete with a tmuxify `.envrc`: hand-written `.envrc` tmux blocks,
+//! tmuxinator projects, and auto-attach logic in shell profiles.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ops::validate;
+
+/// Shell startup files checked for auto-attach logic, relative to home
+const SHELL_PROFILES: &[&str] = &[
+    ".bashrc",
+    ".bash_profile",
+    ".profile",
+    ".zshrc",
+    ".zprofile",
+    ".zlogin",
+    ".config/fish/config.fish",
+];
+
+/// tmux subcommands that attach to or create a session
+const ATTACH_SUBCOMMANDS: &[&str] = &["a", "at", "attach", "attach-session", "new", "new-session"];
+
+/// Where a competing auto-attach mechanism lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// A `.envrc` tmux block tmuxify didn't write
+    Envrc,
+    /// A tmuxinator project for the same directory
+    Tmuxinator,
+    /// A shell profile that starts or attaches tmux on login
+    ShellProfile,
+}
+
+/// A competing auto-attach mechanism
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    pub path: PathBuf,
+    /// 1-based line of the offending command, when there is one
+    pub line: Option<usize>,
+    pub text: String,
+}
+
+impl Conflict {
+    /// What the conflict is, with its location
+    pub fn describe(&self) -> String {
+        let location = match self.line {
+            Some(line) => format!("{}:{}", self.path.display(), line),
+            None => self.path.display().to_string(),
+        };
+        let what = match self.kind {
+            ConflictKind::Envrc => ".envrc starts tmux without tmuxify",
+            ConflictKind::Tmuxinator => "tmuxinator also manages this project",
+            ConflictKind::ShellProfile => "Shell profile auto-attaches tmux",
+        };
+        format!("{} ({})", what, location)
+    }
+
+    /// How to resolve it
+    pub fn hint(&self) -> &'static str {
+        match self.kind {
+            ConflictKind::Envrc => {
+                "Run tmuxify in the project to take it over (the current .envrc is backed up)"
+            }
+            ConflictKind::Tmuxinator => {
+                "Start the session with only one tool; remove the tmuxinator project or the tmuxify .envrc"
+            }
+            ConflictKind::ShellProfile => {
+                "Guard it with [ -z \"$TMUX\" ] or remove it; direnv already attaches in tmuxify projects"
+            }
+        }
+    }
+}
+
+/// Words that can precede a command without being one
+const PREFIX_WORDS: &[&str] = &["then", "do", "else", "exec", "command", "!", "{", "("];
+
+/// Whether a shell line starts or attaches a tmux session. Each command of
+/// the line is checked, ignoring comments and prefixes like `then` or `exec`.
+fn starts_tmux(line: &str) -> bool {
+    let code = line.split('#').next().unwrap_or_default();
+    code.split([';', '&', '|']).any(|command| {
+        let words: Vec<&str> = command
+            .split_whitespace()
+            .map(|word| word.trim_matches(['"', '\'', '`']))
+            .skip_while(|word| PREFIX_WORDS.contains(word))
+            .collect();
+        match words.as_slice() {
+            ["tmux", rest @ ..] => rest
+                .iter()
+                .find(|word| !word.starts_with('-'))
+                .is_none_or(|word| ATTACH_SUBCOMMANDS.contains(word)),
+            ["tmuxinator", "start", ..] | ["tmuxp", "load", ..] => true,
+            _ => false,
+        }
+    })
+}
+
+/// Whether a shell line tests `$TMUX`, as in `[ -z "$TMUX" ]`, so whatever
+/// it guards doesn't run inside tmux
+fn tests_tmux_var(line: &str) -> bool {
+    let code = line.split('#').next().unwrap_or_default();
+    (code.contains("$TMUX") || code.contains("${TMUX}"))
+        && code
+            .split_whitespace()
+            .any(|word| word == "-z" || word == "-n")
+}
+
+/// Lines of a shell file that start or attach tmux, with 1-based line
+/// numbers. Lines guarded by a `$TMUX` test, on the line itself or in an
+/// enclosing `if`, are skipped.
+pub fn auto_attach_lines(content: &str) -> Vec<(usize, String)> {
+    let mut ifs: Vec<bool> = Vec::new();
+    let mut found = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let guarded = tests_tmux_var(line) || ifs.contains(&true);
+        if !guarded && starts_tmux(line) {
+            found.push((i + 1, line.trim().to_string()));
+        }
+        let code = line.split('#').next().unwrap_or_default();
+        for command in code.split(';') {
+            match command.split_whitespace().next() {
+                Some("if") => ifs.push(tests_tmux_var(command)),
+                Some("fi") => {
+                    ifs.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+/// Whether a `.envrc` was written by tmuxify (inline block or `layout tmux`)
+fn is_tmuxify_envrc(content: &str) -> bool {
+    content.contains("TMUXIFY_REMOTE_ATTACH")
+        || content
+            .lines()
+            .any(|line| line.trim_start().starts_with("layout tmux"))
+}
+
+/// The `root:` of a tmuxinator project file
+fn tmuxinator_root(content: &str) -> Option<&str> {
+    content.lines().find_map(|line| {
+        let value = line
+            .strip_prefix("root:")
+            .or_else(|| line.strip_prefix("project_root:"))?;
+        Some(value.trim().trim_matches(['"', '\'']))
+    })
+}
+
+/// Directories holding global tmuxinator projects
+pub fn tmuxinator_dirs() -> Vec<PathBuf> {
+    let xdg = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+    xdg.map(|dir| dir.join("tmuxinator"))
+        .into_iter()
+        .chain(dirs::home_dir().map(|home| home.join(".tmuxinator")))
+        .collect()
+}
+
+/// Competing auto-attach mechanisms for a project: a hand-written `.envrc`
+/// tmux block and tmuxinator projects rooted there
+pub fn project_conflicts(project_dir: &Path) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+
+    let envrc = project_dir.join(".envrc");
+    if let Ok(content) = fs::read_to_string(&envrc)
+        && !is_tmuxify_envrc(&content)
+    {
+        conflicts.extend(
+            auto_attach_lines(&content)
+                .into_iter()
+                .take(1)
+                .map(|(line, text)| Conflict {
+                    kind: ConflictKind::Envrc,
+                    path: envrc.clone(),
+                    line: Some(line),
+                    text,
+                }),
+        );
+    }
+
+    let local = [".tmuxinator.yml", ".tmuxinator.yaml"]
+        .iter()
+        .map(|name| project_dir.join(name))
+        .filter(|path| path.is_file());
+    let global = tmuxinator_dirs()
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            fs::read_to_string(path).is_ok_and(|content| {
+                tmuxinator_root(&content).is_some_and(|root| {
+                    same_dir(&validate::resolve_user_path(Path::new(root)), project_dir)
+                })
+            })
+        });
+    conflicts.extend(local.chain(global).map(|path| Conflict {
+        kind: ConflictKind::Tmuxinator,
+        text: "tmuxinator starts a session for this directory".to_string(),
+        path,
+        line: None,
+    }));
+    conflicts
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Shell profiles that start or attach tmux on every new shell
+pub fn shell_profile_conflicts() -> Vec<Conflict> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    SHELL_PROFILES
+        .iter()
+        .map(|name| home.join(name))
+        .filter_map(|path| Some((fs::read_to_string(&path).ok()?, path)))
+        .flat_map(|(content, path)| {
+            auto_attach_lines(&content)
+                .into_iter()
+                .map(move |(line, text)| Conflict {
+                    kind: ConflictKind::ShellProfile,
+                    path: path.clone(),
+                    line: Some(line),
+                    text,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Config, TmuxpLocation};
+    use tempfile::tempdir;
+
+    #[test]
+    fn attach_commands_are_recognized() {
+        let profile = "# tmux attach in a comment\n\
+                       alias t='tmux attach'\n\
+                       if [ -z \"$TMUX\" ]; then exec tmux; fi\n\
+                       tmux new-session -A -s main\n\
+                       tmux -2 attach || tmux new\n\
+                       tmux source-file ~/.tmux.conf\n\
+                       tmuxinator start work\n\
+                       if [[ -z $TMUX ]]; then\n\
+                         tmux attach\n\
+                       fi\n\
+                       [ -n \"$TMUX\" ] || tmux new\n\
+                       tmux attach\n";
+        let lines: Vec<usize> = auto_attach_lines(profile)
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+        assert_eq!(lines, [4, 5, 7, 12]);
+    }
+
+    #[test]
+    fn project_conflicts_skip_tmuxify_envrc() {
+        let dir = tempdir().unwrap();
+        let config = Config::new("app".into(), dir.path().into(), vec![]);
+        let envrc = dir.path().join(".envrc");
+
+        fs::write(
+            &envrc,
+            config.generate_envrc(TmuxpLocation::Project, &Default::default()),
+        )
+        .unwrap();
+        assert!(project_conflicts(dir.path()).is_empty());
+
+        fs::write(&envrc, "export PATH=bin:$PATH\ntmux new -A -s app\n").unwrap();
+        fs::write(dir.path().join(".tmuxinator.yml"), "name: app\n").unwrap();
+        let conflicts = project_conflicts(dir.path());
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].kind, ConflictKind::Envrc);
+        assert_eq!(conflicts[0].line, Some(2));
+        assert_eq!(conflicts[1].kind, ConflictKind::Tmuxinator);
+    }
+}
@@ -0,0 +1,662 @@
+//! `tmuxify convert`: turn tmuxinator and teamocil projects into tmuxp configs.
+
+use anyhow::{Context, Result};
+use console::style;
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::model::{Config, Pane, TmuxpLocation, Window, WindowLayout};
+use crate::ops::{conflicts, show, tmux, validate, write};
+
+/// Formats `tmuxify convert --from` reads
+pub const FORMATS: &[&str] = &["tmuxinator", "teamocil"];
+
+/// A converted config plus what couldn't be carried over
+#[derive(Debug)]
+struct Conversion {
+    config: Config,
+    warnings: Vec<String>,
+}
+
+/// Index tmux gives the first window and the first pane of a window
+#[derive(Debug, Clone, Copy)]
+struct BaseIndexes {
+    window: u32,
+    pane: u32,
+    /// No tmux server was running, so these come from tmux.conf or default to 0
+    guessed: bool,
+}
+
+/// A string, a list of strings, or nothing, as a list of commands
+fn commands(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(command) => vec![command.clone()],
+        Value::Sequence(items) => items.iter().flat_map(commands).collect(),
+        Value::Number(n) => vec![n.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+/// Quote `word` for `sh`
+fn sh_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// A tmuxinator pane: a command, a list of commands, nothing, or a
+/// one-key mapping of a pane title to its commands
+fn tmuxinator_pane(value: &Value) -> Pane {
+    match value {
+        Value::Mapping(map) if map.len() == 1 => {
+            Pane::new(map.values().next().map(commands).unwrap_or_default())
+        }
+        value => Pane::new(commands(value)),
+    }
+}
+
+/// A window's `layout`, built-in or a raw tmux layout string
+fn layout(body: &serde_yaml::Mapping, window: &str) -> Result<Option<WindowLayout>> {
+    body.get("layout")
+        .map(|layout| {
+            serde_yaml::from_value::<WindowLayout>(layout.clone())
+                .with_context(|| format!("window {} has an invalid layout", window))
+        })
+        .transpose()
+}
+
+/// One `windows:` entry, a one-key mapping of the window name to a command
+/// or to a mapping with `root`, `layout`, `pre`, `synchronize`, and `panes`
+fn tmuxinator_window(entry: &Value, warnings: &mut Vec<String>) -> Result<Window> {
+    let (name, body) = entry
+        .as_mapping()
+        .filter(|map| map.len() == 1)
+        .and_then(|map| map.iter().next())
+        .ok_or_else(|| anyhow::anyhow!("each window must be a single `name: ...` entry"))?;
+    let name = match name {
+        Value::String(name) => name.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => anyhow::bail!("window names must be strings"),
+    };
+
+    let Value::Mapping(body) = body else {
+        return Ok(Window::new(
+            Some(name),
+            None,
+            vec![Pane::new(commands(body))],
+        ));
+    };
+
+    let panes: Vec<Pane> = match body.get("panes") {
+        Some(Value::Sequence(panes)) if !panes.is_empty() => {
+            panes.iter().map(tmuxinator_pane).collect()
+        }
+        _ => vec![Pane::empty()],
+    };
+    let mut window = Window::new(Some(name.clone()), layout(body, &name)?, panes);
+
+    if let Some(root) = body.get("root").and_then(Value::as_str) {
+        window
+            .extra
+            .insert("start_directory".into(), root.to_string().into());
+    }
+    let pre = body.get("pre").map(commands).unwrap_or_default();
+    if !pre.is_empty() {
+        window.extra.insert(
+            "shell_command_before".into(),
+            serde_yaml::to_value(pre).unwrap_or_default(),
+        );
+    }
+    match body.get("synchronize") {
+        None | Some(Value::Bool(false)) => {}
+        Some(_) => {
+            window
+                .options
+                .insert("synchronize-panes".into(), "on".into());
+        }
+    }
+    for key in body.keys().filter_map(Value::as_str) {
+        if !["panes", "layout", "root", "pre", "synchronize"].contains(&key) {
+            warnings.push(format!("window {}: `{}` isn't converted", name, key));
+        }
+    }
+    Ok(window)
+}
+
+/// Convert a parsed tmuxinator project. `fallback_name` names the session
+/// when the file has no `name`.
+fn from_tmuxinator(project: &Value, fallback_name: &str, bases: BaseIndexes) -> Result<Conversion> {
+    let mut warnings = Vec::new();
+    let session_name = project
+        .get("name")
+        .or_else(|| project.get("project_name"))
+        .and_then(Value::as_str)
+        .unwrap_or(fallback_name)
+        .to_string();
+    let root = project
+        .get("root")
+        .or_else(|| project.get("project_root"))
+        .and_then(Value::as_str)
+        .unwrap_or(".");
+
+    let entries = project
+        .get("windows")
+        .or_else(|| project.get("tabs"))
+        .and_then(Value::as_sequence)
+        .ok_or_else(|| anyhow::anyhow!("no `windows` list"))?;
+    let windows = entries
+        .iter()
+        .map(|entry| tmuxinator_window(entry, &mut warnings))
+        .collect::<Result<Vec<_>>>()?;
+    let mut config = Config::new(session_name, PathBuf::from(root), windows);
+
+    // Commands typed into every pane before its own
+    let pre_window: Vec<String> = ["pre_window", "pre_tab", "rbenv", "rvm"]
+        .iter()
+        .filter_map(|key| project.get(*key))
+        .flat_map(commands)
+        .collect();
+    if !pre_window.is_empty() {
+        config.extra.insert(
+            "shell_command_before".into(),
+            serde_yaml::to_value(pre_window)?,
+        );
+    }
+
+    // Start hooks run once, before the session is built
+    let hooks: Vec<&str> = ["on_project_start", "on_project_first_start", "pre"]
+        .into_iter()
+        .filter(|key| project.get(*key).is_some_and(|v| !commands(v).is_empty()))
+        .collect();
+    let start: Vec<String> = hooks
+        .iter()
+        .filter_map(|key| project.get(*key))
+        .flat_map(commands)
+        .collect();
+    if !start.is_empty() {
+        config.extra.insert(
+            "before_script".into(),
+            format!("sh -c {}", sh_quote(&start.join(" && "))).into(),
+        );
+        let keys: Vec<String> = hooks.iter().map(|key| format!("`{}`", key)).collect();
+        warnings.push(format!(
+            "{} merged into before_script, which runs each time tmuxp creates the \
+             session, stops the load if it fails, and never runs on attach",
+            keys.join(", ") + if keys.len() == 1 { " was" } else { " were" }
+        ));
+    }
+
+    let numbered = matches!(project.get("startup_window"), Some(Value::Number(_)))
+        || project.get("startup_pane").is_some();
+    if bases.guessed && numbered {
+        warnings.push(format!(
+            "No tmux server is running, so startup_window and startup_pane were read \
+             with base-index {} and pane-base-index {}; check which window and pane get focus",
+            bases.window, bases.pane
+        ));
+    }
+
+    if let Some(target) = project.get("startup_window") {
+        let index = match target {
+            Value::Number(n) => n
+                .as_u64()
+                .and_then(|n| usize::try_from(n.checked_sub(u64::from(bases.window))?).ok()),
+            Value::String(name) => config
+                .windows
+                .iter()
+                .position(|w| w.window_name.as_deref() == Some(name.as_str())),
+            _ => None,
+        };
+        match index.and_then(|i| config.windows.get_mut(i)) {
+            Some(window) => {
+                window.focus = Some(true);
+                let pane = project
+                    .get("startup_pane")
+                    .and_then(Value::as_u64)
+                    .and_then(|n| usize::try_from(n.checked_sub(u64::from(bases.pane))?).ok());
+                if let Some(pane) = pane.and_then(|i| window.panes.get_mut(i)) {
+                    pane.focus = Some(true);
+                }
+            }
+            None => warnings.push("startup_window doesn't match any window".to_string()),
+        }
+    }
+
+    let handled = [
+        "name",
+        "project_name",
+        "root",
+        "project_root",
+        "windows",
+        "tabs",
+        "pre_window",
+        "pre_tab",
+        "rbenv",
+        "rvm",
+        "on_project_start",
+        "on_project_first_start",
+        "pre",
+        "startup_window",
+        "startup_pane",
+    ];
+    let ignored: Vec<&str> = project
+        .as_mapping()
+        .map(|map| map.keys().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for key in ignored.into_iter().filter(|key| !handled.contains(key)) {
+        let reason = match key {
+            "post" | "on_project_exit" | "on_project_stop" | "on_project_restart" => {
+                "tmuxp has no hook for this; run it yourself"
+            }
+            _ => "no tmuxp equivalent",
+        };
+        warnings.push(format!("`{}` isn't converted ({})", key, reason));
+    }
+
+    Ok(Conversion { config, warnings })
+}
+
+/// A teamocil pane: a command, nothing, or a mapping with `commands` (or
+/// the older `cmd`) and `focus`
+fn teamocil_pane(value: &Value) -> Pane {
+    let Value::Mapping(pane) = value else {
+        return Pane::new(commands(value));
+    };
+    let commands = pane
+        .get("commands")
+        .or_else(|| pane.get("cmd"))
+        .map(commands)
+        .unwrap_or_default();
+    let mut pane_model = Pane::new(commands);
+    if pane.get("focus").and_then(Value::as_bool) == Some(true) {
+        pane_model.focus = Some(true);
+    }
+    pane_model
+}
+
+/// One teamocil window: `name`, `root`, `layout`, `focus`, `options`,
+/// `filters`, and its `panes` (or the older `splits`)
+fn teamocil_window(entry: &Value, index: usize, warnings: &mut Vec<String>) -> Result<Window> {
+    let body = entry
+        .as_mapping()
+        .ok_or_else(|| anyhow::anyhow!("window {} is not a mapping", index + 1))?;
+    let name = match body.get("name") {
+        Some(Value::String(name)) => name.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => format!("window{}", index + 1),
+    };
+    let panes: Vec<Pane> = match body.get("panes").or_else(|| body.get("splits")) {
+        Some(Value::Sequence(panes)) if !panes.is_empty() => {
+            panes.iter().map(teamocil_pane).collect()
+        }
+        _ => vec![Pane::empty()],
+    };
+    let mut window = Window::new(Some(name.clone()), layout(body, &name)?, panes);
+
+    if let Some(root) = body.get("root").and_then(Value::as_str) {
+        window
+            .extra
+            .insert("start_directory".into(), root.to_string().into());
+    }
+    if body.get("focus").and_then(Value::as_bool) == Some(true) {
+        window.focus = Some(true);
+    }
+    if let Some(Value::Mapping(options)) = body.get("options") {
+        for (key, value) in options {
+            let value = match value {
+                Value::Bool(true) => "on".to_string(),
+                Value::Bool(false) => "off".to_string(),
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                _ => continue,
+            };
+            if let Some(key) = key.as_str() {
+                window.options.insert(key.to_string(), value);
+            }
+        }
+    }
+    if let Some(filters) = body.get("filters") {
+        let before = filters.get("before").map(commands).unwrap_or_default();
+        if !before.is_empty() {
+            window.extra.insert(
+                "shell_command_before".into(),
+                serde_yaml::to_value(before).unwrap_or_default(),
+            );
+        }
+        if filters.get("after").is_some() {
+            warnings.push(format!(
+                "window {}: `filters.after` isn't converted (no tmuxp equivalent)",
+                name
+            ));
+        }
+    }
+    let known = [
+        "name", "root", "layout", "focus", "options", "filters", "panes", "splits",
+    ];
+    for key in body.keys().filter_map(Value::as_str) {
+        if !known.contains(&key) && key != "clear" {
+            warnings.push(format!("window {}: `{}` isn't converted", name, key));
+        }
+    }
+    Ok(window)
+}
+
+/// Convert a parsed teamocil layout, either the current schema or the
+/// older one nested under `session:`
+fn from_teamocil(layout: &Value, fallback_name: &str) -> Result<Conversion> {
+    let layout = layout.get("session").unwrap_or(layout);
+    let mut warnings = Vec::new();
+    let session_name = layout
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or(fallback_name)
+        .to_string();
+    let entries = layout
+        .get("windows")
+        .and_then(Value::as_sequence)
+        .ok_or_else(|| anyhow::anyhow!("no `windows` list"))?;
+    let mut windows = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| teamocil_window(entry, i, &mut warnings))
+        .collect::<Result<Vec<_>>>()?;
+
+    // teamocil has no session root; when every window shares one, hoist it
+    let roots: Vec<Option<&Value>> = windows
+        .iter()
+        .map(|w| w.extra.get("start_directory"))
+        .collect();
+    let shared = roots
+        .first()
+        .copied()
+        .flatten()
+        .filter(|first| roots.iter().all(|root| *root == Some(*first)))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    if shared.is_some() {
+        for window in &mut windows {
+            window.extra.remove("start_directory");
+        }
+    }
+    let root = PathBuf::from(shared.unwrap_or_else(|| ".".to_string()));
+
+    for key in layout.as_mapping().into_iter().flat_map(|map| map.keys()) {
+        if let Some(key) = key
+            .as_str()
+            .filter(|key| !["name", "windows"].contains(key))
+        {
+            warnings.push(format!("`{}` isn't converted (no tmuxp equivalent)", key));
+        }
+    }
+    Ok(Conversion {
+        config: Config::new(session_name, root, windows),
+        warnings,
+    })
+}
+
+/// Directories a tool keeps its named projects in
+fn project_dirs(from: &str) -> Vec<PathBuf> {
+    match from {
+        "teamocil" => dirs::home_dir()
+            .map(|home| vec![home.join(".teamocil")])
+            .unwrap_or_default(),
+        _ => conflicts::tmuxinator_dirs(),
+    }
+}
+
+/// A project file path, or the name of a project in the tool's config
+/// directories
+fn resolve(from: &str, spec: &str) -> Result<PathBuf> {
+    let path = Path::new(spec);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    project_dirs(from)
+        .into_iter()
+        .flat_map(|dir| ["yml", "yaml"].map(|ext| dir.join(format!("{}.{}", spec, ext))))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| anyhow::anyhow!("No {} project found at or named '{}'", from, spec))
+}
+
+/// `~`-relative paths resolved against the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => validate::resolve_user_path(path),
+    }
+}
+
+/// Convert a project file from another tool and write it as a tmuxp config
+pub fn run(args: &Args, from: &str, spec: &str) -> Result<()> {
+    let path = resolve(from, spec)?;
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let project: Value = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let fallback = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "session".to_string());
+    let pane_base = tmux::server_option("pane-base-index");
+    let bases = BaseIndexes {
+        window: tmux::base_index(),
+        guessed: pane_base.is_none(),
+        pane: pane_base.and_then(|v| v.parse().ok()).unwrap_or(0),
+    };
+    let converted = match from {
+        "teamocil" => from_teamocil(&project, &fallback),
+        _ => from_tmuxinator(&project, &fallback, bases),
+    };
+    let Conversion {
+        mut config,
+        mut warnings,
+    } = converted.with_context(|| format!("{} is not a {} project", path.display(), from))?;
+    if from == "tmuxinator" && content.contains("<%") {
+        warnings.push("ERB tags (<% %>) were kept as literal text".to_string());
+    }
+
+    // Relative roots are relative to where the tool was started
+    let root = expand_home(&config.start_directory);
+    let project_dir = match &args.project {
+        Some(_) => args.project_dir()?,
+        None if root.is_absolute() => root.clone(),
+        None => std::env::current_dir()?.join(&root),
+    };
+    config.start_directory = if root.is_absolute() {
+        root
+    } else {
+        project_dir.clone()
+    };
+    if let Some(session) = &args.session {
+        config.session_name = session.clone();
+    }
+
+    let location = args.tmuxp_location.unwrap_or(TmuxpLocation::Home);
+    let target = args
+        .format
+        .unwrap_or_default()
+        .apply(&config.get_file_path(location, Some(&project_dir))?);
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+    let backed_up = write::save_config(&config, &target, &options)?;
+
+    for warning in &warnings {
+        eprintln!("{} {}", style("⚠").yellow().bold(), warning);
+    }
+    if !args.dry_run {
+        show::print_summary(&config);
+        println!(
+            "{} Converted {} to {}{}",
+            style("✓").green().bold(),
+            path.display(),
+            target.display(),
+            if backed_up {
+                " (backed up existing file)"
+            } else {
+                ""
+            }
+        );
+        println!(
+            "  Run {} in {} to generate its .envrc.",
+            style("tmuxify apply").yellow(),
+            project_dir.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASES: BaseIndexes = BaseIndexes {
+        window: 1,
+        pane: 0,
+        guessed: false,
+    };
+
+    fn convert(yaml: &str) -> Conversion {
+        from_tmuxinator(&serde_yaml::from_str(yaml).unwrap(), "fallback", BASES).unwrap()
+    }
+
+    #[test]
+    fn tmuxinator_windows_panes_and_hooks_convert() {
+        let Conversion { config, warnings } = convert(
+            "name: blog\n\
+             root: ~/code/blog\n\
+             on_project_start: docker compose up -d\n\
+             on_project_stop: docker compose down\n\
+             pre_window: nvm use\n\
+             startup_window: server\n\
+             startup_pane: 1\n\
+             windows:\n\
+             - editor: vim\n\
+             - server:\n    layout: main-vertical\n    root: api\n    panes:\n\
+             \x20     - bin/rails s\n      - null\n      - logs:\n          - cd log\n          - tail -f dev.log\n\
+             - shell:\n",
+        );
+
+        assert_eq!(config.session_name, "blog");
+        assert_eq!(config.start_directory, PathBuf::from("~/code/blog"));
+        assert_eq!(config.extra["shell_command_before"][0], "nvm use");
+        assert_eq!(
+            config.extra["before_script"],
+            "sh -c 'docker compose up -d'"
+        );
+
+        let names: Vec<_> = config
+            .windows
+            .iter()
+            .map(|w| w.window_name.clone())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                Some("editor".into()),
+                Some("server".into()),
+                Some("shell".into())
+            ]
+        );
+        assert_eq!(config.windows[0].panes[0].shell_command, ["vim"]);
+
+        let server = &config.windows[1];
+        assert_eq!(server.layout, Some(WindowLayout::MainVertical));
+        assert_eq!(server.extra["start_directory"], "api");
+        assert_eq!(server.focus, Some(true));
+        let panes: Vec<Vec<String>> = server.panes.iter().map(|p| p.commands()).collect();
+        assert_eq!(
+            panes,
+            [
+                vec!["bin/rails s".to_string()],
+                vec![],
+                vec!["cd log".to_string(), "tail -f dev.log".to_string()]
+            ]
+        );
+        assert_eq!(server.panes[1].focus, Some(true));
+        assert!(config.windows[2].panes[0].shell_command.is_empty());
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("`on_project_start` was merged into before_script"));
+        assert!(warnings[1].starts_with("`on_project_stop` isn't converted"));
+    }
+
+    #[test]
+    fn guessed_base_indexes_are_reported() {
+        let yaml = "pre: make deps\n\
+                    on_project_first_start: bin/setup\n\
+                    startup_window: 2\n\
+                    windows:\n- a: ls\n- b: ls\n";
+        let bases = BaseIndexes {
+            guessed: true,
+            ..BASES
+        };
+        let warnings = from_tmuxinator(&serde_yaml::from_str(yaml).unwrap(), "x", bases)
+            .unwrap()
+            .warnings;
+        assert!(warnings[0].starts_with("`on_project_first_start`, `pre` were merged"));
+        assert!(warnings[1].contains("base-index 1 and pane-base-index 0"));
+        assert!(
+            convert(yaml)
+                .warnings
+                .iter()
+                .all(|w| !w.contains("base-index"))
+        );
+    }
+
+    #[test]
+    fn teamocil_windows_and_panes_convert() {
+        let teamocil =
+            |yaml: &str| from_teamocil(&serde_yaml::from_str(yaml).unwrap(), "fallback").unwrap();
+        let Conversion { config, warnings } = teamocil(
+            "name: shop\n\
+             windows:\n\
+             - name: code\n  root: ~/shop\n  layout: main-vertical\n  focus: true\n\
+             \x20 options:\n    synchronize-panes: true\n\
+             \x20 panes:\n  - vim\n  - commands: [git status, git log]\n    focus: true\n  - null\n\
+             - name: logs\n  root: ~/shop\n  panes: [tail -f log/dev.log]\n",
+        );
+        assert_eq!(config.session_name, "shop");
+        assert_eq!(config.start_directory, PathBuf::from("~/shop"));
+        assert!(warnings.is_empty());
+
+        let code = &config.windows[0];
+        assert_eq!(code.layout, Some(WindowLayout::MainVertical));
+        assert_eq!(code.focus, Some(true));
+        assert_eq!(code.options["synchronize-panes"], "on");
+        assert!(code.extra.is_empty());
+        assert_eq!(code.panes[0].shell_command, ["vim"]);
+        assert_eq!(code.panes[1].shell_command, ["git status", "git log"]);
+        assert_eq!(code.panes[1].focus, Some(true));
+        assert!(code.panes[2].shell_command.is_empty());
+
+        // The older schema nests everything under `session:` and uses `splits`
+        let Conversion { config, warnings } = teamocil(
+            "session:\n  windows:\n\
+             \x20 - name: api\n    root: /srv/api\n\
+             \x20   filters: {before: [nvm use], after: [clear]}\n\
+             \x20   splits:\n      - cmd: make run\n  - name: db\n    root: /srv/db\n",
+        );
+        assert_eq!(config.session_name, "fallback");
+        assert_eq!(config.start_directory, PathBuf::from("."));
+        let api = &config.windows[0];
+        assert_eq!(api.extra["start_directory"], "/srv/api");
+        assert_eq!(api.extra["shell_command_before"][0], "nvm use");
+        assert_eq!(api.panes[0].shell_command, ["make run"]);
+        assert_eq!(config.windows[1].panes.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("filters.after"));
+    }
+
+    #[test]
+    fn start_hooks_are_quoted_for_sh() {
+        let config = convert("on_project_start:\n- echo 'hi'\n- make\nwindows:\n- a: ls\n").config;
+        assert_eq!(config.session_name, "fallback");
+        assert_eq!(
+            config.extra["before_script"],
+            r"sh -c 'echo '\''hi'\'' && make'"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! `tmuxify delete`: remove a project's generated files, with backups.
+
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::Confirm;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::model::{Config, ConfigFormat, TmuxpLocation};
+use crate::ops::{load, theme, validate, write};
+
+/// What `delete` does to one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Removal {
+    /// Delete the whole file
+    Delete(PathBuf),
+    /// Rewrite the file without tmuxify's block, keeping the rest
+    Rewrite(PathBuf, String),
+}
+
+impl Removal {
+    fn path(&self) -> &PathBuf {
+        match self {
+            Removal::Delete(path) | Removal::Rewrite(path, _) => path,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Removal::Delete(path) => format!("delete {}", path.display()),
+            Removal::Rewrite(path, _) => {
+                format!("remove tmuxify's block from {}", path.display())
+            }
+        }
+    }
+}
+
+/// The comment only tmuxify's inline `.envrc` block contains
+const INLINE_MARKER: &str = "TMUXIFY_REMOTE_ATTACH";
+
+/// Variables tmuxify writes above its `layout tmux` line
+const LAYOUT_VARIABLES: &[&str] = &["TMUXIFY_TMUXP_ARGS=", "TMUXIFY_DETACHED_SESSION="];
+
+/// The lines of tmuxify's block in `lines`, found by its markers rather than
+/// its exact text, so blocks written by older versions or for other
+/// sessions, formats, and load arguments are recognized
+fn generated_lines(lines: &[&str]) -> Option<std::ops::Range<usize>> {
+    let (mut start, mut end) = if let Some(layout) = lines
+        .iter()
+        .position(|line| line.starts_with("layout tmux"))
+    {
+        let mut start = layout;
+        while start > 0
+            && LAYOUT_VARIABLES
+                .iter()
+                .any(|v| lines[start - 1].starts_with(v))
+        {
+            start -= 1;
+        }
+        (start, layout + 1)
+    } else {
+        // The inline block: `if [ -z "$TMUX" ]` through its `fi`, with the marker
+        let opening = lines.iter().enumerate().position(|(i, line)| {
+            line.starts_with("if [ -z \"$TMUX\" ]")
+                && lines[i..]
+                    .iter()
+                    .take_while(|line| **line != "fi")
+                    .any(|line| line.contains(INLINE_MARKER))
+        })?;
+        let closing = opening + lines[opening..].iter().position(|line| *line == "fi")?;
+        (opening, closing + 1)
+    };
+    if start > 0 && lines[start - 1] == "source_env_if_exists .envrc.local" {
+        start -= 1;
+    }
+    // The commented list of other sessions
+    let mut next = end;
+    while lines.get(next).is_some_and(|line| line.is_empty()) {
+        next += 1;
+    }
+    if lines.get(next) == Some(&"# Other sessions for this project:") {
+        end = next + 1;
+        while lines.get(end).is_some_and(|line| line.starts_with("#   ")) {
+            end += 1;
+        }
+    }
+    Some(start..end)
+}
+
+/// `content` without tmuxify's block: `Some(None)` when nothing else is
+/// left, `Some(Some(rest))` when other lines remain, `None` when no
+/// generated block is found
+fn strip_generated(content: &str) -> Option<Option<String>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let block = generated_lines(&lines)?;
+    let before = lines[..block.start].join("\n");
+    let after = lines[block.end..].join("\n");
+    let rest = [before.trim(), after.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Some((!rest.is_empty()).then(|| format!("{}\n", rest)))
+}
+
+/// Whether the config at `path` starts in the same directory as `config`
+fn same_project(path: &Path, config: &Config) -> bool {
+    load::load_config(path).is_ok_and(|other| other.start_directory == config.start_directory)
+}
+
+/// Remove the project's config (both the project and `~/.tmuxp` copies)
+/// and tmuxify's `.envrc` block, backing each file up first
+pub fn run(args: &Args, yes: bool) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let (config_path, _) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let config = load::load_config(&config_path)?;
+
+    let mut removals = Vec::new();
+    for location in [TmuxpLocation::Project, TmuxpLocation::Home] {
+        let path = config.get_file_path(location, Some(&project_dir))?;
+        for path in [path.clone(), ConfigFormat::Json.apply(&path)] {
+            if !path.is_file() || removals.contains(&Removal::Delete(path.clone())) {
+                continue;
+            }
+            // Home configs are named after the session; leave another project's alone
+            if location == TmuxpLocation::Home
+                && path != config_path
+                && !same_project(&path, &config)
+            {
+                eprintln!(
+                    "{} {} belongs to another project; leaving it alone",
+                    style("⚠").yellow().bold(),
+                    path.display()
+                );
+                continue;
+            }
+            removals.push(Removal::Delete(path));
+        }
+    }
+
+    let envrc = project_dir.join(".envrc");
+    if let Ok(content) = fs::read_to_string(&envrc) {
+        match strip_generated(&content) {
+            Some(None) => removals.push(Removal::Delete(envrc)),
+            Some(Some(rest)) => removals.push(Removal::Rewrite(envrc, rest)),
+            None => eprintln!(
+                "{} {} doesn't contain the block tmuxify generates; leaving it alone",
+                style("⚠").yellow().bold(),
+                envrc.display()
+            ),
+        }
+    }
+
+    println!(
+        "{}",
+        style(format!("Deleting session {}:", config.session_name)).bold()
+    );
+    for removal in &removals {
+        println!("  {}", removal.describe());
+    }
+    if args.dry_run {
+        println!("[DRY RUN] Nothing was deleted");
+        return Ok(());
+    }
+    if !yes {
+        validate::require_terminal("Confirming the delete")?;
+        if !Confirm::with_theme(theme::get())
+            .with_prompt("Delete these files?")
+            .default(false)
+            .interact()?
+        {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    for removal in &removals {
+        let path = removal.path();
+        let backup = write::backup_file(path, args.force)?;
+        match removal {
+            Removal::Delete(_) => fs::remove_file(path),
+            Removal::Rewrite(_, rest) => fs::write(path, rest),
+        }
+        .with_context(|| format!("Failed to update {}", path.display()))?;
+        let verb = match removal {
+            Removal::Delete(_) => "Deleted",
+            Removal::Rewrite(..) => "Removed tmuxify's block from",
+        };
+        let backup = backup
+            .map(|b| format!(" (backup: {})", b.display()))
+            .unwrap_or_default();
+        println!(
+            "  {} {} {}{}",
+            style("✓").green().bold(),
+            verb,
+            path.display(),
+            style(backup).dim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{EnvrcOptions, Window};
+
+    #[test]
+    fn generated_envrc_block_is_stripped_and_the_rest_kept() {
+        let config = Config::new("app".into(), "/src/app".into(), vec![Window::simple()]);
+        let project = config.generate_envrc(TmuxpLocation::Project, &EnvrcOptions::default());
+
+        assert_eq!(strip_generated(&project), Some(None));
+
+        let mixed = format!(
+            "export DATABASE_URL=postgres://localhost\n\n{}\nuse nix\n",
+            project
+        );
+        assert_eq!(
+            strip_generated(&mixed),
+            Some(Some(
+                "export DATABASE_URL=postgres://localhost\n\nuse nix\n".into()
+            ))
+        );
+
+        assert_eq!(strip_generated("use nix\n"), None);
+        let guard = "if [ -z \"$TMUX\" ]; then\n  tmux attach\nfi\n";
+        assert_eq!(strip_generated(guard), None);
+    }
+
+    #[test]
+    fn blocks_for_other_options_are_recognized() {
+        let renamed = Config::new("old-name".into(), "/src/app".into(), vec![Window::simple()]);
+        let options = EnvrcOptions {
+            detach: true,
+            load_args: vec!["--log-file".into(), "/tmp/tmuxp.log".into()],
+            extra_sessions: vec!["~/.tmuxp/app-tests.yaml".into()],
+            ..Default::default()
+        };
+        for use_layout in [false, true] {
+            let options = EnvrcOptions {
+                use_layout,
+                ..options.clone()
+            };
+            let envrc = renamed.generate_envrc(TmuxpLocation::Home, &options);
+            let content = format!("{}\nexport PORT=3000\n", envrc);
+            assert_eq!(
+                strip_generated(&content),
+                Some(Some("export PORT=3000\n".into())),
+                "{}",
+                envrc
+            );
+        }
+    }
+
+    #[test]
+    fn home_configs_of_other_projects_are_not_ours() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new("api".into(), "/tmp/a/api".into(), vec![Window::simple()]);
+        let home = dir.path().join("api.yaml");
+
+        fs::write(&home, config.to_yaml().unwrap()).unwrap();
+        assert!(same_project(&home, &config));
+
+        let other = Config::new("api".into(), "/tmp/b/api".into(), vec![Window::simple()]);
+        fs::write(&home, other.to_yaml().unwrap()).unwrap();
+        assert!(!same_project(&home, &config));
+
+        fs::write(&home, "not: [a config").unwrap();
+        assert!(!same_project(&home, &config));
+    }
+}
@@ -0,0 +1,352 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::model::{Pane, Window, WindowLayout};
+
+/// Maximum number of detected commands placed in a single window
+const MAX_PANES_PER_WINDOW: usize = 4;
+
+/// Broad project ecosystem, used to choose sensible default commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectType {
+    Rust,
+    Node,
+    Python,
+    Go,
+    Unknown,
+}
+
+impl std::fmt::Display for ProjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectType::Rust => write!(f, "rust"),
+            ProjectType::Node => write!(f, "node"),
+            ProjectType::Python => write!(f, "python"),
+            ProjectType::Go => write!(f, "go"),
+            ProjectType::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Detect the project ecosystem from well-known manifest files
+pub fn detect_project_type(project_dir: &Path) -> ProjectType {
+    let has = |name: &str| project_dir.join(name).exists();
+
+    if has("Cargo.toml") {
+        ProjectType::Rust
+    } else if has("package.json") {
+        ProjectType::Node
+    } else if has("pyproject.toml") || has("requirements.txt") || has("setup.py") {
+        ProjectType::Python
+    } else if has("go.mod") {
+        ProjectType::Go
+    } else {
+        ProjectType::Unknown
+    }
+}
+
+/// Where a detected command came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionKind {
+    /// A `scripts` entry in package.json
+    Script,
+    /// A target in a Makefile
+    MakeTarget,
+    /// A service in a docker compose file
+    ComposeService,
+}
+
+impl DetectionKind {
+    /// Short label used for window names and list prefixes
+    pub fn label(&self) -> &'static str {
+        match self {
+            DetectionKind::Script => "scripts",
+            DetectionKind::MakeTarget => "make",
+            DetectionKind::ComposeService => "services",
+        }
+    }
+
+    /// Program the detected command runs, when it isn't the project's own
+    pub fn tool(&self) -> Option<&'static str> {
+        match self {
+            DetectionKind::Script => None,
+            DetectionKind::MakeTarget => Some("make"),
+            DetectionKind::ComposeService => Some("docker"),
+        }
+    }
+}
+
+/// A runnable command discovered in the project directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Detection {
+    pub kind: DetectionKind,
+    pub name: String,
+    pub command: String,
+}
+
+impl std::fmt::Display for Detection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {} ({})",
+            self.kind.label(),
+            self.name,
+            self.command
+        )
+    }
+}
+
+/// Detect runnable commands (package scripts, make targets, compose services)
+pub fn detect_commands(project_dir: &Path) -> Vec<Detection> {
+    let mut detections = Vec::new();
+    detections.extend(detect_package_scripts(project_dir));
+    detections.extend(detect_make_targets(project_dir));
+    detections.extend(detect_compose_services(project_dir));
+    detections
+}
+
+/// Pick the JavaScript package runner based on the lockfile present
+pub fn package_runner(project_dir: &Path) -> &'static str {
+    if project_dir.join("pnpm-lock.yaml").exists() {
+        "pnpm run"
+    } else if project_dir.join("yarn.lock").exists() {
+        "yarn"
+    } else if project_dir.join("bun.lockb").exists() {
+        "bun run"
+    } else {
+        "npm run"
+    }
+}
+
+fn detect_package_scripts(project_dir: &Path) -> Vec<Detection> {
+    let Ok(content) = fs::read_to_string(project_dir.join("package.json")) else {
+        return Vec::new();
+    };
+
+    // JSON is valid YAML, so serde_yaml can read package.json without an extra dependency
+    let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(&content) else {
+        return Vec::new();
+    };
+
+    let runner = package_runner(project_dir);
+    value
+        .get("scripts")
+        .and_then(|s| s.as_mapping())
+        .map(|scripts| {
+            scripts
+                .keys()
+                .filter_map(|k| k.as_str())
+                .map(|name| Detection {
+                    kind: DetectionKind::Script,
+                    name: name.to_string(),
+                    command: format!("{} {}", runner, name),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn detect_make_targets(project_dir: &Path) -> Vec<Detection> {
+    let Some(content) = ["GNUmakefile", "makefile", "Makefile"]
+        .iter()
+        .find_map(|name| fs::read_to_string(project_dir.join(name)).ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut targets: Vec<Detection> = Vec::new();
+    for line in content.lines() {
+        // Recipe lines are indented; targets start at column zero
+        if line.starts_with(char::is_whitespace) || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((target, rest)) = line.split_once(':') else {
+            continue;
+        };
+
+        // Skip variable assignments (`FOO := bar`, `FOO ::= bar`)
+        if rest.starts_with('=') || rest.starts_with(":=") {
+            continue;
+        }
+
+        let target = target.trim();
+        let valid = !target.is_empty()
+            && !target.starts_with('.')
+            && target
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'));
+        if !valid || targets.iter().any(|t| t.name == target) {
+            continue;
+        }
+
+        targets.push(Detection {
+            kind: DetectionKind::MakeTarget,
+            name: target.to_string(),
+            command: format!("make {}", target),
+        });
+    }
+
+    targets
+}
+
+/// The project's docker compose file, if it has one
+pub fn compose_file(project_dir: &Path) -> Option<PathBuf> {
+    [
+        "compose.yaml",
+        "compose.yml",
+        "docker-compose.yaml",
+        "docker-compose.yml",
+    ]
+    .iter()
+    .map(|name| project_dir.join(name))
+    .find(|path| path.is_file())
+}
+
+fn detect_compose_services(project_dir: &Path) -> Vec<Detection> {
+    let Some(content) = compose_file(project_dir).and_then(|path| fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+
+    let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(&content) else {
+        return Vec::new();
+    };
+
+    value
+        .get("services")
+        .and_then(|s| s.as_mapping())
+        .map(|services| {
+            services
+                .keys()
+                .filter_map(|k| k.as_str())
+                .map(|name| Detection {
+                    kind: DetectionKind::ComposeService,
+                    name: name.to_string(),
+                    command: format!("docker compose up {}", name),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Distribute selected detections into windows: one window per source kind,
+/// split into additional windows once a window holds `MAX_PANES_PER_WINDOW` panes
+pub fn distribute_into_windows(selected: &[Detection]) -> Vec<Window> {
+    let mut windows = Vec::new();
+
+    for kind in [
+        DetectionKind::Script,
+        DetectionKind::MakeTarget,
+        DetectionKind::ComposeService,
+    ] {
+        let panes: Vec<Pane> = selected
+            .iter()
+            .filter(|d| d.kind == kind)
+            .map(|d| Pane::new(vec![d.command.clone()]))
+            .collect();
+
+        for (i, chunk) in panes.chunks(MAX_PANES_PER_WINDOW).enumerate() {
+            let name = if i == 0 {
+                kind.label().to_string()
+            } else {
+                format!("{}-{}", kind.label(), i + 1)
+            };
+            windows.push(Window::new(
+                Some(name),
+                WindowLayout::default_for_panes(chunk.len()),
+                chunk.to_vec(),
+            ));
+        }
+    }
+
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn detects_project_type_from_manifest() {
+        let dir = tempdir().unwrap();
+        assert_eq!(detect_project_type(dir.path()), ProjectType::Unknown);
+
+        fs::write(dir.path().join("go.mod"), "module example.com/app\n").unwrap();
+        assert_eq!(detect_project_type(dir.path()), ProjectType::Go);
+
+        fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        assert_eq!(detect_project_type(dir.path()), ProjectType::Rust);
+    }
+
+    #[test]
+    fn detects_package_scripts_with_matching_runner() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"name": "app", "scripts": {"dev": "vite", "test": "vitest"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("yarn.lock"), "").unwrap();
+
+        let found = detect_commands(dir.path());
+        let commands: Vec<&str> = found.iter().map(|d| d.command.as_str()).collect();
+        assert_eq!(commands, vec!["yarn dev", "yarn test"]);
+    }
+
+    #[test]
+    fn detects_make_targets_and_skips_variables() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Makefile"),
+            ".PHONY: build test\nCC := gcc\nbuild: deps\n\tcc main.c\ntest:\n\t./run\n",
+        )
+        .unwrap();
+
+        let names: Vec<String> = detect_commands(dir.path())
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        assert_eq!(names, vec!["build", "test"]);
+    }
+
+    #[test]
+    fn detects_compose_services() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("docker-compose.yml"),
+            "services:\n  db:\n    image: postgres\n  redis:\n    image: redis\n",
+        )
+        .unwrap();
+
+        let found = detect_commands(dir.path());
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].command, "docker compose up db");
+    }
+
+    #[test]
+    fn distributes_by_kind_and_splits_large_groups() {
+        let mut selected: Vec<Detection> = (0..5)
+            .map(|i| Detection {
+                kind: DetectionKind::MakeTarget,
+                name: format!("t{}", i),
+                command: format!("make t{}", i),
+            })
+            .collect();
+        selected.push(Detection {
+            kind: DetectionKind::Script,
+            name: "dev".into(),
+            command: "npm run dev".into(),
+        });
+
+        let windows = distribute_into_windows(&selected);
+        let names: Vec<_> = windows
+            .iter()
+            .map(|w| w.window_name.clone().unwrap())
+            .collect();
+        assert_eq!(names, vec!["scripts", "make", "make-2"]);
+        assert_eq!(windows[1].panes.len(), 4);
+        assert_eq!(windows[2].panes.len(), 1);
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::Result;
+use console::style;
+use std::fs;
+
+use crate::cli::Args;
+use crate::model::Settings;
+use crate::ops::{load, write};
+
+/// Unchanged lines shown around each change in a unified diff
+const CONTEXT: usize = 3;
+
+/// A single line in a line-based diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Compute a line-based diff between two texts (longest common subsequence)
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            diff.push(DiffLine::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(a[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(b[j]));
+            j += 1;
+        }
+    }
+    diff.extend(a[i..].iter().map(|l| DiffLine::Removed(l)));
+    diff.extend(b[j..].iter().map(|l| DiffLine::Added(l)));
+
+    diff
+}
+
+/// Print a diff with `-`/`+` markers, coloring removed and added lines
+pub fn print_diff(old: &str, new: &str) {
+    for line in line_diff(old, new) {
+        match line {
+            DiffLine::Same(l) => println!("  {}", l),
+            DiffLine::Removed(l) => println!("{}", style(format!("- {}", l)).red()),
+            DiffLine::Added(l) => println!("{}", style(format!("+ {}", l)).green()),
+        }
+    }
+}
+
+/// Format a unified diff (`diff -u` style) of two texts; empty when they match
+pub fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let lines = line_diff(old, new);
+    let in_old = |line: &DiffLine| !matches!(line, DiffLine::Added(_));
+    let in_new = |line: &DiffLine| !matches!(line, DiffLine::Removed(_));
+
+    // Ranges of `lines` to show, merging changes whose context overlaps
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+    {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (start, end) in hunks {
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|l| in_old(l)).count();
+        let new_len = hunk.iter().filter(|l| in_new(l)).count();
+        // Empty ranges start at the line before them, as in `diff -u`
+        let old_start =
+            lines[..start].iter().filter(|l| in_old(l)).count() + usize::from(old_len > 0);
+        let new_start =
+            lines[..start].iter().filter(|l| in_new(l)).count() + usize::from(new_len > 0);
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_len, new_start, new_len
+        ));
+        for line in hunk {
+            let (marker, text) = match line {
+                DiffLine::Same(l) => (' ', l),
+                DiffLine::Removed(l) => ('-', l),
+                DiffLine::Added(l) => ('+', l),
+            };
+            out.push(marker);
+            out.push_str(text);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Color a unified diff for the terminal: headers bold, hunk ranges cyan,
+/// removals red, additions green. Plain when colors are off (e.g. piped).
+pub fn colorize(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let styled = if line.starts_with("---") || line.starts_with("+++") {
+                style(line).bold()
+            } else if line.starts_with("@@") {
+                style(line).cyan()
+            } else if line.starts_with('-') {
+                style(line).red()
+            } else if line.starts_with('+') {
+                style(line).green()
+            } else {
+                style(line)
+            };
+            format!("{}\n", styled)
+        })
+        .collect()
+}
+
+/// Regenerate the project's files in memory and print how they differ from
+/// the ones on disk
+pub fn run(args: &Args) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let (path, location) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let config = load::load_config(&path)?;
+    let options = write::WriteOptions {
+        envrc: write::envrc_options(&project_dir, &Settings::load()?),
+        ..Default::default()
+    };
+
+    let mut changed = 0;
+    for (path, content) in write::render_config(&config, location, &project_dir, &options)? {
+        let on_disk = fs::read_to_string(&path).ok();
+        let old_label = match on_disk {
+            Some(_) => path.display().to_string(),
+            None => "/dev/null".to_string(),
+        };
+        let diff = unified(
+            on_disk.as_deref().unwrap_or_default(),
+            &content,
+            &old_label,
+            &path.display().to_string(),
+        );
+        if !diff.is_empty() {
+            changed += 1;
+            print!("{}", colorize(&diff));
+        }
+    }
+
+    if changed == 0 {
+        println!(
+            "{} Files on disk match what tmuxify would generate",
+            style("✓").green().bold()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_shows_changes_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n2\n3\n4\n5\nsix\n7\n8\n9\n10\n11\n";
+        assert_eq!(
+            unified(old, new, "a", "b"),
+            "--- a\n+++ b\n@@ -3,8 +3,9 @@\n 3\n 4\n 5\n-6\n+six\n 7\n 8\n 9\n 10\n+11\n"
+        );
+        assert_eq!(unified(old, old, "a", "b"), "");
+        assert_eq!(
+            unified("", "x\n", "a", "b"),
+            "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+    }
+
+    #[test]
+    fn colorized_diff_keeps_the_text() {
+        let diff = unified("a\nb\n", "a\nc\n", "old", "new");
+        assert_eq!(console::strip_ansi_codes(&colorize(&diff)), diff);
+    }
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let diff = line_diff("a\nb\nc\n", "a\nx\nc\n");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Added("x"),
+                DiffLine::Same("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_handles_trailing_additions_and_removals() {
+        assert_eq!(
+            line_diff("a\n", "a\nb\n"),
+            vec![DiffLine::Same("a"), DiffLine::Added("b")]
+        );
+        assert_eq!(
+            line_diff("a\nb\n", ""),
+            vec![DiffLine::Removed("a"), DiffLine::Removed("b")]
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! Discovery of tmuxp configs (`~/.tmuxp`, `$XDG_CONFIG_HOME/tmuxp`, and
+//! registered projects), with a cache of parsed session names and window
+//! counts keyed by file modification time so repeated lookups skip
+//! re-reading YAML, and of each project's config keyed by its `.envrc`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::ops::{history, load};
+
+/// A tmuxp config found on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discovered {
+    pub path: PathBuf,
+    pub session_name: String,
+    /// Number of windows, or `None` when the config doesn't load
+    pub windows: Option<usize>,
+    /// The registered project that loads this config, if any
+    pub project_dir: Option<PathBuf>,
+}
+
+impl Discovered {
+    /// The file name without extension, which `~/.tmuxp` configs are opened by
+    pub fn stem(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Cached session name and window count of one config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedConfig {
+    /// Modification time in nanoseconds since the epoch
+    modified: u64,
+    session_name: String,
+    // Required, so a cache written before window counts were kept is rebuilt
+    #[serde(deserialize_with = "Option::deserialize")]
+    windows: Option<usize>,
+}
+
+/// Cached config a registered project loads
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedProject {
+    /// Modification time of the project's `.envrc`, which picks the config
+    envrc_modified: Option<u64>,
+    config: PathBuf,
+}
+
+/// Location of the discovery cache
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("tmuxify").join("discovery.yaml"))
+}
+
+/// Location of the cache of which config each project loads
+fn project_cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("tmuxify").join("projects.yaml"))
+}
+
+fn read_cache<T: serde::de::DeserializeOwned>(cache: Option<&Path>) -> BTreeMap<PathBuf, T> {
+    cache
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_yaml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_cache<T: Serialize>(cache: Option<&Path>, entries: &BTreeMap<PathBuf, T>) {
+    if let Some(cache) = cache {
+        let _ = cache
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(cache, serde_yaml::to_string(entries).unwrap_or_default()));
+    }
+}
+
+/// Directories tmuxp reads named configs from
+pub fn config_dirs() -> Vec<PathBuf> {
+    let xdg = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+    dirs::home_dir()
+        .map(|home| home.join(".tmuxp"))
+        .into_iter()
+        .chain(xdg.map(|dir| dir.join("tmuxp")))
+        .collect()
+}
+
+/// Config files directly in `dir`
+fn configs_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml" || ext == "json")
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Every config tmuxify can find, with the project that loads it. A config
+/// loaded by a registered project is listed once, with that project.
+pub fn discover() -> Vec<Discovered> {
+    let projects = history::projects()
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(PathBuf::from)
+        .collect();
+    let mut candidates: Vec<(PathBuf, Option<PathBuf>)> =
+        project_configs(projects, project_cache_path().as_deref())
+            .into_iter()
+            .map(|(dir, path)| (path, Some(dir)))
+            .collect();
+    for dir in config_dirs() {
+        for path in configs_in(&dir) {
+            if !candidates.iter().any(|(known, _)| *known == path) {
+                candidates.push((path, None));
+            }
+        }
+    }
+    resolve(candidates, cache_path().as_deref())
+}
+
+/// The config each project loads, taken from the cache while the project's
+/// `.envrc` is unchanged and the cached config still exists, so listing
+/// doesn't re-read every `.envrc`
+fn project_configs(projects: Vec<PathBuf>, cache: Option<&Path>) -> Vec<(PathBuf, PathBuf)> {
+    let mut cached: BTreeMap<PathBuf, CachedProject> = read_cache(cache);
+    let mut changed = false;
+
+    let found: Vec<(PathBuf, PathBuf)> = projects
+        .into_iter()
+        .filter_map(|dir| {
+            let envrc_modified = modified(&dir.join(".envrc"));
+            let config = match cached.get(&dir) {
+                Some(entry) if entry.envrc_modified == envrc_modified && entry.config.is_file() => {
+                    entry.config.clone()
+                }
+                _ => {
+                    let located = load::locate_project_config(&dir, None).ok();
+                    changed |= cached.remove(&dir).is_some();
+                    let (config, _) = located?;
+                    let entry = CachedProject {
+                        envrc_modified,
+                        config: config.clone(),
+                    };
+                    cached.insert(dir.clone(), entry);
+                    changed = true;
+                    config
+                }
+            };
+            Some((dir, config))
+        })
+        .collect();
+
+    if changed {
+        write_cache(cache, &cached);
+    }
+    found
+}
+
+/// Attach session names and window counts to candidates, from the cache
+/// when a file's mtime is unchanged, and save the cache when anything was
+/// re-read
+fn resolve(candidates: Vec<(PathBuf, Option<PathBuf>)>, cache: Option<&Path>) -> Vec<Discovered> {
+    let mut cached: BTreeMap<PathBuf, CachedConfig> = read_cache(cache);
+    let mut changed = false;
+
+    let discovered: Vec<Discovered> = candidates
+        .into_iter()
+        .filter_map(|(path, project_dir)| {
+            let modified = modified(&path)?;
+            let entry = match cached.get(&path) {
+                Some(entry) if entry.modified == modified => entry.clone(),
+                _ => {
+                    let entry = CachedConfig {
+                        modified,
+                        session_name: read_session_name(&path),
+                        windows: load::load_config(&path).ok().map(|c| c.windows.len()),
+                    };
+                    cached.insert(path.clone(), entry.clone());
+                    changed = true;
+                    entry
+                }
+            };
+            Some(Discovered {
+                path,
+                session_name: entry.session_name,
+                windows: entry.windows,
+                project_dir,
+            })
+        })
+        .collect();
+
+    // Forget files that are gone so the cache doesn't grow forever
+    let before = cached.len();
+    cached.retain(|path, _| path.exists());
+    changed |= cached.len() != before;
+
+    if changed {
+        write_cache(cache, &cached);
+    }
+    discovered
+}
+
+fn modified(path: &Path) -> Option<u64> {
+    let time = fs::metadata(path).ok()?.modified().ok()?;
+    Some(time.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+/// The config's `session_name`, or its file stem when it has none
+fn read_session_name(path: &Path) -> String {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).ok())
+        .and_then(|value| value["session_name"].as_str().map(str::to_string))
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn session_names_are_cached_until_the_file_changes() {
+        let dir = tempdir().unwrap();
+        let cache = dir.path().join("cache").join("discovery.yaml");
+        let config = dir.path().join("api.yaml");
+        fs::write(&config, "session_name: backend\nwindows: []\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        fs::write(dir.path().join("api.yaml.backup.20250101_120000"), "").unwrap();
+        assert_eq!(configs_in(dir.path()), std::slice::from_ref(&config));
+        let candidates = || vec![(config.clone(), None)];
+
+        let found = resolve(candidates(), Some(&cache));
+        assert_eq!(found[0].session_name, "backend");
+        assert_eq!(found[0].stem(), "api");
+        assert!(cache.is_file());
+
+        // An unchanged mtime means the cached name is used without reading
+        let mut cached: BTreeMap<PathBuf, CachedConfig> =
+            serde_yaml::from_str(&fs::read_to_string(&cache).unwrap()).unwrap();
+        cached.get_mut(&config).unwrap().session_name = "from-cache".into();
+        fs::write(&cache, serde_yaml::to_string(&cached).unwrap()).unwrap();
+        assert_eq!(
+            resolve(candidates(), Some(&cache))[0].session_name,
+            "from-cache"
+        );
+
+        cached.get_mut(&config).unwrap().modified -= 1;
+        fs::write(&cache, serde_yaml::to_string(&cached).unwrap()).unwrap();
+        assert_eq!(
+            resolve(candidates(), Some(&cache))[0].session_name,
+            "backend"
+        );
+
+        fs::remove_file(&config).unwrap();
+        assert!(resolve(candidates(), Some(&cache)).is_empty());
+        assert_eq!(fs::read_to_string(&cache).unwrap().trim(), "{}");
+    }
+
+    #[test]
+    fn window_counts_are_cached_and_old_caches_rebuilt() {
+        let dir = tempdir().unwrap();
+        let cache = dir.path().join("discovery.yaml");
+        let app = dir.path().join("app.yaml");
+        let broken = dir.path().join("broken.yaml");
+        fs::write(&app, "session_name: app\nwindows:\n- panes: [vim]\n").unwrap();
+        fs::write(&broken, "session_name: broken\nwindows: oops\n").unwrap();
+        let candidates = || vec![(app.clone(), None), (broken.clone(), None)];
+
+        // A cache from before window counts were kept is read again
+        let old = format!(
+            "{}:\n  modified: {}\n  session_name: app\n",
+            app.display(),
+            modified(&app).unwrap()
+        );
+        fs::write(&cache, old).unwrap();
+        let found = resolve(candidates(), Some(&cache));
+        assert_eq!(found[0].windows, Some(1));
+        assert_eq!(found[1].windows, None);
+
+        let mut cached: BTreeMap<PathBuf, CachedConfig> =
+            serde_yaml::from_str(&fs::read_to_string(&cache).unwrap()).unwrap();
+        cached.get_mut(&app).unwrap().windows = Some(7);
+        fs::write(&cache, serde_yaml::to_string(&cached).unwrap()).unwrap();
+        assert_eq!(resolve(candidates(), Some(&cache))[0].windows, Some(7));
+    }
+
+    #[test]
+    fn project_configs_are_cached_until_the_envrc_changes() {
+        let dir = tempdir().unwrap();
+        let cache = dir.path().join("projects.yaml");
+        let project = dir.path().join("app");
+        fs::create_dir(&project).unwrap();
+        let yaml = project.join(".tmuxp.yaml");
+        fs::write(&yaml, "session_name: app\n").unwrap();
+        fs::write(project.join(".envrc"), "tmuxp load ./.tmuxp.yaml\n").unwrap();
+        let found = || project_configs(vec![project.clone()], Some(&cache));
+
+        assert_eq!(found(), [(project.clone(), yaml.clone())]);
+        assert!(cache.is_file());
+
+        // An unchanged .envrc means the cached mapping is used without reading
+        let json = project.join(".tmuxp.json");
+        fs::write(&json, "{\"session_name\": \"app\"}").unwrap();
+        let mut cached: BTreeMap<PathBuf, CachedProject> =
+            serde_yaml::from_str(&fs::read_to_string(&cache).unwrap()).unwrap();
+        cached.get_mut(&project).unwrap().config = json.clone();
+        fs::write(&cache, serde_yaml::to_string(&cached).unwrap()).unwrap();
+        assert_eq!(found(), [(project.clone(), json.clone())]);
+
+        // A changed .envrc or a missing config is looked up again
+        cached.get_mut(&project).unwrap().envrc_modified = Some(0);
+        fs::write(&cache, serde_yaml::to_string(&cached).unwrap()).unwrap();
+        assert_eq!(found(), [(project.clone(), yaml.clone())]);
+        fs::remove_file(&yaml).unwrap();
+        assert_eq!(found(), [(project.clone(), json)]);
+    }
+}
@@ -1,82 +1,1271 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use console::style;
+use dialoguer::Confirm;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
-use crate::ops::validate;
+use crate::model::Settings;
+use crate::ops::process::{Runner, SystemRunner};
+use crate::ops::{
+    backups, conflicts, hook, lint, load, process, resurrect, theme, tmux, validate,
+    version::VersionInfo, write,
+};
 
-/// Run diagnostics to check system dependencies and configuration
-pub fn run() -> Result<()> {
+/// Outcome of a single diagnostic check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// An optional integration that isn't installed
+    Off,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn symbol(self) -> &'static str {
+        match self {
+            Status::Ok => "✓",
+            Status::Off => "○",
+            Status::Warn => "⚠",
+            Status::Fail => "✗",
+        }
+    }
+
+    fn styled(self) -> console::StyledObject<&'static str> {
+        match self {
+            Status::Ok => style(self.symbol()).green().bold(),
+            Status::Off => style(self.symbol()).dim(),
+            Status::Warn => style(self.symbol()).yellow().bold(),
+            Status::Fail => style(self.symbol()).red().bold(),
+        }
+    }
+}
+
+/// A diagnostic result with optional detail and remediation steps
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub status: Status,
+    pub label: String,
+    pub detail: Option<String>,
+    pub hints: Vec<String>,
+}
+
+impl Check {
+    fn new(status: Status, label: impl Into<String>) -> Self {
+        Self {
+            status,
+            label: label.into(),
+            detail: None,
+            hints: Vec::new(),
+        }
+    }
+
+    fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hints.push(hint.into());
+        self
+    }
+}
+
+/// What a section checks, which decides doctor's exit code when it fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    /// A required program is missing (exit code 10)
+    Dependency,
+    /// The direnv hook or layout library is missing (11)
+    ShellHook,
+    /// The current project's files conflict or disagree (12)
+    Project,
+    /// The tmux server or its socket directory is broken (13)
+    TmuxServer,
+    /// Anything else (1)
+    Other,
+}
+
+impl Category {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Category::Dependency => 10,
+            Category::ShellHook => 11,
+            Category::Project => 12,
+            Category::TmuxServer => 13,
+            Category::Other => 1,
+        }
+    }
+}
+
+/// Returned by `doctor` when a check fails, so the process exits with the
+/// failed category's code instead of printing an error
+#[derive(Debug)]
+pub struct ChecksFailed(pub Category);
+
+impl std::fmt::Display for ChecksFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "doctor checks failed (exit code {})", self.0.exit_code())
+    }
+}
+
+impl std::error::Error for ChecksFailed {}
+
+/// A titled group of checks
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub title: &'static str,
+    pub category: Category,
+    pub checks: Vec<Check>,
+}
+
+/// Run diagnostics to check system dependencies and configuration. With
+/// `fix`, offer to remedy what can be fixed automatically, then check again.
+pub fn run(report: Option<&Path>, fix: bool, dry_run: bool) -> Result<()> {
     println!("{}", style("Running tmuxify doctor...").bold().cyan());
     println!();
 
-    let mut all_ok = true;
+    let mut sections = collect();
+    print_sections(&sections);
+    if fix {
+        validate::require_terminal("doctor --fix")?;
+        if apply_fixes(&SystemRunner::new(dry_run))? && !dry_run {
+            validate::clear_binary_cache();
+            println!();
+            println!("{}", style("Checking again...").bold().cyan());
+            println!();
+            sections = collect();
+            print_sections(&sections);
+        }
+    }
+    if validate::is_interactive() {
+        offer_backup_cleanup()?;
+    }
 
-    // Check each dependency
-    println!("{}", style("Checking dependencies:").bold());
-    for dep in validate::DEPENDENCIES {
-        if dep.is_installed() {
-            println!("  {} {}", style("✓").green().bold(), dep.name);
-        } else {
-            println!(
-                "  {} {} - {}",
-                style("✗").red().bold(),
-                dep.name,
-                style(format!("install with: {}", dep.install_hint())).dim()
+    if let Some(path) = report {
+        fs::write(path, render_markdown(&sections))
+            .with_context(|| format!("Failed to write report to {}", path.display()))?;
+        println!();
+        println!(
+            "{} Wrote report to {}",
+            style("✓").green().bold(),
+            style(path.display()).cyan()
+        );
+    }
+
+    match failed_category(&sections) {
+        Some(category) => Err(ChecksFailed(category).into()),
+        None => Ok(()),
+    }
+}
+
+/// The category of the first failed check in order of [`Category`], which
+/// puts missing dependencies ahead of the problems they cause
+fn failed_category(sections: &[Section]) -> Option<Category> {
+    sections
+        .iter()
+        .filter(|s| s.checks.iter().any(|c| c.status == Status::Fail))
+        .map(|s| s.category)
+        .min()
+}
+
+/// Run every check, grouped into sections
+pub fn collect() -> Vec<Section> {
+    let mut sections = vec![
+        dependency_checks(),
+        optional_checks(),
+        shell_checks(),
+        server_checks(),
+        terminal_checks(),
+        auto_attach_checks(),
+        backup_checks(),
+    ];
+    if let Some(section) = project_checks() {
+        sections.push(section);
+    }
+    if let Some(section) = resurrect_checks() {
+        sections.push(section);
+    }
+
+    // Remote development environments
+    if let Some(env) = validate::detect_remote_environment() {
+        sections.push(Section {
+            title: "Remote development",
+            category: Category::Other,
+            checks: vec![
+                Check::new(Status::Warn, format!("Detected {}", env.name))
+                    .detail(env.env_var)
+                    .hint("Generated .envrc files skip auto-loading tmux here.")
+                    .hint("Export TMUXIFY_REMOTE_ATTACH=1 to opt in."),
+            ],
+        });
+    }
+
+    // WSL-specific notes
+    if validate::is_wsl() {
+        let mut checks = vec![Check::new(Status::Ok, "Running under WSL")];
+        if let Some(cwd) = std::env::current_dir()
+            .ok()
+            .filter(|cwd| validate::is_wsl_windows_mount(cwd))
+        {
+            checks.push(
+                Check::new(Status::Warn, "Current directory is on a Windows drive")
+                    .detail(cwd.display().to_string())
+                    .hint(
+                        "direnv and tmux are slower on /mnt/<drive>; prefer the Linux filesystem",
+                    ),
             );
-            all_ok = false;
         }
+        checks.push(
+            Check::new(
+                Status::Ok,
+                "Windows paths passed to --project are translated",
+            )
+            .detail("C:\\... becomes /mnt/<drive>/..."),
+        );
+        sections.push(Section {
+            title: "WSL",
+            category: Category::Other,
+            checks,
+        });
     }
-    println!();
 
-    // Check shell detection
-    println!("{}", style("Shell configuration:").bold());
-    match validate::detect_shell() {
-        Some(shell) => {
-            println!("  {} Detected shell: {}", style("✓").green().bold(), shell);
-
-            // Check if direnv hook is configured
-            match validate::check_direnv_hook() {
-                Ok(true) => {
-                    println!("  {} direnv hook configured", style("✓").green().bold());
-                }
-                Ok(false) => {
-                    println!("  {} direnv hook not found", style("✗").red().bold());
-                    if let Some(rc_path) = validate::get_shell_rc_path() {
-                        println!("    Add this line to {}:", style(rc_path).cyan());
-                        println!("    {}", style(validate::get_direnv_hook_line()).yellow());
+    sections
+}
+
+fn dependency_checks() -> Section {
+    let checks = validate::DEPENDENCIES
+        .iter()
+        .filter(|dep| dep.is_required())
+        .map(|dep| {
+            if !dep.is_installed() {
+                let check = Check::new(Status::Fail, dep.name)
+                    .hint(format!("install with: {}", dep.install_hint()));
+                return match dep.install_command() {
+                    Some(_) => check.hint("or run: tmuxify doctor --fix"),
+                    None => check,
+                };
+            }
+            let resolved = (dep.binary == "tmuxp")
+                .then(|| validate::resolve_tmuxp(None))
+                .flatten();
+            match resolved {
+                Some(tmuxp) => tmuxp_health(&tmuxp).unwrap_or_else(|| {
+                    let check = Check::new(Status::Ok, dep.name);
+                    if tmuxp.source == "PATH" {
+                        check
+                    } else {
+                        check.detail(format!("{} via {}", tmuxp.source, tmuxp.command))
                     }
-                    all_ok = false;
-                }
-                Err(e) => {
-                    println!(
-                        "  {} Could not check direnv hook: {}",
-                        style("⚠").yellow().bold(),
-                        style(e).dim()
-                    );
-                }
+                }),
+                None => Check::new(Status::Ok, dep.name),
             }
+        })
+        .collect();
+
+    Section {
+        title: "Dependencies",
+        category: Category::Dependency,
+        checks,
+    }
+}
+
+/// Run `tmuxp --version`, which imports tmuxp and libtmux; a failure means
+/// tmuxp is installed but its Python environment is broken
+fn tmuxp_health(tmuxp: &validate::ResolvedTmuxp) -> Option<Check> {
+    let mut parts = tmuxp.command.split_whitespace();
+    let program = parts.next()?;
+    let out = process::output(
+        Command::new(program).args(parts).arg("--version"),
+        process::QUICK_TIMEOUT,
+    );
+    match out {
+        Ok(out) if out.success() => None,
+        Ok(out) => Some(tmuxp_failure(&out.stderr, tmuxp)),
+        Err(e) => {
+            Some(Check::new(Status::Warn, "tmuxp did not answer `--version`").detail(e.to_string()))
+        }
+    }
+}
+
+/// Name the way a tmuxp install is broken from its stderr, with a reinstall
+/// command for where it was installed
+fn tmuxp_failure(stderr: &str, tmuxp: &validate::ResolvedTmuxp) -> Check {
+    let last_line = stderr
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("no error output")
+        .to_string();
+    let label = if stderr.contains("bad interpreter") || stderr.contains("No such file") {
+        "tmuxp's Python interpreter is gone"
+    } else if stderr.contains("SyntaxError") {
+        "tmuxp needs a newer Python than it runs with"
+    } else if stderr.contains("libtmux") && stderr.contains("version") {
+        "tmuxp and libtmux versions don't match"
+    } else if stderr.contains("ModuleNotFoundError") || stderr.contains("ImportError") {
+        "tmuxp's Python environment is missing modules"
+    } else {
+        "tmuxp is installed but fails to run"
+    };
+    let reinstall = match tmuxp.source {
+        "pipx" => "pipx reinstall tmuxp".to_string(),
+        "project virtualenv" => {
+            let bin = tmuxp.command.trim_end_matches("tmuxp");
+            format!("{}pip install --force-reinstall tmuxp libtmux", bin)
+        }
+        "python module" => {
+            let python = tmuxp.command.split_whitespace().next().unwrap_or("python3");
+            format!(
+                "{} -m pip install --user --force-reinstall tmuxp libtmux",
+                python
+            )
+        }
+        _ => "pipx install --force tmuxp".to_string(),
+    };
+    Check::new(Status::Fail, label)
+        .detail(last_line)
+        .hint(format!("Reinstall with: {}", reinstall))
+}
+
+/// Optional integrations never fail doctor; a missing one only turns its feature off
+fn optional_checks() -> Section {
+    let checks = validate::DEPENDENCIES
+        .iter()
+        .filter(|dep| !dep.is_required())
+        .map(|dep| {
+            if dep.is_installed() {
+                Check::new(Status::Ok, dep.name).detail(format!("available: {}", dep.feature))
+            } else {
+                Check::new(Status::Off, dep.name)
+                    .detail(format!("not installed; {} disabled", dep.feature))
+                    .hint(format!("install with: {}", dep.install_hint()))
+            }
+        })
+        .collect();
+    Section {
+        title: "Optional integrations",
+        category: Category::Other,
+        checks,
+    }
+}
+
+fn shell_checks() -> Section {
+    let Some(shell) = validate::detect_shell() else {
+        return Section {
+            title: "Shell configuration",
+            category: Category::ShellHook,
+            checks: vec![Check::new(Status::Warn, "Could not detect shell")],
+        };
+    };
+
+    let mut checks = vec![Check::new(Status::Ok, format!("Detected shell: {}", shell))];
+    checks.push(match validate::check_direnv_hook() {
+        Ok(true) => Check::new(Status::Ok, "direnv hook configured"),
+        Ok(false) => {
+            let check = Check::new(Status::Fail, "direnv hook not found");
+            match validate::get_shell_rc_path() {
+                Some(rc_path) => check
+                    .hint(format!("Add this line to {}:", rc_path))
+                    .hint(validate::get_direnv_hook_line())
+                    .hint(format!("or run: tmuxify hooks {} >> {}", shell, rc_path))
+                    .hint("or let tmuxify doctor --fix add it"),
+                None => check,
+            }
+        }
+        Err(e) => Check::new(Status::Warn, "Could not check direnv hook").detail(e.to_string()),
+    });
+
+    // One-line .envrc files need the shared direnv library
+    if Settings::load().is_ok_and(|s| s.direnv_layout) {
+        checks.push(if hook::direnv_lib_installed() {
+            Check::new(Status::Ok, "direnv layout_tmux library installed")
+        } else {
+            Check::new(
+                Status::Fail,
+                "direnv layout_tmux library missing or outdated",
+            )
+            .detail("direnv_layout is enabled in settings")
+            .hint("tmuxify hook direnv")
+        });
+    }
+
+    Section {
+        title: "Shell configuration",
+        category: Category::ShellHook,
+        checks,
+    }
+}
+
+/// Whether a tmux server can be reached, and the state of its socket directory
+fn server_checks() -> Section {
+    let mut checks = Vec::new();
+    // start-server is a no-op when one runs, and exits with no sessions otherwise
+    let server = process::output(
+        Command::new("tmux").args(["start-server", ";", "list-sessions"]),
+        process::QUICK_TIMEOUT,
+    );
+    checks.push(match server {
+        Ok(out) if out.success() => {
+            let sessions = out.stdout.lines().count();
+            Check::new(Status::Ok, "tmux server responds")
+                .detail(format!("{} session(s)", sessions))
+        }
+        Ok(out) => Check::new(Status::Fail, "tmux server could not be started")
+            .detail(out.stderr.trim().to_string())
+            .hint("Check the socket directory below, or run `tmux kill-server` and retry"),
+        Err(e) if e.to_string().contains("did not finish") => {
+            Check::new(Status::Fail, "tmux server is not responding")
+                .detail("tmuxp hangs while this is the case")
+                .hint("Run `tmux kill-server`, or remove a stale socket in the directory below")
+        }
+        Err(e) => Check::new(Status::Fail, "Could not run tmux").detail(e.to_string()),
+    });
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tmux::socket_dir();
+        let metadata = fs::metadata(&dir).ok().map(|m| (m.uid(), m.mode()));
+        checks.push(socket_dir_check(&dir, tmux::uid(), metadata));
+
+        let tmpdir = std::env::var("TMUX_TMPDIR").ok().filter(|d| !d.is_empty());
+        let socket = std::env::var("TMUX")
+            .ok()
+            .filter(|_| validate::is_inside_tmux());
+        if let Some(check) = tmpdir_check(tmpdir.as_deref(), socket.as_deref(), &dir) {
+            checks.push(check);
+        }
+    }
+
+    Section {
+        title: "tmux server",
+        category: Category::TmuxServer,
+        checks,
+    }
+}
+
+/// tmux refuses a socket directory owned by someone else or open to others
+#[cfg(unix)]
+fn socket_dir_check(dir: &Path, uid: u32, metadata: Option<(u32, u32)>) -> Check {
+    let label = format!("Socket directory {}", dir.display());
+    let fix = format!(
+        "Fix with: chmod 700 {0} (or remove {0} while no tmux runs)",
+        dir.display()
+    );
+    match metadata {
+        None => Check::new(Status::Ok, label).detail("not created yet; tmux creates it"),
+        Some((owner, _)) if owner != uid => Check::new(Status::Fail, label)
+            .detail(format!("owned by uid {}, not {}", owner, uid))
+            .hint(format!(
+                "Remove it while no tmux runs: rm -rf {}",
+                dir.display()
+            )),
+        Some((_, mode)) if mode & 0o077 != 0 => Check::new(Status::Fail, label)
+            .detail(format!(
+                "permissions {:o}; tmux needs 700 and may hang or refuse to start",
+                mode & 0o777
+            ))
+            .hint(fix),
+        Some(_) => Check::new(Status::Ok, label),
+    }
+}
+
+/// Whether the running server lives under `$TMUX_TMPDIR`; it doesn't when
+/// the variable was set after the server started or isn't exported
+#[cfg(unix)]
+fn tmpdir_check(tmpdir: Option<&str>, tmux_env: Option<&str>, socket_dir: &Path) -> Option<Check> {
+    let tmpdir = tmpdir?;
+    if !Path::new(tmpdir).is_dir() {
+        return Some(
+            Check::new(
+                Status::Fail,
+                format!("TMUX_TMPDIR {} does not exist", tmpdir),
+            )
+            .hint(format!("mkdir -p {}", tmpdir)),
+        );
+    }
+    let socket = tmux_env?.split(',').next().unwrap_or_default();
+    Some(if Path::new(socket).starts_with(socket_dir) {
+        Check::new(Status::Ok, format!("TMUX_TMPDIR {} is in use", tmpdir))
+    } else {
+        Check::new(Status::Warn, format!("TMUX_TMPDIR {} is ignored", tmpdir))
+            .detail(format!("this session's server uses {}", socket))
+            .hint("Export TMUX_TMPDIR before the first tmux starts (e.g. in ~/.profile)")
+    })
+}
+
+/// What the terminal and tmux report about colors
+#[derive(Debug, Default)]
+struct TermInfo {
+    term: Option<String>,
+    colorterm: Option<String>,
+    inside_tmux: bool,
+    /// tmux's `default-terminal`; None when no server runs and the config doesn't set it
+    default_terminal: Option<String>,
+    /// Whether a terminfo entry exists for `default_terminal`, when `infocmp` can tell
+    default_terminal_known: Option<bool>,
+    /// `terminal-overrides`/`terminal-features` pass truecolor through (Tc or RGB)
+    tmux_rgb: bool,
+}
+
+impl TermInfo {
+    fn detect() -> Self {
+        let default_terminal = tmux::server_option("default-terminal")
+            .or_else(|| tmux::config_option_values("default-terminal").pop());
+        let default_terminal_known = default_terminal
+            .as_deref()
+            .filter(|_| validate::has_binary("infocmp"))
+            .map(|name| {
+                process::output(Command::new("infocmp").arg(name), process::QUICK_TIMEOUT)
+                    .is_ok_and(|out| out.success())
+            });
+        let tmux_rgb = ["terminal-overrides", "terminal-features"]
+            .iter()
+            .any(|option| {
+                tmux::server_option(option)
+                    .into_iter()
+                    .chain(tmux::config_option_values(option))
+                    .any(|value| value.contains("Tc") || value.contains("RGB"))
+            });
+        Self {
+            term: std::env::var("TERM").ok().filter(|t| !t.is_empty()),
+            colorterm: std::env::var("COLORTERM").ok(),
+            inside_tmux: validate::is_inside_tmux(),
+            default_terminal,
+            default_terminal_known,
+            tmux_rgb,
+        }
+    }
+}
+
+fn terminal_checks() -> Section {
+    Section {
+        title: "Terminal colors",
+        category: Category::Other,
+        checks: term_checks(&TermInfo::detect()),
+    }
+}
+
+/// `$TERM` and tmux's `default-terminal`, which decide the colors and key
+/// handling programs get inside generated sessions
+fn term_checks(info: &TermInfo) -> Vec<Check> {
+    let is_multiplexer = |term: &str| term.starts_with("tmux") || term.starts_with("screen");
+    let has_256 = |term: &str| term.contains("256color") || term.contains("direct");
+    let tmux_conf_hint = "Add to ~/.tmux.conf: set -g default-terminal \"tmux-256color\"";
+    let mut checks = Vec::new();
+
+    checks.push(match info.term.as_deref() {
+        None => Check::new(Status::Fail, "TERM is not set")
+            .hint("Set it in your terminal emulator's settings (e.g. xterm-256color)"),
+        Some("dumb") => Check::new(Status::Warn, "TERM is dumb")
+            .detail("full-screen programs and colors are disabled")
+            .hint("Set it in your terminal emulator's settings (e.g. xterm-256color)"),
+        Some(term) if info.inside_tmux && !is_multiplexer(term) => Check::new(
+            Status::Warn,
+            format!("TERM is {} inside tmux", term),
+        )
+        .detail(
+            "tmux expects tmux-256color or screen-256color; vim, less and htop may redraw wrongly",
+        )
+        .hint("Remove `export TERM=...` from your shell rc and set default-terminal instead"),
+        Some(term) if !info.inside_tmux && is_multiplexer(term) => {
+            Check::new(Status::Warn, format!("TERM is {} outside tmux", term))
+                .detail("only tmux should set a tmux/screen TERM")
+                .hint("Remove `export TERM=...` from your shell rc")
+        }
+        Some(term) if !info.inside_tmux && !has_256(term) && !truecolor(info) => {
+            Check::new(Status::Warn, format!("TERM is {}", term))
+                .detail("it advertises basic colors only")
+                .hint("Set your terminal emulator to report xterm-256color")
+        }
+        Some(term) => Check::new(Status::Ok, format!("TERM is {}", term)),
+    });
+
+    checks.push(match info.default_terminal.as_deref() {
+        None => Check::new(Status::Warn, "tmux default-terminal is not set")
+            .detail("tmux before 3.5 defaults to screen, which has 8 colors")
+            .hint(tmux_conf_hint),
+        Some(term) if !has_256(term) => {
+            Check::new(Status::Warn, format!("tmux default-terminal is {}", term))
+                .detail("programs inside tmux get limited colors")
+                .hint(tmux_conf_hint)
+        }
+        Some(term) if info.default_terminal_known == Some(false) => {
+            Check::new(Status::Warn, format!("No terminfo entry for {}", term))
+                .detail("programs inside tmux fall back to basic terminal handling")
+                .hint("Add to ~/.tmux.conf: set -g default-terminal \"screen-256color\"")
+        }
+        Some(term) => Check::new(Status::Ok, format!("tmux default-terminal is {}", term)),
+    });
+
+    if truecolor(info) && !info.inside_tmux {
+        checks.push(if info.tmux_rgb {
+            Check::new(Status::Ok, "Truecolor passes through tmux")
+        } else {
+            let term = info.term.as_deref().unwrap_or("xterm-256color");
+            Check::new(Status::Warn, "Truecolor stops at tmux")
+                .detail("your terminal supports 24-bit color, but tmux isn't told to pass it on")
+                .hint(format!(
+                    "Add to ~/.tmux.conf: set -as terminal-features \",{}:RGB\"",
+                    term
+                ))
+        });
+    }
+    checks
+}
+
+fn truecolor(info: &TermInfo) -> bool {
+    matches!(info.colorterm.as_deref(), Some("truecolor" | "24bit"))
+}
+
+/// Other tmux auto-attach setups in shell profiles and the current directory
+fn auto_attach_checks() -> Section {
+    let project = std::env::current_dir()
+        .map(|dir| conflicts::project_conflicts(&dir))
+        .unwrap_or_default();
+    let mut checks: Vec<Check> = conflicts::shell_profile_conflicts()
+        .iter()
+        .chain(&project)
+        .map(conflict_check)
+        .collect();
+    if checks.is_empty() {
+        checks.push(Check::new(
+            Status::Ok,
+            "No competing auto-attach setup found",
+        ));
+    }
+    Section {
+        title: "Auto-attach",
+        category: Category::Project,
+        checks,
+    }
+}
+
+/// A warning for a competing auto-attach mechanism
+fn conflict_check(conflict: &conflicts::Conflict) -> Check {
+    Check::new(Status::Warn, conflict.describe())
+        .detail(format!(
+            "{}; two auto-attach setups can nest sessions or attach in a loop",
+            conflict.text
+        ))
+        .hint(conflict.hint())
+}
+
+/// Whether the current directory's generated files agree, when it's a tmuxify project
+fn project_checks() -> Option<Section> {
+    let dir = std::env::current_dir().ok()?;
+    let envrc = fs::read_to_string(dir.join(".envrc")).ok()?;
+    if !load::envrc_loads_tmuxp(&envrc) {
+        return None;
+    }
+    let problems = lint::consistency_problems(&dir);
+    let checks = if problems.is_empty() {
+        vec![Check::new(
+            Status::Ok,
+            ".envrc, config, and project directory agree",
+        )]
+    } else {
+        problems
+            .into_iter()
+            .map(|problem| {
+                Check::new(Status::Fail, problem)
+                    .hint("Run tmuxify here to regenerate the files, or fix them by hand")
+            })
+            .collect()
+    };
+    Some(Section {
+        title: "Project",
+        category: Category::Project,
+        checks,
+    })
+}
+
+/// More backups than this in one directory is reported as clutter
+const BACKUP_CLUTTER: usize = 5;
+
+/// The current project and `~/.tmuxp`, where config writes leave backups
+fn backup_dirs() -> Vec<PathBuf> {
+    let mut dirs_to_check: Vec<PathBuf> = std::env::current_dir().into_iter().collect();
+    if let Some(tmuxp_dir) = dirs::home_dir().map(|home| home.join(".tmuxp"))
+        && !dirs_to_check.contains(&tmuxp_dir)
+    {
+        dirs_to_check.push(tmuxp_dir);
+    }
+    dirs_to_check
+}
+
+fn backup_checks() -> Section {
+    let checks = backup_dirs()
+        .iter()
+        .map(|dir| backup_check(dir, &backups::in_dir(dir)))
+        .collect();
+    Section {
+        title: "Backups",
+        category: Category::Project,
+        checks,
+    }
+}
+
+fn backup_check(dir: &Path, found: &[backups::Backup]) -> Check {
+    let label = format!("{} backup(s) in {}", found.len(), dir.display());
+    let excess = backups::excess(found, backups::KEEP_PER_FILE).len();
+    if found.len() <= BACKUP_CLUTTER || excess == 0 {
+        return Check::new(Status::Ok, label);
+    }
+    Check::new(Status::Warn, label)
+        .detail(format!(
+            "{} older than the newest {} of each file",
+            excess,
+            backups::KEEP_PER_FILE
+        ))
+        .hint("Run `tmuxify backups prune` to delete them")
+}
+
+/// Offer each automatic fix in turn: installing missing dependencies with
+/// the detected package manager, and adding the direnv hook to the shell rc
+/// file. Returns whether any fix was applied.
+fn apply_fixes(runner: &dyn Runner) -> Result<bool> {
+    let mut applied = false;
+    let confirm = |prompt: String| -> Result<bool> {
+        Ok(Confirm::with_theme(theme::get())
+            .with_prompt(prompt)
+            .default(true)
+            .interact()?)
+    };
+
+    for dep in validate::DEPENDENCIES
+        .iter()
+        .filter(|dep| dep.is_required() && !dep.is_installed())
+    {
+        let Some(command) = dep.install_command() else {
+            println!(
+                "{} No package manager found to install {}",
+                style("○").dim(),
+                dep.name
+            );
+            continue;
+        };
+        println!();
+        if confirm(format!("Install {} with `{}`?", dep.name, command))? {
+            runner.run_attached(Command::new("sh").args(["-c", &command]))?;
+            applied = true;
         }
-        None => {
-            println!("  {} Could not detect shell", style("⚠").yellow().bold());
+    }
+    if applied {
+        // Look again, so a direnv installed just now gets its hook below
+        validate::clear_binary_cache();
+    }
+
+    let shell = validate::detect_shell().unwrap_or_default();
+    if matches!(shell.as_str(), "bash" | "zsh")
+        && validate::has_binary("direnv")
+        && !validate::check_direnv_hook().unwrap_or(true)
+        && let Some(rc) = validate::get_shell_rc_path().map(PathBuf::from)
+    {
+        let line = validate::get_direnv_hook_line();
+        println!();
+        if confirm(format!("Append `{}` to {}?", line, rc.display()))? {
+            if runner.is_dry_run() {
+                println!("[DRY RUN] Would append to {}: {}", rc.display(), line);
+            } else {
+                let backup = append_hook_line(&rc, &line)?;
+                println!(
+                    "{} Added the direnv hook to {}{}",
+                    style("✓").green().bold(),
+                    rc.display(),
+                    backup
+                        .map(|b| format!(" (backup: {})", b.display()))
+                        .unwrap_or_default()
+                );
+                println!("  Open a new shell (or source it) for the hook to take effect.");
+            }
+            applied = true;
         }
     }
+    Ok(applied)
+}
+
+/// Append the direnv hook line to `rc`, backing the file up first; a file
+/// that already has the line is left alone
+fn append_hook_line(rc: &Path, line: &str) -> Result<Option<PathBuf>> {
+    let content = fs::read_to_string(rc).unwrap_or_default();
+    if content.lines().any(|l| l.trim() == line) {
+        return Ok(None);
+    }
+    let backup = write::backup_file(rc, false)?;
+    let separator = if content.is_empty() || content.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    fs::write(
+        rc,
+        format!(
+            "{}{}\n# Added by tmuxify doctor --fix\n{}\n",
+            content, separator, line
+        ),
+    )
+    .with_context(|| format!("Failed to update {}", rc.display()))?;
+    Ok(backup)
+}
+
+/// Offer to delete the backups behind a clutter warning
+fn offer_backup_cleanup() -> Result<()> {
+    let found: Vec<(PathBuf, Vec<backups::Backup>)> = backup_dirs()
+        .into_iter()
+        .map(|dir| {
+            let found = backups::in_dir(&dir);
+            (dir, found)
+        })
+        .filter(|(_, found)| found.len() > BACKUP_CLUTTER)
+        .collect();
+    let excess: Vec<&backups::Backup> = found
+        .iter()
+        .flat_map(|(_, found)| backups::excess(found, backups::KEEP_PER_FILE))
+        .collect();
+    if excess.is_empty() {
+        return Ok(());
+    }
+
     println!();
+    let delete = Confirm::with_theme(theme::get())
+        .with_prompt(format!(
+            "Delete {} old backup(s), keeping the newest {} of each file?",
+            excess.len(),
+            backups::KEEP_PER_FILE
+        ))
+        .default(false)
+        .interact()?;
+    if delete {
+        let removed = backups::remove(&excess)?;
+        println!(
+            "{} Deleted {} backup(s)",
+            style("✓").green().bold(),
+            removed
+        );
+    }
+    Ok(())
+}
+
+/// tmux-resurrect/continuum restoring sessions that tmuxp also creates
+fn resurrect_checks() -> Option<Section> {
+    let plugins = resurrect::Plugins::detect();
+    if !plugins.resurrect {
+        return None;
+    }
+    let check = if plugins.excluded {
+        Check::new(
+            Status::Ok,
+            "tmuxify sessions are excluded from resurrect saves",
+        )
+    } else if plugins.auto_restore {
+        Check::new(
+            Status::Warn,
+            "tmux-continuum restores sessions that tmuxp also loads",
+        )
+        .detail("restored and loaded windows are duplicated")
+        .hint("tmuxify resurrect --write")
+    } else {
+        Check::new(Status::Ok, "tmux-resurrect detected")
+            .detail("manual restores can duplicate tmuxify sessions; see `tmuxify resurrect`")
+    };
+    Some(Section {
+        title: "tmux plugins",
+        category: Category::Other,
+        checks: vec![check],
+    })
+}
+
+fn print_sections(sections: &[Section]) {
+    for section in sections {
+        println!("{}", style(format!("{}:", section.title)).bold());
+        for check in &section.checks {
+            match &check.detail {
+                Some(detail) => println!(
+                    "  {} {} {}",
+                    check.status.styled(),
+                    check.label,
+                    style(format!("({})", detail)).dim()
+                ),
+                None => println!("  {} {}", check.status.styled(), check.label),
+            }
+            for hint in &check.hints {
+                println!("    {}", style(hint).yellow());
+            }
+        }
+        println!();
+    }
 
     // Final summary
-    if all_ok {
+    if failed(sections) {
         println!(
             "{}",
-            style("✓ All checks passed! You're ready to use tmuxify.")
-                .green()
+            style("✗ Some issues found. Please address them before using tmuxify.")
+                .red()
                 .bold()
         );
     } else {
         println!(
             "{}",
-            style("✗ Some issues found. Please address them before using tmuxify.")
-                .red()
+            style("✓ All checks passed! You're ready to use tmuxify.")
+                .green()
                 .bold()
         );
     }
+}
 
-    Ok(())
+fn failed(sections: &[Section]) -> bool {
+    failed_category(sections).is_some()
+}
+
+/// Render the checks plus versions and paths as a Markdown report
+pub fn render_markdown(sections: &[Section]) -> String {
+    let info = VersionInfo::current();
+    let mut out = String::from("# tmuxify doctor report\n\n");
+    out.push_str(&format!(
+        "Generated {}\n\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z")
+    ));
+    out.push_str(if failed(sections) {
+        "**Result:** issues found\n\n"
+    } else {
+        "**Result:** all checks passed\n\n"
+    });
+
+    out.push_str("## Environment\n\n");
+    out.push_str("| | |\n|---|---|\n");
+    let mut rows = vec![
+        (
+            "tmuxify",
+            format!(
+                "{}{}",
+                info.version,
+                info.git_sha
+                    .map(|sha| format!(" ({})", sha))
+                    .unwrap_or_default()
+            ),
+        ),
+        (
+            "OS",
+            format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        ),
+        (
+            "Shell",
+            std::env::var("SHELL").unwrap_or_else(|_| "unknown".into()),
+        ),
+        (
+            "TERM",
+            std::env::var("TERM").unwrap_or_else(|_| "unset".into()),
+        ),
+        (
+            "Inside tmux",
+            if validate::is_inside_tmux() {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+        ),
+    ];
+    for dep in validate::DEPENDENCIES
+        .iter()
+        .filter(|dep| dep.is_required())
+    {
+        let version = tool_version(dep.binary).unwrap_or_else(|| "not found".into());
+        rows.push((dep.name, version));
+    }
+    for (name, value) in rows {
+        out.push_str(&format!("| {} | `{}` |\n", name, value.replace('|', "\\|")));
+    }
+    out.push('\n');
+
+    out.push_str("## Paths\n\n");
+    let tmuxp_dir = dirs::home_dir().map(|h| h.join(".tmuxp").display().to_string());
+    let settings = Settings::path().map(|p| p.display().to_string());
+    let tmuxp = validate::resolve_tmuxp(None).map(|t| t.command);
+    for (name, value) in [
+        ("Home tmuxp configs", tmuxp_dir),
+        ("User settings", settings),
+        ("tmuxp command", tmuxp),
+        ("Shell rc file", validate::get_shell_rc_path()),
+    ] {
+        out.push_str(&format!(
+            "- {}: `{}`\n",
+            name,
+            value.unwrap_or_else(|| "unknown".into())
+        ));
+    }
+    out.push('\n');
+
+    for section in sections {
+        out.push_str(&format!("## {}\n\n", section.title));
+        for check in &section.checks {
+            let marker = match check.status {
+                Status::Ok => "ok",
+                Status::Off => "off",
+                Status::Warn => "warning",
+                Status::Fail => "FAIL",
+            };
+            out.push_str(&format!("- **{}** {}", marker, check.label));
+            if let Some(detail) = &check.detail {
+                out.push_str(&format!(" ({})", detail));
+            }
+            out.push('\n');
+            for hint in &check.hints {
+                out.push_str(&format!("  - `{}`\n", hint));
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// First line of `<binary> -V` (or `--version`), if the tool runs
+fn tool_version(binary: &str) -> Option<String> {
+    let command = match binary {
+        "tmuxp" => validate::resolve_tmuxp(None)?.command,
+        other => other.to_string(),
+    };
+    let mut parts = command.split_whitespace();
+    let program = parts.next()?;
+    let base: Vec<&str> = parts.collect();
+
+    ["-V", "--version", "version"].iter().find_map(|flag| {
+        process::run(
+            Command::new(program).args(&base).arg(flag),
+            process::QUICK_TIMEOUT,
+        )
+        .ok()
+        .and_then(|out| out.lines().next().map(|l| l.trim().to_string()))
+        .filter(|line| !line.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(info: &TermInfo) -> Vec<(Status, String)> {
+        term_checks(info)
+            .into_iter()
+            .map(|check| (check.status, check.label))
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hook_line_is_appended_once_with_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let rc = dir.path().join(".zshrc");
+        fs::write(&rc, "export EDITOR=vim").unwrap();
+        let line = "eval \"$(direnv hook zsh)\"";
+
+        let backup = append_hook_line(&rc, line).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "export EDITOR=vim");
+        let content = fs::read_to_string(&rc).unwrap();
+        assert!(content.starts_with("export EDITOR=vim\n\n# Added by tmuxify"));
+        assert!(content.ends_with(&format!("{}\n", line)));
+
+        assert_eq!(append_hook_line(&rc, line).unwrap(), None);
+        assert_eq!(fs::read_to_string(&rc).unwrap(), content);
+    }
+
+    #[test]
+    fn socket_directory_must_be_private_and_owned() {
+        let dir = Path::new("/tmp/tmux-1000");
+        assert_eq!(socket_dir_check(dir, 1000, None).status, Status::Ok);
+        assert_eq!(
+            socket_dir_check(dir, 1000, Some((1000, 0o40700))).status,
+            Status::Ok
+        );
+        let open = socket_dir_check(dir, 1000, Some((1000, 0o40777)));
+        assert_eq!(open.status, Status::Fail);
+        assert_eq!(
+            open.detail.as_deref(),
+            Some("permissions 777; tmux needs 700 and may hang or refuse to start")
+        );
+        assert_eq!(
+            socket_dir_check(dir, 1000, Some((0, 0o40700))).status,
+            Status::Fail
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tmpdir_must_hold_the_running_server() {
+        let tmp = tempfile::tempdir().unwrap();
+        let tmpdir = tmp.path().to_str().unwrap();
+        let socket_dir = tmp.path().join("tmux-1000");
+
+        assert!(tmpdir_check(None, None, &socket_dir).is_none());
+        let in_use = format!("{}/default,123,0", socket_dir.display());
+        let check = tmpdir_check(Some(tmpdir), Some(&in_use), &socket_dir).unwrap();
+        assert_eq!(check.status, Status::Ok);
+        let check = tmpdir_check(
+            Some(tmpdir),
+            Some("/tmp/tmux-1000/default,1,0"),
+            &socket_dir,
+        )
+        .unwrap();
+        assert_eq!(check.status, Status::Warn);
+        let missing = tmpdir_check(Some("/no/such/dir"), None, &socket_dir).unwrap();
+        assert_eq!(missing.status, Status::Fail);
+    }
+
+    #[test]
+    fn many_old_backups_are_clutter() {
+        let dir = Path::new("/p");
+        let backup = |original: &str, day: usize| backups::Backup {
+            path: dir.join(format!("{}.backup.202501{:02}_000000", original, day)),
+            original: original.to_string(),
+            timestamp: format!("202501{:02}_000000", day),
+        };
+
+        let few: Vec<_> = (1..=5).map(|day| backup(".envrc", day)).collect();
+        assert_eq!(backup_check(dir, &few).status, Status::Ok);
+
+        let many: Vec<_> = (1..=7).map(|day| backup(".envrc", day)).collect();
+        let check = backup_check(dir, &many);
+        assert_eq!(check.status, Status::Warn);
+        assert_eq!(
+            check.detail.as_deref(),
+            Some("4 older than the newest 3 of each file")
+        );
+
+        // Spread over enough files, nothing is beyond the newest of each
+        let spread: Vec<_> = [".envrc", ".tmuxp.yaml", ".tmuxp.dev.yaml"]
+            .iter()
+            .flat_map(|original| (1..=2).map(|day| backup(original, day)))
+            .collect();
+        assert_eq!(backup_check(dir, &spread).status, Status::Ok);
+    }
+
+    #[test]
+    fn term_checks_flag_color_problems() {
+        let healthy = TermInfo {
+            term: Some("xterm-256color".into()),
+            default_terminal: Some("tmux-256color".into()),
+            default_terminal_known: Some(true),
+            ..Default::default()
+        };
+        assert!(
+            statuses(&healthy)
+                .iter()
+                .all(|(status, _)| *status == Status::Ok)
+        );
+
+        let overridden = TermInfo {
+            term: Some("xterm-256color".into()),
+            inside_tmux: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            statuses(&overridden),
+            [
+                (
+                    Status::Warn,
+                    "TERM is xterm-256color inside tmux".to_string()
+                ),
+                (Status::Warn, "tmux default-terminal is not set".to_string()),
+            ]
+        );
+
+        let truecolor = TermInfo {
+            colorterm: Some("truecolor".into()),
+            default_terminal: Some("screen".into()),
+            ..healthy
+        };
+        let checks = term_checks(&truecolor);
+        assert_eq!(checks[1].label, "tmux default-terminal is screen");
+        assert_eq!(checks[2].label, "Truecolor stops at tmux");
+        assert_eq!(
+            checks[2].hints,
+            ["Add to ~/.tmux.conf: set -as terminal-features \",xterm-256color:RGB\""]
+        );
+    }
+
+    #[test]
+    fn broken_tmuxp_installs_get_a_targeted_reinstall() {
+        let pipx = validate::ResolvedTmuxp {
+            command: "/home/u/.local/bin/tmuxp".into(),
+            source: "pipx",
+        };
+        let check = tmuxp_failure(
+            "Traceback (most recent call last):\n  File \"...\", line 5\n\
+             ModuleNotFoundError: No module named 'libtmux'\n",
+            &pipx,
+        );
+        assert_eq!(check.label, "tmuxp's Python environment is missing modules");
+        assert_eq!(
+            check.detail.as_deref(),
+            Some("ModuleNotFoundError: No module named 'libtmux'")
+        );
+        assert_eq!(check.hints, ["Reinstall with: pipx reinstall tmuxp"]);
+
+        let venv = validate::ResolvedTmuxp {
+            command: "./.venv/bin/tmuxp".into(),
+            source: "project virtualenv",
+        };
+        let check = tmuxp_failure(
+            "/bin/sh: ./.venv/bin/tmuxp: /usr/bin/python3.9: bad interpreter\n",
+            &venv,
+        );
+        assert_eq!(check.label, "tmuxp's Python interpreter is gone");
+        assert_eq!(
+            check.hints,
+            ["Reinstall with: ./.venv/bin/pip install --force-reinstall tmuxp libtmux"]
+        );
+    }
+
+    #[test]
+    fn exit_code_comes_from_the_most_basic_failure() {
+        let section = |category, status| Section {
+            title: "t",
+            category,
+            checks: vec![Check::new(status, "c")],
+        };
+        let mut sections = vec![
+            section(Category::Other, Status::Fail),
+            section(Category::Dependency, Status::Warn),
+        ];
+        assert_eq!(failed_category(&sections), Some(Category::Other));
+
+        sections.push(section(Category::ShellHook, Status::Fail));
+        sections.push(section(Category::TmuxServer, Status::Fail));
+        assert_eq!(
+            failed_category(&sections).map(Category::exit_code),
+            Some(11)
+        );
+
+        assert_eq!(failed_category(&sections[1..2]), None);
+    }
+
+    #[test]
+    fn markdown_report_lists_checks_and_hints() {
+        let sections = vec![Section {
+            title: "Shell configuration",
+            category: Category::ShellHook,
+            checks: vec![
+                Check::new(Status::Ok, "Detected shell: zsh"),
+                Check::new(Status::Fail, "direnv hook not found")
+                    .hint("eval \"$(direnv hook zsh)\""),
+            ],
+        }];
+
+        let report = render_markdown(&sections);
+        assert!(report.starts_with("# tmuxify doctor report\n"));
+        assert!(report.contains("**Result:** issues found"));
+        assert!(report.contains("## Shell configuration\n\n- **ok** Detected shell: zsh\n"));
+        assert!(
+            report
+                .contains("- **FAIL** direnv hook not found\n  - `eval \"$(direnv hook zsh)\"`\n")
+        );
+        // No terminal escape codes in the report
+        assert!(!report.contains('\u{1b}'));
+    }
 }
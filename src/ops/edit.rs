@@ -0,0 +1,379 @@
+use anyhow::Result;
+use console::style;
+use dialoguer::{Confirm, Editor};
+
+use crate::cli::Args;
+use crate::model::{Config, Pane, Settings, Window, WindowLayout};
+use crate::ops::{diff, interactive, lint, load, theme, tmux, validate, write};
+
+/// A built-in layout name, or a split description such as h70[v50,v50]
+/// along with the number of panes it splits into
+pub(crate) fn parse_layout(layout: &str) -> Result<(WindowLayout, Option<usize>)> {
+    if let Some(builtin) = WindowLayout::from_str(layout) {
+        return Ok((builtin, None));
+    }
+    let (custom, count) = WindowLayout::from_split(layout).map_err(|e| {
+        let valid: Vec<String> = WindowLayout::all().iter().map(|l| l.to_string()).collect();
+        anyhow::anyhow!(
+            "Invalid layout: {}. Use one of: {}, or a split like h70[v50,v50] ({})",
+            layout,
+            valid.join(", "),
+            e
+        )
+    })?;
+    Ok((custom, Some(count)))
+}
+
+/// Append a window described by CLI flags to the project's existing config
+pub fn add_window(
+    args: &Args,
+    name: Option<String>,
+    layout: Option<String>,
+    panes: Vec<String>,
+    index: Option<u32>,
+    env: Vec<String>,
+) -> Result<()> {
+    let environment = env
+        .iter()
+        .map(|pair| interactive::parse_env_assignment(pair))
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| anyhow::anyhow!("Invalid --env: {}", e))?;
+
+    let (layout, split_panes) = match layout {
+        Some(l) => {
+            let (layout, count) = parse_layout(&l)?;
+            (Some(layout), count)
+        }
+        None => (None, None),
+    };
+
+    if let Some(count) = split_panes.filter(|&count| count != panes.len().max(1)) {
+        anyhow::bail!(
+            "Split layout expects {} panes but {} --pane given",
+            count,
+            panes.len()
+        );
+    }
+
+    let project_dir = args.project_dir()?;
+    let (path, _) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let mut config = load::load_config(&path)?;
+
+    // No --pane flags means a single empty pane
+    let panes = if panes.is_empty() {
+        vec![Pane::empty()]
+    } else {
+        panes.into_iter().map(|cmd| Pane::new(vec![cmd])).collect()
+    };
+
+    // No --layout: pick one from the pane count
+    let layout = match layout {
+        Some(layout) => Some(layout),
+        None => Settings::load()?.default_layout_for(panes.len()),
+    };
+
+    let mut window = Window::new(name, layout, panes);
+    window.window_index = index;
+    window.environment = environment;
+    if let Some(warning) = window.layout_warning(validate::terminal_size()) {
+        eprintln!("{} {}", style("⚠").yellow().bold(), warning.message);
+    }
+    let label = window
+        .window_name
+        .clone()
+        .unwrap_or_else(|| format!("#{}", config.windows.len() + 1));
+    config.windows.push(window);
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+    let backed_up = write::save_config(&config, &path, &options)?;
+
+    if !args.dry_run {
+        println!(
+            "{} Added window {} to {}{}",
+            style("✓").green().bold(),
+            style(label).cyan(),
+            path.display(),
+            if backed_up {
+                " (backed up existing file)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove a window (or a single pane of it) from the project's existing config
+pub fn rm_window(args: &Args, target: &str, pane: Option<usize>) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let (path, _) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let original = load::load_config(&path)?;
+    let mut config = original.clone();
+
+    let idx = find_window(&config, target)?;
+    let removed = match pane {
+        Some(pane_num) => {
+            let window = &mut config.windows[idx];
+            if pane_num == 0 || pane_num > window.panes.len() {
+                anyhow::bail!(
+                    "Window {} has {} pane(s); --pane must be between 1 and {}",
+                    target,
+                    window.panes.len(),
+                    window.panes.len()
+                );
+            }
+            if window.panes.len() == 1 {
+                anyhow::bail!(
+                    "Cannot remove the only pane of window {}; remove the window instead",
+                    target
+                );
+            }
+            window.panes.remove(pane_num - 1);
+            format!("pane #{} of window {}", pane_num, target)
+        }
+        None => {
+            if config.windows.len() == 1 {
+                anyhow::bail!("Cannot remove the only window in {}", path.display());
+            }
+            config.windows.remove(idx);
+            format!("window {}", target)
+        }
+    };
+
+    // Preview the change before saving
+    println!(
+        "{}",
+        style(format!("Changes to {}:", path.display())).bold()
+    );
+    diff::print_diff(&original.to_yaml()?, &config.to_yaml()?);
+    println!();
+
+    if args.dry_run {
+        println!("[DRY RUN] Would remove {}", removed);
+        return Ok(());
+    }
+
+    validate::require_terminal("Confirming rm-window")?;
+    if !Confirm::with_theme(theme::get())
+        .with_prompt("Save changes?")
+        .default(true)
+        .interact()?
+    {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let options = write::WriteOptions {
+        dry_run: false,
+        force: args.force,
+        ..Default::default()
+    };
+    let backed_up = write::save_config(&config, &path, &options)?;
+
+    println!(
+        "{} Removed {}{}",
+        style("✓").green().bold(),
+        removed,
+        if backed_up {
+            " (backed up existing file)"
+        } else {
+            ""
+        }
+    );
+
+    Ok(())
+}
+
+/// Interactively edit the project's config, or just one window of it, then
+/// show the diff and save
+pub fn edit(args: &Args, target: Option<&str>) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let (path, _) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let original = load::load_config(&path)?;
+    let mut config = original.clone();
+    let suppress_history = config.suppress_history.unwrap_or(true);
+    let settings = Settings::load()?;
+
+    match target {
+        Some(target) => {
+            let idx = find_window(&config, target)?;
+            validate::require_terminal("tmuxify edit")?;
+            let window = config.windows[idx].clone();
+            config.windows[idx] = interactive::edit_window_interactive(
+                window,
+                &project_dir,
+                suppress_history,
+                &settings,
+            )?;
+        }
+        None => {
+            validate::require_terminal("tmuxify edit")?;
+            println!(
+                "Editing {} ({})",
+                style(&config.session_name).bold(),
+                path.display()
+            );
+            let windows = std::mem::take(&mut config.windows);
+            config.windows = interactive::edit_windows_interactive(
+                windows,
+                &project_dir,
+                suppress_history,
+                &settings,
+            )?;
+        }
+    }
+
+    let (before, after) = (original.to_yaml()?, config.to_yaml()?);
+    if before == after {
+        println!("No changes.");
+        return Ok(());
+    }
+    println!();
+    println!(
+        "{}",
+        style(format!("Changes to {}:", path.display())).bold()
+    );
+    diff::print_diff(&before, &after);
+    println!();
+
+    if args.dry_run {
+        println!("[DRY RUN] Would save {}", path.display());
+        return Ok(());
+    }
+    if !Confirm::with_theme(theme::get())
+        .with_prompt("Save changes?")
+        .default(true)
+        .interact()?
+    {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let options = write::WriteOptions {
+        force: args.force,
+        ..Default::default()
+    };
+    let backed_up = write::save_config(&config, &path, &options)?;
+    println!(
+        "{} Saved {}{}",
+        style("✓").green().bold(),
+        path.display(),
+        if backed_up {
+            " (backed up existing file)"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Check hand-edited YAML the way `tmuxify lint` would, then parse it
+fn parse_edited(text: &str) -> Result<Config> {
+    let problems: Vec<String> = lint::structure_problems(text)
+        .iter()
+        .map(|p| format!("line {}:{}: {}", p.line, p.column, p.message))
+        .collect();
+    if !problems.is_empty() {
+        anyhow::bail!("{}", problems.join("; "));
+    }
+    let config = Config::from_yaml(text)?;
+    config.validate_window_indexes(tmux::base_index)?;
+    Ok(config)
+}
+
+/// Open `config` as YAML in $EDITOR and return the edited config once it
+/// parses and validates. Closing the editor without saving keeps it as it
+/// was; `None` means an invalid edit was abandoned.
+pub fn edit_yaml(config: &Config) -> Result<Option<Config>> {
+    validate::require_terminal("Editing the config in $EDITOR")?;
+    let mut text = config.to_yaml()?;
+    loop {
+        let Some(edited) = Editor::new().extension(".yaml").edit(&text)? else {
+            return Ok(Some(config.clone()));
+        };
+        match parse_edited(&edited) {
+            Ok(config) => return Ok(Some(config)),
+            Err(e) => {
+                eprintln!("{} Invalid config: {:#}", style("✗").red().bold(), e);
+                text = edited;
+                if !Confirm::with_theme(theme::get())
+                    .with_prompt("Edit it again?")
+                    .default(true)
+                    .interact()?
+                {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+/// Find a window by name, falling back to a 1-based index
+fn find_window(config: &Config, target: &str) -> Result<usize> {
+    if let Some(idx) = config
+        .windows
+        .iter()
+        .position(|w| w.window_name.as_deref() == Some(target))
+    {
+        return Ok(idx);
+    }
+
+    match target.parse::<usize>() {
+        Ok(n) if n >= 1 && n <= config.windows.len() => Ok(n - 1),
+        _ => anyhow::bail!(
+            "No window named '{}' (and not an index between 1 and {})",
+            target,
+            config.windows.len()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(names: &[Option<&str>]) -> Config {
+        let windows = names
+            .iter()
+            .map(|n| Window::new(n.map(str::to_string), None, vec![Pane::empty()]))
+            .collect();
+        Config::new("s".into(), "/tmp".into(), windows)
+    }
+
+    #[test]
+    fn find_window_prefers_names_over_indexes() {
+        let cfg = config_with(&[Some("editor"), Some("1"), None]);
+        assert_eq!(find_window(&cfg, "editor").unwrap(), 0);
+        assert_eq!(find_window(&cfg, "1").unwrap(), 1);
+        assert_eq!(find_window(&cfg, "3").unwrap(), 2);
+    }
+
+    #[test]
+    fn find_window_rejects_out_of_range() {
+        let cfg = config_with(&[Some("editor")]);
+        assert!(find_window(&cfg, "0").is_err());
+        assert!(find_window(&cfg, "2").is_err());
+        assert!(find_window(&cfg, "logs").is_err());
+    }
+
+    #[test]
+    fn edited_yaml_is_linted_before_parsing() {
+        let cfg = config_with(&[Some("editor")]);
+        let edited = cfg.to_yaml().unwrap().replace("editor", "logs");
+        assert_eq!(
+            parse_edited(&edited).unwrap().windows[0]
+                .window_name
+                .as_deref(),
+            Some("logs")
+        );
+
+        let err = parse_edited("session_name: s\nwindows: []\n").unwrap_err();
+        assert!(err.to_string().contains("no windows"), "{}", err);
+        assert!(parse_edited("session_name: [").is_err());
+    }
+}
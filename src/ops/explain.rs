@@ -0,0 +1,79 @@
+use anyhow::Result;
+use console::style;
+
+/// A built-in reference topic
+struct Topic {
+    name: &'static str,
+    summary: &'static str,
+    body: &'static str,
+}
+
+/// Topics available to `tmuxify explain`
+const TOPICS: &[Topic] = &[
+    Topic {
+        name: "layouts",
+        summary: "Built-in window layouts and custom splits, with diagrams",
+        body: include_str!("../../docs/explain/layouts.md"),
+    },
+    Topic {
+        name: "location",
+        summary: "Storing the tmuxp config in ~/.tmuxp vs the project",
+        body: include_str!("../../docs/explain/location.md"),
+    },
+    Topic {
+        name: "direnv",
+        summary: "Shell hooks, direnv allow, and nested-session guards",
+        body: include_str!("../../docs/explain/direnv.md"),
+    },
+    Topic {
+        name: "enter",
+        summary: "Typing commands without running them (enter: false)",
+        body: include_str!("../../docs/explain/enter.md"),
+    },
+];
+
+/// Print a reference topic, or the list of topics when none is given
+pub fn run(topic: Option<&str>) -> Result<()> {
+    let Some(name) = topic else {
+        print_topics();
+        return Ok(());
+    };
+
+    let topic = TOPICS
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = TOPICS.iter().map(|t| t.name).collect();
+            anyhow::anyhow!("Unknown topic: {}. Available: {}", name, names.join(", "))
+        })?;
+
+    render(topic.body);
+    Ok(())
+}
+
+fn print_topics() {
+    println!("{}", style("Available topics:").bold());
+    for topic in TOPICS {
+        println!("  {:<10} {}", style(topic.name).cyan(), topic.summary);
+    }
+    println!();
+    println!(
+        "Run {} to read one.",
+        style("tmuxify explain <topic>").yellow()
+    );
+}
+
+/// Render embedded Markdown with light terminal styling
+fn render(markdown: &str) {
+    for line in markdown.lines() {
+        if let Some(title) = line.strip_prefix("# ") {
+            println!("{}", style(title).bold().cyan());
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            println!("{}", style(heading).bold());
+        } else if line.starts_with("    ") {
+            println!("{}", style(line).yellow());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
@@ -0,0 +1,602 @@
+use anyhow::Result;
+use console::style;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::model::{Config, Pane, Settings, Window};
+use crate::ops::{history, json, load, shell, validate, write};
+
+/// Formats `tmuxify export --to` can produce
+pub const FORMATS: &[&str] = &["vscode-tasks", "systemd", "aliases"];
+
+/// Write the project's config in another tool's format
+pub fn run(args: &Args, to: &str) -> Result<()> {
+    if to == "aliases" {
+        return export_aliases(args);
+    }
+    let project_dir = args.project_dir()?;
+    let (config_path, _) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let config = load::load_config(&config_path)?;
+
+    let (path, content) = match to {
+        "vscode-tasks" => {
+            for (level, key) in vscode_dropped_keys(&config) {
+                eprintln!(
+                    "{} VS Code tasks have no equivalent of {} '{}'; it was left out",
+                    style("⚠").yellow().bold(),
+                    level,
+                    key
+                );
+            }
+            (
+                project_dir.join(".vscode").join("tasks.json"),
+                json::to_json_pretty(&vscode_tasks(&config, &project_dir))?,
+            )
+        }
+        "systemd" => {
+            let unit_dir = dirs::config_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
+                .join("systemd")
+                .join("user");
+            let tmuxp = tmuxp_exec(&project_dir)?;
+            let tmux = validate::find_binary("tmux")
+                .ok_or_else(|| anyhow::anyhow!("tmux is not installed"))?;
+            (
+                unit_dir.join(unit_name(&config)),
+                systemd_unit(&config, &config_path, &project_dir, &tmuxp, &tmux),
+            )
+        }
+        other => anyhow::bail!(
+            "Unknown export format '{}'; use one of: {}",
+            other,
+            FORMATS.join(", ")
+        ),
+    };
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+    let backed_up = write::write_file(Some(&project_dir), &path, content, &options)?;
+    if !args.dry_run {
+        println!(
+            "{} Wrote {}{}",
+            style("✓").green().bold(),
+            path.display(),
+            if backed_up {
+                " (backed up existing file)"
+            } else {
+                ""
+            }
+        );
+    }
+    if to == "systemd" {
+        let unit = unit_name(&config);
+        println!("\nStart the session at login with:");
+        println!("  {}", style("systemctl --user daemon-reload").yellow());
+        println!(
+            "  {}",
+            style(format!("systemctl --user enable --now {}", unit)).yellow()
+        );
+        println!(
+            "To stop starting it: systemctl --user disable --now {}",
+            unit
+        );
+    }
+    Ok(())
+}
+
+/// Write a shell file with a `work-<session>` function per registered project
+fn export_aliases(args: &Args) -> Result<()> {
+    let path = aliases_path()?;
+    let projects: Vec<(String, PathBuf)> = history::projects()?
+        .into_iter()
+        .map(PathBuf::from)
+        .filter_map(|dir| {
+            let (config_path, _) = load::locate_project_config(&dir, None).ok()?;
+            let config = load::load_config(&config_path).ok()?;
+            Some((config.session_name, dir))
+        })
+        .collect();
+    if projects.is_empty() {
+        anyhow::bail!("No registered projects; run tmuxify in a project first");
+    }
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+    write::write_file(None, &path, aliases_file(&projects), &options)?;
+    if !args.dry_run {
+        println!(
+            "{} Wrote {} project function(s) to {}",
+            style("✓").green().bold(),
+            projects.len(),
+            path.display()
+        );
+    }
+    println!("\nAdd this to your ~/.bashrc or ~/.zshrc:");
+    println!(
+        "  {}",
+        style(format!("[ -f {0} ] && . {0}", path.display())).yellow()
+    );
+    println!("Re-run `tmuxify export --to aliases` after adding projects.");
+    Ok(())
+}
+
+/// `~/.config/tmuxify/aliases.sh`, next to the user settings
+fn aliases_path() -> Result<PathBuf> {
+    Settings::path()
+        .and_then(|settings| settings.parent().map(|dir| dir.join("aliases.sh")))
+        .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))
+}
+
+/// One `work-<session>` function per project that changes into it and opens
+/// its session. Later projects with an already-used name are left out.
+fn aliases_file(projects: &[(String, PathBuf)]) -> String {
+    let mut out = String::from(
+        "# Generated by tmuxify; re-run `tmuxify export --to aliases` to update.\n\
+         # Source from ~/.bashrc or ~/.zshrc.\n",
+    );
+    let mut seen = BTreeSet::new();
+    for (session, dir) in projects {
+        let name: String = session
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        if !seen.insert(name.clone()) {
+            out.push_str(&format!(
+                "# skipped {}: work-{} is already defined\n",
+                dir.display(),
+                name
+            ));
+            continue;
+        }
+        out.push_str(&format!(
+            "work-{}() {{ cd -- {} && tmuxify open; }}\n",
+            name,
+            shell::quote(&dir.display().to_string())
+        ));
+    }
+    out
+}
+
+/// `tmuxify-<session>.service`, with characters systemd would escape replaced
+fn unit_name(config: &Config) -> String {
+    let name: String = config
+        .session_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("tmuxify-{}.service", name)
+}
+
+/// The resolved tmuxp command with its program made absolute, since systemd
+/// doesn't search the user's PATH
+fn tmuxp_exec(project_dir: &Path) -> Result<Vec<String>> {
+    let tmuxp = validate::resolve_tmuxp(Some(project_dir)).ok_or_else(|| {
+        anyhow::anyhow!("tmuxp is not installed; run 'tmuxify doctor' for install hints")
+    })?;
+    let mut parts = tmuxp.command.split_whitespace();
+    let program = parts.next().unwrap_or("tmuxp");
+    let absolute = if program.contains('/') {
+        project_dir.join(program)
+    } else {
+        validate::find_binary(program).unwrap_or_else(|| program.into())
+    };
+    Ok(std::iter::once(absolute.display().to_string())
+        .chain(parts.map(str::to_string))
+        .collect())
+}
+
+/// A user service that loads the session detached at login and kills it on stop
+fn systemd_unit(
+    config: &Config,
+    config_path: &Path,
+    project_dir: &Path,
+    tmuxp: &[String],
+    tmux: &Path,
+) -> String {
+    let path = std::env::var("PATH").unwrap_or_default();
+    format!(
+        "# Generated by tmuxify from {config}; re-run `tmuxify export --to systemd` after edits.
+[Unit]
+Description=tmux session {session} (tmuxify)
+
+[Service]
+Type=oneshot
+RemainAfterExit=yes
+WorkingDirectory={dir}
+# Panes inherit this PATH; tmux's server would otherwise get systemd's minimal one
+Environment={path}
+ExecStart={tmuxp} load -d {config_arg}
+ExecStop={tmux} kill-session -t {target}
+# Leave the tmux server (and any other sessions on it) running on stop
+KillMode=process
+
+[Install]
+WantedBy=default.target
+",
+        config = config_path.display(),
+        session = systemd_escape(&config.session_name),
+        dir = systemd_escape(&project_dir.display().to_string()),
+        path = systemd_quote(&format!("PATH={}", path)),
+        tmuxp = tmuxp
+            .iter()
+            .map(|word| systemd_quote(word))
+            .collect::<Vec<_>>()
+            .join(" "),
+        config_arg = systemd_quote(&config_path.display().to_string()),
+        tmux = systemd_quote(&tmux.display().to_string()),
+        target = systemd_quote(&format!("={}", config.session_name)),
+    )
+}
+
+/// Escape `%` so systemd doesn't expand it as a specifier. Settings that
+/// take a single path, like `WorkingDirectory=`, are not unquoted, so
+/// spaces stay as they are.
+fn systemd_escape(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+/// Quote a unit-file command-line argument if needed, escaping `%` specifiers
+fn systemd_quote(arg: &str) -> String {
+    let escaped = systemd_escape(arg);
+    if escaped.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
+
+/// `.vscode/tasks.json`
+#[derive(Debug, Serialize)]
+struct TasksFile {
+    version: &'static str,
+    tasks: Vec<Task>,
+}
+
+#[derive(Debug, Serialize)]
+struct Task {
+    label: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    kind: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<TaskOptions>,
+    #[serde(rename = "dependsOn", skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+    #[serde(rename = "dependsOrder", skip_serializing_if = "Option::is_none")]
+    depends_order: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presentation: Option<Presentation>,
+    #[serde(rename = "isBackground", skip_serializing_if = "std::ops::Not::not")]
+    is_background: bool,
+    #[serde(rename = "problemMatcher")]
+    problem_matcher: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Presentation {
+    /// Tasks sharing a group open as split terminals, like panes in a window
+    group: String,
+    panel: &'static str,
+}
+
+/// One shell task per pane with commands, plus a task per window that starts
+/// its panes together and an "all windows" task
+fn vscode_tasks(config: &Config, project_dir: &Path) -> TasksFile {
+    let mut tasks = Vec::new();
+    let mut window_labels = Vec::new();
+
+    for (i, window) in config.windows.iter().enumerate() {
+        let window_name = window
+            .window_name
+            .clone()
+            .unwrap_or_else(|| format!("window {}", i + 1));
+        let mut pane_labels = Vec::new();
+
+        for (j, pane) in window.panes.iter().enumerate() {
+            if pane.shell_command.is_empty() {
+                continue;
+            }
+            let label = if window.panes.len() == 1 {
+                window_name.clone()
+            } else {
+                format!("{}: pane {}", window_name, j + 1)
+            };
+            pane_labels.push(label.clone());
+            let commands: Vec<String> = [&config.extra, &window.extra, &pane.extra]
+                .into_iter()
+                .flat_map(|extra| extra_commands(extra, "shell_command_before"))
+                .chain(pane.commands())
+                .collect();
+            tasks.push(Task {
+                label,
+                kind: Some("shell"),
+                command: Some(commands.join(" && ")),
+                options: task_options(config, window, pane, project_dir),
+                depends_on: Vec::new(),
+                depends_order: None,
+                presentation: Some(Presentation {
+                    group: window_name.clone(),
+                    panel: "dedicated",
+                }),
+                is_background: true,
+                problem_matcher: Vec::new(),
+            });
+        }
+
+        // A lone pane task already carries the window's name
+        match pane_labels.len() {
+            0 => {}
+            1 => window_labels.push(pane_labels.remove(0)),
+            _ => {
+                let label = format!("{} (all panes)", window_name);
+                window_labels.push(label.clone());
+                tasks.push(compound(label, pane_labels));
+            }
+        }
+    }
+
+    if window_labels.len() > 1 {
+        tasks.push(compound(
+            format!("{}: all windows", config.session_name),
+            window_labels,
+        ));
+    }
+
+    TasksFile {
+        version: "2.0.0",
+        tasks,
+    }
+}
+
+/// A task that starts other tasks in parallel
+fn compound(label: String, depends_on: Vec<String>) -> Task {
+    Task {
+        label,
+        kind: None,
+        command: None,
+        options: None,
+        depends_on,
+        depends_order: Some("parallel"),
+        presentation: None,
+        is_background: false,
+        problem_matcher: Vec::new(),
+    }
+}
+
+/// Keys a VS Code task carries over besides the modelled fields
+const TASK_KEYS: &[&str] = &["shell_command_before", "start_directory"];
+
+/// Config keys with no VS Code task equivalent, as (level, key), once each
+fn vscode_dropped_keys(config: &Config) -> Vec<(&'static str, String)> {
+    let mut dropped = BTreeSet::new();
+    let mut add = |level: &'static str, keys: Vec<&String>| {
+        for key in keys {
+            if !TASK_KEYS.contains(&key.as_str()) {
+                dropped.insert((level, key.clone()));
+            }
+        }
+    };
+    add("session key", config.extra.keys().collect());
+    for window in &config.windows {
+        add("window key", window.extra.keys().collect());
+        add("window option", window.options.keys().collect());
+        for pane in &window.panes {
+            add("pane key", pane.extra.keys().collect());
+        }
+    }
+    dropped.into_iter().collect()
+}
+
+/// A passed-through key holding one command or a list of them
+fn extra_commands(extra: &BTreeMap<String, serde_yaml::Value>, key: &str) -> Vec<String> {
+    match extra.get(key) {
+        Some(serde_yaml::Value::String(command)) => vec![command.clone()],
+        Some(serde_yaml::Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Working directory relative to the workspace, and the merged
+/// session/window/pane environment
+fn task_options(
+    config: &Config,
+    window: &Window,
+    pane: &Pane,
+    project_dir: &Path,
+) -> Option<TaskOptions> {
+    let mut base = config.start_directory.clone();
+    if base.as_os_str().is_empty() || base == project_dir {
+        base = "${workspaceFolder}".into();
+    } else if let Ok(rest) = base.strip_prefix(project_dir) {
+        base = Path::new("${workspaceFolder}").join(rest);
+    }
+    if let Some(dir) = window.extra.get("start_directory").and_then(|v| v.as_str()) {
+        base = base.join(dir);
+    }
+    let cwd = match &pane.start_directory {
+        Some(dir) => Some(base.join(dir)),
+        None if base == Path::new("${workspaceFolder}") => None,
+        None => Some(base),
+    };
+
+    let mut env = config.environment.clone();
+    env.extend(window.environment.clone());
+    env.extend(pane.environment.clone());
+
+    if cwd.is_none() && env.is_empty() {
+        return None;
+    }
+    Some(TaskOptions {
+        cwd: cwd.map(|dir| dir.to_string_lossy().into_owned()),
+        env,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tasks_mirror_panes_grouped_by_window() {
+        let mut dev = Window::new(
+            Some("dev".into()),
+            None,
+            vec![
+                Pane::new(vec!["npm install".into(), "npm run dev".into()]),
+                Pane::new(vec![]),
+                Pane::new(vec!["npm test -- --watch".into()]),
+            ],
+        );
+        dev.environment.insert("PORT".into(), "3000".into());
+        dev.panes[2].start_directory = Some("web".into());
+        let logs = Window::new(
+            Some("logs".into()),
+            None,
+            vec![Pane::new(vec!["tail -f log/dev.log".into()])],
+        );
+        let project = PathBuf::from("/work/app");
+        let config = Config::new("app".into(), project.clone(), vec![dev, logs]);
+
+        let file = vscode_tasks(&config, &project);
+        let labels: Vec<&str> = file.tasks.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "dev: pane 1",
+                "dev: pane 3",
+                "dev (all panes)",
+                "logs",
+                "app: all windows"
+            ]
+        );
+
+        let first = &file.tasks[0];
+        assert_eq!(first.command.as_deref(), Some("npm install && npm run dev"));
+        assert_eq!(first.presentation.as_ref().unwrap().group, "dev");
+        let options = first.options.as_ref().unwrap();
+        assert_eq!(options.cwd, None);
+        assert_eq!(options.env["PORT"], "3000");
+
+        let web = file.tasks[1].options.as_ref().unwrap();
+        assert_eq!(web.cwd.as_deref(), Some("${workspaceFolder}/web"));
+        assert_eq!(file.tasks[2].depends_on, ["dev: pane 1", "dev: pane 3"]);
+        assert!(file.tasks[3].options.is_none());
+
+        let rendered = json::to_json_pretty(&file).unwrap();
+        assert!(rendered.contains("\"dependsOrder\": \"parallel\""));
+        assert!(rendered.contains("\"type\": \"shell\""));
+    }
+
+    #[test]
+    fn tasks_carry_session_and_window_settings() {
+        let config = Config::from_yaml(
+            "session_name: app\n\
+             start_directory: /work/app\n\
+             before_script: ./bin/setup\n\
+             shell_command_before: source .venv/bin/activate\n\
+             environment:\n  RUST_LOG: debug\n\
+             windows:\n\
+             - window_name: web\n\
+             \x20 start_directory: web\n\
+             \x20 shell_command_before: [nvm use]\n\
+             \x20 environment:\n    PORT: '3000'\n\
+             \x20 options:\n    automatic-rename: 'off'\n\
+             \x20 panes:\n  - npm run dev\n",
+        )
+        .unwrap();
+        let project = PathBuf::from("/work/app");
+        let file = vscode_tasks(&config, &project);
+        let task = &file.tasks[0];
+        assert_eq!(
+            task.command.as_deref(),
+            Some("source .venv/bin/activate && nvm use && npm run dev")
+        );
+        let options = task.options.as_ref().unwrap();
+        assert_eq!(options.cwd.as_deref(), Some("${workspaceFolder}/web"));
+        assert_eq!(options.env["RUST_LOG"], "debug");
+        assert_eq!(options.env["PORT"], "3000");
+        assert_eq!(
+            vscode_dropped_keys(&config),
+            [
+                ("session key", "before_script".to_string()),
+                ("window option", "automatic-rename".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn systemd_unit_loads_detached_and_stops_the_session() {
+        let config = Config::new("my app".into(), "/work/app".into(), vec![]);
+        assert_eq!(unit_name(&config), "tmuxify-my-app.service");
+
+        let unit = systemd_unit(
+            &config,
+            Path::new("/home/me/.tmuxp/my app.yaml"),
+            Path::new("/work/app"),
+            &["/usr/bin/tmuxp".to_string()],
+            Path::new("/usr/bin/tmux"),
+        );
+        let exec = "ExecStart=/usr/bin/tmuxp load -d \"/home/me/.tmuxp/my app.yaml\"\n";
+        assert!(unit.contains(exec));
+        assert!(unit.contains("ExecStop=/usr/bin/tmux kill-session -t \"=my app\"\n"));
+        assert_eq!(systemd_quote("50%"), "50%%");
+        assert!(unit.contains("WorkingDirectory=/work/app\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+
+        let unit = systemd_unit(
+            &config,
+            Path::new("/home/me/.tmuxp/app.yaml"),
+            Path::new("/work/100% app"),
+            &["/opt/my tools/uv".to_string(), "run".into(), "tmuxp".into()],
+            Path::new("/opt/my tools/tmux"),
+        );
+        assert!(unit.contains("WorkingDirectory=/work/100%% app\n"));
+        assert!(unit.contains("ExecStart=\"/opt/my tools/uv\" run tmuxp load -d "));
+        assert!(unit.contains("ExecStop=\"/opt/my tools/tmux\" kill-session "));
+    }
+
+    #[test]
+    fn aliases_cd_and_open_each_project_once() {
+        let projects = vec![
+            ("my app".to_string(), PathBuf::from("/src/my app")),
+            ("api".to_string(), PathBuf::from("/src/it's")),
+            ("api".to_string(), PathBuf::from("/old/api")),
+        ];
+        let file = aliases_file(&projects);
+        assert!(file.contains("work-my-app() { cd -- '/src/my app' && tmuxify open; }\n"));
+        assert!(file.contains(r"work-api() { cd -- '/src/it'\''s' && tmuxify open; }"));
+        assert!(file.contains("# skipped /old/api: work-api is already defined\n"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Snapshotting running tmux sessions into tmuxp configs.
+
+use anyhow::Result;
+use console::style;
+use dialoguer::Confirm;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::Args;
+use crate::model::{Config, Pane, Settings, TmuxpLocation, Window, WindowLayout};
+use crate::ops::{layouts, process, resurrect, show, theme, tmux, validate, write};
+
+/// Processes treated as an idle prompt rather than a command to re-run
+const SHELLS: &[&str] = &[
+    "bash", "zsh", "fish", "sh", "dash", "ksh", "tcsh", "csh", "nu", "xonsh",
+];
+
+/// `list-panes` fields, tab-separated; the path comes last since it may contain tabs
+const PANE_FORMAT: &str = "#{window_index}\t#{window_name}\t#{window_layout}\t\
+                           #{window_active}\t#{pane_active}\t#{pane_pid}\t\
+                           #{pane_current_command}\t#{pane_current_path}";
+
+/// One pane of a running session as reported by tmux
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PaneInfo {
+    window_index: u32,
+    window_name: String,
+    window_layout: String,
+    window_active: bool,
+    pane_active: bool,
+    pane_pid: u32,
+    command: String,
+    path: String,
+}
+
+fn parse_listing(listing: &str) -> Vec<PaneInfo> {
+    listing
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(8, '\t');
+            let mut next = || fields.next();
+            Some(PaneInfo {
+                window_index: next()?.parse().ok()?,
+                window_name: next()?.to_string(),
+                window_layout: next()?.to_string(),
+                window_active: next()? == "1",
+                pane_active: next()? == "1",
+                pane_pid: next()?.parse().unwrap_or_default(),
+                command: next()?.to_string(),
+                path: next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Command lines of running processes by parent pid, for recovering the
+/// arguments of the program in the foreground of each pane
+fn child_commands() -> HashMap<u32, String> {
+    process::run(
+        Command::new("ps").args(["-ax", "-o", "ppid=,args="]),
+        process::QUICK_TIMEOUT,
+    )
+    .unwrap_or_default()
+    .lines()
+    .filter_map(|line| {
+        let (ppid, args) = line.trim_start().split_once(' ')?;
+        Some((ppid.parse().ok()?, args.trim().to_string()))
+    })
+    .collect()
+}
+
+/// Build a config from a session's panes: window names and layouts, pane
+/// directories, and the foreground command of panes not sitting at a prompt
+fn config_from_panes(session: &str, panes: &[PaneInfo], children: &HashMap<u32, String>) -> Config {
+    let start_directory = panes.first().map(|p| p.path.clone()).unwrap_or_default();
+    let mut windows: Vec<Window> = Vec::new();
+    let mut current: Option<u32> = None;
+
+    for info in panes {
+        if current != Some(info.window_index) {
+            current = Some(info.window_index);
+            let mut window = Window::new(Some(info.window_name.clone()), None, Vec::new());
+            window.layout = Some(WindowLayout::Custom(info.window_layout.clone()));
+            window.focus = info.window_active.then_some(true);
+            windows.push(window);
+        }
+
+        let commands = if SHELLS.contains(&info.command.as_str()) {
+            Vec::new()
+        } else {
+            let full = children
+                .get(&info.pane_pid)
+                .filter(|args| args.contains(&info.command));
+            vec![full.unwrap_or(&info.command).clone()]
+        };
+        let mut pane = Pane::new(commands)
+            .with_start_directory((info.path != start_directory).then(|| info.path.clone()));
+        pane.focus = info.pane_active.then_some(true);
+        if let Some(window) = windows.last_mut() {
+            window.panes.push(pane);
+        }
+    }
+
+    for window in &mut windows {
+        if window.panes.len() == 1 {
+            window.layout = None;
+            window.panes[0].focus = None;
+        }
+    }
+    if windows.len() == 1 {
+        windows[0].focus = None;
+    }
+    Config::new(session.to_string(), start_directory.into(), windows)
+}
+
+/// Capture a running session as a config
+pub fn capture(session: &str) -> Result<Config> {
+    let target = format!("={}", session);
+    let listing = tmux::query(&["list-panes", "-s", "-t", &target, "-F", PANE_FORMAT])?;
+    let panes = parse_listing(&listing);
+    if panes.is_empty() {
+        anyhow::bail!("tmux reported no panes");
+    }
+    Ok(config_from_panes(session, &panes, &child_commands()))
+}
+
+/// The session of the tmux client this command runs in
+fn current_session() -> Result<String> {
+    if std::env::var_os("TMUX").is_none() {
+        anyhow::bail!("Not inside tmux; name the session to freeze or pass --all");
+    }
+    Ok(tmux::query(&["display-message", "-p", "#{session_name}"])?
+        .trim()
+        .to_string())
+}
+
+/// The first pane's directory as the project, refused when it is `$HOME` or
+/// `/` and confirmed otherwise, since freeze writes an `.envrc` there
+fn guessed_project(dir: &Path, dry_run: bool) -> Result<PathBuf> {
+    if dir == Path::new("/") || dirs::home_dir().is_some_and(|home| home == dir) {
+        anyhow::bail!(
+            "The session's first pane is in {}, which is not a project directory; \
+             pass --project <DIR>",
+            dir.display()
+        );
+    }
+    if !dry_run {
+        validate::require_terminal("Confirming the project directory (or pass --project)")?;
+        let confirmed = Confirm::with_theme(theme::get())
+            .with_prompt(format!("Use {} as the project directory?", dir.display()))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            anyhow::bail!("Aborted; pass --project <DIR> to choose the project directory");
+        }
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// Freeze one session (the current one by default) into a config and
+/// `.envrc` for its project, like the wizard would write them
+pub fn run(args: &Args, session: Option<&str>) -> Result<()> {
+    let session = match session {
+        Some(session) => session.to_string(),
+        None => current_session()?,
+    };
+    if !tmux::has_session(&session) {
+        anyhow::bail!("No running tmux session named '{}'", session);
+    }
+    let mut config = capture(&session)?;
+
+    // The first pane's directory stands in for the project unless --project says otherwise
+    let project_dir = match &args.project {
+        Some(_) => args.project_dir()?,
+        None => guessed_project(&config.start_directory, args.dry_run)?,
+    };
+    config.start_directory = project_dir.clone();
+    if let Some(name) = &args.session {
+        config.session_name = name.clone();
+    }
+    let location = args.tmuxp_location.unwrap_or(TmuxpLocation::Home);
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        envrc: write::envrc_options(&project_dir, &Settings::load()?),
+        format: args.format,
+        ..Default::default()
+    };
+    let result = write::write_config(&config, location, &project_dir, &options)?;
+    if !args.dry_run {
+        show::print_summary(&config);
+        result.print_summary();
+        println!(
+            "{}",
+            style(
+                "Commands are captured as they run now; review the config for \
+                 start-up steps that no longer appear."
+            )
+            .dim()
+        );
+    }
+    Ok(())
+}
+
+/// Freeze every session on the tmux server into `~/.tmuxp/<session>.yaml`
+pub fn run_all(args: &Args) -> Result<()> {
+    let sessions = tmux::list_sessions();
+    if sessions.is_empty() {
+        anyhow::bail!("No tmux server is running");
+    }
+    let managed = resurrect::managed_sessions();
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+
+    let mut captured = Vec::new();
+    let mut skipped = Vec::new();
+    for session in sessions {
+        if session == layouts::DEMO_SESSION {
+            skipped.push((session, "tmuxify layout demo".to_string()));
+            continue;
+        }
+        if session.contains('/') {
+            skipped.push((session, "name can't be used as a file name".to_string()));
+            continue;
+        }
+        if managed.contains(&session) && !args.force {
+            skipped.push((
+                session,
+                "already has a config (--force to overwrite)".to_string(),
+            ));
+            continue;
+        }
+
+        let frozen = capture(&session).and_then(|config| {
+            let path = config.get_file_path(TmuxpLocation::Home, None)?;
+            write::save_config(&config, &path, &options)?;
+            Ok((config, path))
+        });
+        match frozen {
+            Ok((config, path)) => captured.push((config, path)),
+            Err(e) => skipped.push((session, format!("{:#}", e))),
+        }
+    }
+
+    println!();
+    let verb = if args.dry_run {
+        "Would capture"
+    } else {
+        "Captured"
+    };
+    println!("{} {} session(s):", verb, captured.len());
+    for (config, path) in &captured {
+        let panes: usize = config.windows.iter().map(|w| w.panes.len()).sum();
+        println!(
+            "  {} {} → {} {}",
+            style("✓").green().bold(),
+            style(&config.session_name).bold(),
+            path.display(),
+            style(format!(
+                "({} windows, {} panes)",
+                config.windows.len(),
+                panes
+            ))
+            .dim()
+        );
+    }
+    if !skipped.is_empty() {
+        println!("Skipped {} session(s):", skipped.len());
+        for (session, reason) in &skipped {
+            println!("  {} {}: {}", style("-").yellow(), session, reason);
+        }
+    }
+    if !captured.is_empty() {
+        println!(
+            "{}",
+            style(
+                "Commands are captured as they run now; review each config for \
+                 start-up steps that no longer appear."
+            )
+            .dim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = "1\teditor\tb25f,208x52,0,0\t1\t1\t100\tnvim\t/src/app\n\
+                           2\tserver\tc1a2,208x52,0,0{104x52,0,0,1,103x52,105,0,2}\t0\t1\t200\t\
+                           node\t/src/app/api\n\
+                           2\tserver\tc1a2,208x52,0,0{104x52,0,0,1,103x52,105,0,2}\t0\t0\t300\t\
+                           zsh\t/src/app\n";
+
+    #[test]
+    fn listing_parses_each_pane() {
+        let panes = parse_listing(LISTING);
+        assert_eq!(panes.len(), 3);
+        assert_eq!(panes[1].window_name, "server");
+        assert_eq!(panes[1].path, "/src/app/api");
+        assert!(panes[0].window_active && !panes[1].window_active);
+    }
+
+    #[test]
+    fn panes_become_windows_with_commands_and_directories() {
+        let children: HashMap<u32, String> =
+            [(200, "node server.js --port 3000".to_string())].into();
+        let config = config_from_panes("app", &parse_listing(LISTING), &children);
+
+        assert_eq!(config.start_directory, std::path::PathBuf::from("/src/app"));
+        assert_eq!(config.windows.len(), 2);
+
+        let editor = &config.windows[0];
+        assert_eq!(editor.focus, Some(true));
+        assert!(editor.layout.is_none());
+        assert_eq!(editor.panes[0].shell_command, vec!["nvim"]);
+
+        let server = &config.windows[1];
+        assert!(matches!(server.layout, Some(WindowLayout::Custom(_))));
+        assert_eq!(
+            server.panes[0].shell_command,
+            vec!["node server.js --port 3000"]
+        );
+        assert_eq!(
+            server.panes[0].start_directory.as_deref(),
+            Some("/src/app/api")
+        );
+        assert_eq!(server.panes[0].focus, Some(true));
+        assert!(server.panes[1].shell_command.is_empty());
+        assert_eq!(server.panes[1].start_directory, None);
+    }
+
+    #[test]
+    fn home_and_root_are_not_guessed_as_projects() {
+        assert!(guessed_project(Path::new("/"), true).is_err());
+        if let Some(home) = dirs::home_dir() {
+            assert!(guessed_project(&home, true).is_err());
+        }
+        assert_eq!(
+            guessed_project(Path::new("/src/app"), true).unwrap(),
+            Path::new("/src/app")
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! `tmuxify generate`: write a project's config and `.envrc` from flags or
+//! a spec file, without prompting.
+
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::cli::Args;
+use crate::model::{Config, Pane, Settings, TmuxpLocation, Window, WindowLayout};
+use crate::ops::{edit, load, show, validate, write};
+
+/// A `--window` flag before its panes are known
+#[derive(Debug)]
+struct WindowFlag {
+    name: String,
+    layout: Option<WindowLayout>,
+    /// Pane count a split layout expects
+    split_panes: Option<usize>,
+    commands: Vec<String>,
+}
+
+/// Parse `NAME[:LAYOUT][=CMD]`
+fn parse_window(flag: &str) -> Result<WindowFlag> {
+    let (head, command) = match flag.split_once('=') {
+        Some((head, command)) => (head, Some(command)),
+        None => (flag, None),
+    };
+    let (name, layout) = match head.split_once(':') {
+        Some((name, layout)) => (name, Some(layout)),
+        None => (head, None),
+    };
+    if name.trim().is_empty() {
+        anyhow::bail!("Invalid --window '{}': expected NAME[:LAYOUT][=CMD]", flag);
+    }
+    let (layout, split_panes) = match layout {
+        Some(layout) => {
+            let (layout, count) = edit::parse_layout(layout)?;
+            (Some(layout), count)
+        }
+        None => (None, None),
+    };
+    Ok(WindowFlag {
+        name: name.trim().to_string(),
+        layout,
+        split_panes,
+        commands: command.map(str::to_string).into_iter().collect(),
+    })
+}
+
+/// Windows from `--window` and `--pane` flags. Windows without a layout
+/// get `default_layout`, or the layout configured for their pane count.
+fn windows_from_flags(
+    windows: &[String],
+    panes: &[String],
+    default_layout: Option<&WindowLayout>,
+    settings: &Settings,
+) -> Result<Vec<Window>> {
+    let mut flags = windows
+        .iter()
+        .map(|flag| parse_window(flag))
+        .collect::<Result<Vec<_>>>()?;
+    for pane in panes {
+        let (target, command) = pane
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Invalid --pane '{}': expected WINDOW=CMD", pane))?;
+        let window = flags
+            .iter_mut()
+            .find(|w| w.name == target.trim())
+            .ok_or_else(|| anyhow::anyhow!("--pane '{}' names no --window", pane))?;
+        window.commands.push(command.to_string());
+    }
+
+    flags
+        .into_iter()
+        .map(|flag| {
+            let panes: Vec<Pane> = if flag.commands.is_empty() {
+                vec![Pane::empty()]
+            } else {
+                flag.commands
+                    .into_iter()
+                    .map(|command| Pane::new(vec![command]))
+                    .collect()
+            };
+            if let Some(count) = flag.split_panes.filter(|&count| count != panes.len()) {
+                anyhow::bail!(
+                    "Window {}'s split layout expects {} panes but has {}",
+                    flag.name,
+                    count,
+                    panes.len()
+                );
+            }
+            let layout = flag
+                .layout
+                .or_else(|| default_layout.cloned())
+                .or_else(|| settings.default_layout_for(panes.len()));
+            Ok(Window::new(Some(flag.name), layout, panes))
+        })
+        .collect()
+}
+
+/// A config from a spec file: a tmuxp config whose `session_name` and
+/// `start_directory` may be left out
+fn config_from_spec(content: &str, session_name: &str, start_dir: &Path) -> Result<Config> {
+    let mut spec: Value = serde_yaml::from_str(content)?;
+    let map = spec
+        .as_mapping_mut()
+        .ok_or_else(|| anyhow::anyhow!("the spec must be a mapping with a `windows` list"))?;
+    if !map.contains_key("session_name") {
+        map.insert("session_name".into(), session_name.into());
+    }
+    if !map.contains_key("start_directory") {
+        map.insert(
+            "start_directory".into(),
+            start_dir.display().to_string().into(),
+        );
+    }
+    Ok(serde_yaml::from_value(spec)?)
+}
+
+/// Read a spec file, or stdin for `-`
+fn read_spec(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read the spec from stdin")?;
+        return Ok(content);
+    }
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Write the project's files from `--window`/`--pane` flags, a spec file,
+/// `--from`, `--preset`, or a single empty window, never prompting
+pub fn run(args: &Args, windows: &[String], panes: &[String], spec: Option<&Path>) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let settings = Settings::load()?;
+    let default_session = project_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().replace('\u{FFFD}', "_"))
+        .unwrap_or_else(|| "my-session".to_string());
+    let start_dir = match &args.start_dir {
+        Some(dir) => validate::resolve_user_path(dir),
+        None => project_dir.clone(),
+    };
+
+    let mut config = if let Some(path) = spec {
+        let content = read_spec(path)?;
+        let mut config = config_from_spec(&content, &default_session, &start_dir)
+            .with_context(|| format!("Invalid spec {}", path.display()))?;
+        if let Some(dir) = &args.start_dir {
+            config.start_directory = validate::resolve_user_path(dir);
+        }
+        config
+    } else {
+        let windows = if !windows.is_empty() {
+            windows_from_flags(windows, panes, args.layout.as_ref(), &settings)?
+        } else if let Some(from) = &args.from {
+            load::load_config(&load::resolve_config_path(from)?)?.windows
+        } else if let Some(preset) = args.preset {
+            preset.windows(&project_dir)
+        } else {
+            vec![Window::simple()]
+        };
+        Config::new(default_session, start_dir, windows)
+    };
+    if let Some(session) = &args.session {
+        config.session_name = session.clone();
+    }
+    config.environment.extend(args.env.iter().cloned());
+    if config.windows.is_empty() {
+        anyhow::bail!("A config needs at least one window");
+    }
+    config.suppress_history.get_or_insert(true);
+    config.apply_default_focus();
+    if args.edit_preview {
+        match edit::edit_yaml(&config)? {
+            Some(edited) => config = edited,
+            None => {
+                println!("Aborted.");
+                return Ok(());
+            }
+        }
+    }
+
+    let location = args.tmuxp_location.unwrap_or(TmuxpLocation::Home);
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        envrc: write::envrc_options(&project_dir, &settings),
+        format: args.format,
+        ..Default::default()
+    };
+    let result = write::write_config(&config, location, &project_dir, &options)?;
+    if !args.dry_run {
+        show::print_summary(&config);
+        result.print_summary();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn flags_build_windows_and_panes() {
+        let windows = windows_from_flags(
+            &strings(&["editor=vim", "server:main-vertical", "shell"]),
+            &strings(&["server=npm run dev", "server=npm test"]),
+            None,
+            &Settings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].panes[0].shell_command, ["vim"]);
+        assert_eq!(windows[1].layout, Some(WindowLayout::MainVertical));
+        let server: Vec<Vec<String>> = windows[1].panes.iter().map(|p| p.commands()).collect();
+        assert_eq!(server, [strings(&["npm run dev"]), strings(&["npm test"])]);
+        assert!(windows[2].panes[0].shell_command.is_empty());
+
+        let mismatch = windows_from_flags(
+            &strings(&["split:h50[v50,v50]=a"]),
+            &[],
+            None,
+            &Settings::default(),
+        );
+        assert!(
+            mismatch
+                .unwrap_err()
+                .to_string()
+                .contains("expects 3 panes")
+        );
+        assert!(
+            windows_from_flags(&[], &strings(&["nope=ls"]), None, &Settings::default()).is_err()
+        );
+        assert!(parse_window(":tiled").is_err());
+    }
+
+    #[test]
+    fn spec_fills_in_session_and_start_directory() {
+        let config = config_from_spec(
+            "windows:\n- window_name: editor\n  panes: [vim, null]\n",
+            "app",
+            Path::new("/src/app"),
+        )
+        .unwrap();
+        assert_eq!(config.session_name, "app");
+        assert_eq!(config.start_directory, Path::new("/src/app"));
+        assert_eq!(config.windows[0].panes.len(), 2);
+
+        let named = config_from_spec(
+            "session_name: api\nstart_directory: /srv/api\nwindows:\n- panes: [make]\n",
+            "app",
+            Path::new("/src/app"),
+        )
+        .unwrap();
+        assert_eq!(named.session_name, "api");
+        assert_eq!(named.start_directory, Path::new("/srv/api"));
+    }
+}
@@ -0,0 +1,177 @@
+//! Minimal YAML syntax highlighting for previews.
+//!
+//! Colors go through `console`, so output is plain when colors are disabled
+//! (not a terminal, `NO_COLOR`, or `CLICOLOR=0`).
+
+use console::style;
+
+/// What a fragment of a YAML line is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Plain,
+    Key,
+    Punct,
+    Str,
+    Literal,
+    Comment,
+}
+
+/// Split one line into highlighted fragments. `in_block` is true for lines
+/// of a block scalar (`|` or `>`), which are string content.
+fn tokens(line: &str, in_block: bool) -> Vec<(Kind, &str)> {
+    let body = line.trim_start();
+    let (indent, _) = line.split_at(line.len() - body.len());
+    let mut out = vec![(Kind::Plain, indent)];
+
+    if in_block {
+        out.push((Kind::Str, body));
+        return out;
+    }
+    if body.starts_with('#') {
+        out.push((Kind::Comment, body));
+        return out;
+    }
+    if body == "---" || body == "..." {
+        out.push((Kind::Punct, body));
+        return out;
+    }
+
+    let mut rest = body;
+    while let Some(after) = rest.strip_prefix("- ").or((rest == "-").then_some("")) {
+        out.push((Kind::Punct, &rest[..rest.len() - after.len()]));
+        rest = after;
+    }
+
+    let key_end = if rest.starts_with(['"', '\'']) {
+        None
+    } else {
+        rest.find(": ")
+            .or_else(|| rest.strip_suffix(':').map(|key| key.len()))
+    };
+    if let Some(end) = key_end {
+        out.push((Kind::Key, &rest[..end]));
+        out.push((Kind::Punct, ":"));
+        rest = &rest[end + 1..];
+        let value = rest.trim_start();
+        out.push((Kind::Plain, &rest[..rest.len() - value.len()]));
+        rest = value;
+    }
+
+    let (value, comment) = match rest.find(" #") {
+        Some(i) if !rest.starts_with(['"', '\'']) => rest.split_at(i),
+        _ => (rest, ""),
+    };
+    if !value.is_empty() {
+        out.push((value_kind(value), value));
+    }
+    if !comment.is_empty() {
+        out.push((Kind::Comment, comment));
+    }
+    out
+}
+
+fn value_kind(value: &str) -> Kind {
+    let is_literal = matches!(value, "true" | "false" | "null" | "~" | "{}" | "[]")
+        || value.parse::<f64>().is_ok();
+    if is_literal {
+        Kind::Literal
+    } else if value.starts_with(['|', '>']) {
+        Kind::Punct
+    } else {
+        Kind::Str
+    }
+}
+
+/// Highlight YAML: keys, strings, literals, and comments
+pub fn yaml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // Indentation of the key that opened the current block scalar
+    let mut block_indent: Option<usize> = None;
+
+    for line in text.split_inclusive('\n') {
+        let (content, newline) = match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        };
+        let indent = content.len() - content.trim_start().len();
+        let in_block =
+            block_indent.is_some_and(|opened| content.trim().is_empty() || indent > opened);
+        if !in_block {
+            block_indent = None;
+        }
+
+        let fragments = tokens(content, in_block);
+        if !in_block
+            && fragments
+                .last()
+                .is_some_and(|(kind, text)| *kind == Kind::Punct && text.starts_with(['|', '>']))
+        {
+            block_indent = Some(indent);
+        }
+        for (kind, text) in fragments {
+            let styled = match kind {
+                Kind::Plain => text.to_string(),
+                Kind::Key => style(text).cyan().to_string(),
+                Kind::Punct => style(text).dim().to_string(),
+                Kind::Str => style(text).green().to_string(),
+                Kind::Literal => style(text).magenta().to_string(),
+                Kind::Comment => style(text).dim().italic().to_string(),
+            };
+            out.push_str(&styled);
+        }
+        out.push_str(newline);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str) -> Vec<(Kind, &str)> {
+        tokens(line, false)
+            .into_iter()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn lines_split_into_keys_values_and_comments() {
+        assert_eq!(
+            kinds("  - window_name: editor # main"),
+            [
+                (Kind::Punct, "- "),
+                (Kind::Key, "window_name"),
+                (Kind::Punct, ":"),
+                (Kind::Str, "editor"),
+                (Kind::Comment, " # main"),
+            ]
+        );
+        assert_eq!(
+            kinds("suppress_history: false"),
+            [
+                (Kind::Key, "suppress_history"),
+                (Kind::Punct, ":"),
+                (Kind::Literal, "false")
+            ]
+        );
+        assert_eq!(
+            kinds("windows:"),
+            [(Kind::Key, "windows"), (Kind::Punct, ":")]
+        );
+        assert_eq!(
+            kinds("    - 'a: b'"),
+            [(Kind::Punct, "- "), (Kind::Str, "'a: b'")]
+        );
+        assert_eq!(kinds("# comment"), [(Kind::Comment, "# comment")]);
+    }
+
+    #[test]
+    fn highlighting_preserves_the_text() {
+        let yaml_text = "session_name: app\nwindows:\n- panes:\n  - shell_command:\n    - |-\n      \
+                         echo a: b\n      echo c\n    - ls\n";
+        assert_eq!(console::strip_ansi_codes(&yaml(yaml_text)), yaml_text);
+        // Block scalar lines are content, not keys
+        assert_eq!(tokens("      echo a: b", true)[1], (Kind::Str, "echo a: b"));
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::ops::{diff, json};
+
+/// Number of entries `tmuxify history` shows by default
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// One file touched by a write
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    /// Whether the file did not exist before
+    pub created: bool,
+    pub added: usize,
+    pub removed: usize,
+    /// Copy of the previous contents, if one was made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+}
+
+impl FileChange {
+    /// Summarize the change from `old` (None if the file is new) to `new`
+    pub fn new(path: &Path, old: Option<&str>, new: &str, backup: Option<&Path>) -> Self {
+        let (added, removed) =
+            diff::line_diff(old.unwrap_or_default(), new)
+                .iter()
+                .fold((0, 0), |(a, r), line| match line {
+                    diff::DiffLine::Added(_) => (a + 1, r),
+                    diff::DiffLine::Removed(_) => (a, r + 1),
+                    diff::DiffLine::Same(_) => (a, r),
+                });
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            created: old.is_none(),
+            added,
+            removed,
+            backup: backup.map(|b| b.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+/// A recorded write operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: String,
+    pub project: String,
+    pub files: Vec<FileChange>,
+    pub version: String,
+}
+
+/// Location of the history log (`~/.local/share/tmuxify/history.jsonl` on Linux)
+pub fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tmuxify").join("history.jsonl"))
+}
+
+/// Append an entry for a write to the history log
+pub fn record(project: &Path, files: Vec<FileChange>) -> Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    let entry = HistoryEntry {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        project: project.to_string_lossy().into_owned(),
+        files,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    append(&path, &entry)
+}
+
+fn append(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", json::to_json_line(entry)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Read every entry in a history log, skipping lines that fail to parse
+fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    // Each line is JSON, which serde_yaml reads as a flow mapping
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_yaml::from_str(line).ok())
+        .collect())
+}
+
+/// Distinct project directories that appear in the history log, oldest first
+pub fn projects() -> Result<Vec<String>> {
+    let Some(path) = path() else {
+        return Ok(Vec::new());
+    };
+    let mut projects: Vec<String> = Vec::new();
+    for entry in read_entries(&path)? {
+        if !projects.contains(&entry.project) {
+            projects.push(entry.project);
+        }
+    }
+    Ok(projects)
+}
+
+/// Every file tmuxify has written, per the history log; configs missing
+/// from it were written by hand or by another tool
+pub fn written_files() -> Result<Vec<PathBuf>> {
+    let Some(path) = path() else {
+        return Ok(Vec::new());
+    };
+    Ok(files_in(&read_entries(&path)?))
+}
+
+fn files_in(entries: &[HistoryEntry]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = entries
+        .iter()
+        .flat_map(|entry| &entry.files)
+        .map(|file| PathBuf::from(&file.path))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Print recent history, limited to `--project` when given
+pub fn run(args: &Args, limit: usize) -> Result<()> {
+    let path = path().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    let mut entries = read_entries(&path)?;
+
+    if args.project.is_some() {
+        let project = args.project_dir()?;
+        entries.retain(|e| same_project(&e.project, &project));
+    }
+
+    if entries.is_empty() {
+        println!("No history recorded yet ({}).", path.display());
+        return Ok(());
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    for entry in &entries[skip..] {
+        let when = chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|_| entry.timestamp.clone());
+        println!(
+            "{}  {} {}",
+            style(when).bold(),
+            style(&entry.project).cyan(),
+            style(format!("(tmuxify {})", entry.version)).dim()
+        );
+        for file in &entry.files {
+            let (marker, summary) = if file.created {
+                (style("+").green(), "new".to_string())
+            } else {
+                (
+                    style("~").yellow(),
+                    format!("+{} -{}", file.added, file.removed),
+                )
+            };
+            println!("    {} {} {}", marker, file.path, style(summary).dim());
+            if let Some(backup) = &file.backup {
+                println!("      {} {}", style("previous:").dim(), backup);
+            }
+        }
+    }
+
+    if skip > 0 {
+        println!();
+        println!(
+            "{}",
+            style(format!("{} older entries hidden (use --limit)", skip)).dim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Compare a recorded project path with a directory, ignoring trailing slashes
+fn same_project(recorded: &str, dir: &Path) -> bool {
+    let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    canonical(Path::new(recorded.trim_end_matches('/'))) == canonical(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn summarizes_line_changes() {
+        let change = FileChange::new(
+            Path::new("/p/.tmuxp.yaml"),
+            Some("a\nb\n"),
+            "a\nc\nd\n",
+            None,
+        );
+        assert!(!change.created);
+        assert_eq!((change.added, change.removed), (2, 1));
+
+        let created = FileChange::new(Path::new("/p/.envrc"), None, "x\n", None);
+        assert!(created.created);
+        assert_eq!(created.added, 1);
+    }
+
+    #[test]
+    fn appends_and_reads_entries() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("history.jsonl");
+        let entry = HistoryEntry {
+            timestamp: "2025-01-02T03:04:05+00:00".into(),
+            project: "/work/app".into(),
+            files: vec![FileChange::new(
+                Path::new("/work/app/.envrc"),
+                None,
+                "x\n",
+                None,
+            )],
+            version: "0.1.0".into(),
+        };
+
+        append(&log, &entry).unwrap();
+        append(&log, &entry).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&log)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let entries = read_entries(&log).unwrap();
+        assert_eq!(entries, vec![entry.clone(), entry]);
+        assert_eq!(files_in(&entries), [PathBuf::from("/work/app/.envrc")]);
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{Context, Result};
+use console::style;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::Args;
+use crate::ops::validate;
+
+/// Hooks `tmuxify hook` can install
+pub const TARGETS: &[&str] = &["direnv"];
+
+/// Shells `tmuxify hooks` prints activation snippets for
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "nu"];
+
+/// direnv library defining `layout_tmux`, used by `.envrc` files containing
+/// `layout tmux [CONFIG] [TMUXP COMMAND...]`
+pub const DIRENV_LIB: &str = r#"# Managed by tmuxify (`tmuxify hook direnv`); local edits are overwritten.
+#
+# Usage in .envrc: layout tmux [CONFIG] [TMUXP COMMAND...]
+# CONFIG defaults to ./.tmuxp.yaml and the command to `tmuxp`. Extra
+# `tmuxp load` arguments come from $TMUXIFY_TMUXP_ARGS, split on whitespace.
+# With $TMUXIFY_DETACHED_SESSION set to the session's name, the session loads
+# detached and a hint to attach is printed instead.
+#
+# An uncommitted .envrc.local is sourced first, and .tmuxp.local.yaml is
+# merged over CONFIG (by `tmuxify overlay`) when it exists.
+layout_tmux() {
+  local config="${1:-./.tmuxp.yaml}"
+  if [ $# -gt 0 ]; then shift; fi
+  source_env_if_exists .envrc.local
+  if [ -n "$TMUX" ]; then return 0; fi
+  if [ -n "$CODESPACES$GITPOD_WORKSPACE_ID$JETBRAINS_REMOTE_RUN" ] && [ -z "$TMUXIFY_REMOTE_ATTACH" ]; then
+    # remote development environment; export TMUXIFY_REMOTE_ATTACH=1 to auto-load
+    return 0
+  fi
+  if [ $# -eq 0 ]; then set -- tmuxp; fi
+  if [ -f .tmuxp.local.yaml ] && command -v tmuxify >/dev/null 2>&1; then
+    config="$(tmuxify overlay "$config" || echo "$config")"
+  fi
+  # shellcheck disable=SC2086
+  if [ -n "$TMUXIFY_DETACHED_SESSION" ]; then
+    if tmux has-session -t "=$TMUXIFY_DETACHED_SESSION" 2>/dev/null \
+      || "$@" load -d $TMUXIFY_TMUXP_ARGS "$config"; then
+      echo "tmuxify: session '$TMUXIFY_DETACHED_SESSION' ready - run tmux attach -t $TMUXIFY_DETACHED_SESSION" >&2
+    fi
+    return 0
+  fi
+  # shellcheck disable=SC2086
+  "$@" load $TMUXIFY_TMUXP_ARGS "$config"
+}
+"#;
+
+/// Where direnv looks for library files (`$XDG_CONFIG_HOME/direnv/lib`)
+pub fn direnv_lib_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config.join("direnv").join("lib").join("tmuxify.sh"))
+}
+
+/// Whether the installed direnv library matches this version of tmuxify
+pub fn direnv_lib_installed() -> bool {
+    direnv_lib_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .is_some_and(|content| content == DIRENV_LIB)
+}
+
+/// Install or update a shell integration hook
+pub fn run(args: &Args, target: &str) -> Result<()> {
+    match target {
+        "direnv" => install_direnv_lib(args),
+        other => anyhow::bail!(
+            "Unknown hook '{}'; use one of: {}",
+            other,
+            TARGETS.join(", ")
+        ),
+    }
+}
+
+fn install_direnv_lib(args: &Args) -> Result<()> {
+    let path = direnv_lib_path()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine the direnv config directory"))?;
+
+    if direnv_lib_installed() {
+        println!(
+            "{} {} is up to date",
+            style("✓").green().bold(),
+            path.display()
+        );
+        return Ok(());
+    }
+    if args.dry_run {
+        println!("[DRY RUN] Would write to: {}", path.display());
+        println!("---\n{}---", DIRENV_LIB);
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, DIRENV_LIB).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    println!("{} Wrote {}", style("✓").green().bold(), path.display());
+    println!(
+        "Set {} in your tmuxify settings to generate one-line .envrc files.",
+        style("direnv_layout: true").yellow()
+    );
+    Ok(())
+}
+
+/// The line (or block) that activates direnv in `shell`
+fn direnv_snippet(shell: &str) -> String {
+    match shell {
+        "fish" => "direnv hook fish | source\n".to_string(),
+        // direnv has no nushell hook; load its JSON export before each prompt
+        "nu" => r#"$env.config.hooks.pre_prompt = ($env.config.hooks.pre_prompt? | default [] | append {||
+    if (which direnv | is-empty) { return }
+    direnv export json | from json | default {} | load-env
+})
+"#
+        .to_string(),
+        shell => format!("eval \"$(direnv hook {})\"\n", shell),
+    }
+}
+
+/// A function that opens the project's session whenever the shell enters a
+/// directory with a tmuxify config, without direnv
+fn native_snippet(shell: &str) -> &'static str {
+    match shell {
+        "bash" => {
+            r#"# tmuxify: open the project's session when entering a tmuxify project
+_tmuxify_auto_attach() {
+  [ -z "$TMUX" ] || return
+  [ "$PWD" = "$_TMUXIFY_LAST_DIR" ] && return
+  _TMUXIFY_LAST_DIR="$PWD"
+  if [ -f .tmuxp.yaml ] || grep -qs -e 'tmuxp load' -e 'tmuxify up' -e '^layout tmux' .envrc; then
+    tmuxify --project "$PWD" open
+  fi
+}
+PROMPT_COMMAND="_tmuxify_auto_attach${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+"#
+        }
+        "zsh" => {
+            r#"# tmuxify: open the project's session when entering a tmuxify project
+_tmuxify_auto_attach() {
+  [ -z "$TMUX" ] || return
+  if [ -f .tmuxp.yaml ] || grep -qs -e 'tmuxp load' -e 'tmuxify up' -e '^layout tmux' .envrc; then
+    tmuxify --project "$PWD" open
+  fi
+}
+autoload -U add-zsh-hook
+add-zsh-hook chpwd _tmuxify_auto_attach
+"#
+        }
+        "fish" => {
+            r#"# tmuxify: open the project's session when entering a tmuxify project
+function _tmuxify_auto_attach --on-variable PWD
+    set -q TMUX; and return
+    if test -f .tmuxp.yaml; or grep -qs -e 'tmuxp load' -e 'tmuxify up' -e '^layout tmux' .envrc
+        tmuxify --project "$PWD" open
+    end
+end
+"#
+        }
+        _ => {
+            r#"# tmuxify: open the project's session when entering a tmuxify project
+$env.config.hooks.env_change.PWD = ($env.config.hooks.env_change.PWD? | default [] | append {|before, after|
+    if ($env.TMUX? | is-not-empty) { return }
+    let envrc = ($after | path join .envrc)
+    let loads = (($envrc | path exists) and (open --raw $envrc | str contains "tmux"))
+    if ($after | path join .tmuxp.yaml | path exists) or $loads {
+        tmuxify --project $after open
+    }
+})
+"#
+        }
+    }
+}
+
+/// The activation snippet for `shell`: the direnv hook, or with `native`
+/// tmuxify's own auto-attach function
+pub fn snippet(shell: &str, native: bool) -> Result<String> {
+    if !SHELLS.contains(&shell) {
+        anyhow::bail!(
+            "Unsupported shell: {} (expected {})",
+            shell,
+            SHELLS.join(", ")
+        );
+    }
+    Ok(if native {
+        native_snippet(shell).to_string()
+    } else {
+        direnv_snippet(shell)
+    })
+}
+
+/// Print the activation snippet for `shell` (the current shell by default)
+pub fn print_snippet(shell: Option<&str>, native: bool) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell.to_string(),
+        None => validate::detect_shell()
+            .ok_or_else(|| anyhow::anyhow!("Could not detect the shell; name it, e.g. zsh"))?,
+    };
+    let shell = if shell == "nushell" { "nu" } else { &shell };
+    print!("{}", snippet(shell, native)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Config, EnvrcOptions, TmuxpLocation};
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    /// Source the library and run a generated `.envrc` body with a stub tmuxp
+    fn run_layout(envrc: &str, env: &[(&str, &str)]) -> String {
+        let dir = tempdir().unwrap();
+        let lib = dir.path().join("tmuxify.sh");
+        fs::write(&lib, DIRENV_LIB).unwrap();
+        let script = format!(
+            "tmuxp() {{ echo \"tmuxp $*\"; }}\n\
+             source_env_if_exists() {{ :; }}\n\
+             layout() {{ name=$1; shift; \"layout_$name\" \"$@\"; }}\n\
+             . {}\n{}",
+            lib.display(),
+            envrc
+        );
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", &script])
+            .env_remove("TMUX")
+            .env_remove("CODESPACES");
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+        String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+    }
+
+    #[test]
+    fn snippets_cover_each_shell() {
+        assert_eq!(
+            snippet("zsh", false).unwrap(),
+            "eval \"$(direnv hook zsh)\"\n"
+        );
+        assert_eq!(
+            snippet("fish", false).unwrap(),
+            "direnv hook fish | source\n"
+        );
+        assert!(snippet("nu", false).unwrap().contains("direnv export json"));
+        assert!(snippet("elvish", false).is_err());
+
+        for shell in SHELLS {
+            assert!(snippet(shell, true).unwrap().contains("tmuxify --project"));
+        }
+        let bash = Command::new("bash")
+            .args(["-n", "-c", &snippet("bash", true).unwrap()])
+            .status()
+            .unwrap();
+        assert!(bash.success());
+    }
+
+    #[test]
+    fn layout_tmux_loads_the_config_outside_tmux() {
+        let cfg = Config::new("app".into(), "/tmp".into(), vec![]);
+        let options = EnvrcOptions {
+            use_layout: true,
+            ..Default::default()
+        };
+
+        let project = cfg.generate_envrc(TmuxpLocation::Project, &options);
+        assert_eq!(run_layout(&project, &[]), "tmuxp load ./.tmuxp.yaml\n");
+        assert_eq!(run_layout(&project, &[("TMUX", "/tmp/tmux-1/default")]), "");
+        assert_eq!(
+            run_layout(&project, &[("TMUXIFY_DETACHED_SESSION", "app")]),
+            "tmuxp load -d ./.tmuxp.yaml\n"
+        );
+        assert_eq!(
+            run_layout(&project, &[("TMUXIFY_TMUXP_ARGS", "-d --log-file x.log")]),
+            "tmuxp load -d --log-file x.log ./.tmuxp.yaml\n"
+        );
+
+        let home = cfg.generate_envrc(TmuxpLocation::Home, &options);
+        assert!(run_layout(&home, &[]).ends_with("/.tmuxp/app.yaml\n"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Importing hand-written tmuxp configs and ones shared with `tmuxify share`.
+
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::Confirm;
+use serde_yaml::Value;
+use std::path::Path;
+use std::process::Command;
+
+use crate::cli::Args;
+use crate::model::{Config, Settings, TmuxpLocation};
+use crate::ops::{load, process, show, theme, validate, write};
+
+/// Download a shared config and write it for the current project
+pub fn from_url(args: &Args, url: &str, yes: bool) -> Result<()> {
+    args.require_network("Importing from a URL")?;
+    if !validate::has_binary("curl") {
+        anyhow::bail!("Importing from a URL needs curl");
+    }
+    let raw = raw_url(url);
+    let content = process::run(
+        Command::new("curl")
+            .args(["--fail", "--silent", "--show-error", "--location"])
+            .arg(&raw),
+        process::SLOW_TIMEOUT,
+    )
+    .with_context(|| format!("Failed to download {}", raw))?;
+    let config =
+        Config::from_yaml(&content).with_context(|| format!("{} is not a tmuxp config", url))?;
+    adopt(args, config, TmuxpLocation::Home, yes)
+}
+
+/// Import a hand-written tmuxp config, rewriting it in tmuxify's form
+/// alongside a generated `.envrc`
+pub fn from_path(args: &Args, path: &Path, yes: bool) -> Result<()> {
+    let config = load::load_config(path)?;
+    let location = if is_project_config(path, &args.project_dir()?) {
+        TmuxpLocation::Project
+    } else {
+        TmuxpLocation::Home
+    };
+    adopt(args, config, location, yes)
+}
+
+/// `path` is the project's own `.tmuxp.*`, which is rewritten in place
+/// rather than copied to `~/.tmuxp`
+fn is_project_config(path: &Path, project_dir: &Path) -> bool {
+    let local = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(".tmuxp."));
+    let parent = path
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf));
+    local && parent.is_some_and(|dir| project_dir.canonicalize().is_ok_and(|p| p == dir))
+}
+
+/// Show what `config` runs, confirm, and write it for the current project,
+/// at `--tmuxp-location` or else `default_location`
+fn adopt(
+    args: &Args,
+    mut config: Config,
+    default_location: TmuxpLocation,
+    yes: bool,
+) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    config.start_directory = project_dir.clone();
+    if let Some(session) = &args.session {
+        config.session_name = session.clone();
+    }
+    let location = args.tmuxp_location.unwrap_or(default_location);
+
+    // Imported configs run their commands on load, so show them before writing
+    println!(
+        "Session {} with {} window(s); what runs when it loads:",
+        style(&config.session_name).bold(),
+        config.windows.len()
+    );
+    print_executed(&config);
+
+    if !args.dry_run && !yes {
+        validate::require_terminal("Confirming the import")?;
+        if !Confirm::with_theme(theme::get())
+            .with_prompt("Write this config?")
+            .default(false)
+            .interact()?
+        {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        envrc: write::envrc_options(&project_dir, &Settings::load()?),
+        format: args.format,
+        ..Default::default()
+    };
+    let result = write::write_config(&config, location, &project_dir, &options)?;
+    if !args.dry_run {
+        show::print_summary(&config);
+        result.print_summary();
+    }
+    Ok(())
+}
+
+/// List what `config` runs when it loads, one labelled line each
+pub(crate) fn print_executed(config: &Config) {
+    let executed = executed(config);
+    if executed.is_empty() {
+        println!("  {}", style("(nothing)").dim());
+    }
+    for (source, command) in executed {
+        println!(
+            "  {} {}",
+            style(format!("{}:", source)).dim(),
+            style(command).yellow()
+        );
+    }
+    println!();
+}
+
+/// Everything in `config` that can run code when the session loads, labelled
+/// with where it comes from: pane commands, tmux options, and passed-through
+/// keys such as `before_script` and `shell_command_before`
+fn executed(config: &Config) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    for (key, value) in &config.extra {
+        found.extend(value_lines(value).map(|line| (format!("session {}", key), line)));
+    }
+    for (w, window) in config.windows.iter().enumerate() {
+        let window_label = match &window.window_name {
+            Some(name) => format!("window {}", name),
+            None => format!("window {}", w + 1),
+        };
+        for (option, value) in &window.options {
+            found.push((format!("{} option {}", window_label, option), value.clone()));
+        }
+        for (key, value) in &window.extra {
+            found
+                .extend(value_lines(value).map(|line| (format!("{} {}", window_label, key), line)));
+        }
+        for (p, pane) in window.panes.iter().enumerate() {
+            let pane_label = format!("{}, pane {}", window_label, p + 1);
+            for command in &pane.shell_command {
+                found.push((pane_label.clone(), command.cmd.clone()));
+            }
+            for (key, value) in &pane.extra {
+                found.extend(
+                    value_lines(value).map(|line| (format!("{} {}", pane_label, key), line)),
+                );
+            }
+        }
+    }
+    found
+}
+
+/// A passed-through value as lines to show: each string in a list, or the
+/// value as YAML
+fn value_lines(value: &Value) -> impl Iterator<Item = String> {
+    let lines: Vec<String> = match value {
+        Value::String(text) => vec![text.clone()],
+        Value::Sequence(items) => items.iter().flat_map(value_lines).collect(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    };
+    lines.into_iter()
+}
+
+/// The raw-content URL for a gist page; other URLs are used as given
+fn raw_url(url: &str) -> String {
+    let trimmed = url.trim_end_matches('/');
+    match trimmed.strip_prefix("https://gist.github.com/") {
+        Some(path) if !path.contains("/raw") => {
+            format!("https://gist.githubusercontent.com/{}/raw", path)
+        }
+        _ => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_lists_everything_that_runs() {
+        let config = Config::from_yaml(
+            "session_name: app\nbefore_script: ./bin/setup\n\
+             shell_command_before: [source .env]\n\
+             windows:\n- window_name: dev\n  options:\n    default-command: zsh\n\
+             \x20 shell_command_before: nvm use\n  panes:\n  - vim\n",
+        )
+        .unwrap();
+        let executed = executed(&config);
+        let find = |source: &str| {
+            executed
+                .iter()
+                .find(|(s, _)| s == source)
+                .map(|(_, command)| command.as_str())
+        };
+        assert_eq!(find("session before_script"), Some("./bin/setup"));
+        assert_eq!(find("session shell_command_before"), Some("source .env"));
+        assert_eq!(find("window dev option default-command"), Some("zsh"));
+        assert_eq!(find("window dev shell_command_before"), Some("nvm use"));
+        assert_eq!(find("window dev, pane 1"), Some("vim"));
+    }
+
+    #[test]
+    fn gist_pages_resolve_to_raw_content() {
+        assert_eq!(
+            raw_url("https://gist.github.com/ada/abc123"),
+            "https://gist.githubusercontent.com/ada/abc123/raw"
+        );
+        assert_eq!(raw_url("https://paste.rs/Xyz"), "https://paste.rs/Xyz");
+    }
+
+    #[test]
+    fn the_projects_own_config_is_rewritten_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join(".tmuxp.yaml");
+        let other = dir.path().join("api.yaml");
+        std::fs::write(&local, "session_name: app\n").unwrap();
+        std::fs::write(&other, "session_name: api\n").unwrap();
+        assert!(is_project_config(&local, dir.path()));
+        assert!(!is_project_config(&other, dir.path()));
+
+        let elsewhere = tempfile::tempdir().unwrap();
+        assert!(!is_project_config(&local, elsewhere.path()));
+    }
+}
@@ -0,0 +1,116 @@
+//! Prompt input remembered across wizard runs, recalled with the arrow keys.
+//!
+//! Each kind of input has its own file in `~/.local/share/tmuxify/input/`,
+//! one entry per line, newest last. Saving is best effort and never makes
+//! a prompt fail.
+
+use dialoguer::History;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ops::log;
+
+/// Entries kept per kind of input
+const MAX_ENTRIES: usize = 100;
+
+/// History for one kind of prompt input (session names, commands, directories)
+pub struct InputHistory {
+    path: Option<PathBuf>,
+    /// Newest first, as dialoguer reads position 0 on the first Up
+    entries: VecDeque<String>,
+}
+
+impl InputHistory {
+    /// Load the history stored under `kind`
+    pub fn load(kind: &str) -> Self {
+        Self::from_path(dirs::data_dir().map(|dir| dir.join("tmuxify").join("input").join(kind)))
+    }
+
+    fn from_path(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .rev()
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content: String = self
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| format!("{}\n", entry))
+            .collect();
+        fs::write(path, content)
+    }
+}
+
+impl<T: ToString> History<T> for InputHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.entries.get(pos).cloned()
+    }
+
+    fn write(&mut self, val: &T) {
+        let value = val.to_string();
+        let value = value.trim();
+        if value.is_empty() || value.contains('\n') {
+            return;
+        }
+        self.entries.retain(|entry| entry != value);
+        self.entries.push_front(value.to_string());
+        self.entries.truncate(MAX_ENTRIES);
+        if let Some(path) = &self.path
+            && let Err(e) = self.save(path)
+        {
+            log::debug(format!(
+                "Could not save input history {}: {}",
+                path.display(),
+                e
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn entries_persist_newest_first_without_duplicates() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("input").join("commands");
+
+        let mut history = InputHistory::from_path(Some(path.clone()));
+        for command in ["npm run dev", "cargo watch", "", "npm run dev"] {
+            History::<String>::write(&mut history, &command.to_string());
+        }
+
+        let reloaded = InputHistory::from_path(Some(path.clone()));
+        assert_eq!(
+            History::<String>::read(&reloaded, 0).as_deref(),
+            Some("npm run dev")
+        );
+        assert_eq!(
+            History::<String>::read(&reloaded, 1).as_deref(),
+            Some("cargo watch")
+        );
+        assert_eq!(History::<String>::read(&reloaded, 2), None);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "cargo watch\nnpm run dev\n"
+        );
+    }
+}
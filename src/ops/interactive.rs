@@ -1,13 +1,27 @@
 use anyhow::Result;
 use console::style;
-use dialoguer::{Confirm, Editor, Input, Select, theme::ColorfulTheme};
+use dialoguer::{Completion, Confirm, Editor, Input, Select};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 use crate::cli::Args;
-use crate::model::{Config, Pane, TmuxpLocation, Window, WindowLayout};
-use crate::ops::{validate, write};
+use crate::model::{
+    Config, ConfigFormat, Pane, Settings, TmuxpLocation, Window, WindowLayout, is_valid_pane_size,
+};
+use crate::ops::archetype::{self, Archetype};
+use crate::ops::detect::ProjectType;
+use crate::ops::input_history::InputHistory;
+use crate::ops::merge::{self, MergeChoice};
+use crate::ops::presets::Preset;
+use crate::ops::{
+    conflicts, detect, edit, highlight, load, log, open, picker, process, shell, show, table,
+    theme, validate, write,
+};
 
 /// Run the interactive configuration wizard
 pub fn run(args: Args) -> Result<()> {
+    validate::require_terminal("Interactive mode")?;
+
     println!("{}", style("Welcome to tmuxify!").bold().cyan());
     println!();
 
@@ -16,8 +30,12 @@ pub fn run(args: Args) -> Result<()> {
         eprintln!("{}", style("Warning:").yellow().bold());
         eprintln!("You are currently inside a tmux session.");
 
-        if let Some(session_name) = validate::get_current_tmux_session() {
-            eprintln!("Current session: {}", style(&session_name).cyan());
+        match validate::get_current_tmux_session() {
+            Ok(Some(session_name)) => {
+                eprintln!("Current session: {}", style(&session_name).cyan())
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Could not query the current session: {}", style(e).dim()),
         }
 
         eprintln!();
@@ -25,7 +43,7 @@ pub fn run(args: Args) -> Result<()> {
         eprintln!("Running it from within tmux may cause unexpected behavior.");
         eprintln!();
 
-        if !Confirm::with_theme(&ColorfulTheme::default())
+        if !Confirm::with_theme(theme::get())
             .with_prompt("Continue anyway?")
             .default(false)
             .interact()?
@@ -38,6 +56,19 @@ pub fn run(args: Args) -> Result<()> {
         println!();
     }
 
+    if let Some(env) = validate::detect_remote_environment() {
+        eprintln!(
+            "{} Running inside {}. The generated .envrc will not auto-load tmux here",
+            style("Note:").yellow().bold(),
+            env.name
+        );
+        eprintln!(
+            "unless {} is set.",
+            style("TMUXIFY_REMOTE_ATTACH=1").yellow()
+        );
+        eprintln!();
+    }
+
     // Check dependencies first
     if let Err(e) = validate::check_dependencies() {
         eprintln!("{}", style("Error:").red().bold());
@@ -52,35 +83,49 @@ pub fn run(args: Args) -> Result<()> {
     }
 
     // Determine project directory
-    let project_dir = args
-        .project
-        .clone()
-        .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
+    let project_dir = args.project_dir()?;
+
+    // WSL: files under /mnt/<drive> live on the Windows filesystem
+    if validate::is_wsl() && validate::is_wsl_windows_mount(&project_dir) {
+        eprintln!("{}", style("Warning:").yellow().bold());
+        eprintln!(
+            "{} is on a Windows drive mounted into WSL.",
+            style(project_dir.display()).cyan()
+        );
+        eprintln!("direnv and tmux are noticeably slower there, and file permissions");
+        eprintln!("may prevent direnv from trusting .envrc. Consider moving the project");
+        eprintln!("into the Linux filesystem (e.g. ~/projects).");
+        eprintln!();
+    }
 
     // Determine session name
+    // Non-UTF-8 bytes in the directory name become '_' rather than dropping the default
     let default_session_name = project_dir
         .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("my-session")
-        .to_string();
+        .map(|n| n.to_string_lossy().replace('\u{FFFD}', "_"))
+        .unwrap_or_else(|| "my-session".to_string());
 
     let session_name = if let Some(name) = args.session {
         name
     } else {
-        Input::with_theme(&ColorfulTheme::default())
+        let mut history = InputHistory::load("sessions");
+        Input::with_theme(theme::get())
             .with_prompt("Session name")
             .default(default_session_name)
+            .history_with(&mut history)
             .interact_text()?
     };
 
     // Determine tmuxp location
-    let location = if let Some(loc_str) = args.tmuxp_location {
-        TmuxpLocation::from_str(&loc_str).ok_or_else(|| {
-            anyhow::anyhow!("Invalid location: {}. Use 'home' or 'project'", loc_str)
-        })?
+    let location = if let Some(location) = args.tmuxp_location {
+        location
     } else {
+        println!(
+            "{}",
+            style("See `tmuxify explain location` for the trade-offs.").dim()
+        );
         let choices = vec!["home (~/.tmuxp/)", "project (./.tmuxp.yaml)"];
-        let selection = Select::with_theme(&ColorfulTheme::default())
+        let selection = Select::with_theme(theme::get())
             .with_prompt("Where should the tmuxp config be stored?")
             .items(&choices)
             .default(0)
@@ -94,44 +139,265 @@ pub fn run(args: Args) -> Result<()> {
     };
 
     // Determine start directory
-    let start_dir = if let Some(dir) = args.start_dir {
-        dir.display().to_string()
+    let start_dir = match &args.start_dir {
+        Some(dir) => validate::resolve_user_path(dir),
+        None => project_dir.clone(),
+    };
+
+    // Check for an existing config before any data entry
+    let existing_path = Config::file_path_for(&session_name, location, Some(&project_dir))?;
+    // A config written with `--format json` counts too
+    let copies = [
+        existing_path.clone(),
+        ConfigFormat::Json.apply(&existing_path),
+    ];
+    let existing_path = load::pick_config(&copies, &project_dir).unwrap_or(existing_path);
+    log::debug(format!(
+        "project: {}, start_dir: {}, config: {} (exists: {})",
+        project_dir.display(),
+        start_dir.display(),
+        existing_path.display(),
+        existing_path.exists()
+    ));
+    // The existing config to merge the new windows into, when chosen
+    let mut merge_base = None;
+    let existing = if existing_path.exists() {
+        println!();
+        let choice = Select::with_theme(theme::get())
+            .with_prompt(format!(
+                "{} already exists. What would you like to do?",
+                existing_path.display()
+            ))
+            .items([
+                "Edit it",
+                "Merge it with a new config, window by window",
+                "Replace it",
+                "Abort",
+            ])
+            .default(0)
+            .interact()?;
+
+        match choice {
+            0 => Some(load::load_config(&existing_path)?),
+            1 => {
+                merge_base = Some(load::load_config(&existing_path)?);
+                None
+            }
+            2 => None,
+            _ => {
+                println!("Aborted.");
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+
+    // --from seeds a new config; editing or merging starts from the existing one
+    let from = match (&existing, &merge_base, &args.from) {
+        (None, None, Some(from)) => {
+            let path = load::resolve_config_path(from)?;
+            let source = load::load_config(&path)?;
+            Some((path, source))
+        }
+        _ => None,
+    };
+    let base = existing
+        .as_ref()
+        .or(merge_base.as_ref())
+        .or(from.as_ref().map(|(_, source)| source))
+        .cloned();
+    let base = base.as_ref();
+
+    let project_type = detect::detect_project_type(&project_dir);
+
+    // tmuxp hides pane commands from shell history unless told otherwise
+    let suppress_history = Confirm::with_theme(theme::get())
+        .with_prompt("Keep startup commands out of shell history?")
+        .default(base.and_then(|c| c.suppress_history).unwrap_or(true))
+        .interact()?;
+
+    // Variables every window starts with; --env answers without asking
+    let mut environment = base.map(|c| c.environment.clone()).unwrap_or_default();
+    if args.env.is_empty() {
+        let current: Vec<String> = environment
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, shell::quote(value)))
+            .collect();
+        let env_input: String = Input::with_theme(theme::get())
+            .with_prompt("Session environment (optional, KEY=VALUE separated by spaces, quote values with spaces)")
+            .with_initial_text(current.join(" "))
+            .allow_empty(true)
+            .validate_with(|input: &String| parse_env_assignments(input).map(|_| ()))
+            .interact_text()?;
+        environment = parse_env_assignments(&env_input).map_err(|e| anyhow::anyhow!(e))?;
     } else {
-        project_dir.display().to_string()
+        environment.extend(args.env.iter().cloned());
+    }
+
+    let mut settings = Settings::load()?;
+    if let Some(layout) = &args.layout {
+        settings.wizard.layout = Some(layout.to_string());
+    }
+    let ctx = WizardContext {
+        project_dir: &project_dir,
+        project_type,
+        suppress_history,
+        settings: &settings,
     };
 
     println!();
     println!("{}", style("Configuring windows and panes...").bold());
+    let compose = detect::compose_file(&project_dir).is_some();
+    if project_type != ProjectType::Unknown || compose {
+        let detected = match (project_type, compose) {
+            (ProjectType::Unknown, _) => "Detected a docker compose project".to_string(),
+            (_, true) => format!("Detected {} project with docker compose", project_type),
+            (_, false) => format!("Detected {} project", project_type),
+        };
+        println!("{}", style(detected).dim());
+    }
     println!();
 
+    // Start from the existing config, another config, or detected project commands
+    let windows = if let Some(existing) = existing {
+        review_windows_interactive(existing.windows)?
+    } else if let Some((path, source)) = from {
+        println!(
+            "{}",
+            style(format!(
+                "Starting from {} ({} window(s))",
+                path.display(),
+                source.windows.len()
+            ))
+            .dim()
+        );
+        println!();
+        review_windows_interactive(source.windows)?
+    } else if let Some(preset) = select_preset(args.preset, &project_dir, project_type)? {
+        println!(
+            "{}",
+            style(format!("Starting from the {} preset", preset)).dim()
+        );
+        println!();
+        preset.windows(&project_dir)
+    } else {
+        let detected = select_detected_windows(&project_dir)?;
+        if !detected.is_empty() {
+            println!(
+                "{}",
+                style(format!(
+                    "Created {} window(s) from detected commands",
+                    detected.len()
+                ))
+                .dim()
+            );
+            println!();
+        }
+        detected
+    };
+
     // Create windows interactively
-    let mut windows = Vec::new();
-    loop {
-        let window = create_window_interactive(windows.len() + 1)?;
-        windows.push(window);
+    let mut windows = configure_windows(windows, &ctx)?;
+    if let Some(base) = merge_base {
+        windows = merge_windows_interactive(base.windows, windows)?;
+    }
+
+    // Create config
+    let mut config = rebuild_session(base, session_name.clone(), start_dir.clone(), windows);
+    config.suppress_history = Some(suppress_history);
+    config.environment = environment.clone();
+
+    // Additional sessions for the same project (e.g. myapp-infra)
+    let mut extra_sessions: Vec<Config> = Vec::new();
+    while Confirm::with_theme(theme::get())
+        .with_prompt("Define another session for this project?")
+        .default(false)
+        .interact()?
+    {
+        let mut history = InputHistory::load("sessions");
+        let name: String = Input::with_theme(theme::get())
+            .with_prompt("Session name")
+            .history_with(&mut history)
+            .default(format!("{}-{}", session_name, extra_sessions.len() + 2))
+            .validate_with(|input: &String| -> std::result::Result<(), String> {
+                if input == &session_name || extra_sessions.iter().any(|c| &c.session_name == input)
+                {
+                    Err("Session name already used for this project".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+            .interact_text()?;
+
+        println!();
+        println!(
+            "{}",
+            style(format!("Configuring session {}...", name)).bold()
+        );
+        println!();
+
+        let windows = configure_windows(Vec::new(), &ctx)?;
+        let mut extra = Config::new(name, start_dir.clone(), windows);
+        extra.suppress_history = Some(suppress_history);
+        extra.environment = environment.clone();
+        extra_sessions.push(extra);
+    }
 
-        if !Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("Add another window?")
+    // tmux otherwise opens on the last-created window
+    if Confirm::with_theme(theme::get())
+        .with_prompt("Focus the first window and pane when the session opens?")
+        .default(true)
+        .interact()?
+    {
+        config.apply_default_focus();
+        for extra in &mut extra_sessions {
+            extra.apply_default_focus();
+        }
+    }
+
+    // Show preview
+    print_preview(&config, &extra_sessions)?;
+
+    // One last manual tweak, re-validated before anything is written
+    if args.edit_preview
+        || Confirm::with_theme(theme::get())
+            .with_prompt("Edit the YAML in $EDITOR before saving?")
             .default(false)
             .interact()?
-        {
-            break;
+    {
+        for cfg in std::iter::once(&mut config).chain(&mut extra_sessions) {
+            match edit::edit_yaml(cfg)? {
+                Some(edited) => *cfg = edited,
+                None => {
+                    println!("Aborted.");
+                    return Ok(());
+                }
+            }
         }
+        print_preview(&config, &extra_sessions)?;
     }
 
-    // Create config
-    let config = Config::new(session_name, start_dir, windows);
-
-    // Show preview
-    println!();
-    println!("{}", style("Configuration preview:").bold().cyan());
-    println!("---");
-    println!("{}", config.to_yaml()?);
-    println!("---");
-    println!();
+    // Other tools that start tmux here would fight the generated .envrc
+    let conflicts = conflicts::project_conflicts(&project_dir);
+    for conflict in &conflicts {
+        eprintln!("{} {}", style("⚠").yellow().bold(), conflict.describe());
+        eprintln!("  {}", style(&conflict.text).dim());
+    }
+    if conflicts
+        .iter()
+        .any(|c| c.kind == conflicts::ConflictKind::Envrc)
+        && !Confirm::with_theme(theme::get())
+            .with_prompt("Let tmuxify take over auto-attach for this project (replacing .envrc)?")
+            .default(false)
+            .interact()?
+    {
+        println!("Aborted.");
+        return Ok(());
+    }
 
     // Confirm
-    if !Confirm::with_theme(&ColorfulTheme::default())
+    if !Confirm::with_theme(theme::get())
         .with_prompt("Proceed with this configuration?")
         .default(true)
         .interact()?
@@ -144,22 +410,56 @@ pub fn run(args: Args) -> Result<()> {
     let write_options = write::WriteOptions {
         dry_run: args.dry_run,
         force: args.force,
+        envrc: write::envrc_options(&project_dir, &settings),
+        format: args.format,
+        ..Default::default()
     };
 
-    let result = write::write_config(&config, location, &project_dir, &write_options)?;
+    let result = write::write_sessions(
+        &config,
+        &extra_sessions,
+        location,
+        &project_dir,
+        &write_options,
+    )?;
 
-    if !args.dry_run {
-        println!();
+    let runner = process::SystemRunner::new(args.dry_run);
+    if args.dry_run {
+        // Show the follow-up commands the wizard would offer to run
+        write::run_direnv_allow(&runner, &project_dir)?;
+        if args.attach {
+            open::start_session(&runner, &project_dir, &config, &result.tmuxp_path, false)?;
+        }
+    } else {
+        for cfg in std::iter::once(&config).chain(&extra_sessions) {
+            show::print_summary(cfg);
+        }
         result.print_summary();
 
         // Offer to run direnv allow
         println!();
-        if Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("Run 'direnv allow' now?")
-            .default(true)
-            .interact()?
-        {
-            write::run_direnv_allow(&project_dir)?;
+        let allow = match settings.wizard.direnv_allow {
+            Some(answer) => answer,
+            None => Confirm::with_theme(theme::get())
+                .with_prompt("Run 'direnv allow' now?")
+                .default(true)
+                .interact()?,
+        };
+        if allow {
+            write::run_direnv_allow(&runner, &project_dir)?;
+        }
+
+        let load = args.attach
+            || match settings.wizard.load_session {
+                Some(answer) => answer,
+                None => Confirm::with_theme(theme::get())
+                    .with_prompt("Load the session now?")
+                    .default(true)
+                    .interact()?,
+            };
+        if load {
+            open::start_session(&runner, &project_dir, &config, &result.tmuxp_path, false)?;
+        } else if allow {
             println!();
             println!(
                 "{}",
@@ -174,12 +474,613 @@ pub fn run(args: Args) -> Result<()> {
     Ok(())
 }
 
+/// A config with the wizard's windows that keeps what it doesn't ask about
+/// from `base` (the config being edited, merged or started from): the
+/// session environment and keys like `before_script` and `options`
+fn rebuild_session(
+    base: Option<&Config>,
+    session_name: String,
+    start_dir: PathBuf,
+    windows: Vec<Window>,
+) -> Config {
+    let mut config = Config::new(session_name, start_dir, windows);
+    if let Some(base) = base {
+        config.suppress_history = base.suppress_history;
+        config.environment = base.environment.clone();
+        config.extra = base.extra.clone();
+    }
+    config
+}
+
+/// Print each session's YAML, highlighted, between `---` separators
+fn print_preview(config: &Config, extra_sessions: &[Config]) -> Result<()> {
+    println!();
+    println!("{}", style("Configuration preview:").bold().cyan());
+    for cfg in std::iter::once(config).chain(extra_sessions) {
+        println!("---");
+        println!("{}", highlight::yaml(&cfg.to_yaml()?));
+    }
+    println!("---");
+    println!();
+    Ok(())
+}
+
+/// Add, duplicate, or finish windows until the user is done, starting from `windows`
+fn configure_windows(mut windows: Vec<Window>, ctx: &WizardContext) -> Result<Vec<Window>> {
+    let mut action = if windows.is_empty() {
+        WindowAction::Add
+    } else {
+        prompt_window_action(ctx.settings)?
+    };
+    loop {
+        match action {
+            WindowAction::Add => {
+                let window = create_window_interactive(windows.len() + 1, ctx)?;
+                windows.push(window);
+            }
+            WindowAction::Duplicate => {
+                if let Some(last) = windows.last() {
+                    let copy = duplicate_window_interactive(last, windows.len() + 1)?;
+                    windows.push(copy);
+                }
+            }
+            WindowAction::Done => break,
+        }
+
+        action = prompt_window_action(ctx.settings)?;
+    }
+
+    Ok(windows)
+}
+
+/// Session-wide answers shared by the window and pane prompts
+struct WizardContext<'a> {
+    project_dir: &'a std::path::Path,
+    project_type: ProjectType,
+    suppress_history: bool,
+    settings: &'a Settings,
+}
+
+/// What to do after a window has been configured
+enum WindowAction {
+    Add,
+    Duplicate,
+    Done,
+}
+
+/// Ask whether to add a window, duplicate the last one, or finish
+fn prompt_window_action(settings: &Settings) -> Result<WindowAction> {
+    let selection = Select::with_theme(theme::get())
+        .with_prompt("Next")
+        .items(["Done", "Add another window", "Duplicate this window"])
+        .default(usize::from(settings.wizard.add_another_window))
+        .interact()?;
+
+    Ok(match selection {
+        1 => WindowAction::Add,
+        2 => WindowAction::Duplicate,
+        _ => WindowAction::Done,
+    })
+}
+
+/// Index of the layout choice preselected by the `wizard.layout` setting:
+/// 0 for auto, then the built-in layouts, then the custom split
+fn default_layout_choice(settings: &Settings) -> usize {
+    match settings.wizard.layout.as_deref() {
+        None | Some("auto") => 0,
+        Some("split") => WindowLayout::all().len() + 1,
+        Some(name) => match WindowLayout::all()
+            .iter()
+            .position(|l| l.to_string() == name)
+        {
+            Some(index) => index + 1,
+            None => {
+                log::debug(format!("Ignoring unknown wizard.layout '{}'", name));
+                0
+            }
+        },
+    }
+}
+
+/// Copy an existing window and let the user tweak its name and pane commands
+fn duplicate_window_interactive(source: &Window, window_num: usize) -> Result<Window> {
+    println!(
+        "{}",
+        style(format!(
+            "Window #{} (copy of #{})",
+            window_num,
+            window_num - 1
+        ))
+        .bold()
+    );
+
+    let default_name = source
+        .window_name
+        .as_ref()
+        .map(|n| format!("{}-copy", n))
+        .unwrap_or_default();
+    tweak_window_interactive(source.clone(), default_name)
+}
+
+/// Walk through windows loaded from another config, keeping, editing, or dropping each
+fn review_windows_interactive(source: Vec<Window>) -> Result<Vec<Window>> {
+    let mut windows = Vec::new();
+
+    for (i, window) in source.into_iter().enumerate() {
+        let label = window
+            .window_name
+            .clone()
+            .unwrap_or_else(|| "unnamed".to_string());
+        let choice = Select::with_theme(theme::get())
+            .with_prompt(format!(
+                "Window #{} ({}, {} pane(s))",
+                i + 1,
+                label,
+                window.panes.len()
+            ))
+            .items(["Keep", "Edit", "Remove"])
+            .default(0)
+            .interact()?;
+
+        match choice {
+            0 => windows.push(window),
+            1 => {
+                let name = window.window_name.clone().unwrap_or_default();
+                windows.push(tweak_window_interactive(window, name)?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(windows)
+}
+
+/// Choose, for each window in the existing or the new config, which version
+/// to keep. Windows identical in both are kept without asking.
+fn merge_windows_interactive(existing: Vec<Window>, new: Vec<Window>) -> Result<Vec<Window>> {
+    println!();
+    println!("{}", style("Merging with the existing config...").bold());
+    let mut windows = Vec::new();
+    for pair in merge::pair_windows(existing, new) {
+        let label = pair.label();
+        if pair.unchanged() {
+            println!("  {} {} (unchanged)", style("✓").green(), label);
+            windows.extend(pair.resolve(MergeChoice::Existing));
+            continue;
+        }
+
+        let choices = pair.choices();
+        let items: Vec<String> = choices
+            .iter()
+            .map(|choice| match choice {
+                MergeChoice::Existing => format!(
+                    "Keep existing ({} pane(s))",
+                    pair.existing.as_ref().map_or(0, |w| w.panes.len())
+                ),
+                MergeChoice::New => format!(
+                    "Use new ({} pane(s))",
+                    pair.new.as_ref().map_or(0, |w| w.panes.len())
+                ),
+                MergeChoice::Drop => "Drop".to_string(),
+            })
+            .collect();
+        let status = match (&pair.existing, &pair.new) {
+            (Some(_), Some(_)) => "changed",
+            (Some(_), None) => "only in existing",
+            _ => "only in new",
+        };
+        let selection = Select::with_theme(theme::get())
+            .with_prompt(format!("Window {} ({})", label, status))
+            .items(&items)
+            .default(0)
+            .interact()?;
+        windows.extend(pair.resolve(choices[selection]));
+    }
+    println!();
+    Ok(windows)
+}
+
+/// Let the user rename a window and edit the commands of each pane
+fn tweak_window_interactive(mut window: Window, default_name: String) -> Result<Window> {
+    let window_name: String = Input::with_theme(theme::get())
+        .with_prompt("  Window name (optional, press Enter to skip)")
+        .with_initial_text(default_name)
+        .allow_empty(true)
+        .interact_text()?;
+    window.window_name = if window_name.is_empty() {
+        None
+    } else {
+        Some(window_name)
+    };
+
+    for (i, pane) in window.panes.iter_mut().enumerate() {
+        let edit = Confirm::with_theme(theme::get())
+            .with_prompt(format!(
+                "    Edit pane #{} ({})?",
+                i + 1,
+                pane_summary(pane)
+            ))
+            .default(false)
+            .interact()?;
+        if edit {
+            edit_pane_commands(pane)?;
+        }
+    }
+
+    Ok(window)
+}
+
+/// A pane's commands on one line, for prompts
+fn pane_summary(pane: &Pane) -> String {
+    if pane.shell_command.is_empty() {
+        "no commands".to_string()
+    } else {
+        pane.commands().join("; ")
+    }
+}
+
+/// Open the pane's commands in the editor, one per line
+fn edit_pane_commands(pane: &mut Pane) -> Result<()> {
+    let mut initial = String::from("# Enter commands (one per line)\n");
+    for cmd in &pane.shell_command {
+        initial.push_str(cmd);
+        initial.push('\n');
+    }
+    if let Some(text) = Editor::new().edit(&initial)? {
+        pane.set_commands(parse_command_lines(&text));
+    }
+    Ok(())
+}
+
+/// Edit a single window through a menu (name, layout, panes, commands),
+/// leaving the rest of its config untouched
+pub(crate) fn edit_window_interactive(
+    mut window: Window,
+    project_dir: &std::path::Path,
+    suppress_history: bool,
+    settings: &Settings,
+) -> Result<Window> {
+    let ctx = WizardContext {
+        project_dir,
+        project_type: detect::detect_project_type(project_dir),
+        suppress_history,
+        settings,
+    };
+
+    loop {
+        println!();
+        let layout = window
+            .layout
+            .as_ref()
+            .map_or("none".to_string(), |l| l.to_string());
+        println!(
+            "{} {}",
+            style(window.window_name.as_deref().unwrap_or("unnamed")).bold(),
+            style(format!("[{}]", table::truncate(&layout, 30))).yellow()
+        );
+        for (i, pane) in window.panes.iter().enumerate() {
+            println!(
+                "  {} {}",
+                style(format!("pane {}", i + 1)).green(),
+                pane_summary(pane)
+            );
+        }
+
+        let choice = Select::with_theme(theme::get())
+            .with_prompt("Edit")
+            .items([
+                "Rename",
+                "Change layout",
+                "Edit a pane's commands",
+                "Add a pane",
+                "Remove a pane",
+                "Done",
+            ])
+            .default(2)
+            .interact()?;
+        match choice {
+            0 => {
+                let name: String = Input::with_theme(theme::get())
+                    .with_prompt("  Window name (empty for none)")
+                    .with_initial_text(window.window_name.clone().unwrap_or_default())
+                    .allow_empty(true)
+                    .interact_text()?;
+                let name = name.trim();
+                window.window_name = (!name.is_empty()).then(|| name.to_string());
+            }
+            1 => window.layout = choose_layout(&window)?,
+            2 => {
+                let index = choose_pane(&window, "  Which pane?")?;
+                edit_pane_commands(&mut window.panes[index])?;
+            }
+            3 => {
+                let pane = create_pane_interactive(window.panes.len() + 1, &ctx)?;
+                window.panes.push(pane);
+                rechoose_custom_layout(&mut window)?;
+            }
+            4 if window.panes.len() == 1 => {
+                println!("  {}", style("The only pane can't be removed").yellow());
+            }
+            4 => {
+                let index = choose_pane(&window, "  Remove which pane?")?;
+                window.panes.remove(index);
+                rechoose_custom_layout(&mut window)?;
+            }
+            _ => break,
+        }
+    }
+
+    if let Some(warning) = window.layout_warning(validate::terminal_size()) {
+        println!("{} {}", style("⚠").yellow().bold(), warning.message);
+    }
+    Ok(window)
+}
+
+/// Edit a whole config's windows through a menu: edit, add, rename, or
+/// remove windows until done
+pub(crate) fn edit_windows_interactive(
+    mut windows: Vec<Window>,
+    project_dir: &std::path::Path,
+    suppress_history: bool,
+    settings: &Settings,
+) -> Result<Vec<Window>> {
+    let ctx = WizardContext {
+        project_dir,
+        project_type: detect::detect_project_type(project_dir),
+        suppress_history,
+        settings,
+    };
+
+    loop {
+        println!();
+        for (i, window) in windows.iter().enumerate() {
+            println!(
+                "  {} {} {}",
+                style(format!("{}.", i + 1)).dim(),
+                style(window.window_name.as_deref().unwrap_or("unnamed")).bold(),
+                style(format!("({} pane(s))", window.panes.len())).dim()
+            );
+        }
+
+        let choice = Select::with_theme(theme::get())
+            .with_prompt("Edit")
+            .items([
+                "Edit a window",
+                "Add a window",
+                "Rename a window",
+                "Remove a window",
+                "Done",
+            ])
+            .default(0)
+            .interact()?;
+        match choice {
+            0 => {
+                let index = choose_window(&windows, "  Which window?")?;
+                let window = windows[index].clone();
+                windows[index] =
+                    edit_window_interactive(window, project_dir, suppress_history, settings)?;
+            }
+            1 => {
+                let window = create_window_interactive(windows.len() + 1, &ctx)?;
+                windows.push(window);
+            }
+            2 => {
+                let index = choose_window(&windows, "  Rename which window?")?;
+                let name: String = Input::with_theme(theme::get())
+                    .with_prompt("  Window name (empty for none)")
+                    .with_initial_text(windows[index].window_name.clone().unwrap_or_default())
+                    .allow_empty(true)
+                    .interact_text()?;
+                let name = name.trim();
+                windows[index].window_name = (!name.is_empty()).then(|| name.to_string());
+            }
+            3 if windows.len() == 1 => {
+                println!("  {}", style("The only window can't be removed").yellow());
+            }
+            3 => {
+                let index = choose_window(&windows, "  Remove which window?")?;
+                windows.remove(index);
+            }
+            _ => break,
+        }
+    }
+
+    Ok(windows)
+}
+
+fn choose_window(windows: &[Window], prompt: &str) -> Result<usize> {
+    let items: Vec<String> = windows
+        .iter()
+        .enumerate()
+        .map(|(i, window)| {
+            format!(
+                "window {}: {}",
+                i + 1,
+                window.window_name.as_deref().unwrap_or("unnamed")
+            )
+        })
+        .collect();
+    Ok(Select::with_theme(theme::get())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact()?)
+}
+
+fn choose_pane(window: &Window, prompt: &str) -> Result<usize> {
+    let items: Vec<String> = window
+        .panes
+        .iter()
+        .enumerate()
+        .map(|(i, pane)| format!("pane {}: {}", i + 1, pane_summary(pane)))
+        .collect();
+    Ok(Select::with_theme(theme::get())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact()?)
+}
+
+/// Ask for a new layout when a custom one no longer matches the pane count
+fn rechoose_custom_layout(window: &mut Window) -> Result<()> {
+    let Some(expected) = window.layout.as_ref().and_then(WindowLayout::custom_panes) else {
+        return Ok(());
+    };
+    if expected == window.panes.len() {
+        return Ok(());
+    }
+    println!(
+        "  {} The custom layout was made for {} panes; the window now has {}",
+        style("⚠").yellow().bold(),
+        expected,
+        window.panes.len()
+    );
+    window.layout = choose_layout(window)?;
+    Ok(())
+}
+
+/// Pick a new layout for an existing window; a split must match its pane
+/// count. A custom layout that still fits is offered first.
+fn choose_layout(window: &Window) -> Result<Option<WindowLayout>> {
+    let panes = window.panes.len();
+    let mut choices = vec!["none".to_string()];
+    choices.extend(WindowLayout::all().iter().map(|l| l.to_string()));
+    let custom = choices.len();
+    choices.push("custom split (e.g. h70[v50,v50])".to_string());
+    let keep = window
+        .layout
+        .as_ref()
+        .filter(|layout| layout.custom_panes() == Some(panes))
+        .cloned();
+    if keep.is_some() {
+        choices.push("keep the current custom layout".to_string());
+    }
+
+    let current = match &window.layout {
+        Some(WindowLayout::Custom(_)) if keep.is_some() => custom + 1,
+        Some(WindowLayout::Custom(_)) => custom,
+        Some(layout) => WindowLayout::all()
+            .iter()
+            .position(|b| b == layout)
+            .map_or(0, |i| i + 1),
+        None => 0,
+    };
+    let choice = Select::with_theme(theme::get())
+        .with_prompt("  Layout")
+        .items(&choices)
+        .default(current)
+        .interact()?;
+
+    if choice == 0 {
+        return Ok(None);
+    }
+    if choice > custom {
+        return Ok(keep);
+    }
+    if let Some(layout) = WindowLayout::all().get(choice - 1) {
+        return Ok(Some(layout.clone()));
+    }
+    let dsl: String = Input::with_theme(theme::get())
+        .with_prompt(format!("  Split for {} panes", panes))
+        .validate_with(|input: &String| -> std::result::Result<(), String> {
+            match WindowLayout::from_split(input) {
+                Ok((_, count)) if count == panes => Ok(()),
+                Ok((_, count)) => Err(format!("That split has {} panes, not {}", count, panes)),
+                Err(e) => Err(e.to_string()),
+            }
+        })
+        .interact_text()?;
+    Ok(Some(WindowLayout::from_split(&dsl)?.0))
+}
+
+/// Offer detected project commands as a multi-select list and turn the
+/// selections into pre-filled windows
+fn select_detected_windows(project_dir: &std::path::Path) -> Result<Vec<Window>> {
+    // Commands for tools that aren't installed would only fail in their panes
+    let (detections, unavailable): (Vec<_>, Vec<_>) = detect::detect_commands(project_dir)
+        .into_iter()
+        .partition(|d| d.kind.tool().is_none_or(validate::has_binary));
+    let mut missing: Vec<&str> = unavailable.iter().filter_map(|d| d.kind.tool()).collect();
+    missing.dedup();
+    if !missing.is_empty() {
+        println!(
+            "{}",
+            style(format!(
+                "Skipped {} detected command(s): {} not installed",
+                unavailable.len(),
+                missing.join(", ")
+            ))
+            .dim()
+        );
+    }
+    if detections.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let items: Vec<picker::Item> = detections
+        .iter()
+        .map(|d| picker::Item::new(d.to_string()))
+        .collect();
+    let defaults = vec![false; items.len()];
+    let selected = picker::multi_select("Detected commands", &items, &defaults)?;
+
+    let selected: Vec<detect::Detection> = selected
+        .into_iter()
+        .map(|i| detections[i].clone())
+        .collect();
+
+    Ok(detect::distribute_into_windows(&selected))
+}
+
+/// `--preset`, or a preset picked from the list, defaulting to the one for
+/// the detected project type
+fn select_preset(
+    preset: Option<Preset>,
+    project_dir: &std::path::Path,
+    project_type: ProjectType,
+) -> Result<Option<Preset>> {
+    if preset.is_some() {
+        return Ok(preset);
+    }
+    let mut choices = vec!["No preset".to_string()];
+    choices.extend(Preset::all().iter().map(|p| p.describe(project_dir)));
+    let default = Preset::for_project_type(project_type)
+        .and_then(|detected| Preset::all().iter().position(|p| *p == detected))
+        .map_or(0, |i| i + 1);
+    let selection = Select::with_theme(theme::get())
+        .with_prompt("Start from a preset?")
+        .items(&choices)
+        .default(default)
+        .interact()?;
+    Ok(selection.checked_sub(1).map(|i| Preset::all()[i]))
+}
+
 /// Interactively create a window configuration
-fn create_window_interactive(window_num: usize) -> Result<Window> {
+fn create_window_interactive(window_num: usize, ctx: &WizardContext) -> Result<Window> {
     println!("{}", style(format!("Window #{}", window_num)).bold());
 
+    // Quick pick: start from an archetype or a blank window
+    let archetypes = Archetype::available(ctx.project_dir);
+    let mut start_choices = vec!["blank".to_string()];
+    start_choices.extend(
+        archetypes
+            .iter()
+            .map(|a| a.describe(ctx.project_dir, ctx.project_type)),
+    );
+    let start_idx = Select::with_theme(theme::get())
+        .with_prompt("  Start from")
+        .items(&start_choices)
+        .default(0)
+        .interact()?;
+
+    if start_idx > 0 {
+        let window = archetypes[start_idx - 1].build(ctx.project_dir, ctx.project_type);
+        let name = window.window_name.clone().unwrap_or_default();
+        return tweak_window_interactive(window, name);
+    }
+
     // Window name
-    let window_name: String = Input::with_theme(&ColorfulTheme::default())
+    let window_name: String = Input::with_theme(theme::get())
         .with_prompt("  Window name (optional, press Enter to skip)")
         .allow_empty(true)
         .interact_text()?;
@@ -190,48 +1091,160 @@ fn create_window_interactive(window_num: usize) -> Result<Window> {
         Some(window_name)
     };
 
-    // Layout
-    let layout_choices: Vec<String> = WindowLayout::all().iter().map(|l| l.to_string()).collect();
+    // Layout; "auto" picks one from the pane count
+    if window_num == 1 {
+        println!(
+            "  {}",
+            style("See `tmuxify explain layouts` for diagrams.").dim()
+        );
+    }
+    let mut layout_choices = vec!["auto (based on pane count)".to_string()];
+    layout_choices.extend(WindowLayout::all().iter().map(|l| l.to_string()));
+    layout_choices.push("custom split (e.g. h70[v50,v50])".to_string());
 
-    let layout_idx = Select::with_theme(&ColorfulTheme::default())
+    let layout_idx = Select::with_theme(theme::get())
         .with_prompt("  Layout")
         .items(&layout_choices)
-        .default(0)
+        .default(default_layout_choice(ctx.settings))
         .interact()?;
 
-    let layout = Some(WindowLayout::all()[layout_idx]);
+    // A custom split fixes the number of panes
+    let (chosen_layout, split_panes) = if layout_idx == 0 {
+        (None, None)
+    } else if layout_idx <= WindowLayout::all().len() {
+        (Some(WindowLayout::all()[layout_idx - 1].clone()), None)
+    } else {
+        let dsl: String = Input::with_theme(theme::get())
+            .with_prompt("  Split (h = left/right, v = top/bottom, sizes in %)")
+            .validate_with(|input: &String| -> std::result::Result<(), String> {
+                WindowLayout::from_split(input)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })
+            .interact_text()?;
+        let (layout, panes) = WindowLayout::from_split(&dsl)?;
+        println!("    {}", style(format!("{} panes", panes)).dim());
+        (Some(layout), Some(panes))
+    };
 
     // Number of panes
-    let num_panes: usize = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("  Number of panes")
-        .default(1)
+    let num_panes: usize = match split_panes {
+        Some(panes) => panes,
+        None => Input::with_theme(theme::get())
+            .with_prompt("  Number of panes")
+            .default(ctx.settings.wizard.panes.unwrap_or(1))
+            .interact_text()?,
+    };
+
+    let mut layout = if layout_idx == 0 {
+        let auto = ctx.settings.default_layout_for(num_panes);
+        let label = auto.as_ref().map_or("none".to_string(), |l| l.to_string());
+        println!("    {}", style(format!("Layout: {}", label)).dim());
+        auto
+    } else {
+        chosen_layout
+    };
+
+    // Catch layouts that squeeze the panes before the session is ever loaded
+    let warning = layout
+        .as_ref()
+        .and_then(|l| l.warning(num_panes, validate::terminal_size()));
+    if let Some(warning) = warning {
+        println!("    {} {}", style("⚠").yellow().bold(), warning.message);
+        if let Some(alternative) = warning.alternative
+            && Confirm::with_theme(theme::get())
+                .with_prompt(format!("    Use {} instead?", alternative))
+                .default(true)
+                .interact()?
+        {
+            layout = Some(alternative);
+        }
+    }
+
+    // Main pane sizing for main-horizontal/main-vertical
+    let main_pane_size = match layout.as_ref().and_then(|l| l.main_pane_option()) {
+        Some(option) => {
+            let size: String = Input::with_theme(theme::get())
+                .with_prompt(format!(
+                    "  Main pane size (optional, {}: cells like 40 or percent like 70%)",
+                    option
+                ))
+                .allow_empty(true)
+                .validate_with(|input: &String| -> std::result::Result<(), String> {
+                    let input = input.trim();
+                    if input.is_empty() || is_valid_pane_size(input) {
+                        Ok(())
+                    } else {
+                        Err("Use a cell count (40) or a percentage (70%)".to_string())
+                    }
+                })
+                .interact_text()?;
+            Some(size.trim().to_string()).filter(|s| !s.is_empty())
+        }
+        None => None,
+    };
+
+    // Explicit window index
+    let index_input: String = Input::with_theme(theme::get())
+        .with_prompt("  Window index (optional, press Enter to skip)")
+        .allow_empty(true)
+        .validate_with(|input: &String| -> std::result::Result<(), String> {
+            if input.trim().is_empty() || input.trim().parse::<u32>().is_ok() {
+                Ok(())
+            } else {
+                Err("Enter a non-negative number".to_string())
+            }
+        })
+        .interact_text()?;
+    let window_index = index_input.trim().parse::<u32>().ok();
+
+    // Environment shared by all panes of this window
+    let env_input: String = Input::with_theme(theme::get())
+        .with_prompt("  Window environment (optional, KEY=VALUE separated by spaces, quote values with spaces)")
+        .allow_empty(true)
+        .validate_with(|input: &String| parse_env_assignments(input).map(|_| ()))
         .interact_text()?;
+    let environment = parse_env_assignments(&env_input).map_err(|e| anyhow::anyhow!(e))?;
 
     // Create panes
     let mut panes = Vec::new();
     for pane_num in 1..=num_panes {
-        let pane = create_pane_interactive(pane_num)?;
+        let pane = create_pane_interactive(pane_num, ctx)?;
         panes.push(pane);
     }
 
-    Ok(Window::new(window_name, layout, panes))
+    let mut window = Window::new(window_name, layout, panes);
+    window.window_index = window_index;
+    window.environment = environment;
+    if let Some(size) = main_pane_size {
+        window.set_main_pane_size(&size)?;
+    }
+
+    Ok(window)
 }
 
 /// Interactively create a pane configuration
-fn create_pane_interactive(pane_num: usize) -> Result<Pane> {
+fn create_pane_interactive(pane_num: usize, ctx: &WizardContext) -> Result<Pane> {
     println!("    {}", style(format!("Pane #{}", pane_num)).dim());
 
-    let input_method = Select::with_theme(&ColorfulTheme::default())
+    // The project's own commands come first; pane N defaults to the Nth
+    let suggestions = archetype::suggested_commands(ctx.project_dir, ctx.project_type);
+    let mut methods: Vec<String> = suggestions.iter().map(|c| format!("Run `{}`", c)).collect();
+    methods.extend(["Single line", "Multi-line (editor)", "No commands"].map(String::from));
+    let choice = Select::with_theme(theme::get())
         .with_prompt("      Enter commands")
-        .items(["Single line", "Multi-line (editor)", "No commands"])
-        .default(0)
+        .items(&methods)
+        .default((pane_num - 1).min(suggestions.len()))
         .interact()?;
 
-    let commands = match input_method {
-        0 => {
+    let commands = match choice.checked_sub(suggestions.len()) {
+        None => vec![suggestions[choice].clone()],
+        Some(0) => {
             // Single line
-            let cmd: String = Input::with_theme(&ColorfulTheme::default())
+            let mut history = InputHistory::load("commands");
+            let cmd: String = Input::with_theme(theme::get())
                 .with_prompt("      Command")
+                .history_with(&mut history)
                 .allow_empty(true)
                 .interact_text()?;
 
@@ -241,13 +1254,10 @@ fn create_pane_interactive(pane_num: usize) -> Result<Pane> {
                 vec![cmd]
             }
         }
-        1 => {
+        Some(1) => {
             // Multi-line editor
             if let Some(text) = Editor::new().edit("# Enter commands (one per line)\n")? {
-                text.lines()
-                    .filter(|line| !line.trim().is_empty() && !line.trim().starts_with('#'))
-                    .map(|line| line.to_string())
-                    .collect()
+                parse_command_lines(&text)
             } else {
                 Vec::new()
             }
@@ -255,5 +1265,224 @@ fn create_pane_interactive(pane_num: usize) -> Result<Pane> {
         _ => Vec::new(), // No commands
     };
 
-    Ok(Pane::new(commands))
+    // Optional working directory, completed relative to the project root
+    let completion = DirCompletion {
+        base: ctx.project_dir.to_path_buf(),
+    };
+    let mut history = InputHistory::load("directories");
+    let dir: String = Input::with_theme(theme::get())
+        .with_prompt("      Working directory (optional, Tab to complete)")
+        .completion_with(&completion)
+        .history_with(&mut history)
+        .allow_empty(true)
+        .interact_text()?;
+    let dir = dir.trim();
+    let start_directory = if dir.is_empty() {
+        None
+    } else {
+        Some(dir.to_string())
+    };
+
+    // Optional per-pane environment variables
+    let env_input: String = Input::with_theme(theme::get())
+        .with_prompt(
+            "      Environment (optional, KEY=VALUE separated by spaces, quote values with spaces)",
+        )
+        .allow_empty(true)
+        .validate_with(|input: &String| parse_env_assignments(input).map(|_| ()))
+        .interact_text()?;
+    let environment = parse_env_assignments(&env_input).map_err(|e| anyhow::anyhow!(e))?;
+
+    // Per-pane history override, only recorded when it differs from the session
+    let suppress_history = if commands.is_empty() {
+        None
+    } else {
+        let suppress = Confirm::with_theme(theme::get())
+            .with_prompt("      Keep these commands out of shell history?")
+            .default(ctx.suppress_history)
+            .interact()?;
+        (suppress != ctx.suppress_history).then_some(suppress)
+    };
+
+    // Optional startup delay, e.g. so a client pane waits for a server pane
+    let delay: u32 = if commands.is_empty() {
+        0
+    } else {
+        Input::with_theme(theme::get())
+            .with_prompt("      Delay before running (seconds, 0 for none)")
+            .default(0)
+            .interact_text()?
+    };
+
+    let mut pane = Pane::new(commands)
+        .with_delay(delay)
+        .with_start_directory(start_directory)
+        .with_environment(environment);
+    pane.suppress_history = suppress_history;
+
+    Ok(pane)
+}
+
+/// Parse space-separated `KEY=VALUE` assignments; values are quoted like in a shell
+pub(crate) fn parse_env_assignments(
+    input: &str,
+) -> std::result::Result<BTreeMap<String, String>, String> {
+    shell::split(input)?
+        .iter()
+        .map(|pair| parse_env_assignment(pair))
+        .collect()
+}
+
+/// Parse a single `KEY=VALUE` assignment; the value may contain spaces
+pub(crate) fn parse_env_assignment(pair: &str) -> std::result::Result<(String, String), String> {
+    let Some((key, value)) = pair.split_once('=') else {
+        return Err(format!("'{}' is not a KEY=VALUE pair", pair));
+    };
+
+    let valid_key = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(format!("'{}' is not a valid variable name", key));
+    }
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Tab completion of directory names relative to a base directory
+struct DirCompletion {
+    base: std::path::PathBuf,
+}
+
+impl Completion for DirCompletion {
+    fn get(&self, input: &str) -> Option<String> {
+        let (parent, prefix) = match input.rfind('/') {
+            Some(idx) => (&input[..=idx], &input[idx + 1..]),
+            None => ("", input),
+        };
+
+        let search_dir = if parent.starts_with('/') {
+            std::path::PathBuf::from(parent)
+        } else {
+            self.base.join(parent)
+        };
+
+        let mut matches: Vec<String> = std::fs::read_dir(search_dir)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+            .filter(|name| name.starts_with(prefix) && !name.starts_with('.'))
+            .collect();
+        matches.sort();
+
+        match matches.as_slice() {
+            [] => None,
+            [only] => Some(format!("{}{}/", parent, only)),
+            [first, rest @ ..] => {
+                // Complete up to the longest common prefix
+                let common_len = rest.iter().fold(first.len(), |len, name| {
+                    first
+                        .chars()
+                        .zip(name.chars())
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                        .min(len)
+                });
+                let common: String = first.chars().take(common_len).collect();
+                (common.len() > prefix.len()).then(|| format!("{}{}", parent, common))
+            }
+        }
+    }
+}
+
+/// Parse editor text into commands, skipping blank lines and `#` comments
+fn parse_command_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.trim().starts_with('#'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wizard_layout_setting_selects_a_choice() {
+        let mut settings = Settings::default();
+        assert_eq!(default_layout_choice(&settings), 0);
+        settings.wizard.layout = Some("tiled".into());
+        assert_eq!(default_layout_choice(&settings), 1);
+        settings.wizard.layout = Some("split".into());
+        assert_eq!(
+            default_layout_choice(&settings),
+            WindowLayout::all().len() + 1
+        );
+        settings.wizard.layout = Some("sideways".into());
+        assert_eq!(default_layout_choice(&settings), 0);
+    }
+
+    #[test]
+    fn rebuilt_session_keeps_keys_the_wizard_does_not_ask_about() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        std::fs::write(
+            &path,
+            "session_name: app\nstart_directory: /src/app\n\
+             before_script: ./bin/setup\nshell_command_before:\n- source .env\n\
+             options:\n  mouse: 'on'\nenvironment:\n  PORT: '3000'\n\
+             windows:\n- window_name: old\n  panes:\n  - vim\n",
+        )
+        .unwrap();
+        let base = load::load_config(&path).unwrap();
+
+        let windows = vec![Window::new(Some("new".into()), None, vec![Pane::empty()])];
+        let config = rebuild_session(Some(&base), "app".into(), "/src/app".into(), windows);
+        let options = write::WriteOptions {
+            skip_history: true,
+            force: true,
+            ..Default::default()
+        };
+        write::save_config(&config, &path, &options).unwrap();
+
+        let saved = load::load_config(&path).unwrap();
+        assert!(saved.extra.contains_key("before_script"));
+        assert_eq!(saved.extra, base.extra);
+        assert_eq!(saved.environment, base.environment);
+        assert_eq!(saved.windows[0].window_name.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn parses_env_assignments() {
+        let env = parse_env_assignments("PORT=3000 RUST_LOG=debug EMPTY=").unwrap();
+        assert_eq!(env.get("PORT").map(String::as_str), Some("3000"));
+        assert_eq!(env.get("RUST_LOG").map(String::as_str), Some("debug"));
+        assert_eq!(env.get("EMPTY").map(String::as_str), Some(""));
+        assert!(parse_env_assignments("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn spaced_env_values_round_trip() {
+        let env = BTreeMap::from([
+            ("JAVA_OPTS".to_string(), "-Xmx1g -Xms1g".to_string()),
+            ("GREETING".to_string(), "it's here".to_string()),
+            ("PORT".to_string(), "3000".to_string()),
+        ]);
+        let prefill: Vec<String> = env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, shell::quote(value)))
+            .collect();
+        assert_eq!(parse_env_assignments(&prefill.join(" ")).unwrap(), env);
+        assert!(parse_env_assignments("JAVA_OPTS='-Xmx1g").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_env_assignments() {
+        assert!(parse_env_assignments("PORT").is_err());
+        assert!(parse_env_assignments("1PORT=3000").is_err());
+        assert!(parse_env_assignments("MY-VAR=1").is_err());
+    }
 }
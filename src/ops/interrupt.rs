@@ -0,0 +1,95 @@
+//! Deferred Ctrl-C handling for critical sections such as multi-file writes.
+//!
+//! While a [`Guard`] is alive, SIGINT and SIGTERM only set a flag; the code in
+//! the critical section checks [`interrupted`] and cleans up before bailing.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether a signal arrived since the current guard was installed
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::{INTERRUPTED, Ordering};
+
+    pub const SIGINT: i32 = 2;
+    pub const SIGTERM: i32 = 15;
+
+    /// `sighandler_t`; `SIG_ERR` is -1
+    pub type Handler = usize;
+    pub const SIG_ERR: Handler = usize::MAX;
+
+    unsafe extern "C" {
+        fn signal(signum: i32, handler: Handler) -> Handler;
+    }
+
+    extern "C" fn on_signal(_: i32) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    pub fn set_flag_handler(signum: i32) -> Option<Handler> {
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        let previous = unsafe { signal(signum, on_signal as extern "C" fn(i32) as Handler) };
+        (previous != SIG_ERR).then_some(previous)
+    }
+
+    pub fn restore(signum: i32, handler: Handler) {
+        // SAFETY: restores the disposition returned by an earlier `signal` call
+        unsafe {
+            signal(signum, handler);
+        }
+    }
+}
+
+/// Defers SIGINT/SIGTERM until dropped, restoring the previous handlers
+pub struct Guard {
+    #[cfg(unix)]
+    previous: Vec<(i32, sys::Handler)>,
+}
+
+impl Guard {
+    /// Start deferring signals and clear any earlier interruption
+    pub fn install() -> Self {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+
+        #[cfg(unix)]
+        {
+            let previous = [sys::SIGINT, sys::SIGTERM]
+                .into_iter()
+                .filter_map(|signum| sys::set_flag_handler(signum).map(|h| (signum, h)))
+                .collect();
+            Guard { previous }
+        }
+        #[cfg(not(unix))]
+        {
+            Guard {}
+        }
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        for (signum, handler) in self.previous.drain(..) {
+            sys::restore(signum, handler);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guard_installs_and_restores_handlers() {
+        let guard = Guard::install();
+        assert!(!interrupted());
+        #[cfg(unix)]
+        assert_eq!(guard.previous.len(), 2);
+        drop(guard);
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_yaml::Value;
+
+/// Serialize a value as pretty-printed JSON.
+///
+/// Goes through `serde_yaml::Value` so no separate JSON dependency is needed.
+pub fn to_json_pretty<T: Serialize>(value: &T) -> Result<String> {
+    let value = serde_yaml::to_value(value)?;
+    let mut out = String::new();
+    write_value(&value, Some(0), &mut out)?;
+    out.push('\n');
+    Ok(out)
+}
+
+/// Serialize a value as single-line JSON (for JSON Lines files).
+///
+/// Separators keep a space after `:` and `,` so the line also parses as YAML.
+pub fn to_json_line<T: Serialize>(value: &T) -> Result<String> {
+    let value = serde_yaml::to_value(value)?;
+    let mut out = String::new();
+    write_value(&value, None, &mut out)?;
+    Ok(out)
+}
+
+/// Write a value; `indent` is the nesting level when pretty-printing, `None` for one line
+fn write_value(value: &Value, indent: Option<usize>, out: &mut String) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => write_string(s, out),
+        Value::Sequence(items) => {
+            if items.is_empty() {
+                out.push_str("[]");
+                return Ok(());
+            }
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                push_break(indent.map(|n| n + 1), out);
+                write_value(item, indent.map(|n| n + 1), out)?;
+                if i + 1 < items.len() {
+                    out.push(',');
+                }
+            }
+            push_break(indent, out);
+            out.push(']');
+        }
+        Value::Mapping(map) => {
+            if map.is_empty() {
+                out.push_str("{}");
+                return Ok(());
+            }
+            out.push('{');
+            for (i, (key, item)) in map.iter().enumerate() {
+                push_break(indent.map(|n| n + 1), out);
+                let key = match key {
+                    Value::String(s) => s.clone(),
+                    Value::Number(n) => n.to_string(),
+                    Value::Bool(b) => b.to_string(),
+                    other => anyhow::bail!("Unsupported JSON object key: {:?}", other),
+                };
+                write_string(&key, out);
+                out.push_str(": ");
+                write_value(item, indent.map(|n| n + 1), out)?;
+                if i + 1 < map.len() {
+                    out.push(',');
+                }
+            }
+            push_break(indent, out);
+            out.push('}');
+        }
+        Value::Tagged(tagged) => write_value(&tagged.value, indent, out)?,
+    }
+    Ok(())
+}
+
+/// Start a new indented line when pretty-printing, or separate with a space
+fn push_break(indent: Option<usize>, out: &mut String) {
+    match indent {
+        Some(level) => {
+            out.push('\n');
+            out.push_str(&"  ".repeat(level));
+        }
+        None => out.push(' '),
+    }
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Config, Pane, Window};
+
+    #[test]
+    fn renders_config_as_json() {
+        let cfg = Config::new(
+            "my\"app".into(),
+            "/tmp".into(),
+            vec![Window::new(
+                None,
+                None,
+                vec![Pane::new(vec!["echo hi".into()])],
+            )],
+        );
+        let json = to_json_pretty(&cfg).unwrap();
+        assert!(json.contains(r#""session_name": "my\"app""#));
+        assert!(json.contains(r#""shell_command": ["#));
+        assert!(json.contains(r#""echo hi""#));
+        assert!(json.starts_with("{\n  \"session_name\""));
+        assert!(json.ends_with("}\n"));
+    }
+
+    #[test]
+    fn json_lines_round_trip_through_yaml() {
+        let cfg = Config::new("app".into(), "/tmp".into(), vec![]);
+        let line = to_json_line(&cfg).unwrap();
+        assert!(!line.contains('\n'));
+        assert!(line.starts_with(r#"{ "session_name": "app","#));
+
+        let parsed: Config = serde_yaml::from_str(&line).unwrap();
+        assert_eq!(parsed.session_name, "app");
+    }
+}
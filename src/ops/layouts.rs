@@ -0,0 +1,235 @@
+use anyhow::Result;
+use console::style;
+
+use crate::cli::Args;
+use crate::model::WindowLayout;
+use crate::ops::process::SystemRunner;
+use crate::ops::{tmux, validate};
+
+/// Session created by `tmuxify layouts --demo`
+pub const DEMO_SESSION: &str = "tmuxify-layouts";
+
+/// Preview width in characters
+const PREVIEW_WIDTH: usize = 40;
+
+/// A pane's outline on the preview grid; edges are shared with neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+}
+
+/// Print ASCII previews of the built-in layouts, optionally opening a live demo session
+pub fn run(args: &Args, panes: usize, demo: bool) -> Result<()> {
+    let height = (2 * panes + 1).max(9);
+
+    for layout in WindowLayout::all() {
+        println!("{}", style(layout.to_string()).bold().cyan());
+        let rects = pane_rects(layout, panes, PREVIEW_WIDTH, height);
+        println!("{}", render(&rects, PREVIEW_WIDTH, height));
+        println!();
+    }
+
+    if demo {
+        start_demo(args, panes)?;
+    } else {
+        println!(
+            "Run {} to try them in a throwaway tmux session.",
+            style(format!("tmuxify layouts --panes {} --demo", panes)).yellow()
+        );
+    }
+
+    Ok(())
+}
+
+/// Split the span `start..=end` into `parts` segments sharing their boundaries
+fn segments(start: usize, end: usize, parts: usize) -> Vec<(usize, usize)> {
+    let span = end - start;
+    (0..parts)
+        .map(|i| {
+            (
+                start + (i * span + parts / 2) / parts,
+                start + ((i + 1) * span + parts / 2) / parts,
+            )
+        })
+        .collect()
+}
+
+/// Approximate where tmux places `count` panes for a layout
+fn pane_rects(layout: &WindowLayout, count: usize, width: usize, height: usize) -> Vec<Rect> {
+    let (right, bottom) = (width - 1, height - 1);
+    let whole = Rect {
+        x0: 0,
+        y0: 0,
+        x1: right,
+        y1: bottom,
+    };
+    if count <= 1 {
+        return vec![whole];
+    }
+
+    let columns = |y0, y1, n| {
+        segments(0, right, n)
+            .into_iter()
+            .map(move |(x0, x1)| Rect { x0, y0, x1, y1 })
+    };
+    let rows = |x0, x1, n| {
+        segments(0, bottom, n)
+            .into_iter()
+            .map(move |(y0, y1)| Rect { x0, y0, x1, y1 })
+    };
+
+    match layout {
+        WindowLayout::EvenHorizontal => columns(0, bottom, count).collect(),
+        WindowLayout::EvenVertical => rows(0, right, count).collect(),
+        WindowLayout::MainHorizontal => {
+            let split = bottom * 3 / 5;
+            let mut rects = vec![Rect { y1: split, ..whole }];
+            rects.extend(columns(split, bottom, count - 1));
+            rects
+        }
+        WindowLayout::MainVertical => {
+            let split = right / 2;
+            let mut rects = vec![Rect { x1: split, ..whole }];
+            rects.extend(rows(split, right, count - 1));
+            rects
+        }
+        // tmux grows rows and columns alternately until the grid fits every pane
+        WindowLayout::Tiled | WindowLayout::Custom(_) => {
+            let (mut grid_rows, mut grid_cols) = (1, 1);
+            while grid_rows * grid_cols < count {
+                grid_rows += 1;
+                if grid_rows * grid_cols < count {
+                    grid_cols += 1;
+                }
+            }
+            segments(0, bottom, grid_rows)
+                .into_iter()
+                .enumerate()
+                .flat_map(|(row, (y0, y1))| {
+                    let in_row = grid_cols.min(count.saturating_sub(row * grid_cols));
+                    columns(y0, y1, in_row.max(1)).take(in_row)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Draw pane outlines, numbered from 1, onto a character grid
+fn render(rects: &[Rect], width: usize, height: usize) -> String {
+    let mut grid = vec![vec![' '; width]; height];
+
+    for r in rects {
+        grid[r.y0][r.x0..=r.x1].fill('-');
+        grid[r.y1][r.x0..=r.x1].fill('-');
+        for row in grid.iter_mut().take(r.y1 + 1).skip(r.y0) {
+            row[r.x0] = '|';
+            row[r.x1] = '|';
+        }
+    }
+
+    for (i, r) in rects.iter().enumerate() {
+        for (x, y) in [(r.x0, r.y0), (r.x1, r.y0), (r.x0, r.y1), (r.x1, r.y1)] {
+            grid[y][x] = '+';
+        }
+        let label = (i + 1).to_string();
+        let y = (r.y0 + r.y1) / 2;
+        let x = (r.x0 + r.x1) / 2;
+        for (offset, c) in label.chars().enumerate() {
+            grid[y][x + offset] = c;
+        }
+    }
+
+    grid.into_iter()
+        .map(|row| row.into_iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Open a throwaway session with one window per built-in layout
+fn start_demo(args: &Args, panes: usize) -> Result<()> {
+    if !validate::has_binary("tmux") {
+        anyhow::bail!("tmux is not installed; run 'tmuxify doctor' for install hints");
+    }
+    if tmux::has_session(DEMO_SESSION) {
+        anyhow::bail!(
+            "Session '{}' already exists (tmux kill-session -t {})",
+            DEMO_SESSION,
+            DEMO_SESSION
+        );
+    }
+
+    let runner = SystemRunner::new(args.dry_run);
+    for (i, layout) in WindowLayout::all().iter().enumerate() {
+        let name = layout.to_string();
+        let target = format!("{}:{}", DEMO_SESSION, name);
+        if i == 0 {
+            tmux::run(
+                &runner,
+                &["new-session", "-d", "-s", DEMO_SESSION, "-n", &name],
+            )?;
+        } else {
+            let session = format!("{}:", DEMO_SESSION);
+            tmux::run(&runner, &["new-window", "-t", &session, "-n", &name])?;
+        }
+        // Re-apply the layout after each split so small terminals keep room for the next pane
+        for _ in 1..panes {
+            tmux::run(&runner, &["split-window", "-t", &target])?;
+            tmux::run(&runner, &["select-layout", "-t", &target, &name])?;
+        }
+        tmux::run(&runner, &["select-layout", "-t", &target, &name])?;
+    }
+    tmux::run(
+        &runner,
+        &["select-window", "-t", &format!("{}:^", DEMO_SESSION)],
+    )?;
+
+    if !args.dry_run {
+        println!(
+            "{} Started '{}'; switch windows to compare layouts, then run {}",
+            style("✓").green().bold(),
+            DEMO_SESSION,
+            style(format!("tmux kill-session -t {}", DEMO_SESSION)).yellow()
+        );
+    }
+
+    if validate::is_inside_tmux() {
+        tmux::run(&runner, &["switch-client", "-t", DEMO_SESSION])
+    } else {
+        tmux::attach(&runner, DEMO_SESSION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_layout_places_each_pane() {
+        for layout in WindowLayout::all() {
+            for count in 1..=10 {
+                assert_eq!(pane_rects(layout, count, PREVIEW_WIDTH, 21).len(), count);
+            }
+        }
+    }
+
+    #[test]
+    fn tiled_grid_matches_tmux_shape() {
+        // Five panes: three rows, the first two with two columns
+        let rects = pane_rects(&WindowLayout::Tiled, 5, PREVIEW_WIDTH, 13);
+        let rows: Vec<usize> = rects.iter().map(|r| r.y0).collect();
+        assert_eq!(rows, vec![0, 0, 4, 4, 8]);
+        assert_eq!(rects[4].x1, PREVIEW_WIDTH - 1);
+    }
+
+    #[test]
+    fn renders_numbered_outlines() {
+        let rects = pane_rects(&WindowLayout::EvenHorizontal, 2, 9, 5);
+        assert_eq!(
+            render(&rects, 9, 5),
+            "+---+---+\n|   |   |\n| 1 | 2 |\n|   |   |\n+---+---+"
+        );
+    }
+}
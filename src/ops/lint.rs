@@ -0,0 +1,652 @@
+//! `tmuxify validate`: checks that a config is usable, optionally by loading
+//! it with tmuxp on a throwaway tmux server.
+
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::Confirm;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::cli::Args;
+use crate::model::{Config, WindowLayout};
+use crate::ops::process::{self, Runner, SystemRunner};
+use crate::ops::{conflicts, import, load, theme, validate};
+
+/// How long tmuxp may take to build the session during a load check
+const LOAD_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Validate a config (defaults to the current project's)
+pub fn run(args: &Args, target: Option<&str>, load_check: bool, yes: bool) -> Result<()> {
+    let (path, project_dir) = match target {
+        Some(spec) => {
+            let path = load::resolve_config_path(spec)?;
+            // Only project-local configs say which project they belong to
+            let project_dir = path
+                .file_name()
+                .is_some_and(|name| name == ".tmuxp.yaml")
+                .then(|| path.parent().map(Path::to_path_buf))
+                .flatten();
+            (path, project_dir)
+        }
+        None => {
+            let project_dir = args.project_dir()?;
+            let path = match load::locate_project_config(&project_dir, args.session.as_deref()) {
+                Ok((path, _)) => path,
+                // A dangling .envrc says more than "no config found"
+                Err(e) => {
+                    report_problems(&project_dir, &consistency_problems(&project_dir))?;
+                    return Err(e);
+                }
+            };
+            (path, Some(project_dir))
+        }
+    };
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let problems = structure_problems(&content);
+    if !problems.is_empty() {
+        for problem in &problems {
+            println!(
+                "{} {}:{}:{}: {}",
+                style("✗").red().bold(),
+                path.display(),
+                problem.line,
+                problem.column,
+                problem.message
+            );
+        }
+        anyhow::bail!("{} problem(s) in {}", problems.len(), path.display());
+    }
+    let config = load::load_config(&path)?;
+    println!(
+        "{} {} parses as a tmuxp config",
+        style("✓").green().bold(),
+        path.display()
+    );
+
+    for (i, window) in config.windows.iter().enumerate() {
+        if let Some(warning) = window.layout_warning(validate::terminal_size()) {
+            let label = window
+                .window_name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", i + 1));
+            println!(
+                "{} window {}: {}",
+                style("⚠").yellow().bold(),
+                label,
+                warning.message
+            );
+        }
+    }
+
+    let project_conflicts = project_dir
+        .as_deref()
+        .map(conflicts::project_conflicts)
+        .unwrap_or_default();
+    for conflict in conflicts::shell_profile_conflicts()
+        .iter()
+        .chain(&project_conflicts)
+    {
+        println!("{} {}", style("⚠").yellow().bold(), conflict.describe());
+        println!("    {}", style(conflict.hint()).yellow());
+    }
+
+    // The load check runs the config's commands for real, so show them first
+    if load_check && !args.dry_run && !yes {
+        println!("\nThe load check runs everything the config runs when it loads:");
+        import::print_executed(&config);
+        validate::require_terminal("Confirming the load check")?;
+        let confirmed = Confirm::with_theme(theme::get())
+            .with_prompt("Load it on a throwaway tmux server?")
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            anyhow::bail!("Load check skipped");
+        }
+    }
+    if load_check {
+        check_load(&config, &path, &SystemRunner::new(args.dry_run))?;
+    }
+
+    // Project mode: the generated files must still agree with each other
+    if target.is_none()
+        && let Some(dir) = &project_dir
+    {
+        report_problems(dir, &consistency_problems(dir))?;
+        println!(
+            "{} .envrc, config, and project directory agree",
+            style("✓").green().bold()
+        );
+    }
+    Ok(())
+}
+
+/// A structural problem in a config file, at a 1-based line and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Problem {
+    fn at((line, column): (usize, usize), message: String) -> Self {
+        Self {
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+/// Position of the `nth` (0-based) `key:` line in `content`, optionally
+/// only those whose value is `value`; the start of the file when not found.
+/// Keys in flow-style YAML (`{panes: []}`) aren't found.
+fn position(content: &str, key: &str, value: Option<&str>, nth: usize) -> (usize, usize) {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let start = line.len() - line.trim_start_matches([' ', '-']).len();
+            let rest = line[start..].strip_prefix(key)?.strip_prefix(':')?;
+            let found = rest.trim().trim_matches(['"', '\'']);
+            value
+                .is_none_or(|value| found == value)
+                .then_some((i + 1, start + 1))
+        })
+        .nth(nth)
+        .unwrap_or((1, 1))
+}
+
+/// Structural problems tmuxp would trip over: a missing session name,
+/// windows without panes, layouts tmux rejects, and duplicate window names.
+/// A file that isn't valid YAML, or doesn't fit tmuxp's config shape,
+/// gives one problem at the parser's position.
+pub fn structure_problems(content: &str) -> Vec<Problem> {
+    let value: serde_yaml::Value = match serde_yaml::from_str(content) {
+        Ok(value) => value,
+        Err(e) => return vec![parse_problem(&e)],
+    };
+    let mut problems = Vec::new();
+
+    if value["session_name"].as_str().is_none_or(str::is_empty) {
+        problems.push(Problem::at((1, 1), "missing session_name".to_string()));
+    }
+
+    let windows = match value["windows"].as_sequence() {
+        Some(windows) if !windows.is_empty() => windows,
+        Some(_) => {
+            let at = position(content, "windows", None, 0);
+            problems.push(Problem::at(at, "no windows".to_string()));
+            return problems;
+        }
+        None => {
+            problems.push(Problem::at((1, 1), "missing windows".to_string()));
+            return problems;
+        }
+    };
+
+    let mut panes_seen = 0;
+    let mut layouts_seen = 0;
+    let mut names: Vec<&str> = Vec::new();
+    for (i, window) in windows.iter().enumerate() {
+        let name = window["window_name"].as_str();
+        let label = name.map_or_else(|| format!("#{}", i + 1), |n| format!("'{}'", n));
+        let window_at = match name {
+            Some(name) => {
+                let nth = names.iter().filter(|n| **n == name).count();
+                position(content, "window_name", Some(name), nth)
+            }
+            None => position(content, "windows", None, 0),
+        };
+
+        if let Some(name) = name {
+            if let Some(first) = names.iter().position(|n| *n == name) {
+                problems.push(Problem::at(
+                    window_at,
+                    format!(
+                        "duplicate window name '{}' (also window #{})",
+                        name,
+                        first + 1
+                    ),
+                ));
+            }
+            names.push(name);
+        }
+
+        let has_panes = window.get("panes").is_some();
+        if window["panes"].as_sequence().is_none_or(|p| p.is_empty()) {
+            let at = if has_panes {
+                position(content, "panes", None, panes_seen)
+            } else {
+                window_at
+            };
+            problems.push(Problem::at(at, format!("window {} has no panes", label)));
+        }
+        panes_seen += usize::from(has_panes);
+
+        if let Some(layout) = window.get("layout") {
+            let at = position(content, "layout", None, layouts_seen);
+            layouts_seen += 1;
+            let known = serde_yaml::from_value::<WindowLayout>(layout.clone())
+                .is_ok_and(|layout| layout.is_known());
+            if !known {
+                let valid: Vec<String> =
+                    WindowLayout::all().iter().map(|l| l.to_string()).collect();
+                problems.push(Problem::at(
+                    at,
+                    format!(
+                        "window {}: unknown layout {} (use one of {}, or a tmux layout string)",
+                        label,
+                        serde_yaml::to_string(layout).unwrap_or_default().trim(),
+                        valid.join(", ")
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Anything else the model rejects, such as a pane of the wrong type
+    if problems.is_empty()
+        && let Err(e) = serde_yaml::from_str::<Config>(content)
+    {
+        problems.push(parse_problem(&e));
+    }
+    problems
+}
+
+fn parse_problem(error: &serde_yaml::Error) -> Problem {
+    let at = error.location().map_or((1, 1), |l| (l.line(), l.column()));
+    // The position is reported separately
+    let message = error
+        .to_string()
+        .replacen(&format!(" at line {} column {}", at.0, at.1), "", 1);
+    Problem::at(at, message)
+}
+
+/// Print consistency problems, failing when there are any
+fn report_problems(project_dir: &Path, problems: &[String]) -> Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    for problem in problems {
+        println!("{} {}", style("✗").red().bold(), problem);
+    }
+    anyhow::bail!(
+        "{} consistency problem(s) in {}",
+        problems.len(),
+        project_dir.display()
+    )
+}
+
+/// The config file a `.envrc` loads, as written: the `layout tmux` argument
+/// (defaulting to `./.tmuxp.yaml`) or the argument of `tmuxp load` or (with
+/// the native driver) `tmuxify up`
+pub fn envrc_load_path(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["layout", "tmux"] => Some("./.tmuxp.yaml".to_string()),
+                ["layout", "tmux", path, ..] => Some(path.to_string()),
+                _ => {
+                    let load = words.windows(2).position(|pair| {
+                        (pair[0].ends_with("tmuxp") && pair[1] == "load")
+                            || (pair[0].ends_with("tmuxify") && pair[1] == "up")
+                    })?;
+                    // The path comes last, after any options and their values
+                    words[load + 2..]
+                        .iter()
+                        .take_while(|word| !["&&", "||", "|", ";"].contains(word))
+                        .filter(|word| !word.starts_with('-'))
+                        .last()
+                        .map(|path| path.trim_end_matches(';').to_string())
+                }
+            }
+        })
+}
+
+/// Expand `~/` and resolve relative paths against `base`
+pub fn expand(path: &str, base: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => base.join(path.strip_prefix("./").unwrap_or(path)),
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Mismatches between a project's `.envrc`, the config it loads, and the
+/// project directory, each described precisely
+pub fn consistency_problems(project_dir: &Path) -> Vec<String> {
+    let envrc_path = project_dir.join(".envrc");
+    let Ok(envrc) = fs::read_to_string(&envrc_path) else {
+        return vec![format!(
+            "No .envrc in {}; the session won't start on cd",
+            project_dir.display()
+        )];
+    };
+    let Some(written) = envrc_load_path(&envrc) else {
+        return vec![".envrc doesn't load a tmuxp config".to_string()];
+    };
+
+    let path = expand(&written, project_dir);
+    if !path.is_file() {
+        let mut problem = format!(".envrc loads {}, which doesn't exist", written);
+        let found = alternatives(&path, project_dir);
+        if !found.is_empty() {
+            let names: Vec<String> = found.iter().map(|p| p.display().to_string()).collect();
+            problem.push_str(&format!("; found {} instead", names.join(", ")));
+        }
+        return vec![problem];
+    }
+    let config = match load::load_config(&path) {
+        Ok(config) => config,
+        Err(e) => return vec![format!("{:#}", e)],
+    };
+
+    let mut problems = Vec::new();
+    let in_project = path.parent().is_some_and(|dir| same_path(dir, project_dir));
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    if !in_project && stem != config.session_name {
+        problems.push(format!(
+            "{} has session_name '{}'; home configs must be named after their session ({}.yaml)",
+            written, config.session_name, stem
+        ));
+    }
+
+    let start = &config.start_directory;
+    if !start.as_os_str().is_empty() {
+        let config_dir = path.parent().unwrap_or(project_dir);
+        let start_dir = match start.to_str() {
+            Some(dir) => expand(dir, config_dir),
+            None => config_dir.join(start),
+        };
+        let inside = match (start_dir.canonicalize(), project_dir.canonicalize()) {
+            (Ok(start), Ok(project)) => start.starts_with(project),
+            _ => start_dir.starts_with(project_dir),
+        };
+        if !inside {
+            problems.push(format!(
+                "{} has start_directory {}, outside the project {}",
+                written,
+                start.display(),
+                project_dir.display()
+            ));
+        }
+    }
+    problems
+}
+
+/// Configs the `.envrc` may have meant: the same name with another extension,
+/// or the project-local `.tmuxp.yaml`
+fn alternatives(missing: &Path, project_dir: &Path) -> Vec<PathBuf> {
+    let siblings = ["yaml", "yml", "json"]
+        .iter()
+        .map(|ext| missing.with_extension(ext));
+    let local = [".tmuxp.yaml", ".tmuxp.yml", ".tmuxp.json"]
+        .iter()
+        .map(|name| project_dir.join(name));
+    let mut found: Vec<PathBuf> = Vec::new();
+    for path in siblings.chain(local) {
+        if path != missing && path.is_file() && !found.contains(&path) {
+            found.push(path);
+        }
+    }
+    found
+}
+
+/// Name of the tmux socket used for one load check
+fn check_socket() -> String {
+    format!("tmuxify-check-{}", std::process::id())
+}
+
+/// `tmuxp load` detached on `socket`, answering yes to its prompts
+fn load_command(tmuxp: &str, socket: &str, path: &Path) -> Command {
+    let mut parts = tmuxp.split_whitespace();
+    let mut cmd = Command::new(parts.next().unwrap_or("tmuxp"));
+    cmd.args(parts)
+        .args(["load", "-y", "-d", "-L", socket])
+        .arg(path)
+        // Keep tmuxp from treating the check as a nested session of the current client
+        .env_remove("TMUX");
+    cmd
+}
+
+/// Load the config with tmuxp on a private tmux server, confirm the session
+/// and its windows exist, then kill that server.
+///
+/// Pane commands, `before_script`, and `shell_command_before` do run, since
+/// tmuxp sends them as it builds panes; `run` asks before calling this.
+fn check_load(config: &Config, path: &Path, runner: &dyn Runner) -> Result<()> {
+    let project_dir = path.parent().map(Path::to_path_buf);
+    let tmuxp = validate::resolve_tmuxp(project_dir.as_deref()).ok_or_else(|| {
+        anyhow::anyhow!("tmuxp is not installed; run 'tmuxify doctor' for install hints")
+    })?;
+    let socket = check_socket();
+    let mut load = load_command(&tmuxp.command, &socket, path);
+    if let Some(dir) = project_dir.filter(|dir| dir.is_dir()) {
+        load.current_dir(dir);
+    }
+
+    let loaded = runner.run(&mut load, LOAD_CHECK_TIMEOUT).and_then(|_| {
+        if runner.is_dry_run() {
+            return Ok(0);
+        }
+        count_windows(&socket, &config.session_name)
+    });
+    // Always tear the server down, including after a partial load
+    let _ = runner.run(
+        Command::new("tmux").args(["-L", &socket, "kill-server"]),
+        process::QUICK_TIMEOUT,
+    );
+    let windows = loaded.context("tmuxp could not load the config")?;
+
+    if runner.is_dry_run() {
+        return Ok(());
+    }
+    if windows != config.windows.len() {
+        anyhow::bail!(
+            "tmuxp created {} window(s) but the config has {}",
+            windows,
+            config.windows.len()
+        );
+    }
+    println!(
+        "{} tmuxp loads {} ({} window(s)) on a throwaway tmux server",
+        style("✓").green().bold(),
+        style(&config.session_name).bold(),
+        windows
+    );
+    Ok(())
+}
+
+/// Number of windows in `session` on the tmux server behind `socket`
+fn count_windows(socket: &str, session: &str) -> Result<usize> {
+    let listing = process::run(
+        Command::new("tmux").args([
+            "-L",
+            socket,
+            "list-windows",
+            "-t",
+            &format!("={}", session),
+            "-F",
+            "#{window_index}",
+        ]),
+        process::QUICK_TIMEOUT,
+    )
+    .with_context(|| format!("Session {} was not created", session))?;
+    Ok(listing.lines().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structure_problems_point_at_their_lines() {
+        let content = "windows:\n\
+                       - window_name: editor\n  layout: main-verticle\n  panes:\n  - vim\n\
+                       - window_name: editor\n  panes: []\n\
+                       - window_name: logs\n";
+        let problems = structure_problems(content);
+        let positions: Vec<(usize, usize)> = problems.iter().map(|p| (p.line, p.column)).collect();
+        assert_eq!(positions, [(1, 1), (3, 3), (6, 3), (7, 3), (8, 3)]);
+        assert_eq!(problems[0].message, "missing session_name");
+        assert!(problems[1].message.contains("unknown layout main-verticle"));
+        assert!(
+            problems[2]
+                .message
+                .starts_with("duplicate window name 'editor'")
+        );
+        assert_eq!(problems[4].message, "window 'logs' has no panes");
+
+        let custom = "session_name: s\nwindows:\n\
+                      - layout: bb62,159x48,0,0{79x48,0,0,79x48,80,0}\n  panes: [vim, null]\n";
+        assert!(structure_problems(custom).is_empty());
+
+        let broken = "session_name: s\nwindows: [\n";
+        let problem = &structure_problems(broken)[0];
+        assert_eq!((problem.line, problem.column), (3, 1));
+        assert!(!problem.message.contains("line 3"));
+    }
+    use crate::model::{EnvrcOptions, TmuxpLocation};
+    use tempfile::tempdir;
+
+    #[test]
+    fn envrc_load_paths_are_found_in_every_style() {
+        assert_eq!(
+            envrc_load_path("layout tmux\n").as_deref(),
+            Some("./.tmuxp.yaml")
+        );
+        assert_eq!(
+            envrc_load_path("layout tmux ~/.tmuxp/app.yaml ./.venv/bin/tmuxp\n").as_deref(),
+            Some("~/.tmuxp/app.yaml")
+        );
+        let config = Config::new("app".into(), "/p".into(), vec![]);
+        let inline = config.generate_envrc(TmuxpLocation::Home, &Default::default());
+        assert_eq!(
+            envrc_load_path(&inline).as_deref(),
+            Some("~/.tmuxp/app.yaml")
+        );
+        let detached = EnvrcOptions {
+            detach: true,
+            load_args: vec!["--log-file".into(), "x.log".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            envrc_load_path(&config.generate_envrc(TmuxpLocation::Project, &detached)).as_deref(),
+            Some("./.tmuxp.yaml")
+        );
+        assert_eq!(
+            envrc_load_path("python3 -m tmuxp load -y ./.tmuxp.yaml").as_deref(),
+            Some("./.tmuxp.yaml")
+        );
+        let native = EnvrcOptions {
+            native: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            envrc_load_path(&config.generate_envrc(TmuxpLocation::Project, &native)).as_deref(),
+            Some("./.tmuxp.yaml")
+        );
+        assert_eq!(
+            envrc_load_path("tmuxify up -d ./.tmuxp.json && echo attach").as_deref(),
+            Some("./.tmuxp.json")
+        );
+        assert_eq!(envrc_load_path("# tmuxp load ./x.yaml\nuse nix\n"), None);
+    }
+
+    #[test]
+    fn consistency_problems_name_each_mismatch() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("app");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join(".envrc"), "layout tmux\n").unwrap();
+        assert_eq!(
+            consistency_problems(&project),
+            [".envrc loads ./.tmuxp.yaml, which doesn't exist"]
+        );
+
+        fs::write(
+            project.join(".tmuxp.yml"),
+            "session_name: app\nwindows: []\n",
+        )
+        .unwrap();
+        assert!(consistency_problems(&project)[0].ends_with(&format!(
+            "found {} instead",
+            project.join(".tmuxp.yml").display()
+        )));
+
+        let home_config = dir.path().join("other.yaml");
+        fs::write(
+            &home_config,
+            format!(
+                "session_name: app\nstart_directory: {}\nwindows: []\n",
+                dir.path().display()
+            ),
+        )
+        .unwrap();
+        fs::write(
+            project.join(".envrc"),
+            format!("layout tmux {}\n", home_config.display()),
+        )
+        .unwrap();
+        let problems = consistency_problems(&project);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("has session_name 'app'"));
+        assert!(problems[1].contains("outside the project"));
+
+        fs::write(
+            &home_config,
+            format!(
+                "session_name: other\nstart_directory: {}\nwindows: []\n",
+                project.display()
+            ),
+        )
+        .unwrap();
+        assert!(consistency_problems(&project).is_empty());
+    }
+
+    #[test]
+    fn load_check_runs_tmuxp_detached_on_its_own_socket() {
+        let cmd = load_command(
+            "python3 -m tmuxp",
+            "tmuxify-check-1",
+            Path::new("/p/app.yaml"),
+        );
+        assert_eq!(cmd.get_program(), "python3");
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy()).collect();
+        assert_eq!(
+            args,
+            [
+                "-m",
+                "tmuxp",
+                "load",
+                "-y",
+                "-d",
+                "-L",
+                "tmuxify-check-1",
+                "/p/app.yaml"
+            ]
+        );
+        assert!(
+            cmd.get_envs()
+                .any(|(key, value)| key == "TMUX" && value.is_none())
+        );
+    }
+}
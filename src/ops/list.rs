@@ -0,0 +1,117 @@
+//! `tmuxify list`: every tmuxp config tmuxify knows about, with whether its
+//! session is running.
+
+use anyhow::Result;
+use console::style;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::ops::discovery::{self, Discovered};
+use crate::ops::{json, table, tmux};
+
+/// One row of `tmuxify list`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct Entry {
+    session_name: String,
+    /// `project` for a project's `.tmuxp.yaml`, `home` for tmuxp's config dirs
+    location: &'static str,
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_dir: Option<PathBuf>,
+    /// Window count, or none when the config doesn't parse
+    windows: Option<usize>,
+    running: bool,
+}
+
+fn entries(discovered: Vec<Discovered>, running: &[String]) -> Vec<Entry> {
+    discovered
+        .into_iter()
+        .map(|found| {
+            let in_project = found
+                .project_dir
+                .as_deref()
+                .is_some_and(|dir| found.path.starts_with(dir));
+            Entry {
+                running: running.contains(&found.session_name),
+                windows: found.windows,
+                location: if in_project { "project" } else { "home" },
+                session_name: found.session_name,
+                path: found.path,
+                project_dir: found.project_dir,
+            }
+        })
+        .collect()
+}
+
+/// `path` with the home directory shortened to `~`
+fn display(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(PathBuf::from)) {
+        Some(rest) => Path::new("~").join(rest).display().to_string(),
+        None => path.display().to_string(),
+    }
+}
+
+/// Print known configs as a table, or as JSON with `json`
+pub fn run(json: bool) -> Result<()> {
+    let entries = entries(discovery::discover(), &tmux::list_sessions());
+    if json {
+        print!("{}", json::to_json_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("No tmuxp configs found. Create one with: tmuxify");
+        return Ok(());
+    }
+
+    let mut table = table::Table::new(&["SESSION", "LOCATION", "WINDOWS", "STATUS", "PATH"]);
+    for entry in &entries {
+        let status = if entry.running {
+            style("running").green().to_string()
+        } else {
+            style("stopped").dim().to_string()
+        };
+        table.add_row(vec![
+            entry.session_name.clone(),
+            entry.location.to_string(),
+            entry
+                .windows
+                .map_or_else(|| style("invalid").red().to_string(), |n| n.to_string()),
+            status,
+            display(&entry.path),
+        ]);
+    }
+    print!("{}", table.render());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_report_location_windows_and_status() {
+        let project = PathBuf::from("/src/app");
+        let discovered = vec![
+            Discovered {
+                path: project.join(".tmuxp.yaml"),
+                session_name: "app".into(),
+                windows: Some(1),
+                project_dir: Some(project.clone()),
+            },
+            Discovered {
+                path: "/home/me/.tmuxp/api.yaml".into(),
+                session_name: "api".into(),
+                windows: None,
+                project_dir: None,
+            },
+        ];
+        let entries = entries(discovered, &["app".to_string()]);
+
+        assert_eq!(entries[0].location, "project");
+        assert_eq!(entries[0].windows, Some(1));
+        assert!(entries[0].running);
+        assert_eq!(entries[1].location, "home");
+        assert_eq!(entries[1].windows, None);
+        assert!(!entries[1].running);
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::model::{Config, ConfigFormat, TmuxpLocation};
+use crate::ops::{discovery, lint};
+
+/// Resolve a `--from` style reference to a tmuxp config file.
+///
+/// Accepts, in order:
+/// - a path to a YAML file
+/// - a project directory containing `.tmuxp.yaml`
+/// - a name stored as `~/.tmuxp/<name>.yaml` (or in `~/.config/tmuxp`)
+/// - the session name of a discovered config, such as a registered project's
+pub fn resolve_config_path(spec: &str) -> Result<PathBuf> {
+    let path = Path::new(spec);
+
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+
+    if path.is_dir() {
+        if let Some(local) = local_config(path) {
+            return Ok(local);
+        }
+        anyhow::bail!("No .tmuxp.yaml found in {}", path.display());
+    }
+
+    for tmuxp_dir in discovery::config_dirs() {
+        for ext in ["yaml", "yml", "json"] {
+            let candidate = tmuxp_dir.join(format!("{}.{}", spec, ext));
+            if candidate.is_file() {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    // A session name that differs from its file name, or a project's config
+    if let Some(config) = discovery::discover()
+        .into_iter()
+        .find(|config| config.session_name == spec)
+    {
+        return Ok(config.path);
+    }
+
+    anyhow::bail!(
+        "Could not find a config for '{}' (expected a file, a project directory, or ~/.tmuxp/{}.yaml)",
+        spec,
+        spec
+    )
+}
+
+/// A project's own `.tmuxp.yaml`, or the `.tmuxp.json` written with `--format json`
+pub fn local_config(project_dir: &Path) -> Option<PathBuf> {
+    let candidates = [".tmuxp.yaml", ".tmuxp.json"].map(|name| project_dir.join(name));
+    pick_config(&candidates, project_dir)
+}
+
+/// Which of a config's copies in different formats to use: the only one that
+/// exists, else the one the project's `.envrc` loads, else the newest
+pub fn pick_config(candidates: &[PathBuf], project_dir: &Path) -> Option<PathBuf> {
+    let existing: Vec<&PathBuf> = candidates.iter().filter(|path| path.is_file()).collect();
+    if existing.len() <= 1 {
+        return existing.first().map(|path| path.to_path_buf());
+    }
+    let loaded = fs::read_to_string(project_dir.join(".envrc"))
+        .ok()
+        .and_then(|content| lint::envrc_load_path(&content))
+        .map(|written| lint::expand(&written, project_dir));
+    if let Some(loaded) = loaded
+        && let Some(path) = existing.iter().find(|path| ***path == loaded)
+    {
+        return Some(path.to_path_buf());
+    }
+    existing
+        .into_iter()
+        .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .cloned()
+}
+
+/// Locate the tmuxp config generated for a project.
+///
+/// Checks the project-local `.tmuxp.yaml` (or `.json`) first, then the home
+/// config referenced by the project's `.envrc`, then `~/.tmuxp/<session_hint>.yaml`.
+pub fn locate_project_config(
+    project_dir: &Path,
+    session_hint: Option<&str>,
+) -> Result<(PathBuf, TmuxpLocation)> {
+    if let Some(local) = local_config(project_dir) {
+        return Ok((local, TmuxpLocation::Project));
+    }
+
+    let envrc_session = fs::read_to_string(project_dir.join(".envrc"))
+        .ok()
+        .and_then(|content| session_from_envrc(&content));
+
+    let candidates = envrc_session
+        .into_iter()
+        .chain(session_hint.map(str::to_string))
+        .chain(
+            project_dir
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_string),
+        );
+
+    for session in candidates {
+        let path = Config::file_path_for(&session, TmuxpLocation::Home, None)?;
+        let copies = [path.clone(), ConfigFormat::Json.apply(&path)];
+        if let Some(path) = pick_config(&copies, project_dir) {
+            return Ok((path, TmuxpLocation::Home));
+        }
+    }
+
+    anyhow::bail!(
+        "No tmuxp config found for {} (run tmuxify there first)",
+        project_dir.display()
+    )
+}
+
+/// Extract the session name from a generated `.envrc` that loads
+/// `~/.tmuxp/<name>.yaml` (or `.json`)
+pub fn session_from_envrc(content: &str) -> Option<String> {
+    let start = content.find("~/.tmuxp/")? + "~/.tmuxp/".len();
+    let rest = &content[start..];
+    let end = [".yaml", ".json"]
+        .iter()
+        .filter_map(|ext| rest.find(ext))
+        .min()?;
+    Some(rest[..end].to_string())
+}
+
+/// Whether a `.envrc` loads a tmuxp session, inline, through `layout tmux`,
+/// or with the native driver's `tmuxify up`
+pub fn envrc_loads_tmuxp(content: &str) -> bool {
+    content.contains("tmuxp load")
+        || content.contains("tmuxify up")
+        || content
+            .lines()
+            .any(|line| line.trim_start().starts_with("layout tmux"))
+}
+
+/// Load a tmuxp config file into the model
+pub fn load_config(path: &Path) -> Result<Config> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Config::from_yaml(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn resolves_project_directory_to_local_config() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join(".tmuxp.yaml");
+        fs::write(&cfg_path, "session_name: demo\nwindows: []\n").unwrap();
+
+        let resolved = resolve_config_path(&dir.path().display().to_string()).unwrap();
+        assert_eq!(resolved, cfg_path);
+
+        let cfg = load_config(&resolved).unwrap();
+        assert_eq!(cfg.session_name, "demo");
+    }
+
+    #[test]
+    fn extracts_session_from_generated_envrc() {
+        let cfg = Config::new("myapp".into(), "/tmp".into(), vec![]);
+        let envrc = cfg.generate_envrc(TmuxpLocation::Home, &Default::default());
+        assert_eq!(session_from_envrc(&envrc).as_deref(), Some("myapp"));
+        assert_eq!(session_from_envrc("tmuxp load ./.tmuxp.yaml"), None);
+        assert_eq!(
+            session_from_envrc("tmuxp load ~/.tmuxp/my.app.json").as_deref(),
+            Some("my.app")
+        );
+    }
+
+    #[test]
+    fn locates_json_project_configs() {
+        let dir = tempdir().unwrap();
+        let json = dir.path().join(".tmuxp.json");
+        fs::write(&json, "{\"session_name\": \"demo\", \"windows\": []}\n").unwrap();
+
+        let (path, location) = locate_project_config(dir.path(), None).unwrap();
+        assert_eq!(
+            (path.as_path(), location),
+            (json.as_path(), TmuxpLocation::Project)
+        );
+        assert_eq!(load_config(&json).unwrap().session_name, "demo");
+        assert_eq!(
+            resolve_config_path(&dir.path().display().to_string()).unwrap(),
+            json
+        );
+    }
+
+    #[test]
+    fn the_copy_the_envrc_loads_wins_over_a_stale_one() {
+        let dir = tempdir().unwrap();
+        let yaml = dir.path().join(".tmuxp.yaml");
+        let json = dir.path().join(".tmuxp.json");
+        fs::write(&json, "{\"session_name\": \"new\", \"windows\": []}\n").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(&yaml, "session_name: old\nwindows: []\n").unwrap();
+        assert_eq!(local_config(dir.path()), Some(yaml.clone()));
+
+        fs::write(dir.path().join(".envrc"), "tmuxp load ./.tmuxp.json\n").unwrap();
+        assert_eq!(local_config(dir.path()), Some(json.clone()));
+        assert_eq!(locate_project_config(dir.path(), None).unwrap().0, json);
+    }
+
+    #[test]
+    fn unknown_reference_is_an_error() {
+        assert!(resolve_config_path("/definitely/not/a/real/tmuxify/project").is_err());
+    }
+
+    #[test]
+    fn layout_envrc_counts_as_loading_tmuxp() {
+        let envrc = "layout tmux ~/.tmuxp/api.yaml\n";
+        assert!(envrc_loads_tmuxp(envrc));
+        assert_eq!(session_from_envrc(envrc).as_deref(), Some("api"));
+        assert!(!envrc_loads_tmuxp("layout python3\n"));
+    }
+}
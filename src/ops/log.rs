@@ -0,0 +1,163 @@
+//! Per-run debug log in `~/.local/share/tmuxify/logs/` on Linux and
+//! `~/Library/Application Support/tmuxify/logs/` on macOS.
+//!
+//! Every run gets its own file recording resolved paths, decisions and
+//! external commands; only the newest [`KEEP`] files are kept. Logging is
+//! best effort and never makes a command fail.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Number of log files kept after rotation
+const KEEP: usize = 10;
+
+/// Longest command output copied into the log
+const MAX_OUTPUT: usize = 4000;
+
+struct Log {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+static LOG: OnceLock<Log> = OnceLock::new();
+
+/// Directory holding the log files
+pub fn dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tmuxify").join("logs"))
+}
+
+/// Open this run's log file, rotate old ones, and record the command line
+pub fn init() {
+    let Some(dir) = dir() else {
+        return;
+    };
+    if fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+    let name = format!(
+        "tmuxify-{}-{}.log",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    );
+    let path = dir.join(name);
+    let Ok(file) = File::create(&path) else {
+        return;
+    };
+    rotate(&dir, KEEP);
+
+    let _ = LOG.set(Log {
+        path,
+        file: Mutex::new(file),
+    });
+    let argv: Vec<String> = std::env::args().collect();
+    debug(format!(
+        "tmuxify {} {}",
+        env!("CARGO_PKG_VERSION"),
+        argv.join(" ")
+    ));
+    if let Ok(cwd) = std::env::current_dir() {
+        debug(format!("cwd: {}", cwd.display()));
+    }
+}
+
+/// Path of this run's log file, if logging is active
+pub fn path() -> Option<&'static Path> {
+    LOG.get().map(|log| log.path.as_path())
+}
+
+/// Append a timestamped line to the log
+pub fn debug(message: impl AsRef<str>) {
+    let Some(log) = LOG.get() else {
+        return;
+    };
+    if let Ok(mut file) = log.file.lock() {
+        let _ = writeln!(
+            file,
+            "{} {}",
+            chrono::Local::now().format("%H:%M:%S%.3f"),
+            message.as_ref()
+        );
+    }
+}
+
+/// Log captured command output under a label, truncated to [`MAX_OUTPUT`] bytes
+pub fn output(label: &str, text: &str) {
+    let text = text.trim_end();
+    if text.is_empty() {
+        return;
+    }
+    let mut end = text.len().min(MAX_OUTPUT);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let suffix = if end < text.len() {
+        "\n  ... (truncated)"
+    } else {
+        ""
+    };
+    debug(format!(
+        "{}:\n  {}{}",
+        label,
+        text[..end].replace('\n', "\n  "),
+        suffix
+    ));
+}
+
+/// Delete all but the newest `keep` log files in `dir`
+fn rotate(dir: &Path, keep: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("tmuxify-") && n.ends_with(".log"))
+        })
+        .collect();
+    // Names start with a sortable timestamp, so the oldest sort first
+    logs.sort();
+    let excess = logs.len().saturating_sub(keep);
+    for old in &logs[..excess] {
+        let _ = fs::remove_file(old);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn rotation_keeps_the_newest_logs() {
+        let dir = tempdir().unwrap();
+        for day in 1..=5 {
+            fs::write(
+                dir.path()
+                    .join(format!("tmuxify-2024010{}-120000-1.log", day)),
+                "",
+            )
+            .unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        rotate(dir.path(), 2);
+
+        let mut left: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "notes.txt",
+                "tmuxify-20240104-120000-1.log",
+                "tmuxify-20240105-120000-1.log"
+            ]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Window-by-window merge of an existing config with a newly built one.
+
+use crate::model::Window;
+
+/// What to do with one window when merging
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeChoice {
+    /// Keep the window from the existing config
+    Existing,
+    /// Take the window from the new config
+    New,
+    /// Leave the window out
+    Drop,
+}
+
+/// A window name with its version in the existing and the new config
+#[derive(Debug, Clone)]
+pub struct WindowPair {
+    pub existing: Option<Window>,
+    pub new: Option<Window>,
+}
+
+impl WindowPair {
+    /// The window's name, or "unnamed"
+    pub fn label(&self) -> String {
+        self.existing
+            .iter()
+            .chain(&self.new)
+            .find_map(|w| w.window_name.clone())
+            .unwrap_or_else(|| "unnamed".to_string())
+    }
+
+    /// Whether both configs have the window with the same content
+    pub fn unchanged(&self) -> bool {
+        match (&self.existing, &self.new) {
+            (Some(a), Some(b)) => serde_yaml::to_string(a).ok() == serde_yaml::to_string(b).ok(),
+            _ => false,
+        }
+    }
+
+    /// The choices that make sense for this pair, in menu order
+    pub fn choices(&self) -> Vec<MergeChoice> {
+        let mut choices = Vec::new();
+        if self.existing.is_some() {
+            choices.push(MergeChoice::Existing);
+        }
+        if self.new.is_some() {
+            choices.push(MergeChoice::New);
+        }
+        choices.push(MergeChoice::Drop);
+        choices
+    }
+
+    /// The window kept by `choice`, if any
+    pub fn resolve(self, choice: MergeChoice) -> Option<Window> {
+        match choice {
+            MergeChoice::Existing => self.existing,
+            MergeChoice::New => self.new,
+            MergeChoice::Drop => None,
+        }
+    }
+}
+
+/// Pair windows by name: existing windows in their order, each with the
+/// new window of the same name, then the new windows left over. Unnamed
+/// windows are never paired.
+pub fn pair_windows(existing: Vec<Window>, new: Vec<Window>) -> Vec<WindowPair> {
+    let mut new: Vec<Option<Window>> = new.into_iter().map(Some).collect();
+    let mut pairs: Vec<WindowPair> = existing
+        .into_iter()
+        .map(|window| {
+            let matched = window.window_name.as_ref().and_then(|name| {
+                new.iter_mut()
+                    .find(|w| {
+                        w.as_ref()
+                            .is_some_and(|w| w.window_name.as_ref() == Some(name))
+                    })?
+                    .take()
+            });
+            WindowPair {
+                existing: Some(window),
+                new: matched,
+            }
+        })
+        .collect();
+    pairs.extend(new.into_iter().flatten().map(|window| WindowPair {
+        existing: None,
+        new: Some(window),
+    }));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Pane;
+
+    fn window(name: &str, command: &str) -> Window {
+        Window::new(
+            Some(name.into()),
+            None,
+            vec![Pane::new(vec![command.into()])],
+        )
+    }
+
+    #[test]
+    fn windows_pair_by_name_and_resolve_per_choice() {
+        let existing = vec![window("editor", "vim"), window("logs", "tail -f log")];
+        let new = vec![
+            window("server", "npm run dev"),
+            window("editor", "nvim"),
+            window("logs", "tail -f log"),
+        ];
+        let pairs = pair_windows(existing, new);
+
+        let labels: Vec<String> = pairs.iter().map(WindowPair::label).collect();
+        assert_eq!(labels, ["editor", "logs", "server"]);
+        assert!(!pairs[0].unchanged());
+        assert!(pairs[1].unchanged());
+        assert_eq!(pairs[2].choices(), [MergeChoice::New, MergeChoice::Drop]);
+
+        let merged: Vec<Window> = pairs
+            .into_iter()
+            .zip([MergeChoice::New, MergeChoice::Existing, MergeChoice::Drop])
+            .filter_map(|(pair, choice)| pair.resolve(choice))
+            .collect();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].panes[0].shell_command, ["nvim"]);
+    }
+}
@@ -1,4 +1,56 @@
+pub mod apply;
+pub mod archetype;
+pub mod audit;
+pub mod backups;
+pub mod clone;
+pub mod complete;
+pub mod conflicts;
+pub mod convert;
+pub mod delete;
+pub mod detect;
+pub mod diff;
+pub mod discovery;
 pub mod doctor;
+pub mod edit;
+pub mod explain;
+pub mod export;
+pub mod freeze;
+pub mod generate;
+pub mod highlight;
+pub mod history;
+pub mod hook;
+pub mod import;
+pub mod input_history;
 pub mod interactive;
+pub mod interrupt;
+pub mod json;
+pub mod layouts;
+pub mod lint;
+pub mod list;
+pub mod load;
+pub mod log;
+pub mod merge;
+pub mod open;
+pub mod overlay;
+pub mod picker;
+pub mod presets;
+pub mod process;
+pub mod prune;
+pub mod rename;
+pub mod resurrect;
+pub mod secrets;
+pub mod share;
+pub mod shell;
+pub mod show;
+pub mod sync;
+pub mod table;
+pub mod template;
+pub mod theme;
+pub mod tmux;
+pub mod toml;
+pub mod tui;
+pub mod update;
 pub mod validate;
+pub mod version;
 pub mod write;
+pub mod zoxide;
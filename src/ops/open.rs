@@ -0,0 +1,246 @@
+use anyhow::Result;
+use console::style;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::Args;
+use crate::model::{Config, Driver, Settings};
+use crate::ops::process::{self, Runner, SystemRunner};
+use crate::ops::{discovery, history, load, picker, tmux, validate, zoxide};
+
+/// Start or attach to a project's session, found by path, registered
+/// project name, or zoxide's database
+pub fn run(args: &Args, query: Option<&str>) -> Result<()> {
+    let project_dir = match query {
+        Some(query) => find_project(query)?,
+        None => args.project_dir()?,
+    };
+    let located = load::locate_project_config(&project_dir, args.session.as_deref());
+    // Outside a project with no query, offer the registered projects instead
+    let (project_dir, (config_path, _)) = match located {
+        Err(_) if query.is_none() && args.project.is_none() && validate::is_interactive() => {
+            let dir = pick_project()?;
+            let config = load::locate_project_config(&dir, None)?;
+            (dir, config)
+        }
+        located => (project_dir, located?),
+    };
+    let config = load::load_config(&config_path)?;
+    start_session(
+        &SystemRunner::new(args.dry_run),
+        &project_dir,
+        &config,
+        &config_path,
+        false,
+    )
+}
+
+/// Attach to (or switch to) the config's session when it is running, else
+/// load it with the configured driver, attached or `detached`
+pub fn start_session(
+    runner: &dyn Runner,
+    project_dir: &Path,
+    config: &Config,
+    config_path: &Path,
+    detached: bool,
+) -> Result<()> {
+    if tmux::has_session(&config.session_name) {
+        if detached {
+            println!(
+                "{} Session {} is already running",
+                style("✓").green().bold(),
+                style(&config.session_name).bold()
+            );
+            return Ok(());
+        }
+        return join_session(runner, &config.session_name);
+    }
+    if Settings::load()?.driver == Driver::Native {
+        return start_native(runner, config, config_path, detached);
+    }
+
+    let tmuxp = validate::resolve_tmuxp(Some(project_dir)).ok_or_else(|| {
+        anyhow::anyhow!("tmuxp is not installed; run 'tmuxify doctor' for install hints")
+    })?;
+    let mut parts = tmuxp.command.split_whitespace();
+    let program = parts.next().unwrap_or("tmuxp");
+    println!(
+        "{} Loading {} from {}",
+        style("→").cyan(),
+        style(&config.session_name).bold(),
+        config_path.display()
+    );
+    let mut load = Command::new(program);
+    load.args(parts)
+        .args(["load", "-y"])
+        .args(detached.then_some("-d"))
+        .args(Settings::load()?.tmuxp_load_args)
+        .arg(config_path)
+        .current_dir(project_dir);
+    if detached {
+        runner.run(&mut load, process::SLOW_TIMEOUT).map(|_| ())
+    } else {
+        runner.run_attached(&mut load)
+    }
+}
+
+/// Attach to a running session, or switch to it from inside tmux
+fn join_session(runner: &dyn Runner, session: &str) -> Result<()> {
+    if validate::is_inside_tmux() {
+        return tmux::run(runner, &["switch-client", "-t", &format!("={}", session)]);
+    }
+    tmux::attach(runner, session)
+}
+
+/// Build the session with tmux commands (the native driver), then attach
+/// unless `detached`
+fn start_native(
+    runner: &dyn Runner,
+    config: &Config,
+    config_path: &Path,
+    detached: bool,
+) -> Result<()> {
+    println!(
+        "{} Starting {} from {}",
+        style("→").cyan(),
+        style(&config.session_name).bold(),
+        config_path.display()
+    );
+    tmux::build_session(runner, config, validate::terminal_size())?;
+    if detached {
+        if runner.is_dry_run() {
+            return Ok(());
+        }
+        println!(
+            "{} Session {} is running; attach with: tmux attach -t {}",
+            style("✓").green().bold(),
+            style(&config.session_name).bold(),
+            config.session_name
+        );
+        return Ok(());
+    }
+    join_session(runner, &config.session_name)
+}
+
+/// `tmuxify up`: start (or attach to) a session with the native driver,
+/// from `config_path` or the project's config
+pub fn up(args: &Args, config_path: Option<&Path>, detached: bool) -> Result<()> {
+    let config_path = match config_path {
+        Some(path) => path.to_path_buf(),
+        None => load::locate_project_config(&args.project_dir()?, args.session.as_deref())?.0,
+    };
+    let config = load::load_config(&config_path)?;
+    let runner = SystemRunner::new(args.dry_run);
+    if tmux::has_session(&config.session_name) {
+        if detached {
+            println!(
+                "{} Session {} is already running",
+                style("✓").green().bold(),
+                style(&config.session_name).bold()
+            );
+            return Ok(());
+        }
+        return join_session(&runner, &config.session_name);
+    }
+    start_native(&runner, &config, &config_path, detached)
+}
+
+/// Resolve a query to a project directory: an existing path first, then a
+/// project tmuxify has written configs for, then zoxide
+fn find_project(query: &str) -> Result<PathBuf> {
+    let path = validate::resolve_user_path(Path::new(query));
+    if path.is_dir() {
+        return Ok(path);
+    }
+
+    let registered = history::projects()?;
+    if let Some(dir) = match_registered(&registered, query) {
+        return Ok(dir);
+    }
+
+    // A project whose session is named differently from its directory
+    if let Some(dir) = discovery::discover()
+        .into_iter()
+        .find(|config| config.session_name == query)
+        .and_then(|config| config.project_dir)
+    {
+        return Ok(dir);
+    }
+
+    if let Some(dir) = zoxide::query(query) {
+        println!(
+            "{} {} is not a tmuxify project; using zoxide's match {}",
+            style("→").cyan(),
+            query,
+            dir.display()
+        );
+        return Ok(dir);
+    }
+
+    let hint = if zoxide::is_available() {
+        ""
+    } else {
+        " (install zoxide to also search directories you've visited)"
+    };
+    anyhow::bail!("No project matches '{}'{}", query, hint)
+}
+
+/// Choose among registered projects that still exist, previewing their configs
+fn pick_project() -> Result<PathBuf> {
+    let projects: Vec<(PathBuf, PathBuf)> = discovery::discover()
+        .into_iter()
+        .filter_map(|config| Some((config.project_dir?, config.path)))
+        .collect();
+    if projects.is_empty() {
+        anyhow::bail!("No tmuxp config here and no registered projects; run tmuxify first");
+    }
+
+    let items: Vec<picker::Item> = projects
+        .iter()
+        .map(|(dir, config)| {
+            picker::Item::new(dir.display().to_string()).with_preview(config.clone())
+        })
+        .collect();
+    let index = picker::select("Open project", &items)?;
+    Ok(projects[index].0.clone())
+}
+
+/// A registered project whose directory name equals `query`, else the most
+/// recently registered one whose path contains it
+fn match_registered(projects: &[String], query: &str) -> Option<PathBuf> {
+    let existing = || {
+        projects
+            .iter()
+            .rev()
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_dir())
+    };
+    existing()
+        .find(|dir| dir.file_name().is_some_and(|name| name == query))
+        .or_else(|| existing().find(|dir| dir.to_string_lossy().contains(query)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn registered_projects_match_by_name_before_substring() {
+        let root = tempdir().unwrap();
+        let api = root.path().join("api");
+        let api_docs = root.path().join("api-docs");
+        let gone = root.path().join("gone");
+        fs::create_dir(&api).unwrap();
+        fs::create_dir(&api_docs).unwrap();
+        let projects: Vec<String> = [&api, &api_docs, &gone]
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+
+        assert_eq!(match_registered(&projects, "api"), Some(api));
+        assert_eq!(match_registered(&projects, "docs"), Some(api_docs));
+        assert_eq!(match_registered(&projects, "gone"), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Personal overrides: `.tmuxp.local.yaml` merged over a project's shared
+//! config when the session loads.
+
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::ops::load;
+
+/// Uncommitted overlay merged over the project's config at load time
+pub const LOCAL_CONFIG: &str = ".tmuxp.local.yaml";
+
+/// Merge `overlay` into `base`: mappings merge key by key, `windows` merge by
+/// `window_name` (unmatched overlay windows are appended), and any other
+/// value in the overlay replaces the base's
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) if key.as_str() == Some("windows") => {
+                        merge_windows(existing, value)
+                    }
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn merge_windows(base: &mut Value, overlay: Value) {
+    let (Value::Sequence(base), Value::Sequence(overlay)) = (&mut *base, &overlay) else {
+        *base = overlay;
+        return;
+    };
+    for window in overlay {
+        let name = window.get("window_name").cloned();
+        let existing = name
+            .as_ref()
+            .and_then(|name| base.iter_mut().find(|w| w.get("window_name") == Some(name)));
+        match existing {
+            Some(existing) => merge(existing, window.clone()),
+            None => base.push(window.clone()),
+        }
+    }
+}
+
+/// Where the merged config for `session` is written. Path separators and
+/// leading dots are replaced so the name can't leave the cache directory.
+fn merged_path(session: &str) -> Option<PathBuf> {
+    let name: String = session
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+        .collect();
+    let name = match name.trim_start_matches('.') {
+        "" => "session".to_string(),
+        rest if rest.len() < name.len() => format!("_{}", rest),
+        _ => name,
+    };
+    dirs::cache_dir().map(|dir| {
+        dir.join("tmuxify")
+            .join("overlay")
+            .join(format!("{}.yaml", name))
+    })
+}
+
+/// Merge the project's `.tmuxp.local.yaml` over `config_path`. Both stay
+/// plain YAML, so tmuxp shorthands the model doesn't know survive.
+pub fn merged(config_path: &Path, project_dir: &Path) -> Result<Value> {
+    let read = |path: &Path| -> Result<Value> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    };
+    let mut base = read(config_path)?;
+    merge(&mut base, read(&project_dir.join(LOCAL_CONFIG))?);
+
+    // tmuxp resolves a relative start_directory and a `.`-prefixed
+    // before_script against the config's directory, which for the merged
+    // file is the cache
+    if let Value::Mapping(map) = &mut base {
+        let config_dir = config_path.parent().unwrap_or(project_dir);
+        let start = map
+            .get("start_directory")
+            .and_then(Value::as_str)
+            .unwrap_or(".");
+        if Path::new(start).is_relative() && !start.starts_with(['~', '$']) {
+            let dir = config_dir.join(start);
+            map.insert("start_directory".into(), dir.display().to_string().into());
+        }
+        let script = map.get("before_script").and_then(Value::as_str);
+        if let Some(script) = script.filter(|script| script.starts_with('.')) {
+            let script = config_dir.join(script);
+            map.insert("before_script".into(), script.display().to_string().into());
+        }
+    }
+    Ok(base)
+}
+
+/// Write the merged config to tmuxify's cache and print its path, for
+/// `tmuxp load "$(tmuxify overlay CONFIG)"` in `.envrc`
+pub fn run(args: &Args, config: &str) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let config_path = load::resolve_config_path(config)?;
+    let config = merged(&config_path, &project_dir)?;
+    let session = config["session_name"].as_str().unwrap_or("session");
+    let path = merged_path(session)
+        .ok_or_else(|| anyhow::anyhow!("Could not determine the cache directory"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, serde_yaml::to_string(&config)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn local_config_merges_over_the_shared_one() {
+        let dir = tempdir().unwrap();
+        let shared = dir.path().join(".tmuxp.yaml");
+        fs::write(
+            &shared,
+            "session_name: app\n\
+             before_script: ./bin/setup\n\
+             windows:\n\
+             - window_name: editor\n  panes:\n  - vim\n\
+             - window_name: server\n  layout: tiled\n  panes:\n  - npm run dev\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(LOCAL_CONFIG),
+            "windows:\n\
+             - window_name: editor\n  panes:\n  - nvim\n\
+             - window_name: notes\n  panes:\n  - less notes.md\n",
+        )
+        .unwrap();
+
+        let merged = merged(&shared, dir.path()).unwrap();
+        assert_eq!(merged["session_name"], "app");
+        assert_eq!(
+            merged["start_directory"],
+            dir.path().join(".").display().to_string().as_str()
+        );
+        assert_eq!(
+            merged["before_script"],
+            dir.path()
+                .join("./bin/setup")
+                .display()
+                .to_string()
+                .as_str()
+        );
+        let windows = merged["windows"].as_sequence().unwrap();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0]["panes"][0], "nvim");
+        assert_eq!(windows[1]["layout"], "tiled");
+        assert_eq!(windows[2]["window_name"], "notes");
+    }
+
+    #[test]
+    fn merged_path_stays_in_the_cache() {
+        let Some(cache) = dirs::cache_dir() else {
+            return;
+        };
+        let dir = cache.join("tmuxify").join("overlay");
+        for session in ["app", "../../.bashrc", "..", "a/b", ".hidden", ""] {
+            let path = merged_path(session).unwrap();
+            assert_eq!(path.parent(), Some(dir.as_path()), "{}", session);
+        }
+        assert_eq!(merged_path("a/b").unwrap(), dir.join("a_b.yaml"));
+        assert_eq!(merged_path("..").unwrap(), dir.join("session.yaml"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Selection prompts for long lists, delegated to fzf or skim when the
+//! `fuzzy_finder` setting asks for it and falling back to dialoguer otherwise.
+
+use anyhow::{Context, Result};
+use dialoguer::{MultiSelect, Select};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::model::Settings;
+use crate::ops::{log, theme, validate};
+
+/// Finders tried, in order, for `fuzzy_finder: auto`
+const FINDERS: &[&str] = &["fzf", "sk"];
+
+/// A list entry, optionally previewed by showing a file's contents
+pub struct Item {
+    pub label: String,
+    pub preview: Option<PathBuf>,
+}
+
+impl Item {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            preview: None,
+        }
+    }
+
+    pub fn with_preview(mut self, path: PathBuf) -> Self {
+        self.preview = Some(path);
+        self
+    }
+}
+
+/// Pick one item, returning its index
+pub fn select(prompt: &str, items: &[Item]) -> Result<usize> {
+    if let Some(finder) = finder() {
+        let picked = run_finder(finder, prompt, items, false)?;
+        return picked
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Nothing selected"));
+    }
+    let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+    Ok(Select::with_theme(theme::get())
+        .with_prompt(prompt)
+        .items(&labels)
+        .default(0)
+        .interact()?)
+}
+
+/// Pick any number of items, returning their indexes; `defaults` pre-selects
+/// items in the built-in prompt
+pub fn multi_select(prompt: &str, items: &[Item], defaults: &[bool]) -> Result<Vec<usize>> {
+    if let Some(finder) = finder() {
+        return run_finder(finder, prompt, items, true);
+    }
+    let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+    Ok(MultiSelect::with_theme(theme::get())
+        .with_prompt(format!("{} (space to toggle, enter to confirm)", prompt))
+        .items(&labels)
+        .defaults(defaults)
+        .interact()?)
+}
+
+/// The configured finder binary, if it is installed
+fn finder() -> Option<&'static str> {
+    let setting = Settings::load().ok()?.fuzzy_finder?;
+    let found = match setting.as_str() {
+        "auto" => FINDERS.iter().copied().find(|f| validate::has_binary(f)),
+        name => FINDERS
+            .iter()
+            .copied()
+            .find(|f| *f == name)
+            .filter(|f| validate::has_binary(f)),
+    };
+    if found.is_none() {
+        log::debug(format!(
+            "fuzzy_finder '{}' unavailable, using built-in prompts",
+            setting
+        ));
+    }
+    found
+}
+
+/// One input line per item: index, preview path, then the visible label
+fn finder_input(items: &[Item]) -> String {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let preview = item
+                .preview
+                .as_ref()
+                .map(|p| p.to_string_lossy().replace('\t', " "))
+                .unwrap_or_default();
+            format!("{}\t{}\t{}\n", i, preview, item.label.replace('\n', " "))
+        })
+        .collect()
+}
+
+/// Indexes from the lines a finder printed
+fn parse_selection(output: &str) -> Vec<usize> {
+    output
+        .lines()
+        .filter_map(|line| line.split('\t').next()?.parse().ok())
+        .collect()
+}
+
+fn run_finder(finder: &str, prompt: &str, items: &[Item], multi: bool) -> Result<Vec<usize>> {
+    let mut cmd = Command::new(finder);
+    cmd.args([
+        "--delimiter",
+        "\t",
+        "--with-nth",
+        "3..",
+        "--height",
+        "40%",
+        "--reverse",
+    ])
+    .arg(format!("--prompt={} > ", prompt));
+    if multi {
+        cmd.args(["--multi", "--header", "Tab to toggle, Enter to confirm"]);
+    }
+    if items.iter().any(|i| i.preview.is_some()) {
+        cmd.args(["--preview", "cat -- {2}", "--preview-window", "right:60%"]);
+    }
+    log::debug(format!(
+        "running {} for '{}' ({} items)",
+        finder,
+        prompt,
+        items.len()
+    ));
+
+    // The finder draws on the terminal itself; only the list and result are piped
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", finder))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The finder may exit before reading everything; that's not an error
+        let _ = stdin.write_all(finder_input(items).as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to wait for {}", finder))?;
+
+    match output.status.code() {
+        Some(0) => Ok(parse_selection(&String::from_utf8_lossy(&output.stdout))),
+        // No match
+        Some(1) => Ok(Vec::new()),
+        // Esc or Ctrl-C
+        Some(130) => anyhow::bail!("Selection cancelled"),
+        _ => anyhow::bail!("{} exited with {}", finder, output.status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finder_lines_round_trip_to_indexes() {
+        let items = vec![
+            Item::new("api (stale)").with_preview(PathBuf::from("/home/me/.tmuxp/api.yaml")),
+            Item::new("web"),
+        ];
+        let input = finder_input(&items);
+        assert_eq!(
+            input,
+            "0\t/home/me/.tmuxp/api.yaml\tapi (stale)\n1\t\tweb\n"
+        );
+
+        // fzf prints the selected input lines unchanged
+        let picked: String = input.lines().rev().map(|l| format!("{}\n", l)).collect();
+        assert_eq!(parse_selection(&picked), [1, 0]);
+    }
+}
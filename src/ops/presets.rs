@@ -0,0 +1,113 @@
+//! Curated starting configs for common ecosystems, offered by the wizard and
+//! `--preset`.
+
+use std::path::Path;
+
+use crate::model::{Pane, Window, WindowLayout};
+use crate::ops::archetype;
+use crate::ops::detect::ProjectType;
+
+/// A built-in preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Preset {
+    /// Editor, `cargo watch -x test`, and a shell
+    Rust,
+    /// Editor, the dev server, and a shell
+    Node,
+    /// Editor, a pytest watcher, and a shell
+    Python,
+    /// Editor, a test watcher, and a shell
+    Go,
+}
+
+impl Preset {
+    /// Get all presets as a list
+    pub fn all() -> &'static [Preset] {
+        &[Preset::Rust, Preset::Node, Preset::Python, Preset::Go]
+    }
+
+    /// The preset for a detected project type, if there is one
+    pub fn for_project_type(project_type: ProjectType) -> Option<Preset> {
+        Preset::all()
+            .iter()
+            .copied()
+            .find(|preset| preset.project_type() == project_type)
+    }
+
+    fn project_type(&self) -> ProjectType {
+        match self {
+            Preset::Rust => ProjectType::Rust,
+            Preset::Node => ProjectType::Node,
+            Preset::Python => ProjectType::Python,
+            Preset::Go => ProjectType::Go,
+        }
+    }
+
+    /// The preset's windows, with commands tailored to the project
+    pub fn windows(&self, project_dir: &Path) -> Vec<Window> {
+        let project_type = self.project_type();
+        // The dev server is what a Node project watches; the others watch tests
+        let watcher = match self {
+            Preset::Node => archetype::server_command(project_dir, project_type),
+            _ => archetype::test_watch_command(project_dir, project_type),
+        };
+        vec![Window::new(
+            Some("dev".to_string()),
+            Some(WindowLayout::MainVertical),
+            vec![
+                Pane::new(vec!["${EDITOR:-vim} .".to_string()]),
+                Pane::new(watcher.into_iter().collect()),
+                Pane::empty(),
+            ],
+        )]
+    }
+
+    /// One-line description listing the preset's panes
+    pub fn describe(&self, project_dir: &Path) -> String {
+        let commands: Vec<String> = self
+            .windows(project_dir)
+            .iter()
+            .flat_map(|w| &w.panes)
+            .map(|p| match p.shell_command.first() {
+                Some(command) => command.cmd.clone(),
+                None => "shell".to_string(),
+            })
+            .collect();
+        format!("{}: {}", self, commands.join(", "))
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Preset::Rust => write!(f, "rust"),
+            Preset::Node => write!(f, "node"),
+            Preset::Python => write!(f, "python"),
+            Preset::Go => write!(f, "go"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_preset_has_editor_watcher_and_shell() {
+        let windows = Preset::Rust.windows(Path::new("/nonexistent"));
+        let commands: Vec<Vec<String>> = windows[0].panes.iter().map(|p| p.commands()).collect();
+        assert_eq!(
+            commands,
+            [
+                vec!["${EDITOR:-vim} .".to_string()],
+                vec!["cargo watch -x test".to_string()],
+                vec![]
+            ]
+        );
+        assert_eq!(
+            Preset::for_project_type(ProjectType::Node),
+            Some(Preset::Node)
+        );
+        assert_eq!(Preset::for_project_type(ProjectType::Unknown), None);
+    }
+}
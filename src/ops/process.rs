@@ -0,0 +1,394 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::ops::{interrupt, log};
+
+/// Timeout for quick queries (tmux options, version probes)
+pub const QUICK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timeout for slower operations (downloads, archive extraction)
+pub const SLOW_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How often a running child is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Captured result of a finished command
+#[derive(Debug)]
+pub struct Output {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+}
+
+/// Run a command with a timeout, capturing stdout and stderr.
+///
+/// Fails if the program can't be started or doesn't finish in time; a
+/// non-zero exit is returned as an `Output` for the caller to inspect.
+pub fn output(cmd: &mut Command, timeout: Duration) -> Result<Output> {
+    execute(cmd, timeout, None, Stdio::null())
+}
+
+/// Like [`output`], but passes each stderr line to `on_stderr` as it arrives
+/// and stops the child if the user presses Ctrl-C.
+pub fn stream(
+    cmd: &mut Command,
+    timeout: Duration,
+    on_stderr: &mut dyn FnMut(&str),
+) -> Result<Output> {
+    let _guard = interrupt::Guard::install();
+    execute(cmd, timeout, Some(on_stderr), Stdio::null())
+}
+
+/// Run a command with a timeout and return its stdout, failing with its
+/// stderr on a non-zero exit
+pub fn run(cmd: &mut Command, timeout: Duration) -> Result<String> {
+    let description = describe(cmd);
+    checked(&description, output(cmd, timeout)?)
+}
+
+/// Like [`run`], but with `input` as the command's stdin
+pub fn run_with_input(cmd: &mut Command, input: File, timeout: Duration) -> Result<String> {
+    let description = describe(cmd);
+    checked(&description, execute(cmd, timeout, None, input.into())?)
+}
+
+fn execute(
+    cmd: &mut Command,
+    timeout: Duration,
+    mut on_stderr: Option<&mut dyn FnMut(&str)>,
+    stdin: Stdio,
+) -> Result<Output> {
+    let description = describe(cmd);
+    let cancellable = on_stderr.is_some();
+    match cmd.get_current_dir() {
+        Some(dir) => log::debug(format!("run: {} (in {})", description, dir.display())),
+        None => log::debug(format!("run: {}", description)),
+    }
+    let mut child = cmd
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                anyhow::anyhow!("Failed to run `{}`: program not found on PATH", description)
+            }
+            _ => anyhow::anyhow!("Failed to run `{}`: {}", description, e),
+        })?;
+
+    // Drain both pipes on threads so a chatty child can't block on a full pipe
+    let stdout = read_pipe(child.stdout.take());
+    let (lines_tx, lines) = mpsc::channel();
+    let stderr = read_lines(child.stderr.take(), lines_tx);
+    let mut forward = |lines: &mpsc::Receiver<String>| {
+        if let Some(callback) = on_stderr.as_mut() {
+            lines.try_iter().for_each(|line| callback(&line));
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        forward(&lines);
+        // Checked first: Ctrl-C also reaches the child, which may exit on its own
+        if cancellable && interrupt::interrupted() {
+            let _ = child.kill();
+            let _ = child.wait();
+            log::debug(format!("cancelled: {}", description));
+            anyhow::bail!("`{}` was cancelled", description);
+        }
+        if let Some(status) = child
+            .try_wait()
+            .with_context(|| format!("Failed to wait for `{}`", description))?
+        {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            log::debug(format!(
+                "timed out after {}s: {}",
+                timeout.as_secs(),
+                description
+            ));
+            anyhow::bail!(
+                "`{}` did not finish within {}s and was stopped",
+                description,
+                timeout.as_secs()
+            );
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    forward(&lines);
+    log::debug(format!("exit: {} ({})", description, status));
+    log::output("stdout", &stdout);
+    log::output("stderr", &stderr);
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Stdout of a finished command, or an error with its stderr on a non-zero exit
+fn checked(description: &str, output: Output) -> Result<String> {
+    if !output.success() {
+        let stderr = output.stderr.trim();
+        anyhow::bail!(
+            "`{}` failed ({}){}",
+            description,
+            output.status,
+            if stderr.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", stderr)
+            }
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Runs commands with side effects (direnv allow, tmux session changes) so
+/// `--dry-run` can print them instead and tests can record them
+pub trait Runner {
+    /// Run a command to completion with a timeout, returning its stdout
+    fn run(&self, cmd: &mut Command, timeout: Duration) -> Result<String>;
+
+    /// Like [`Runner::run`], passing stderr lines to `on_stderr` as they arrive;
+    /// Ctrl-C cancels the command
+    fn run_streaming(
+        &self,
+        cmd: &mut Command,
+        timeout: Duration,
+        on_stderr: &mut dyn FnMut(&str),
+    ) -> Result<String>;
+
+    /// Run a command attached to the terminal (e.g. `tmux attach`)
+    fn run_attached(&self, cmd: &mut Command) -> Result<()>;
+
+    /// Whether commands are only printed
+    fn is_dry_run(&self) -> bool;
+}
+
+/// Runs commands for real, or prints them under `--dry-run`
+pub struct SystemRunner {
+    dry_run: bool,
+}
+
+impl SystemRunner {
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run }
+    }
+
+    fn print(cmd: &Command) {
+        log::debug(format!("dry run, skipped: {}", describe(cmd)));
+        match cmd.get_current_dir() {
+            Some(dir) => println!(
+                "[DRY RUN] Would run: {} (in {})",
+                describe(cmd),
+                dir.display()
+            ),
+            None => println!("[DRY RUN] Would run: {}", describe(cmd)),
+        }
+    }
+}
+
+impl Runner for SystemRunner {
+    fn run(&self, cmd: &mut Command, timeout: Duration) -> Result<String> {
+        if self.dry_run {
+            Self::print(cmd);
+            return Ok(String::new());
+        }
+        run(cmd, timeout)
+    }
+
+    fn run_streaming(
+        &self,
+        cmd: &mut Command,
+        timeout: Duration,
+        on_stderr: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        if self.dry_run {
+            Self::print(cmd);
+            return Ok(String::new());
+        }
+        let description = describe(cmd);
+        checked(&description, stream(cmd, timeout, on_stderr)?)
+    }
+
+    fn run_attached(&self, cmd: &mut Command) -> Result<()> {
+        if self.dry_run {
+            Self::print(cmd);
+            return Ok(());
+        }
+        let description = describe(cmd);
+        log::debug(format!("run attached: {}", description));
+        let status = cmd
+            .status()
+            .with_context(|| format!("Failed to run `{}`", description))?;
+        if !status.success() {
+            anyhow::bail!("`{}` exited with {}", description, status);
+        }
+        Ok(())
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
+
+/// Records commands instead of running them
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingRunner {
+    pub commands: std::cell::RefCell<Vec<String>>,
+}
+
+#[cfg(test)]
+impl Runner for RecordingRunner {
+    fn run(&self, cmd: &mut Command, _timeout: Duration) -> Result<String> {
+        self.commands.borrow_mut().push(describe(cmd));
+        Ok(String::new())
+    }
+
+    fn run_streaming(
+        &self,
+        cmd: &mut Command,
+        timeout: Duration,
+        _on_stderr: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        self.run(cmd, timeout)
+    }
+
+    fn run_attached(&self, cmd: &mut Command) -> Result<()> {
+        self.commands.borrow_mut().push(describe(cmd));
+        Ok(())
+    }
+
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+}
+
+/// Program and arguments as a single line, for error messages
+fn describe(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| part.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Read a pipe line by line, sending each line as it arrives and returning the whole text
+fn read_lines<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    lines: mpsc::Sender<String>,
+) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut all = String::new();
+        let Some(pipe) = pipe else {
+            return all;
+        };
+        let mut reader = BufReader::new(pipe);
+        let mut buf = Vec::new();
+        while reader.read_until(b'\n', &mut buf).is_ok_and(|n| n > 0) {
+            let line = String::from_utf8_lossy(&buf).into_owned();
+            let _ = lines.send(line.trim_end_matches(['\r', '\n']).to_string());
+            all.push_str(&line);
+            buf.clear();
+        }
+        all
+    })
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_output_and_reports_failures() {
+        let out = run(Command::new("sh").args(["-c", "echo hello"]), QUICK_TIMEOUT).unwrap();
+        assert_eq!(out.trim(), "hello");
+
+        let err = run(
+            Command::new("sh").args(["-c", "echo broken >&2; exit 3"]),
+            QUICK_TIMEOUT,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("broken"), "{}", err);
+        assert!(err.contains("sh -c"), "{}", err);
+    }
+
+    #[test]
+    fn stops_commands_that_hang() {
+        let started = Instant::now();
+        let err = output(
+            Command::new("sh").args(["-c", "sleep 5"]),
+            Duration::from_millis(100),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("did not finish"));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn streams_stderr_lines_as_they_arrive() {
+        let mut seen = Vec::new();
+        let out = stream(
+            Command::new("sh").args(["-c", "echo one >&2; echo two >&2; echo done"]),
+            QUICK_TIMEOUT,
+            &mut |line| seen.push(line.to_string()),
+        )
+        .unwrap();
+        assert_eq!(seen, ["one", "two"]);
+        assert_eq!(out.stderr, "one\ntwo\n");
+        assert_eq!(out.stdout.trim(), "done");
+    }
+
+    #[test]
+    fn dry_run_skips_execution() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let runner = SystemRunner::new(true);
+        runner
+            .run(
+                Command::new("touch").arg(&marker).current_dir(dir.path()),
+                QUICK_TIMEOUT,
+            )
+            .unwrap();
+        runner
+            .run_attached(Command::new("touch").arg(&marker))
+            .unwrap();
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn missing_programs_are_explained() {
+        let err = output(&mut Command::new("tmuxify-no-such-program"), QUICK_TIMEOUT).unwrap_err();
+        assert!(err.to_string().contains("not found on PATH"));
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use console::style;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::Args;
+use crate::ops::{audit, history, load, picker, validate, write};
+
+/// Find orphaned configs tmuxify wrote to `~/.tmuxp` and offer to delete them,
+/// keeping a backup of each
+pub fn run(args: &Args) -> Result<()> {
+    let tmuxp_dir = dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".tmuxp");
+
+    // Hand-written configs aren't tmuxify's to delete
+    let written = history::written_files()?;
+    let orphans: Vec<(PathBuf, String)> = audit::files_in(&tmuxp_dir)
+        .into_iter()
+        .filter(|path| audit::is_config(path) && !audit::is_backup(path))
+        .filter(|path| written.contains(path))
+        .filter_map(|path| {
+            let config = load::load_config(&path).ok()?;
+            let reason = audit::orphan_reason(&path, &config)?;
+            Some((path, reason))
+        })
+        .collect();
+
+    if orphans.is_empty() {
+        println!(
+            "{} No orphaned configs in {}",
+            style("✓").green().bold(),
+            tmuxp_dir.display()
+        );
+        return Ok(());
+    }
+
+    let items: Vec<picker::Item> = orphans
+        .iter()
+        .map(|(path, reason)| {
+            picker::Item::new(format!("{} ({})", path.display(), reason)).with_preview(path.clone())
+        })
+        .collect();
+
+    if args.dry_run {
+        println!("[DRY RUN] Would offer to delete:");
+        for item in &items {
+            println!("  {}", item.label);
+        }
+        return Ok(());
+    }
+
+    validate::require_terminal("Choosing configs to prune")?;
+    let defaults = vec![false; items.len()];
+    let selected = picker::multi_select("Delete these orphaned configs?", &items, &defaults)?;
+
+    if selected.is_empty() {
+        println!("Nothing deleted.");
+        return Ok(());
+    }
+
+    for i in &selected {
+        let path = &orphans[*i].0;
+        write::backup_file(path, false)?;
+        fs::remove_file(path).with_context(|| format!("Failed to delete {}", path.display()))?;
+        println!("  {} Deleted {}", style("✓").green().bold(), path.display());
+    }
+    println!(
+        "{}",
+        style("Backups of deleted configs were kept; see `tmuxify audit`.").dim()
+    );
+
+    Ok(())
+}
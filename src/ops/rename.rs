@@ -0,0 +1,121 @@
+use anyhow::Result;
+use console::style;
+use dialoguer::Confirm;
+
+use crate::cli::Args;
+use crate::model::{ConfigFormat, Settings, TmuxpLocation};
+use crate::ops::process::SystemRunner;
+use crate::ops::{load, theme, tmux, validate, write};
+
+/// Check a new session name: tmux turns `.` and `:` into `_`, so the
+/// running session would no longer match the config
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() || name.contains(['/', '.', ':']) {
+        anyhow::bail!(
+            "Invalid session name: '{}' (it can't be empty or contain '/', '.', or ':')",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Rename a project's session everywhere it is referenced
+pub fn run(args: &Args, new_name: &str) -> Result<()> {
+    validate_name(new_name)?;
+
+    let project_dir = args.project_dir()?;
+    let (old_path, location) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let mut config = load::load_config(&old_path)?;
+    let old_name = std::mem::replace(&mut config.session_name, new_name.to_string());
+
+    if old_name == new_name {
+        println!("Session is already named {}.", style(new_name).cyan());
+        return Ok(());
+    }
+
+    println!(
+        "Renaming session {} -> {}",
+        style(&old_name).cyan(),
+        style(new_name).cyan()
+    );
+
+    // --force only allows replacing another session's config; that file and
+    // the renamed ones are still backed up
+    let write_options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: false,
+        envrc: write::envrc_options(&project_dir, &Settings::load()?),
+        // The new file name drops the old config's format unless given
+        format: args.format.or(Some(ConfigFormat::from_path(&old_path))),
+        ..Default::default()
+    };
+
+    // Home configs are named after the session; don't replace another one
+    if location == TmuxpLocation::Home && !args.force {
+        let format = write_options.format.unwrap_or_default();
+        let target = format.apply(&config.get_file_path(location, Some(&project_dir))?);
+        if target != old_path && target.exists() {
+            anyhow::bail!(
+                "{} already exists for another session; pass --force to replace it",
+                target.display()
+            );
+        }
+    }
+
+    // Rewrite the config under its new name and regenerate .envrc; a home
+    // config moves, so the old file is removed (and backed up) along with it
+    let result = if location == TmuxpLocation::Home {
+        write::move_config(&config, location, &project_dir, &old_path, &write_options)?
+    } else {
+        write::write_config(&config, location, &project_dir, &write_options)?
+    };
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    result.print_summary();
+
+    // Offer to rename the running session too; without a terminal, just say how
+    let running = tmux::has_session(&old_name);
+    if running && !validate::is_interactive() {
+        println!(
+            "Session '{}' is still running; rename it with {}",
+            old_name,
+            style(format!("tmux rename-session -t {} {}", old_name, new_name)).yellow()
+        );
+    } else if running
+        && Confirm::with_theme(theme::get())
+            .with_prompt(format!("Rename running tmux session '{}' too?", old_name))
+            .default(true)
+            .interact()?
+    {
+        tmux::rename_session(&SystemRunner::new(args.dry_run), &old_name, new_name)?;
+        println!("  {} Renamed running session", style("✓").green().bold());
+    }
+
+    println!();
+    println!(
+        "Run {} to trust the updated .envrc.",
+        style("direnv allow").yellow()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_names_tmux_would_change() {
+        assert!(validate_name("api-v2").is_ok());
+        for name in ["", "  ", "a/b", "my.app", "a:b"] {
+            assert!(
+                validate_name(name).is_err(),
+                "{:?} should be rejected",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Coordination with tmux-resurrect and tmux-continuum.
+//!
+//! Restoring a saved session that tmuxp also creates duplicates its windows.
+//! tmuxify can keep its sessions out of resurrect's save file through
+//! resurrect's post-save hook, leaving their creation to tmuxp.
+
+use anyhow::{Context, Result};
+use console::style;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::ops::{audit, history, load, shell, tmux, write};
+
+const BLOCK_START: &str = "# >>> tmuxify resurrect >>>";
+const BLOCK_END: &str = "# <<< tmuxify resurrect <<<";
+
+/// Save-file record types that belong to a session
+const SESSION_RECORDS: &[&str] = &["pane", "window", "grouped_session"];
+
+/// What the user's tmux config says about resurrect and continuum
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Plugins {
+    pub resurrect: bool,
+    pub continuum: bool,
+    /// continuum restores the last save when the tmux server starts
+    pub auto_restore: bool,
+    /// The tmuxify post-save hook is configured
+    pub excluded: bool,
+}
+
+impl Plugins {
+    /// Inspect the user's tmux config files and plugin directory
+    pub fn detect() -> Self {
+        let conf: String = tmux::config_files()
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut plugins = Self::from_config(&conf);
+        if !plugins.resurrect {
+            plugins.resurrect = plugin_dirs()
+                .iter()
+                .any(|dir| dir.join("tmux-resurrect").is_dir());
+        }
+        plugins
+    }
+
+    fn from_config(conf: &str) -> Self {
+        let lines: Vec<&str> = conf
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        let continuum = lines.iter().any(|line| line.contains("tmux-continuum"));
+        Self {
+            resurrect: lines.iter().any(|line| line.contains("tmux-resurrect")),
+            continuum,
+            auto_restore: continuum
+                && lines.iter().any(|line| {
+                    line.contains("@continuum-restore")
+                        && line
+                            .split_whitespace()
+                            .last()
+                            .is_some_and(|value| value.trim_matches(['\'', '"']) == "on")
+                }),
+            excluded: conf.contains(BLOCK_START),
+        }
+    }
+}
+
+fn plugin_dirs() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    vec![
+        home.join(".tmux").join("plugins"),
+        home.join(".config").join("tmux").join("plugins"),
+    ]
+}
+
+/// Session names of the configs tmuxify has written, per the history log;
+/// hand-written tmuxp configs are left to resurrect
+pub fn managed_sessions() -> BTreeSet<String> {
+    history::written_files()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| audit::is_config(path) && !audit::is_backup(path))
+        .filter_map(|path| load::load_config(&path).ok())
+        .map(|config| config.session_name)
+        .collect()
+}
+
+/// The tmux.conf block registering the post-save hook. resurrect runs the
+/// hook through the shell, so `exe` is shell-quoted, then the whole command
+/// is double-quoted for tmux.
+fn hook_block(exe: &Path) -> String {
+    let command = format!(
+        "{} __resurrect-filter",
+        shell::quote(&exe.display().to_string())
+    );
+    format!(
+        "{}\n# tmuxp recreates tmuxify-managed sessions; keep them out of resurrect saves\n\
+         set -g @resurrect-hook-post-save-layout \"{}\"\n{}\n",
+        BLOCK_START,
+        command
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "\\$"),
+        BLOCK_END
+    )
+}
+
+/// `conf` with the tmuxify block replaced by `block`, or appended
+fn with_block(conf: &str, block: &str) -> String {
+    let existing = conf
+        .find(BLOCK_START)
+        .zip(conf.find(BLOCK_END))
+        .filter(|(start, end)| start < end);
+    if let Some((start, end)) = existing {
+        let after = conf[end + BLOCK_END.len()..].trim_start_matches('\n');
+        return format!("{}{}{}", &conf[..start], block, after);
+    }
+    let mut out = conf.to_string();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(block);
+    out
+}
+
+/// A resurrect save file without the records of `sessions`
+fn filter_save(content: &str, sessions: &BTreeSet<String>) -> String {
+    content
+        .lines()
+        .filter(|line| {
+            let mut fields = line.split('\t');
+            let kind = fields.next().unwrap_or_default();
+            let session = fields.next().unwrap_or_default();
+            !(SESSION_RECORDS.contains(&kind) && sessions.contains(session))
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Report resurrect/continuum status and install the exclusion hook with `write`
+pub fn run(args: &Args, write: bool) -> Result<()> {
+    let plugins = Plugins::detect();
+    let exe = std::env::current_exe().context("Could not locate the tmuxify executable")?;
+    let block = hook_block(&exe);
+
+    if !plugins.resurrect {
+        println!("tmux-resurrect is not configured; nothing to coordinate.");
+        return Ok(());
+    }
+    println!(
+        "tmux-resurrect: {}  tmux-continuum: {}  auto-restore: {}",
+        style("yes").green(),
+        if plugins.continuum {
+            style("yes").green()
+        } else {
+            style("no").dim()
+        },
+        if plugins.auto_restore {
+            style("on").yellow()
+        } else {
+            style("off").dim()
+        },
+    );
+    let sessions = managed_sessions();
+    println!(
+        "tmuxify manages {} session(s){}",
+        sessions.len(),
+        if sessions.is_empty() {
+            String::new()
+        } else {
+            format!(
+                ": {}",
+                sessions.iter().cloned().collect::<Vec<_>>().join(", ")
+            )
+        }
+    );
+    println!();
+
+    if !write {
+        if plugins.excluded {
+            println!(
+                "{} tmuxify sessions are already excluded from resurrect saves",
+                style("✓").green().bold()
+            );
+        } else {
+            println!("To keep resurrect from restoring these sessions on top of tmuxp, add:");
+            println!();
+            print!("{}", block);
+            println!();
+            println!("or run {}.", style("tmuxify resurrect --write").yellow());
+            println!(
+                "{}",
+                style(
+                    "Alternatively, leave restoring to tmuxp by turning off continuum's \
+                     auto-restore: set -g @continuum-restore 'off'"
+                )
+                .dim()
+            );
+        }
+        return Ok(());
+    }
+
+    let path = tmux::config_files()
+        .into_iter()
+        .next()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".tmux.conf")))
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+    let conf = fs::read_to_string(&path).unwrap_or_default();
+    let updated = with_block(&conf, &block);
+    if updated == conf {
+        println!(
+            "{} {} is up to date",
+            style("✓").green().bold(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+    write::write_file(None, &path, updated, &options)?;
+    if !args.dry_run {
+        println!("{} Updated {}", style("✓").green().bold(), path.display());
+        println!(
+            "Reload it with {}",
+            style(format!("tmux source-file {}", path.display())).yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Post-save hook: strip tmuxify-managed sessions from a resurrect save file
+pub fn filter(file: &Path) -> Result<()> {
+    let content =
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let filtered = filter_save(&content, &managed_sessions());
+    if filtered != content {
+        fs::write(file, filtered).with_context(|| format!("Failed to write {}", file.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_plugins_and_auto_restore() {
+        let conf = "set -g @plugin 'tmux-plugins/tmux-resurrect'\n\
+                    set -g @plugin 'tmux-plugins/tmux-continuum'\n\
+                    set -g @continuum-restore 'on'\n";
+        let plugins = Plugins::from_config(conf);
+        assert_eq!(
+            plugins,
+            Plugins {
+                resurrect: true,
+                continuum: true,
+                auto_restore: true,
+                excluded: false
+            }
+        );
+
+        let commented = "# set -g @plugin 'tmux-plugins/tmux-continuum'\n";
+        assert!(!Plugins::from_config(commented).continuum);
+    }
+
+    #[test]
+    fn hook_block_is_replaced_not_duplicated() {
+        let block = hook_block(Path::new("/usr/local/bin/tmuxify"));
+        let once = with_block("set -g mouse on", &block);
+        assert_eq!(once, format!("set -g mouse on\n\n{}", block));
+        assert!(Plugins::from_config(&once).excluded);
+
+        let moved = hook_block(Path::new("/opt/bin/tmuxify"));
+        let twice = with_block(&once, &moved);
+        assert_eq!(twice.matches(BLOCK_START).count(), 1);
+        assert!(twice.contains("/opt/bin/tmuxify __resurrect-filter"));
+    }
+
+    #[test]
+    fn hook_block_quotes_the_executable() {
+        let block = hook_block(Path::new("/Users/me/My Tools/tmuxify"));
+        assert!(block.contains(
+            r#"@resurrect-hook-post-save-layout "'/Users/me/My Tools/tmuxify' __resurrect-filter""#
+        ));
+    }
+
+    #[test]
+    fn save_file_drops_only_managed_sessions() {
+        let save = "pane\tapi\t1\t:editor\t1\t:*\t0\t:/src/api\t1\tnvim\t:\n\
+                    window\tapi\t1\t:editor\t1\t:*\tlayout\t:\n\
+                    pane\tscratch\t0\t:zsh\t1\t:*\t0\t:/tmp\t1\tzsh\t:\n\
+                    state\tapi\tscratch\n";
+        let managed: BTreeSet<String> = ["api".to_string()].into();
+        assert_eq!(
+            filter_save(save, &managed),
+            "pane\tscratch\t0\t:zsh\t1\t:*\t0\t:/tmp\t1\tzsh\t:\nstate\tapi\tscratch\n"
+        );
+    }
+}
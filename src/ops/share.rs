@@ -0,0 +1,176 @@
+//! Publishing a config as a GitHub gist or to a paste service.
+
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::Confirm;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::Args;
+use crate::model::{Config, Settings};
+use crate::ops::{load, process, secrets, theme, validate};
+
+/// Upload the project's config with secrets scrubbed and print its URL
+pub fn run(args: &Args, target: Option<&str>, yes: bool) -> Result<()> {
+    args.require_network("Sharing")?;
+    let path = match target {
+        Some(spec) => load::resolve_config_path(spec)?,
+        None => load::locate_project_config(&args.project_dir()?, args.session.as_deref())?.0,
+    };
+    let mut config = load::load_config(&path)?;
+    let redactions = sanitize(&mut config);
+    let yaml = config.to_yaml()?;
+    let settings = Settings::load()?;
+    let destination = settings
+        .share_endpoint
+        .clone()
+        .unwrap_or_else(|| "a secret GitHub gist".to_string());
+
+    println!(
+        "{}",
+        style(format!("--- {}.yaml", config.session_name)).dim()
+    );
+    print!("{}", yaml);
+    println!("{}", style("---").dim());
+    if redactions.is_empty() {
+        println!("{} No secrets detected", style("✓").green().bold());
+    } else {
+        println!("{} Redacted:", style("⚠").yellow().bold());
+        for redaction in &redactions {
+            println!("  {}", redaction);
+        }
+    }
+    println!();
+
+    if args.dry_run {
+        println!("[DRY RUN] Would upload to {}", destination);
+        return Ok(());
+    }
+    if !yes {
+        validate::require_terminal("Confirming the upload")?;
+        if !Confirm::with_theme(theme::get())
+            .with_prompt(format!("Upload to {}?", destination))
+            .default(false)
+            .interact()?
+        {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    // A private directory, removed when dropped, so the file name can be the session's
+    let dir = tempfile::Builder::new()
+        .prefix("tmuxify-share-")
+        .tempdir()
+        .context("Failed to create a temporary directory")?;
+    let file = dir.path().join(format!("{}.yaml", config.session_name));
+    fs::write(&file, &yaml).with_context(|| format!("Failed to write {}", file.display()))?;
+    let url = match &settings.share_endpoint {
+        Some(endpoint) => upload_to_endpoint(endpoint, &file),
+        None => upload_gist(&file),
+    }?;
+
+    println!(
+        "{} Shared at {}",
+        style("✓").green().bold(),
+        style(&url).cyan()
+    );
+    println!(
+        "Import it with {}",
+        style(format!("tmuxify import --url {}", url)).yellow()
+    );
+    Ok(())
+}
+
+/// Scrub secrets and the user's home directory from a config about to leave
+/// the machine, returning what was redacted
+fn sanitize(config: &mut Config) -> Vec<String> {
+    let redactions = secrets::scrub_config(config);
+    if let Some(home) = dirs::home_dir() {
+        config.start_directory = tilde(&config.start_directory, &home);
+    }
+    redactions
+}
+
+/// `path` with a leading `home` replaced by `~`
+fn tilde(path: &Path, home: &Path) -> PathBuf {
+    match path.strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => PathBuf::from("~"),
+        Ok(rest) => Path::new("~").join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+fn upload_gist(file: &Path) -> Result<String> {
+    if !validate::has_binary("gh") {
+        anyhow::bail!(
+            "Sharing as a gist needs the GitHub CLI (gh); install it and run 'gh auth login', \
+             or set share_endpoint in your tmuxify settings"
+        );
+    }
+    let out = process::run(
+        Command::new("gh")
+            .args([
+                "gist",
+                "create",
+                "--desc",
+                "tmuxp session shared with tmuxify",
+            ])
+            .arg(file),
+        process::SLOW_TIMEOUT,
+    )?;
+    last_url(&out).ok_or_else(|| anyhow::anyhow!("gh did not print a gist URL"))
+}
+
+/// POST the file as the request body; paste services reply with its URL
+fn upload_to_endpoint(endpoint: &str, file: &Path) -> Result<String> {
+    if !validate::has_binary("curl") {
+        anyhow::bail!("Uploading to {} needs curl", endpoint);
+    }
+    let out = process::run(
+        Command::new("curl")
+            .args(["--fail", "--silent", "--show-error", "--data-binary"])
+            .arg(format!("@{}", file.display()))
+            .arg(endpoint),
+        process::SLOW_TIMEOUT,
+    )?;
+    last_url(&out).ok_or_else(|| anyhow::anyhow!("{} did not reply with a URL", endpoint))
+}
+
+fn last_url(out: &str) -> Option<String> {
+    out.split_whitespace()
+        .rev()
+        .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn home_is_abbreviated() {
+        let home = Path::new("/home/ada");
+        assert_eq!(
+            tilde(Path::new("/home/ada/src/app"), home),
+            PathBuf::from("~/src/app")
+        );
+        assert_eq!(tilde(Path::new("/home/ada"), home), PathBuf::from("~"));
+        assert_eq!(
+            tilde(Path::new("/srv/app"), home),
+            PathBuf::from("/srv/app")
+        );
+    }
+
+    #[test]
+    fn url_is_taken_from_the_reply() {
+        let gh = "- Creating gist app.yaml\n✓ Created secret gist\n\
+                  https://gist.github.com/ada/abc123\n";
+        assert_eq!(
+            last_url(gh).as_deref(),
+            Some("https://gist.github.com/ada/abc123")
+        );
+        assert_eq!(last_url("error"), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! POSIX shell word splitting and quoting, for values tmuxp splits like a
+//! shell would.
+
+/// Split `line` into words the way `sh` would: whitespace separates words,
+/// single quotes keep everything literal, and double quotes and backslashes
+/// escape. No expansion happens.
+pub fn split(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated ' quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) if "\"\\$`".contains(c) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("unterminated \" quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated \" quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_default().push(c),
+                None => return Err("trailing backslash".to_string()),
+            },
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Quote `word` so [`split`] (or `sh`) reads it back unchanged; plain words
+/// are left bare.
+pub fn quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-+=.,/:@%^".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_like_a_shell() {
+        assert_eq!(
+            split(r#"sh -c 'docker compose up -d' "a \"b\"" c\ d '' "#).unwrap(),
+            ["sh", "-c", "docker compose up -d", r#"a "b""#, "c d", ""]
+        );
+        assert_eq!(split("./bin/setup").unwrap(), ["./bin/setup"]);
+        assert!(split("echo 'oops").is_err());
+        assert!(split(r#"echo "oops"#).is_err());
+    }
+
+    #[test]
+    fn quoted_words_split_back() {
+        for word in ["plain", "-Xmx1g -Xms1g", "it's", "", "$HOME", "a\"b"] {
+            assert_eq!(split(&quote(word)).unwrap(), [word]);
+        }
+        assert_eq!(quote("./bin/setup"), "./bin/setup");
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::Result;
+use console::style;
+
+use crate::cli::Args;
+use crate::model::{Config, WindowLayout};
+use crate::ops::load;
+use crate::ops::table::{self, Table};
+
+/// Output format for `tmuxify show`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShowFormat {
+    Tree,
+    Table,
+    Yaml,
+    Json,
+    Toml,
+}
+
+/// Print a config as a readable tree, or raw YAML/JSON
+pub fn run(args: &Args, target: Option<&str>, format: ShowFormat) -> Result<()> {
+    let path = match target {
+        Some(spec) => load::resolve_config_path(spec)?,
+        None => load::locate_project_config(&args.project_dir()?, args.session.as_deref())?.0,
+    };
+    let config = load::load_config(&path)?;
+
+    match format {
+        ShowFormat::Yaml => print!("{}", config.to_yaml()?),
+        ShowFormat::Json => print!("{}", config.to_json()?),
+        ShowFormat::Toml => print!("{}", config.to_toml()?),
+        ShowFormat::Tree => print_tree(&config, &path.display().to_string()),
+        ShowFormat::Table => {
+            println!(
+                "{} {}",
+                style(&config.session_name).bold().cyan(),
+                style(format!("({})", path.display())).dim()
+            );
+            print!("{}", windows_table(&config).render());
+        }
+    }
+
+    Ok(())
+}
+
+/// Widest first command shown in the window table
+const COMMAND_WIDTH: usize = 40;
+
+/// One row per window: name, layout, pane count, first command, start directory
+pub fn windows_table(config: &Config) -> Table {
+    let mut table = Table::new(&["WINDOW", "LAYOUT", "PANES", "FIRST COMMAND", "START DIR"]);
+    for (i, window) in config.windows.iter().enumerate() {
+        let layout = match &window.layout {
+            Some(WindowLayout::Custom(_)) => "custom".to_string(),
+            Some(layout) => layout.to_string(),
+            None => "-".to_string(),
+        };
+        let first_pane = window.panes.first();
+        let command = first_pane
+            .and_then(|p| p.shell_command.first())
+            .map_or_else(
+                || "(shell)".to_string(),
+                |c| table::truncate(c, COMMAND_WIDTH),
+            );
+        let dir = first_pane
+            .and_then(|p| p.start_directory.clone())
+            .unwrap_or_else(|| ".".to_string());
+        table.add_row(vec![
+            window
+                .window_name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", i + 1)),
+            layout,
+            window.panes.len().to_string(),
+            command,
+            dir,
+        ]);
+    }
+    table
+}
+
+/// Print a session's window table after writing it
+pub fn print_summary(config: &Config) {
+    println!(
+        "\n{} {}",
+        style(&config.session_name).bold().cyan(),
+        style(format!("({})", config.start_directory.display())).dim()
+    );
+    print!("{}", windows_table(config).render());
+}
+
+/// Render session → windows → panes with colors
+fn print_tree(config: &Config, source: &str) {
+    println!(
+        "{} {}",
+        style(&config.session_name).bold().cyan(),
+        style(format!("({})", source)).dim()
+    );
+    if !config.start_directory.as_os_str().is_empty() {
+        println!(
+            "  {} {}",
+            style("start_directory:").dim(),
+            config.start_directory.display()
+        );
+    }
+    if let Some(suppress) = config.suppress_history {
+        println!("  {} {}", style("suppress_history:").dim(), suppress);
+    }
+    if !config.environment.is_empty() {
+        let vars: Vec<String> = config
+            .environment
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        println!("  {} {}", style("environment:").dim(), vars.join(" "));
+    }
+    for key in config.extra.keys() {
+        println!(
+            "  {} {}",
+            style(format!("{}:", key)).dim(),
+            style("(passthrough)").dim()
+        );
+    }
+
+    for (i, window) in config.windows.iter().enumerate() {
+        let last_window = i + 1 == config.windows.len();
+        let (branch, stem) = if last_window {
+            ("└─", "   ")
+        } else {
+            ("├─", "│  ")
+        };
+
+        let name = window.window_name.as_deref().unwrap_or("unnamed");
+        let mut details = Vec::new();
+        if let Some(layout) = &window.layout {
+            details.push(layout.to_string());
+        }
+        if let Some(index) = window.window_index {
+            details.push(format!("index {}", index));
+        }
+        if window.focus == Some(true) {
+            details.push("focus".to_string());
+        }
+        println!(
+            "{} {} {}",
+            branch,
+            style(format!("{}. {}", i + 1, name)).bold(),
+            style(if details.is_empty() {
+                String::new()
+            } else {
+                format!("[{}]", details.join(", "))
+            })
+            .yellow()
+        );
+
+        for (key, value) in &window.options {
+            println!("{}  {} {}", stem, style(format!("{}:", key)).dim(), value);
+        }
+        for (key, value) in &window.environment {
+            println!("{}  {} {}={}", stem, style("env").dim(), key, value);
+        }
+
+        for (j, pane) in window.panes.iter().enumerate() {
+            let last_pane = j + 1 == window.panes.len();
+            let pane_branch = if last_pane { "└─" } else { "├─" };
+            let pane_stem = if last_pane { "   " } else { "│  " };
+
+            let mut label = format!("pane {}", j + 1);
+            if let Some(dir) = &pane.start_directory {
+                label.push_str(&format!(" (in {})", dir));
+            }
+            if pane.focus == Some(true) {
+                label.push_str(" *");
+            }
+            println!("{}{} {}", stem, pane_branch, style(label).green());
+
+            if pane.shell_command.is_empty() {
+                println!("{}{}  {}", stem, pane_stem, style("(shell)").dim());
+            }
+            for cmd in &pane.shell_command {
+                println!("{}{}  $ {}", stem, pane_stem, cmd);
+            }
+            for (key, value) in &pane.environment {
+                println!(
+                    "{}{}  {} {}={}",
+                    stem,
+                    pane_stem,
+                    style("env").dim(),
+                    key,
+                    value
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! `tmuxify sync`: reconcile a project's `.tmuxp.yaml` with its
+//! `~/.tmuxp/<session>.yaml` copy.
+
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::Select;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::cli::Args;
+use crate::model::{Config, ConfigFormat, TmuxpLocation};
+use crate::ops::{diff, load, theme, validate, write};
+
+/// Which copy `sync` replaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SyncTarget {
+    /// Replace `~/.tmuxp/<session>.yaml` with the project's copy
+    Home,
+    /// Replace the project's `.tmuxp.yaml` with the home copy
+    Project,
+}
+
+/// The two copies of a project's config
+struct Copies {
+    project: PathBuf,
+    home: PathBuf,
+}
+
+impl Copies {
+    /// The copy `target` names and the one it is replaced from
+    fn target_and_source(&self, target: SyncTarget) -> (&Path, &Path) {
+        match target {
+            SyncTarget::Home => (&self.home, &self.project),
+            SyncTarget::Project => (&self.project, &self.home),
+        }
+    }
+}
+
+/// Find both copies of the project's config, failing when either is missing
+fn find_copies(project_dir: &Path, session: Option<&str>) -> Result<Copies> {
+    let project = load::local_config(project_dir).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No .tmuxp.yaml in {}; there is nothing to sync",
+            project_dir.display()
+        )
+    })?;
+    let session = match session {
+        Some(session) => session.to_string(),
+        None => load::load_config(&project)?.session_name,
+    };
+    let home = Config::file_path_for(&session, TmuxpLocation::Home, None)?;
+    let copies = [home.clone(), ConfigFormat::Json.apply(&home)];
+    let home = load::pick_config(&copies, project_dir).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No {} for session {}; there is nothing to sync",
+            home.display(),
+            session
+        )
+    })?;
+    Ok(Copies { project, home })
+}
+
+/// Whether both paths lead to the same file, as when one links to the other
+fn linked(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// A config's text for comparison: as written for YAML, re-rendered as
+/// YAML for JSON so the two formats compare by content
+fn comparable(path: &Path) -> Result<String> {
+    match ConfigFormat::from_path(path) {
+        ConfigFormat::Yaml => {
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+        }
+        _ => load::load_config(path)?.to_yaml(),
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Replace `target` with `source`'s config, verbatim when both use the same
+/// format and re-rendered otherwise
+fn copy(
+    source: &Path,
+    target: &Path,
+    project_dir: &Path,
+    options: &write::WriteOptions,
+) -> Result<()> {
+    let config = load::load_config(source)?;
+    let format = ConfigFormat::from_path(target);
+    let content = if ConfigFormat::from_path(source) == format {
+        fs::read_to_string(source)
+            .with_context(|| format!("Failed to read {}", source.display()))?
+    } else {
+        config.render(format)?
+    };
+    write::write_file(Some(project_dir), target, content, options)?;
+    Ok(())
+}
+
+/// Replace `target` with a symlink to `source`, backing it up first
+fn link(source: &Path, target: &Path, options: &write::WriteOptions) -> Result<()> {
+    if ConfigFormat::from_path(source) != ConfigFormat::from_path(target) {
+        anyhow::bail!(
+            "{} and {} use different formats; copy instead of linking",
+            source.display(),
+            target.display()
+        );
+    }
+    let source = fs::canonicalize(source)
+        .with_context(|| format!("Failed to resolve {}", source.display()))?;
+    if options.dry_run {
+        println!(
+            "[DRY RUN] Would replace {} with a symlink to {}",
+            target.display(),
+            source.display()
+        );
+        return Ok(());
+    }
+    write::backup_file(target, options.force)?;
+    fs::remove_file(target).with_context(|| format!("Failed to remove {}", target.display()))?;
+    symlink(&source, target).with_context(|| {
+        format!(
+            "Failed to link {} to {}",
+            target.display(),
+            source.display()
+        )
+    })
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(not(unix))]
+fn symlink(_source: &Path, _target: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::other("symlinks are only supported on Unix"))
+}
+
+/// Ask which copy to replace and whether to link it, or `None` to leave both
+fn prompt_action(copies: &Copies) -> Result<Option<(SyncTarget, bool)>> {
+    validate::require_terminal("Choosing a sync direction")?;
+    let (home, project) = (copies.home.display(), copies.project.display());
+    let choices = [
+        format!("Copy {} over {}", project, home),
+        format!("Copy {} over {}", home, project),
+        format!("Make {} a symlink to {}", home, project),
+        format!("Make {} a symlink to {}", project, home),
+        "Leave both as they are".to_string(),
+    ];
+    let selection = Select::with_theme(theme::get())
+        .with_prompt("How should they be synced?")
+        .items(&choices)
+        .default(0)
+        .interact()?;
+    Ok(match selection {
+        0 => Some((SyncTarget::Home, false)),
+        1 => Some((SyncTarget::Project, false)),
+        2 => Some((SyncTarget::Home, true)),
+        3 => Some((SyncTarget::Project, true)),
+        _ => None,
+    })
+}
+
+/// Compare the project's home and local configs, show how they differ, and
+/// copy or link one over the other
+pub fn run(args: &Args, to: Option<SyncTarget>, link_copies: bool) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let copies = find_copies(&project_dir, args.session.as_deref())?;
+
+    if linked(&copies.home, &copies.project) {
+        println!(
+            "{} {} and {} are the same file",
+            style("✓").green().bold(),
+            copies.home.display(),
+            copies.project.display()
+        );
+        return Ok(());
+    }
+
+    let home_text = comparable(&copies.home)?;
+    let project_text = comparable(&copies.project)?;
+    if home_text == project_text && !link_copies {
+        println!(
+            "{} {} and {} are in sync",
+            style("✓").green().bold(),
+            copies.home.display(),
+            copies.project.display()
+        );
+        return Ok(());
+    }
+    if home_text != project_text {
+        print!(
+            "{}",
+            diff::colorize(&diff::unified(
+                &home_text,
+                &project_text,
+                &copies.home.display().to_string(),
+                &copies.project.display().to_string(),
+            ))
+        );
+        let newer = match (modified(&copies.home), modified(&copies.project)) {
+            (Some(home), Some(project)) if home > project => Some(&copies.home),
+            (Some(home), Some(project)) if project > home => Some(&copies.project),
+            _ => None,
+        };
+        if let Some(newer) = newer {
+            println!("\n{} was changed more recently.", newer.display());
+        }
+    }
+
+    let action = match to {
+        Some(target) => Some((target, link_copies)),
+        None => prompt_action(&copies)?,
+    };
+    let Some((target, link_copies)) = action else {
+        println!("Left both copies unchanged.");
+        return Ok(());
+    };
+    let (target, source) = copies.target_and_source(target);
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+    if link_copies {
+        link(source, target, &options)?;
+    } else {
+        copy(source, target, &project_dir, &options)?;
+    }
+    if !args.dry_run {
+        println!(
+            "{} {} now {} {}",
+            style("✓").green().bold(),
+            target.display(),
+            if link_copies { "links to" } else { "matches" },
+            source.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str =
+        "session_name: app\nstart_directory: /src/app\nwindows:\n- panes:\n  - vim\n";
+
+    fn options() -> write::WriteOptions {
+        write::WriteOptions {
+            skip_history: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn json_and_yaml_copies_compare_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("app.yaml");
+        let json = dir.path().join("app.json");
+        fs::write(&yaml, YAML).unwrap();
+        copy(&yaml, &json, dir.path(), &options()).unwrap();
+
+        assert!(fs::read_to_string(&json).unwrap().starts_with('{'));
+        assert_eq!(
+            comparable(&json).unwrap(),
+            load::load_config(&yaml).unwrap().to_yaml().unwrap()
+        );
+    }
+
+    #[test]
+    fn copy_keeps_text_and_link_shares_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("app.yaml");
+        let project = dir.path().join(".tmuxp.yaml");
+        fs::write(&home, "# hand-written\n".to_string() + YAML).unwrap();
+        fs::write(&project, YAML).unwrap();
+
+        copy(&home, &project, dir.path(), &options()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&project).unwrap(),
+            fs::read_to_string(&home).unwrap()
+        );
+        assert!(!linked(&home, &project));
+
+        link(&project, &home, &options()).unwrap();
+        assert!(linked(&home, &project));
+        assert!(fs::read_dir(dir.path()).unwrap().any(|e| {
+            e.unwrap()
+                .file_name()
+                .to_string_lossy()
+                .contains(".backup.")
+        }));
+        assert!(link(&project, &dir.path().join("app.json"), &options()).is_err());
+    }
+
+    #[test]
+    fn writes_after_linking_go_through_the_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("app.yaml");
+        let project = dir.path().join(".tmuxp.yaml");
+        fs::write(&home, YAML).unwrap();
+        fs::write(&project, YAML).unwrap();
+        link(&home, &project, &options()).unwrap();
+
+        // What add-window and the other edits do to the project's copy
+        let mut config = load::load_config(&project).unwrap();
+        config.session_name = "renamed".into();
+        write::save_config(&config, &project, &options()).unwrap();
+
+        assert!(
+            fs::symlink_metadata(&project)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert!(linked(&home, &project));
+        assert_eq!(load::load_config(&home).unwrap().session_name, "renamed");
+    }
+}
@@ -0,0 +1,84 @@
+//! Plain column-aligned tables for terminal output.
+
+use console::{measure_text_width, style, truncate_str};
+
+/// Rows of cells rendered with each column padded to its widest cell.
+/// Cells may contain styling; widths ignore escape codes.
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn add_row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// Render with a bold header row, two spaces between columns, and no
+    /// trailing whitespace
+    pub fn render(&self) -> String {
+        let widths: Vec<usize> = (0..self.headers.len())
+            .map(|col| {
+                std::iter::once(&self.headers[col])
+                    .chain(self.rows.iter().filter_map(|row| row.get(col)))
+                    .map(|cell| measure_text_width(cell))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let line = |cells: Vec<String>| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    let pad = width.saturating_sub(measure_text_width(cell));
+                    format!("{}{}", cell, " ".repeat(pad))
+                })
+                .collect();
+            format!("{}\n", padded.join("  ").trim_end())
+        };
+
+        let header = self
+            .headers
+            .iter()
+            .map(|h| style(h).bold().to_string())
+            .collect();
+        std::iter::once(line(header))
+            .chain(self.rows.iter().map(|row| line(row.clone())))
+            .collect()
+    }
+}
+
+/// Shorten a cell to `width` columns, ending with an ellipsis when cut
+pub fn truncate(text: &str, width: usize) -> String {
+    truncate_str(text, width, "…").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_align_to_the_widest_cell() {
+        let mut table = Table::new(&["NAME", "PANES"]);
+        table.add_row(vec!["editor".into(), "1".into()]);
+        table.add_row(vec![style("db").red().to_string(), "12".into()]);
+
+        let plain = console::strip_ansi_codes(&table.render()).into_owned();
+        assert_eq!(plain, "NAME    PANES\neditor  1\ndb      12\n");
+    }
+
+    #[test]
+    fn long_cells_are_truncated() {
+        assert_eq!(truncate("npm run dev", 20), "npm run dev");
+        assert_eq!(truncate("docker compose up --build", 10), "docker co…");
+    }
+}
@@ -0,0 +1,252 @@
+//! Named session templates: reusable config skeletons stored under
+//! `~/.config/tmuxify/templates/` and applied to new projects.
+
+use anyhow::{Context, Result};
+use console::style;
+use dialoguer::Input;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::model::{Config, Settings, TmuxpLocation};
+use crate::ops::{load, show, table, theme, validate, write};
+
+/// Directory templates are stored in
+fn templates_dir() -> Result<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("tmuxify").join("templates"))
+        .ok_or_else(|| anyhow::anyhow!("Could not determine the config directory"))
+}
+
+/// The file for template `name`, rejecting names that aren't plain file names
+fn template_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        anyhow::bail!("Invalid template name '{}'", name);
+    }
+    Ok(dir.join(format!("{}.yaml", name)))
+}
+
+/// Strip a config down to what a template keeps: its windows and panes,
+/// with pane directories inside the project made relative to it
+fn skeleton(config: &Config, name: &str) -> Config {
+    let mut template = config.clone();
+    template.session_name = name.to_string();
+    template.start_directory = PathBuf::new();
+    for pane in template.windows.iter_mut().flat_map(|w| &mut w.panes) {
+        let relative = pane.start_directory.as_deref().and_then(|dir| {
+            Path::new(dir)
+                .strip_prefix(&config.start_directory)
+                .ok()
+                .map(|rel| match rel.to_str() {
+                    Some("") => ".".to_string(),
+                    _ => rel.display().to_string(),
+                })
+        });
+        if relative.is_some() {
+            pane.start_directory = relative;
+        }
+    }
+    template
+}
+
+/// Templates in `dir` by name, skipping files that don't parse
+fn templates_in(dir: &Path) -> Vec<(String, Config)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<(String, Config)> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some((name, load::load_config(&path).ok()?))
+        })
+        .collect();
+    templates.sort_by(|a, b| a.0.cmp(&b.0));
+    templates
+}
+
+/// Save the project's config as template `name`
+pub fn save(args: &Args, name: &str) -> Result<()> {
+    let project_dir = args.project_dir()?;
+    let (config_path, _) = load::locate_project_config(&project_dir, args.session.as_deref())?;
+    let config = load::load_config(&config_path)?;
+    let path = template_path(&templates_dir()?, name)?;
+    if path.exists() && !args.force {
+        anyhow::bail!(
+            "Template '{}' already exists; pass --force to replace it",
+            name
+        );
+    }
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        ..Default::default()
+    };
+    write::save_config(&skeleton(&config, name), &path, &options)?;
+    if !args.dry_run {
+        println!(
+            "{} Saved template {} ({} window(s)) to {}",
+            style("✓").green().bold(),
+            style(name).cyan(),
+            config.windows.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Names of the saved templates, for shell completion
+pub fn names() -> Vec<String> {
+    templates_dir()
+        .map(|dir| {
+            templates_in(&dir)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// List saved templates
+pub fn list() -> Result<()> {
+    let templates = templates_in(&templates_dir()?);
+    if templates.is_empty() {
+        println!("No templates yet. Save one with: tmuxify template save <name>");
+        return Ok(());
+    }
+    let mut table = table::Table::new(&["TEMPLATE", "WINDOWS", "PANES"]);
+    for (name, config) in &templates {
+        let windows: Vec<&str> = config
+            .windows
+            .iter()
+            .map(|w| w.window_name.as_deref().unwrap_or("unnamed"))
+            .collect();
+        let panes: usize = config.windows.iter().map(|w| w.panes.len()).sum();
+        table.add_row(vec![
+            name.clone(),
+            table::truncate(&windows.join(", "), 50),
+            panes.to_string(),
+        ]);
+    }
+    print!("{}", table.render());
+    Ok(())
+}
+
+/// Write template `name` for the current project, asking only for the
+/// session name and start directory
+pub fn apply(args: &Args, name: &str) -> Result<()> {
+    let path = template_path(&templates_dir()?, name)?;
+    if !path.is_file() {
+        anyhow::bail!("No template named '{}'; see tmuxify template list", name);
+    }
+    let mut config = load::load_config(&path)?;
+    let project_dir = args.project_dir()?;
+    let interactive = validate::is_interactive();
+
+    let default_session = project_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().replace('\u{FFFD}', "_"))
+        .unwrap_or_else(|| name.to_string());
+    config.session_name = match &args.session {
+        Some(session) => session.clone(),
+        None if interactive => Input::with_theme(theme::get())
+            .with_prompt("Session name")
+            .default(default_session)
+            .interact_text()?,
+        None => default_session,
+    };
+    config.start_directory = match &args.start_dir {
+        Some(dir) => validate::resolve_user_path(dir),
+        None if interactive => {
+            let dir: String = Input::with_theme(theme::get())
+                .with_prompt("Start directory")
+                .default(project_dir.display().to_string())
+                .interact_text()?;
+            validate::resolve_user_path(Path::new(&dir))
+        }
+        None => project_dir.clone(),
+    };
+    config.environment.extend(args.env.iter().cloned());
+    let location = args.tmuxp_location.unwrap_or(TmuxpLocation::Home);
+
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        envrc: write::envrc_options(&project_dir, &Settings::load()?),
+        format: args.format,
+        ..Default::default()
+    };
+    let result = write::write_config(&config, location, &project_dir, &options)
+        .with_context(|| format!("Failed to apply template '{}'", name))?;
+    if !args.dry_run {
+        show::print_summary(&config);
+        result.print_summary();
+    }
+    Ok(())
+}
+
+/// Delete template `name`
+pub fn delete(args: &Args, name: &str) -> Result<()> {
+    let path = template_path(&templates_dir()?, name)?;
+    if !path.is_file() {
+        anyhow::bail!("No template named '{}'", name);
+    }
+    if args.dry_run {
+        println!("[DRY RUN] Would delete {}", path.display());
+        return Ok(());
+    }
+    fs::remove_file(&path).with_context(|| format!("Failed to delete {}", path.display()))?;
+    println!("{} Deleted template {}", style("✓").green().bold(), name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Pane, Window};
+    use tempfile::tempdir;
+
+    #[test]
+    fn skeleton_drops_the_project_but_keeps_windows() {
+        let panes = vec![
+            Pane::empty().with_start_directory(Some("/src/app/api".into())),
+            Pane::empty().with_start_directory(Some("/var/log".into())),
+        ];
+        let config = Config::new(
+            "app".into(),
+            "/src/app".into(),
+            vec![Window::new(Some("server".into()), None, panes)],
+        );
+
+        let template = skeleton(&config, "web");
+        assert_eq!(template.session_name, "web");
+        assert_eq!(template.start_directory, PathBuf::new());
+        let dirs: Vec<Option<&str>> = template.windows[0]
+            .panes
+            .iter()
+            .map(|p| p.start_directory.as_deref())
+            .collect();
+        assert_eq!(dirs, [Some("api"), Some("/var/log")]);
+    }
+
+    #[test]
+    fn templates_are_listed_by_name() {
+        let dir = tempdir().unwrap();
+        let config = Config::new("x".into(), "/src/x".into(), vec![Window::simple()]);
+        for name in ["web", "api"] {
+            let path = template_path(dir.path(), name).unwrap();
+            fs::write(&path, skeleton(&config, name).to_yaml().unwrap()).unwrap();
+        }
+        fs::write(dir.path().join("broken.yaml"), "windows: 3").unwrap();
+
+        let names: Vec<String> = templates_in(dir.path())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["api", "web"]);
+        assert!(template_path(dir.path(), "../evil").is_err());
+        assert!(template_path(dir.path(), "").is_err());
+    }
+}
@@ -0,0 +1,36 @@
+//! The dialoguer theme shared by every prompt, chosen with the `theme` setting.
+
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+use std::sync::OnceLock;
+
+use crate::model::Settings;
+
+/// Values accepted by the `theme` setting
+pub const THEMES: &[&str] = &["colorful", "simple"];
+
+static THEME: OnceLock<Box<dyn Theme + Send + Sync>> = OnceLock::new();
+
+/// The theme for prompts, read from the user settings on first use
+pub fn get() -> &'static dyn Theme {
+    THEME
+        .get_or_init(|| {
+            let name = Settings::load().ok().and_then(|settings| settings.theme);
+            from_name(name.as_deref())
+        })
+        .as_ref()
+}
+
+fn from_name(name: Option<&str>) -> Box<dyn Theme + Send + Sync> {
+    match name {
+        Some("simple") => Box::new(SimpleTheme),
+        Some(other) if other != "colorful" => {
+            eprintln!(
+                "Unknown theme '{}' in settings; use one of: {}",
+                other,
+                THEMES.join(", ")
+            );
+            Box::new(ColorfulTheme::default())
+        }
+        _ => Box::new(ColorfulTheme::default()),
+    }
+}
@@ -0,0 +1,492 @@
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::model::{Config, Pane};
+use crate::ops::process::{self, Runner};
+use crate::ops::shell;
+
+/// Run a tmux command with a timeout, returning stdout or an error with tmux's stderr
+pub fn query(args: &[&str]) -> Result<String> {
+    process::run(Command::new("tmux").args(args), process::QUICK_TIMEOUT).map_err(|e| {
+        if e.to_string().contains("did not finish") {
+            e.context("The tmux server is not responding; check it with `tmux ls` or restart it")
+        } else {
+            e
+        }
+    })
+}
+
+/// Check whether a tmux session with the given name is running
+pub fn has_session(name: &str) -> bool {
+    query(&["has-session", "-t", &format!("={}", name)]).is_ok()
+}
+
+/// Names of the sessions on the running tmux server (empty if none is running)
+pub fn list_sessions() -> Vec<String> {
+    query(&["list-sessions", "-F", "#{session_name}"])
+        .map(|out| out.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Rename a running tmux session
+pub fn rename_session(runner: &dyn Runner, old: &str, new: &str) -> Result<()> {
+    run(runner, &["rename-session", "-t", &format!("={}", old), new])
+}
+
+/// Run a tmux command that changes state, failing with its stderr on a non-zero exit
+pub fn run(runner: &dyn Runner, args: &[&str]) -> Result<()> {
+    runner
+        .run(Command::new("tmux").args(args), process::QUICK_TIMEOUT)
+        .map(|_| ())
+}
+
+/// Attach the current terminal to a session
+pub fn attach(runner: &dyn Runner, name: &str) -> Result<()> {
+    let target = format!("={}", name);
+    runner.run_attached(Command::new("tmux").args(["attach-session", "-t", &target]))
+}
+
+/// The user's tmux config files that exist: `~/.tmux.conf`, then
+/// `$XDG_CONFIG_HOME/tmux/tmux.conf`
+pub fn config_files() -> Vec<PathBuf> {
+    let xdg = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+    [
+        dirs::home_dir().map(|home| home.join(".tmux.conf")),
+        xdg.map(|dir| dir.join("tmux").join("tmux.conf")),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|path| path.is_file())
+    .collect()
+}
+
+/// The real user id, which names tmux's socket directory
+#[cfg(unix)]
+pub fn uid() -> u32 {
+    unsafe extern "C" {
+        fn getuid() -> u32;
+    }
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { getuid() }
+}
+
+/// The directory tmux keeps its sockets in: `${TMUX_TMPDIR:-/tmp}/tmux-<uid>`
+#[cfg(unix)]
+pub fn socket_dir() -> PathBuf {
+    let tmpdir = std::env::var_os("TMUX_TMPDIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+    tmpdir.join(format!("tmux-{}", uid()))
+}
+
+/// A global option from the running tmux server, if one is running
+pub fn server_option(name: &str) -> Option<String> {
+    query(&["show-options", "-gv", name])
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Values given to `name` by `set -g`/`set-option` lines in the user's tmux
+/// config files, in file order, without surrounding quotes
+pub fn config_option_values(name: &str) -> Vec<String> {
+    config_files()
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|conf| option_values(&conf, name))
+        .collect()
+}
+
+fn option_values(conf: &str, name: &str) -> Vec<String> {
+    conf.lines()
+        .filter_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [set, flag, option, value @ ..]
+                    if (*set == "set" || *set == "set-option")
+                        && flag.starts_with('-')
+                        && *option == name
+                        && !value.is_empty() =>
+                {
+                    Some(value.join(" ").trim_matches(['"', '\'']).to_string())
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Get tmux's `base-index`, from the running server or `~/.tmux.conf`, defaulting to 0
+pub fn base_index() -> u32 {
+    server_option("base-index")
+        .and_then(|s| s.parse().ok())
+        .or_else(|| {
+            config_option_values("base-index")
+                .iter()
+                .find_map(|value| value.parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+/// Session keys the native driver understands; others are tmuxp-only
+const NATIVE_SESSION_KEYS: &[&str] = &[
+    "shell_command_before",
+    "before_script",
+    "options",
+    "global_options",
+];
+
+/// A string or a list of strings as a list
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A mapping of scalars as `key -> value` strings
+fn string_map(value: Option<&Value>) -> BTreeMap<String, String> {
+    let Some(Value::Mapping(map)) = value else {
+        return BTreeMap::new();
+    };
+    map.iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Bool(b) => if *b { "on" } else { "off" }.to_string(),
+                Value::Number(n) => n.to_string(),
+                _ => return None,
+            };
+            Some((key.as_str()?.to_string(), value))
+        })
+        .collect()
+}
+
+/// Expand `~` and resolve relative paths against `base`, as tmuxp does
+fn expand(path: &str, base: &Path) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => dirs::home_dir()
+            .unwrap_or_default()
+            .join(rest.trim_start_matches('/')),
+        None => base.join(path),
+    }
+}
+
+/// The program `before_script` runs: like tmuxp, only a path starting with
+/// `.` is resolved against the start directory, and other commands are
+/// looked up on PATH
+fn script_program(program: &str, root: &Path) -> PathBuf {
+    if program.starts_with('~') || program.starts_with('.') {
+        expand(program.strip_prefix("./").unwrap_or(program), root)
+    } else {
+        PathBuf::from(program)
+    }
+}
+
+/// `-e KEY=VALUE` arguments for session, window, and pane environments,
+/// the later ones winning
+fn env_args<'a>(layers: impl IntoIterator<Item = &'a BTreeMap<String, String>>) -> Vec<String> {
+    let mut merged = BTreeMap::new();
+    for layer in layers {
+        merged.extend(layer.iter());
+    }
+    merged
+        .into_iter()
+        .flat_map(|(key, value)| ["-e".to_string(), format!("{}={}", key, value)])
+        .collect()
+}
+
+/// Run tmux and return its trimmed output, or `fallback` when there is none
+/// (a dry run prints the command instead of running it)
+fn created(runner: &dyn Runner, args: &[String], fallback: String) -> Result<String> {
+    let out = runner.run(Command::new("tmux").args(args), process::QUICK_TIMEOUT)?;
+    let out = out.trim();
+    Ok(if out.is_empty() {
+        fallback
+    } else {
+        out.to_string()
+    })
+}
+
+/// Type `text` into a pane, pressing Enter after it unless `enter` is false
+fn send(runner: &dyn Runner, target: &str, text: &str, enter: bool) -> Result<()> {
+    run(runner, &["send-keys", "-t", target, "-l", text])?;
+    if enter {
+        run(runner, &["send-keys", "-t", target, "Enter"])?;
+    }
+    Ok(())
+}
+
+/// Start `config`'s session detached with tmux commands alone, the way
+/// `tmuxp load -d` would, without needing tmuxp. Keys only tmuxp knows are
+/// reported and skipped.
+pub fn build_session(runner: &dyn Runner, config: &Config, size: (usize, usize)) -> Result<()> {
+    let session = &config.session_name;
+    if config.windows.is_empty() {
+        anyhow::bail!("{} has no windows", session);
+    }
+    // `~` is only expanded in UTF-8 paths; others are used as written
+    let root = match config.start_directory.to_str() {
+        Some(dir) => expand(dir, Path::new(".")),
+        None => Path::new(".").join(&config.start_directory),
+    };
+    for key in config
+        .extra
+        .keys()
+        .filter(|key| !NATIVE_SESSION_KEYS.contains(&key.as_str()))
+    {
+        eprintln!("Skipping `{}`: the native driver doesn't support it", key);
+    }
+
+    if let Some(script) = config.extra.get("before_script").and_then(Value::as_str) {
+        let words = shell::split(script).map_err(|e| anyhow::anyhow!("before_script: {}", e))?;
+        if let Some((program, args)) = words.split_first() {
+            runner
+                .run(
+                    Command::new(script_program(program, &root))
+                        .args(args)
+                        .current_dir(&root),
+                    process::SLOW_TIMEOUT,
+                )
+                .context("before_script failed")?;
+        }
+    }
+
+    let session_before = string_list(config.extra.get("shell_command_before"));
+    let mut focused_window = None;
+    for (i, window) in config.windows.iter().enumerate() {
+        let window_dir = match window.extra.get("start_directory").and_then(Value::as_str) {
+            Some(dir) => expand(dir, &root),
+            None => root.clone(),
+        };
+        let pane_dir = |pane: &Pane| match &pane.start_directory {
+            Some(dir) => expand(dir, &window_dir),
+            None => window_dir.clone(),
+        };
+        let first = window.panes.first().cloned().unwrap_or_else(Pane::empty);
+
+        let mut args: Vec<String> = if i == 0 {
+            let (width, height) = size;
+            vec![
+                "new-session".into(),
+                "-d".into(),
+                "-s".into(),
+                session.clone(),
+                "-x".into(),
+                width.to_string(),
+                "-y".into(),
+                height.to_string(),
+            ]
+        } else {
+            let target = match window.window_index {
+                Some(index) => format!("={}:{}", session, index),
+                None => format!("={}:", session),
+            };
+            vec!["new-window".into(), "-d".into(), "-t".into(), target]
+        };
+        args.extend(["-P", "-F", "#{window_id} #{pane_id}"].map(String::from));
+        if let Some(name) = &window.window_name {
+            args.extend(["-n".to_string(), name.clone()]);
+        }
+        args.extend([
+            "-c".to_string(),
+            pane_dir(&first).to_string_lossy().into_owned(),
+        ]);
+        args.extend(env_args([
+            &config.environment,
+            &window.environment,
+            &first.environment,
+        ]));
+        let ids = created(runner, &args, format!("={0}:{1} ={0}:{1}.0", session, i))?;
+        let (window_id, first_pane) = ids.split_once(' ').unwrap_or((&ids, &ids));
+        let (window_id, first_pane) = (window_id.to_string(), first_pane.to_string());
+
+        if i == 0
+            && let Some(index) = window.window_index
+        {
+            run(
+                runner,
+                &[
+                    "move-window",
+                    "-s",
+                    &window_id,
+                    "-t",
+                    &format!("={}:{}", session, index),
+                ],
+            )?;
+        }
+        for (key, value) in &window.options {
+            run(runner, &["set-option", "-w", "-t", &window_id, key, value])?;
+        }
+
+        let mut pane_ids = vec![first_pane];
+        for (j, pane) in window.panes.iter().enumerate().skip(1) {
+            let mut args: Vec<String> =
+                ["split-window", "-t", &window_id, "-P", "-F", "#{pane_id}"]
+                    .map(String::from)
+                    .to_vec();
+            args.extend([
+                "-c".to_string(),
+                pane_dir(pane).to_string_lossy().into_owned(),
+            ]);
+            args.extend(env_args([
+                &config.environment,
+                &window.environment,
+                &pane.environment,
+            ]));
+            pane_ids.push(created(runner, &args, format!("{}.{}", window_id, j))?);
+            // Re-tile so the next split has room
+            run(runner, &["select-layout", "-t", &window_id, "tiled"])?;
+        }
+        if let Some(layout) = &window.layout {
+            run(
+                runner,
+                &["select-layout", "-t", &window_id, &layout.to_string()],
+            )?;
+        }
+
+        let window_before = string_list(window.extra.get("shell_command_before"));
+        let mut focused_pane = None;
+        for (pane, pane_id) in window.panes.iter().zip(&pane_ids) {
+            let quiet = pane
+                .suppress_history
+                .or(config.suppress_history)
+                .unwrap_or(true);
+            // `enter: false` on the pane or a command types it without running it
+            let pane_enter = pane.extra.get("enter").and_then(Value::as_bool);
+            let commands = session_before
+                .iter()
+                .chain(&window_before)
+                .map(|command| (command.as_str(), true))
+                .chain(pane.shell_command.iter().map(|command| {
+                    let enter = command.enter().or(pane_enter).unwrap_or(true);
+                    (command.cmd.as_str(), enter)
+                }));
+            for (command, enter) in commands {
+                // A leading space keeps the command out of shell history
+                let text = if quiet {
+                    format!(" {}", command)
+                } else {
+                    command.to_string()
+                };
+                send(runner, pane_id, &text, enter)?;
+            }
+            if pane.focus == Some(true) {
+                focused_pane = Some(pane_id.clone());
+            }
+        }
+        if let Some(pane_id) = focused_pane {
+            run(runner, &["select-pane", "-t", &pane_id])?;
+        }
+        if window.focus == Some(true) || focused_window.is_none() {
+            focused_window = Some(window_id);
+        }
+    }
+
+    for (key, value) in string_map(config.extra.get("options")) {
+        run(
+            runner,
+            &["set-option", "-t", &format!("={}", session), &key, &value],
+        )?;
+    }
+    for (key, value) in string_map(config.extra.get("global_options")) {
+        run(runner, &["set-option", "-g", &key, &value])?;
+    }
+    if let Some(window_id) = focused_window {
+        run(runner, &["select-window", "-t", &window_id])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_driver_builds_windows_panes_and_focus() {
+        let yaml = "session_name: app\nstart_directory: /src/app\n\
+                    shell_command_before: nvm use\nwindows:\n\
+                    - window_name: editor\n  panes: [vim]\n\
+                    - window_name: server\n  layout: main-vertical\n  focus: true\n\
+                    \x20 environment: {PORT: '3000'}\n\
+                    \x20 panes:\n  - make run\n  - start_directory: log\n    focus: true\n";
+        let config = Config::from_yaml(yaml).unwrap();
+        let runner = process::RecordingRunner::default();
+        build_session(&runner, &config, (120, 40)).unwrap();
+
+        let commands = runner.commands.borrow();
+        assert_eq!(
+            commands[0],
+            "tmux new-session -d -s app -x 120 -y 40 -P -F #{window_id} #{pane_id} \
+             -n editor -c /src/app"
+        );
+        assert!(commands.contains(&"tmux send-keys -t =app:0.0 -l  nvm use".to_string()));
+        assert!(commands.contains(&"tmux send-keys -t =app:0.0 -l  vim".to_string()));
+        assert!(
+            commands.contains(
+                &"tmux new-window -d -t =app: -P -F #{window_id} #{pane_id} -n server \
+              -c /src/app -e PORT=3000"
+                    .to_string()
+            )
+        );
+        assert!(
+            commands.contains(
+                &"tmux split-window -t =app:1 -P -F #{pane_id} -c /src/app/log -e PORT=3000"
+                    .to_string()
+            )
+        );
+        assert!(commands.contains(&"tmux select-layout -t =app:1 main-vertical".to_string()));
+        let tail: Vec<&str> = commands.iter().rev().take(2).map(String::as_str).collect();
+        assert_eq!(
+            tail,
+            [
+                "tmux select-window -t =app:1",
+                "tmux select-pane -t =app:1.1"
+            ]
+        );
+    }
+
+    #[test]
+    fn before_script_runs_commands_and_project_scripts() {
+        for (script, expected) in [
+            ("sh -c 'docker compose up -d'", "sh -c docker compose up -d"),
+            ("./bin/setup --quiet", "/src/app/bin/setup --quiet"),
+        ] {
+            let mut config = Config::from_yaml(
+                "session_name: app\nstart_directory: /src/app\nwindows:\n- panes: [vim]\n",
+            )
+            .unwrap();
+            config
+                .extra
+                .insert("before_script".into(), Value::from(script));
+            let runner = process::RecordingRunner::default();
+            build_session(&runner, &config, (80, 24)).unwrap();
+            assert_eq!(runner.commands.borrow()[0], expected);
+        }
+    }
+
+    #[test]
+    fn config_options_are_read_from_set_lines() {
+        let conf = "set -g base-index 1\n\
+                    set-option -g default-terminal \"tmux-256color\"\n\
+                    set -as terminal-features ',xterm-256color:RGB'\n\
+                    # set -g base-index 5\n";
+        assert_eq!(option_values(conf, "base-index"), ["1"]);
+        assert_eq!(option_values(conf, "default-terminal"), ["tmux-256color"]);
+        assert_eq!(
+            option_values(conf, "terminal-features"),
+            [",xterm-256color:RGB"]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+/// Serialize a value as a TOML document.
+///
+/// Like `json.rs`, goes through `serde_yaml::Value` so no separate TOML
+/// dependency is needed. Lists of mappings become `[[array]]` tables and
+/// nested mappings `[table]` sections; nulls inside mappings are left out.
+pub fn to_toml<T: Serialize>(value: &T) -> Result<String> {
+    let Value::Mapping(map) = serde_yaml::to_value(value)? else {
+        anyhow::bail!("A TOML document must be a table");
+    };
+    let mut out = String::new();
+    write_table(&map, &[], &mut out)?;
+    Ok(out)
+}
+
+/// Whether a value is written as its own `[table]` or `[[array]]` section
+fn is_section(value: &Value) -> bool {
+    match value {
+        Value::Mapping(_) => true,
+        Value::Sequence(items) => !items.is_empty() && items.iter().all(Value::is_mapping),
+        Value::Tagged(tagged) => is_section(&tagged.value),
+        _ => false,
+    }
+}
+
+/// Write a table's `key = value` lines, then its sub-tables under `path`
+fn write_table(map: &Mapping, path: &[String], out: &mut String) -> Result<()> {
+    let entries = map
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| Ok((write_key(key)?, untag(value))))
+        .collect::<Result<Vec<_>>>()?;
+
+    for (key, value) in entries.iter().filter(|(_, value)| !is_section(value)) {
+        out.push_str(key);
+        out.push_str(" = ");
+        write_inline(value, out)?;
+        out.push('\n');
+    }
+
+    for (key, value) in entries.iter().filter(|(_, value)| is_section(value)) {
+        let mut path = path.to_vec();
+        path.push(key.clone());
+        let header = path.join(".");
+        match value {
+            Value::Mapping(map) => {
+                push_header(&format!("[{}]", header), out);
+                write_table(map, &path, out)?;
+            }
+            Value::Sequence(items) => {
+                for item in items.iter().filter_map(|item| untag(item).as_mapping()) {
+                    push_header(&format!("[[{}]]", header), out);
+                    write_table(item, &path, out)?;
+                }
+            }
+            _ => unreachable!("is_section only accepts mappings and lists of them"),
+        }
+    }
+    Ok(())
+}
+
+/// Start a section, separated from what came before by a blank line
+fn push_header(header: &str, out: &mut String) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(header);
+    out.push('\n');
+}
+
+fn untag(value: &Value) -> &Value {
+    match value {
+        Value::Tagged(tagged) => untag(&tagged.value),
+        value => value,
+    }
+}
+
+/// A key, bare when TOML allows it and quoted otherwise
+fn write_key(key: &Value) -> Result<String> {
+    let key = match key {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        other => anyhow::bail!("Unsupported TOML key: {:?}", other),
+    };
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        return Ok(key);
+    }
+    let mut quoted = String::new();
+    write_string(&key, &mut quoted);
+    Ok(quoted)
+}
+
+/// Write a value on one line: arrays as `[...]`, mappings as `{ ... }`
+fn write_inline(value: &Value, out: &mut String) -> Result<()> {
+    match value {
+        Value::Null => anyhow::bail!("TOML has no null value"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => match n.as_f64().filter(|_| n.is_f64()) {
+            Some(f) if f.is_nan() => out.push_str("nan"),
+            Some(f) if f.is_infinite() => out.push_str(if f > 0.0 { "inf" } else { "-inf" }),
+            // Debug keeps the `.0` TOML needs to read it back as a float
+            Some(f) => out.push_str(&format!("{:?}", f)),
+            None => out.push_str(&n.to_string()),
+        },
+        Value::String(s) => write_string(s, out),
+        Value::Sequence(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_inline(item, out)?;
+            }
+            out.push(']');
+        }
+        Value::Mapping(map) => {
+            let entries: Vec<(&Value, &Value)> =
+                map.iter().filter(|(_, value)| !value.is_null()).collect();
+            if entries.is_empty() {
+                out.push_str("{}");
+                return Ok(());
+            }
+            out.push_str("{ ");
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&write_key(key)?);
+                out.push_str(" = ");
+                write_inline(item, out)?;
+            }
+            out.push_str(" }");
+        }
+        Value::Tagged(tagged) => write_inline(&tagged.value, out)?,
+    }
+    Ok(())
+}
+
+/// Write a basic string, escaping what TOML requires
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04X}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Config, Pane, Window, WindowLayout};
+
+    #[test]
+    fn renders_config_as_toml() {
+        let mut editor = Window::new(
+            Some("editor".into()),
+            Some(WindowLayout::MainVertical),
+            vec![Pane::new(vec!["vim \"notes\"".into()]), Pane::empty()],
+        );
+        editor.environment.insert("RUST_LOG".into(), "debug".into());
+        editor.panes[0].focus = Some(true);
+        let mut cfg = Config::new("app".into(), "/src/app".into(), vec![editor]);
+        cfg.suppress_history = Some(true);
+        cfg.extra.insert("my key".into(), Value::Null);
+
+        assert_eq!(
+            to_toml(&cfg).unwrap(),
+            r#"session_name = "app"
+start_directory = "/src/app"
+suppress_history = true
+
+[[windows]]
+window_name = "editor"
+layout = "main-vertical"
+
+[windows.environment]
+RUST_LOG = "debug"
+
+[[windows.panes]]
+shell_command = ["vim \"notes\""]
+focus = true
+
+[[windows.panes]]
+"#
+        );
+    }
+
+    #[test]
+    fn inline_values_and_keys_are_escaped() {
+        let value: Value =
+            serde_yaml::from_str("\"a b\": [1, 2.5, {x: y}, [true]]\nc: \"tab\\there\"\n").unwrap();
+        assert_eq!(
+            to_toml(&value).unwrap(),
+            "\"a b\" = [1, 2.5, { x = \"y\" }, [true]]\nc = \"tab\\there\"\n"
+        );
+        assert!(to_toml(&serde_yaml::from_str::<Value>("a: [null]").unwrap()).is_err());
+        assert!(to_toml(&vec![1]).is_err());
+    }
+}
@@ -0,0 +1,638 @@
+//! `tmuxify tui`: a full-screen builder showing the window/pane tree on the
+//! left and a live YAML preview on the right.
+
+use anyhow::Result;
+use console::{Key, Term, style};
+use dialoguer::Input;
+use std::path::Path;
+
+use crate::cli::Args;
+use crate::model::{Config, Pane, Settings, TmuxpLocation, Window, WindowLayout};
+use crate::ops::{highlight, load, show, theme, validate, write};
+
+/// Key help shown on the last line
+const HELP: &str = "↑↓ move  a window  p pane  r rename  d delete  K/J reorder  \
+                    l layout  s save  q quit";
+
+/// Widest the tree column gets
+const MAX_TREE_WIDTH: usize = 40;
+
+/// A row of the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Window(usize),
+    Pane(usize, usize),
+}
+
+impl Node {
+    fn window(&self) -> usize {
+        match *self {
+            Node::Window(w) | Node::Pane(w, _) => w,
+        }
+    }
+}
+
+/// The config being built and the selected row
+struct Builder {
+    config: Config,
+    selected: usize,
+    modified: bool,
+    status: String,
+    /// The last key warned that `l` would replace a custom layout
+    layout_warned: bool,
+}
+
+/// The window a new config starts with
+fn default_window() -> Window {
+    Window::new(Some("main".to_string()), None, vec![Pane::empty()])
+}
+
+impl Builder {
+    /// Start editing `config`; one without windows gets a default window,
+    /// since there is always a selected row
+    fn new(mut config: Config) -> Self {
+        let mut status = String::new();
+        if config.windows.is_empty() {
+            config.windows.push(default_window());
+            status = "The config had no windows; added one".to_string();
+        }
+        Self {
+            config,
+            selected: 0,
+            modified: false,
+            status,
+            layout_warned: false,
+        }
+    }
+
+    /// Rows of the tree: each window followed by its panes
+    fn nodes(&self) -> Vec<Node> {
+        self.config
+            .windows
+            .iter()
+            .enumerate()
+            .flat_map(|(w, window)| {
+                std::iter::once(Node::Window(w))
+                    .chain((0..window.panes.len()).map(move |p| Node::Pane(w, p)))
+            })
+            .collect()
+    }
+
+    fn current(&self) -> Node {
+        self.nodes()[self.selected]
+    }
+
+    fn select(&mut self, node: Node) {
+        self.selected = self.nodes().iter().position(|n| *n == node).unwrap_or(0);
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let last = self.nodes().len().saturating_sub(1);
+        self.selected = if down {
+            (self.selected + 1).min(last)
+        } else {
+            self.selected.saturating_sub(1)
+        };
+    }
+
+    fn changed(&mut self, status: impl Into<String>) {
+        self.modified = true;
+        self.status = status.into();
+    }
+
+    /// Insert a window with one empty pane after the current one
+    fn add_window(&mut self) {
+        let at = self.current().window() + 1;
+        let name = format!("window{}", self.config.windows.len() + 1);
+        self.config
+            .windows
+            .insert(at, Window::new(Some(name), None, vec![Pane::empty()]));
+        self.select(Node::Window(at));
+        self.changed("Added a window");
+    }
+
+    /// Insert an empty pane after the current pane (or last, on a window)
+    fn add_pane(&mut self) {
+        let node = self.current();
+        let panes = &mut self.config.windows[node.window()].panes;
+        let at = match node {
+            Node::Pane(_, p) => p + 1,
+            Node::Window(_) => panes.len(),
+        };
+        panes.insert(at, Pane::empty());
+        self.select(Node::Pane(node.window(), at));
+        self.changed("Added a pane");
+    }
+
+    /// Delete the current window or pane, keeping at least one of each
+    fn delete(&mut self) {
+        match self.current() {
+            Node::Window(_) if self.config.windows.len() == 1 => {
+                self.status = "A config needs at least one window".to_string();
+            }
+            Node::Window(w) => {
+                self.config.windows.remove(w);
+                self.select(Node::Window(w.min(self.config.windows.len() - 1)));
+                self.changed("Deleted the window");
+            }
+            Node::Pane(w, _) if self.config.windows[w].panes.len() == 1 => {
+                self.status = "A window needs at least one pane".to_string();
+            }
+            Node::Pane(w, p) => {
+                let panes = &mut self.config.windows[w].panes;
+                panes.remove(p);
+                let p = p.min(panes.len() - 1);
+                self.select(Node::Pane(w, p));
+                self.changed("Deleted the pane");
+            }
+        }
+    }
+
+    /// Swap the current window with its neighbour, or the current pane with
+    /// its neighbour in the same window
+    fn reorder(&mut self, down: bool) {
+        let node = self.current();
+        let (items, index) = match node {
+            Node::Window(w) => (self.config.windows.len(), w),
+            Node::Pane(w, p) => (self.config.windows[w].panes.len(), p),
+        };
+        let target = if down {
+            index + 1
+        } else {
+            match index.checked_sub(1) {
+                Some(target) => target,
+                None => return,
+            }
+        };
+        if target >= items {
+            return;
+        }
+        match node {
+            Node::Window(w) => {
+                self.config.windows.swap(w, target);
+                self.select(Node::Window(target));
+            }
+            Node::Pane(w, p) => {
+                self.config.windows[w].panes.swap(p, target);
+                self.select(Node::Pane(w, target));
+            }
+        }
+        self.changed("Moved");
+    }
+
+    /// Step the current window through no layout and the built-in layouts.
+    /// A custom layout can't be cycled back to, so it is only replaced when
+    /// the previous key already warned about it (`confirmed`).
+    fn cycle_layout(&mut self, confirmed: bool) {
+        let current = self.current().window();
+        let window = &mut self.config.windows[current];
+        if matches!(window.layout, Some(WindowLayout::Custom(_))) && !confirmed {
+            self.status =
+                "This window has a custom layout; press l again to replace it".to_string();
+            self.layout_warned = true;
+            return;
+        }
+        let layouts = WindowLayout::all();
+        let next = match window.layout.as_ref() {
+            None | Some(WindowLayout::Custom(_)) => layouts.first(),
+            Some(layout) => layouts
+                .iter()
+                .position(|l| l == layout)
+                .and_then(|i| layouts.get(i + 1)),
+        };
+        window.layout = next.cloned();
+        let label = window
+            .layout
+            .as_ref()
+            .map_or("no layout".to_string(), |l| l.to_string());
+        self.changed(format!("Layout: {}", label));
+    }
+
+    /// The text `r` starts from: a window's name or a pane's commands
+    fn label(&self) -> String {
+        match self.current() {
+            Node::Window(w) => self.config.windows[w]
+                .window_name
+                .clone()
+                .unwrap_or_default(),
+            Node::Pane(w, p) => self.config.windows[w].panes[p].commands().join("; "),
+        }
+    }
+
+    /// Rename the current window, or replace the current pane's commands,
+    /// which are edited as one line joined with `; `
+    fn rename(&mut self, text: &str) {
+        let text = text.trim();
+        if text == self.label() {
+            return;
+        }
+        match self.current() {
+            Node::Window(w) => {
+                self.config.windows[w].window_name = (!text.is_empty()).then(|| text.to_string());
+                self.changed("Renamed the window");
+            }
+            Node::Pane(w, p) => {
+                let commands = text
+                    .split("; ")
+                    .map(str::trim)
+                    .filter(|command| !command.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                self.config.windows[w].panes[p].set_commands(commands);
+                self.changed("Changed the pane's command");
+            }
+        }
+    }
+
+    /// One line per node, without selection highlighting
+    fn tree_lines(&self) -> Vec<String> {
+        self.nodes()
+            .into_iter()
+            .map(|node| match node {
+                Node::Window(w) => {
+                    let window = &self.config.windows[w];
+                    let layout = window
+                        .layout
+                        .as_ref()
+                        .map(|l| format!(" [{}]", l))
+                        .unwrap_or_default();
+                    format!(
+                        "{}{}",
+                        window.window_name.as_deref().unwrap_or("(unnamed)"),
+                        layout
+                    )
+                }
+                Node::Pane(w, p) => {
+                    let pane = &self.config.windows[w].panes[p];
+                    let branch = if p + 1 == self.config.windows[w].panes.len() {
+                        "└─"
+                    } else {
+                        "├─"
+                    };
+                    let command = match pane.shell_command.is_empty() {
+                        true => "(shell)".to_string(),
+                        false => pane.commands().join("; "),
+                    };
+                    format!("  {} {}", branch, command)
+                }
+            })
+            .collect()
+    }
+
+    /// First preview line of the current window's YAML entry
+    fn preview_anchor(&self, yaml: &str) -> usize {
+        let window = self.current().window();
+        yaml.lines()
+            .enumerate()
+            .filter(|(_, line)| line.starts_with("- "))
+            .nth(window)
+            .map_or(0, |(i, _)| i)
+    }
+}
+
+/// The whole screen as `height` lines of at most `width` columns
+fn render(builder: &Builder, width: usize, height: usize) -> Vec<String> {
+    let body = height.saturating_sub(3);
+    let tree_width = (width / 3).clamp(10, MAX_TREE_WIDTH);
+    let preview_width = width.saturating_sub(tree_width + 3);
+
+    let tree = builder.tree_lines();
+    let tree_offset = (builder.selected + 1).saturating_sub(body);
+    let yaml = builder.config.to_yaml().unwrap_or_default();
+    let anchor = builder.preview_anchor(&yaml);
+    let preview_offset = if anchor + 4 > body { anchor } else { 0 };
+    let preview: Vec<String> = highlight::yaml(&yaml)
+        .lines()
+        .skip(preview_offset)
+        .map(str::to_string)
+        .collect();
+
+    let modified = if builder.modified { " (modified)" } else { "" };
+    let mut lines = vec![
+        console::truncate_str(
+            &format!(
+                "{} {}{}",
+                style("tmuxify tui").bold().cyan(),
+                builder.config.session_name,
+                style(modified).yellow()
+            ),
+            width,
+            "…",
+        )
+        .into_owned(),
+    ];
+    for row in 0..body {
+        let index = row + tree_offset;
+        let left = tree.get(index).map_or("", String::as_str);
+        let left = console::truncate_str(left, tree_width, "…");
+        let left = console::pad_str(&left, tree_width, console::Alignment::Left, None);
+        let left = if index == builder.selected {
+            style(left).reverse().to_string()
+        } else {
+            left.into_owned()
+        };
+        let right = preview.get(row).map_or("", String::as_str);
+        lines.push(format!(
+            "{} {} {}",
+            left,
+            style("│").dim(),
+            console::truncate_str(right, preview_width, "…")
+        ));
+    }
+    lines.push(console::truncate_str(&builder.status, width, "…").into_owned());
+    lines.push(
+        style(console::truncate_str(HELP, width, "…"))
+            .dim()
+            .to_string(),
+    );
+    lines
+}
+
+/// The alternate screen, left again on drop so errors don't strand the user
+struct Screen<'a> {
+    term: &'a Term,
+}
+
+impl<'a> Screen<'a> {
+    fn enter(term: &'a Term) -> Result<Self> {
+        term.write_str("\x1b[?1049h")?;
+        term.hide_cursor()?;
+        Ok(Self { term })
+    }
+
+    fn draw(&self, lines: &[String]) -> Result<()> {
+        self.term.move_cursor_to(0, 0)?;
+        for (i, line) in lines.iter().enumerate() {
+            self.term.clear_line()?;
+            self.term.write_str(line)?;
+            if i + 1 < lines.len() {
+                self.term.write_str("\r\n")?;
+            }
+        }
+        self.term.flush()?;
+        Ok(())
+    }
+
+    /// Edit `initial` on the status row
+    fn prompt(&self, row: usize, label: &str, initial: &str) -> Result<String> {
+        self.term.move_cursor_to(0, row)?;
+        self.term.clear_line()?;
+        self.term.show_cursor()?;
+        let text = Input::<String>::with_theme(theme::get())
+            .with_prompt(label)
+            .with_initial_text(initial)
+            .allow_empty(true)
+            .interact_text_on(self.term);
+        self.term.hide_cursor()?;
+        Ok(text?)
+    }
+}
+
+impl Drop for Screen<'_> {
+    fn drop(&mut self) {
+        let _ = self.term.show_cursor();
+        let _ = self.term.write_str("\x1b[?1049l");
+        let _ = self.term.flush();
+    }
+}
+
+/// The project's config, or a new one from `--preset` or a single window
+fn initial_config(args: &Args, project_dir: &Path) -> Result<(Config, Option<TmuxpLocation>)> {
+    if let Ok((path, location)) = load::locate_project_config(project_dir, args.session.as_deref())
+    {
+        return Ok((load::load_config(&path)?, Some(location)));
+    }
+    let session_name = args.session.clone().unwrap_or_else(|| {
+        project_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().replace('\u{FFFD}', "_"))
+            .unwrap_or_else(|| "my-session".to_string())
+    });
+    let start_dir = match &args.start_dir {
+        Some(dir) => validate::resolve_user_path(dir),
+        None => project_dir.to_path_buf(),
+    };
+    let windows = match args.preset {
+        Some(preset) => preset.windows(project_dir),
+        None => vec![default_window()],
+    };
+    Ok((Config::new(session_name, start_dir, windows), None))
+}
+
+/// Edit the project's config (or a new one) full-screen, then write it
+pub fn run(args: &Args) -> Result<()> {
+    validate::require_terminal("The TUI builder")?;
+    let project_dir = args.project_dir()?;
+    let (config, found) = initial_config(args, &project_dir)?;
+    let location = args.tmuxp_location.or(found).unwrap_or(TmuxpLocation::Home);
+
+    let term = Term::stderr();
+    let mut builder = Builder::new(config);
+    let save = {
+        let screen = Screen::enter(&term)?;
+        let mut confirm_quit = false;
+        loop {
+            let (rows, columns) = term.size();
+            let (width, height) = (usize::from(columns), usize::from(rows));
+            screen.draw(&render(&builder, width, height))?;
+            let key = term.read_key_raw()?;
+            if confirm_quit {
+                if key == Key::Char('y') {
+                    break false;
+                }
+                confirm_quit = false;
+                builder.status.clear();
+                continue;
+            }
+            builder.status.clear();
+            let layout_warned = std::mem::take(&mut builder.layout_warned);
+            match key {
+                Key::ArrowDown | Key::Char('j') => builder.move_selection(true),
+                Key::ArrowUp | Key::Char('k') => builder.move_selection(false),
+                Key::Char('J') => builder.reorder(true),
+                Key::Char('K') => builder.reorder(false),
+                Key::Char('a') => builder.add_window(),
+                Key::Char('p') => builder.add_pane(),
+                Key::Char('d') | Key::Del => builder.delete(),
+                Key::Char('l') => builder.cycle_layout(layout_warned),
+                Key::Char('r') | Key::Enter => {
+                    let label = match builder.current() {
+                        Node::Window(_) => "Window name",
+                        Node::Pane(..) => "Command",
+                    };
+                    let text = screen.prompt(height.saturating_sub(2), label, &builder.label())?;
+                    builder.rename(&text);
+                }
+                Key::Char('s') => break true,
+                Key::Char('q') | Key::Escape | Key::CtrlC if builder.modified => {
+                    builder.status = "Discard your changes? (y/N)".to_string();
+                    confirm_quit = true;
+                }
+                Key::Char('q') | Key::Escape | Key::CtrlC => break false,
+                _ => {}
+            }
+        }
+    };
+
+    if !save {
+        println!("Nothing was written.");
+        return Ok(());
+    }
+    let config = builder.config;
+    let options = write::WriteOptions {
+        dry_run: args.dry_run,
+        force: args.force,
+        envrc: write::envrc_options(&project_dir, &Settings::load()?),
+        format: args.format,
+        ..Default::default()
+    };
+    let result = write::write_config(&config, location, &project_dir, &options)?;
+    if !args.dry_run {
+        show::print_summary(&config);
+        result.print_summary();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> Builder {
+        let windows = vec![
+            Window::new(
+                Some("editor".into()),
+                None,
+                vec![Pane::new(vec!["vim".into()])],
+            ),
+            Window::new(
+                Some("server".into()),
+                None,
+                vec![Pane::new(vec!["make run".into()]), Pane::empty()],
+            ),
+        ];
+        Builder::new(Config::new("app".into(), "/src/app".into(), windows))
+    }
+
+    fn names(builder: &Builder) -> Vec<&str> {
+        builder
+            .config
+            .windows
+            .iter()
+            .map(|w| w.window_name.as_deref().unwrap_or(""))
+            .collect()
+    }
+
+    #[test]
+    fn config_without_windows_starts_with_one() {
+        let mut builder = Builder::new(Config::new("app".into(), "/src/app".into(), vec![]));
+        assert_eq!(names(&builder), ["main"]);
+        assert_eq!(builder.current(), Node::Window(0));
+        builder.move_selection(true);
+        builder.move_selection(true);
+        assert_eq!(builder.current(), Node::Pane(0, 0));
+    }
+
+    #[test]
+    fn tree_lists_windows_then_their_panes() {
+        let builder = builder();
+        assert_eq!(
+            builder.tree_lines(),
+            [
+                "editor",
+                "  └─ vim",
+                "server",
+                "  ├─ make run",
+                "  └─ (shell)"
+            ]
+        );
+    }
+
+    #[test]
+    fn nodes_can_be_added_renamed_reordered_and_deleted() {
+        let mut builder = builder();
+        builder.add_window();
+        assert_eq!(names(&builder), ["editor", "window3", "server"]);
+        assert_eq!(builder.current(), Node::Window(1));
+
+        builder.rename("logs");
+        builder.add_pane();
+        builder.rename("tail -f log/dev.log");
+        assert_eq!(builder.current(), Node::Pane(1, 1));
+        assert_eq!(
+            builder.config.windows[1].panes[1].shell_command,
+            ["tail -f log/dev.log"]
+        );
+
+        builder.select(Node::Window(1));
+        builder.reorder(false);
+        assert_eq!(names(&builder), ["logs", "editor", "server"]);
+        assert_eq!(builder.current(), Node::Window(0));
+
+        builder.select(Node::Pane(2, 1));
+        builder.reorder(false);
+        assert!(builder.config.windows[2].panes[0].shell_command.is_empty());
+
+        builder.select(Node::Window(1));
+        builder.delete();
+        assert_eq!(names(&builder), ["logs", "server"]);
+        builder.select(Node::Pane(0, 0));
+        builder.delete();
+        builder.delete();
+        assert_eq!(builder.config.windows[0].panes.len(), 1);
+        assert_eq!(builder.status, "A window needs at least one pane");
+        assert!(builder.modified);
+    }
+
+    #[test]
+    fn layout_cycles_back_to_none() {
+        let mut builder = builder();
+        for _ in WindowLayout::all() {
+            builder.cycle_layout(false);
+            assert!(builder.config.windows[0].layout.is_some());
+        }
+        builder.cycle_layout(false);
+        assert_eq!(builder.config.windows[0].layout, None);
+    }
+
+    #[test]
+    fn custom_layouts_are_replaced_only_after_a_warning() {
+        let mut builder = builder();
+        let (custom, _) = WindowLayout::from_split("h70").unwrap();
+        builder.config.windows[0].layout = Some(custom.clone());
+        builder.cycle_layout(false);
+        assert_eq!(builder.config.windows[0].layout, Some(custom));
+        assert!(builder.layout_warned);
+        builder.cycle_layout(true);
+        assert_eq!(
+            builder.config.windows[0].layout.as_ref(),
+            WindowLayout::all().first()
+        );
+    }
+
+    #[test]
+    fn pane_commands_are_edited_as_one_line() {
+        let mut builder = builder();
+        builder.select(Node::Pane(0, 0));
+        builder.rename(" cd src;  vim ");
+        assert_eq!(
+            builder.config.windows[0].panes[0].commands(),
+            ["cd src", "vim"]
+        );
+        assert_eq!(builder.label(), "cd src; vim");
+        builder.rename("");
+        assert!(builder.config.windows[0].panes[0].shell_command.is_empty());
+    }
+
+    #[test]
+    fn render_fits_the_terminal() {
+        let builder = builder();
+        let lines = render(&builder, 60, 12);
+        assert_eq!(lines.len(), 12);
+        assert!(
+            lines
+                .iter()
+                .all(|line| console::measure_text_width(line) <= 60)
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use anyhow::{Context, Result};
+use console::style;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::Args;
+use crate::ops::{process, validate};
+
+/// GitHub repository releases are published to
+const REPO: &str = "rhymiz/tmuxify";
+
+/// A GitHub release, as returned by the releases API
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+/// A downloadable file attached to a release
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Check for a newer release and replace the running binary with it
+pub fn run(args: &Args, check: bool) -> Result<()> {
+    args.require_network("Checking for updates")?;
+    let current = env!("CARGO_PKG_VERSION");
+    let release = latest_release()?;
+    let latest = release.tag_name.trim_start_matches('v');
+
+    if !is_newer(latest, current)? {
+        println!(
+            "{} tmuxify {} is up to date",
+            style("✓").green().bold(),
+            current
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} tmuxify {} is available (installed: {})",
+        style("↑").cyan().bold(),
+        style(latest).cyan(),
+        current
+    );
+    if check {
+        println!(
+            "Run {} to install it.",
+            style("tmuxify self-update").yellow()
+        );
+        return Ok(());
+    }
+
+    let archive_name = asset_name();
+    let archive = release
+        .assets
+        .iter()
+        .find(|a| a.name == archive_name)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Release {} has no build for this platform ({})",
+                release.tag_name,
+                archive_name
+            )
+        })?;
+
+    let exe = std::env::current_exe().context("Failed to locate the running binary")?;
+    if args.dry_run {
+        println!(
+            "[DRY RUN] Would download {} and replace {}",
+            archive.name,
+            exe.display()
+        );
+        return Ok(());
+    }
+
+    // A fresh private directory (removed when dropped), so nobody else can
+    // swap the download between the checksum and the install
+    let work_dir = tempfile::Builder::new()
+        .prefix("tmuxify-update-")
+        .tempdir()
+        .context("Failed to create a temporary directory")?;
+    install(&release, archive, work_dir.path(), &exe)?;
+
+    println!(
+        "{} Updated {} to {}",
+        style("✓").green().bold(),
+        exe.display(),
+        latest
+    );
+    Ok(())
+}
+
+/// Download, verify, extract, and swap in the new binary. The archive is
+/// opened once, and the checksum and extraction both read that handle.
+fn install(release: &Release, archive: &Asset, work_dir: &Path, exe: &Path) -> Result<()> {
+    let archive_path = work_dir.join(&archive.name);
+    download(&archive.browser_download_url, &archive_path)?;
+    let mut archive_file = File::open(&archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+
+    let expected = expected_checksum(release, &archive.name, work_dir)?;
+    let actual = sha256(&archive_file)?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+        anyhow::bail!(
+            "Checksum mismatch for {} (expected {}, got {}); not installing",
+            archive.name,
+            expected,
+            actual
+        );
+    }
+    println!("{} Checksum verified", style("✓").green().bold());
+
+    archive_file.seek(SeekFrom::Start(0))?;
+    process::run_with_input(
+        Command::new("tar").args(["-xzf", "-", "-C"]).arg(work_dir),
+        archive_file,
+        process::SLOW_TIMEOUT,
+    )
+    .with_context(|| format!("Failed to extract {}", archive.name))?;
+
+    let binary = find_binary(work_dir)
+        .ok_or_else(|| anyhow::anyhow!("No tmuxify binary found in {}", archive.name))?;
+    replace_binary(&binary, exe)
+}
+
+/// Fetch the latest release metadata from GitHub
+fn latest_release() -> Result<Release> {
+    let url = format!("https://api.github.com/repos/{}/releases/latest", REPO);
+    let body = fetch(&url)?;
+    // JSON is valid YAML, so serde_yaml can read the API response without an extra dependency
+    serde_yaml::from_str(&body).context("Failed to parse the GitHub releases response")
+}
+
+/// Release archive name for the current platform, e.g. `tmuxify-x86_64-linux.tar.gz`
+fn asset_name() -> String {
+    format!(
+        "tmuxify-{}-{}.tar.gz",
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    )
+}
+
+/// Find the published SHA-256 for an archive, from `<archive>.sha256` or `SHA256SUMS`
+fn expected_checksum(release: &Release, archive_name: &str, work_dir: &Path) -> Result<String> {
+    let sidecar = format!("{}.sha256", archive_name);
+    let asset = release
+        .assets
+        .iter()
+        .find(|a| a.name == sidecar || a.name == "SHA256SUMS")
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Release {} publishes no checksum for {}; refusing to install",
+                release.tag_name,
+                archive_name
+            )
+        })?;
+
+    let path = work_dir.join(&asset.name);
+    download(&asset.browser_download_url, &path)?;
+    let content = fs::read_to_string(&path)?;
+
+    parse_checksum(&content, archive_name)
+        .ok_or_else(|| anyhow::anyhow!("No checksum for {} in {}", archive_name, asset.name))
+}
+
+/// Pick the hash for `file` out of `sha256sum`-style output (or a bare hash)
+fn parse_checksum(content: &str, file: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let valid = hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+        match parts.next() {
+            Some(name) if valid && name.trim_start_matches('*') == file => Some(hash.to_string()),
+            None if valid => Some(hash.to_string()),
+            _ => None,
+        }
+    })
+}
+
+/// Compare dotted versions numerically; true if `latest` is newer than `current`
+fn is_newer(latest: &str, current: &str) -> Result<bool> {
+    let parse = |v: &str| -> Result<Vec<u64>> {
+        v.split(['.', '-'])
+            .take(3)
+            .map(|part| {
+                part.parse()
+                    .with_context(|| format!("Unrecognized version: {}", v))
+            })
+            .collect()
+    };
+    Ok(parse(latest)? > parse(current)?)
+}
+
+fn fetch(url: &str) -> Result<String> {
+    process::run(
+        Command::new("curl").args(["-fsSL", "-H", "Accept: application/vnd.github+json", url]),
+        process::SLOW_TIMEOUT,
+    )
+    .with_context(|| format!("Request to {} failed", url))
+}
+
+fn download(url: &str, dest: &Path) -> Result<()> {
+    process::run(
+        Command::new("curl")
+            .args(["-fsSL", "-o"])
+            .arg(dest)
+            .arg(url),
+        process::SLOW_TIMEOUT,
+    )
+    .with_context(|| format!("Download of {} failed", url))?;
+    Ok(())
+}
+
+/// SHA-256 of an open file via `sha256sum` or, on macOS, `shasum -a 256`
+fn sha256(file: &File) -> Result<String> {
+    let input = file.try_clone()?;
+    let output = if validate::has_binary("sha256sum") {
+        process::run_with_input(&mut Command::new("sha256sum"), input, process::SLOW_TIMEOUT)
+    } else if validate::has_binary("shasum") {
+        process::run_with_input(
+            Command::new("shasum").args(["-a", "256"]),
+            input,
+            process::SLOW_TIMEOUT,
+        )
+    } else {
+        anyhow::bail!("Neither sha256sum nor shasum is available to verify the download");
+    }?;
+
+    output
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Unexpected checksum output"))
+}
+
+/// Look for the extracted binary at the top level or one directory down
+fn find_binary(dir: &Path) -> Option<PathBuf> {
+    let direct = dir.join("tmuxify");
+    if direct.is_file() {
+        return Some(direct);
+    }
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("tmuxify"))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Copy the new binary next to the old one, then rename it into place
+fn replace_binary(new: &Path, exe: &Path) -> Result<()> {
+    let staged = exe.with_extension("new");
+    fs::copy(new, &staged).with_context(|| {
+        format!(
+            "Failed to write {} (do you have permission to update it?)",
+            staged.display()
+        )
+    })?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+
+    fs::rename(&staged, exe).with_context(|| format!("Failed to replace {}", exe.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer("0.10.0", "0.9.1").unwrap());
+        assert!(is_newer("1.0.0", "0.1.0").unwrap());
+        assert!(!is_newer("0.1.0", "0.1.0").unwrap());
+        assert!(!is_newer("0.1.0", "0.2.0").unwrap());
+        assert!(is_newer("nightly", "0.1.0").is_err());
+    }
+
+    #[test]
+    fn hashes_an_open_file() {
+        if !validate::has_binary("sha256sum") && !validate::has_binary("shasum") {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive.tar.gz");
+        fs::write(&path, "test").unwrap();
+        let file = File::open(&path).unwrap();
+        // The handle keeps the verified content even if the path is replaced
+        fs::remove_file(&path).unwrap();
+        fs::write(&path, "swapped").unwrap();
+        assert_eq!(sha256(&file).unwrap(), HASH);
+    }
+
+    #[test]
+    fn parses_checksum_files() {
+        let sums = format!(
+            "{}  other.tar.gz\n{} *tmuxify.tar.gz\n",
+            "0".repeat(64),
+            HASH
+        );
+        assert_eq!(
+            parse_checksum(&sums, "tmuxify.tar.gz").as_deref(),
+            Some(HASH)
+        );
+        assert_eq!(
+            parse_checksum(HASH, "tmuxify.tar.gz").as_deref(),
+            Some(HASH)
+        );
+        assert_eq!(parse_checksum(&sums, "missing.tar.gz"), None);
+        assert_eq!(
+            parse_checksum("not-a-hash  tmuxify.tar.gz", "tmuxify.tar.gz"),
+            None
+        );
+    }
+}
@@ -1,70 +1,184 @@
 use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
-/// Dependency that needs to be validated
+use crate::model::{DEFAULT_TERMINAL_SIZE, Driver, Settings};
+use crate::ops::{log, process, tmux};
+
+/// External program tmuxify runs
 #[derive(Debug)]
 pub struct Dependency {
     pub name: &'static str,
     pub binary: &'static str,
     pub package_name: &'static str,
+    /// Optional programs only enable a feature; tmuxify works without them
+    pub required: bool,
+    /// What the program enables, shown when an optional one is missing
+    pub feature: &'static str,
 }
 
 impl Dependency {
     /// Check if the dependency is installed
     pub fn is_installed(&self) -> bool {
-        which::which(self.binary).is_ok()
+        match self.binary {
+            // tmuxp is frequently installed outside of PATH (pipx, virtualenvs)
+            "tmuxp" => resolve_tmuxp(None).is_some(),
+            bin => has_binary(bin),
+        }
+    }
+
+    /// Whether tmuxify needs this program; tmuxp isn't needed when the
+    /// native driver starts sessions
+    pub fn is_required(&self) -> bool {
+        self.required
+            && !(self.binary == "tmuxp"
+                && Settings::load().is_ok_and(|s| s.driver == Driver::Native))
     }
 
     /// Get installation hint for missing dependency, adapted to available package manager
     pub fn install_hint(&self) -> String {
-        // Probe common package managers
-        let managers = [
-            ("brew", format!("brew install {}", self.package_name)),
-            (
-                "apt-get",
-                format!(
-                    "sudo apt-get update && sudo apt-get install -y {}",
-                    self.package_name
-                ),
-            ),
-            (
-                "apt",
-                format!(
-                    "sudo apt update && sudo apt install -y {}",
-                    self.package_name
-                ),
-            ),
-            ("dnf", format!("sudo dnf install -y {}", self.package_name)),
-            ("pacman", format!("sudo pacman -S --noconfirm {}", self.package_name)),
-            ("zypper", format!("sudo zypper install -y {}", self.package_name)),
-        ];
-
-        for (bin, cmd) in managers {
-            if which::which(bin).is_ok() {
-                return cmd;
-            }
-        }
+        self.install_command().unwrap_or_else(|| {
+            format!(
+                "Install '{}' using your system's package manager",
+                self.package_name
+            )
+        })
+    }
+
+    /// Shell command installing this dependency with the detected package
+    /// manager, if one was found
+    pub fn install_command(&self) -> Option<String> {
+        let pkg = self.package_name;
+        Some(match package_manager()? {
+            "brew" => format!("brew install {}", pkg),
+            "apt-get" => format!("sudo apt-get update && sudo apt-get install -y {}", pkg),
+            "apt" => format!("sudo apt update && sudo apt install -y {}", pkg),
+            "dnf" => format!("sudo dnf install -y {}", pkg),
+            "pacman" => format!("sudo pacman -S --noconfirm {}", pkg),
+            "zypper" => format!("sudo zypper install -y {}", pkg),
+            _ => return None,
+        })
+    }
+}
+
+/// Package managers used for install hints, in order of preference
+const PACKAGE_MANAGERS: &[&str] = &["brew", "apt-get", "apt", "dnf", "pacman", "zypper"];
+
+/// First available package manager, probed once on first use
+fn package_manager() -> Option<&'static str> {
+    static MANAGER: OnceLock<Option<&'static str>> = OnceLock::new();
+    *MANAGER.get_or_init(|| PACKAGE_MANAGERS.iter().copied().find(|bin| has_binary(bin)))
+}
+
+/// Binaries looked up on PATH so far, see [`find_binary`]
+static BINARIES: OnceLock<Mutex<HashMap<String, Option<PathBuf>>>> = OnceLock::new();
+
+/// tmuxp as resolved from the current directory, see [`resolve_tmuxp`]
+static TMUXP_FROM_CWD: Mutex<Option<Option<ResolvedTmuxp>>> = Mutex::new(None);
+
+/// Forget cached binary and tmuxp lookups, so a program installed since
+/// (e.g. by `doctor --fix`) is found on the next check
+pub fn clear_binary_cache() {
+    if let Some(Ok(mut cache)) = BINARIES.get().map(Mutex::lock) {
+        cache.clear();
+    }
+    if let Ok(mut tmuxp) = TMUXP_FROM_CWD.lock() {
+        *tmuxp = None;
+    }
+}
 
-        // Fallback generic hint
-        format!("Install '{}' using your system's package manager", self.package_name)
+/// Look up a binary on PATH; each name is probed at most once per run,
+/// unless [`clear_binary_cache`] is called
+pub fn find_binary(name: &str) -> Option<PathBuf> {
+    let cache = BINARIES.get_or_init(Default::default);
+
+    if let Some(found) = cache.lock().ok().and_then(|c| c.get(name).cloned()) {
+        return found;
+    }
+    let found = which::which(name).ok();
+    if let Ok(mut c) = cache.lock() {
+        c.insert(name.to_string(), found.clone());
     }
+    found
+}
+
+/// Whether a binary is on PATH (cached, see [`find_binary`])
+pub fn has_binary(name: &str) -> bool {
+    find_binary(name).is_some()
 }
 
-/// All required dependencies
+/// Programs tmuxify runs: the required core, then optional integrations
 pub const DEPENDENCIES: &[Dependency] = &[
     Dependency {
         name: "tmux",
         binary: "tmux",
         package_name: "tmux",
+        required: true,
+        feature: "",
     },
     Dependency {
         name: "tmuxp",
         binary: "tmuxp",
         package_name: "tmuxp",
+        required: true,
+        feature: "the tmuxp driver",
     },
     Dependency {
         name: "direnv",
         binary: "direnv",
         package_name: "direnv",
+        required: true,
+        feature: "",
+    },
+    Dependency {
+        name: "fzf",
+        binary: "fzf",
+        package_name: "fzf",
+        required: false,
+        feature: "fzf prompts for the fuzzy_finder setting",
+    },
+    Dependency {
+        name: "git",
+        binary: "git",
+        package_name: "git",
+        required: false,
+        feature: "the editor window's `git status` pane",
+    },
+    Dependency {
+        name: "zoxide",
+        binary: "zoxide",
+        package_name: "zoxide",
+        required: false,
+        feature: "`tmuxify open` by zoxide keywords",
+    },
+    Dependency {
+        name: "docker",
+        binary: "docker",
+        package_name: "docker",
+        required: false,
+        feature: "docker compose services offered as windows",
+    },
+    Dependency {
+        name: "make",
+        binary: "make",
+        package_name: "make",
+        required: false,
+        feature: "Makefile targets offered as windows",
+    },
+    Dependency {
+        name: "GitHub CLI",
+        binary: "gh",
+        package_name: "gh",
+        required: false,
+        feature: "`tmuxify share` as a gist",
+    },
+    Dependency {
+        name: "curl",
+        binary: "curl",
+        package_name: "curl",
+        required: false,
+        feature: "`tmuxify share` to a paste service and `import --url`",
     },
 ];
 
@@ -72,7 +186,7 @@ pub const DEPENDENCIES: &[Dependency] = &[
 pub fn check_dependencies() -> Result<()> {
     let mut missing = Vec::new();
 
-    for dep in DEPENDENCIES {
+    for dep in DEPENDENCIES.iter().filter(|dep| dep.is_required()) {
         if !dep.is_installed() {
             missing.push(dep);
         }
@@ -93,6 +207,88 @@ pub fn check_dependencies() -> Result<()> {
     Ok(())
 }
 
+/// How tmuxp can be invoked on this system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedTmuxp {
+    /// Shell command used to invoke tmuxp (e.g. `tmuxp` or `python3 -m tmuxp`)
+    pub command: String,
+    /// Human readable description of where tmuxp was found
+    pub source: &'static str,
+}
+
+/// Locate tmuxp, looking beyond PATH at project virtualenvs, pipx and
+/// `python -m tmuxp`. `project_dir` defaults to the current directory, and
+/// that lookup is cached since probing python spawns processes.
+pub fn resolve_tmuxp(project_dir: Option<&Path>) -> Option<ResolvedTmuxp> {
+    let resolved = match project_dir {
+        Some(dir) => probe_tmuxp(Some(dir)),
+        None => match TMUXP_FROM_CWD.lock() {
+            Ok(mut cached) => cached.get_or_insert_with(|| probe_tmuxp(None)).clone(),
+            Err(_) => probe_tmuxp(None),
+        },
+    };
+    match &resolved {
+        Some(t) => log::debug(format!(
+            "tmuxp resolved to `{}` via {}",
+            t.command, t.source
+        )),
+        None => log::debug("tmuxp not found"),
+    }
+    resolved
+}
+
+fn probe_tmuxp(project_dir: Option<&Path>) -> Option<ResolvedTmuxp> {
+    if has_binary("tmuxp") {
+        return Some(ResolvedTmuxp {
+            command: "tmuxp".to_string(),
+            source: "PATH",
+        });
+    }
+
+    // Project virtualenv; .envrc is evaluated from the project root, so keep it relative
+    let project_dir = project_dir
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok());
+    if let Some(dir) = &project_dir {
+        for venv in [".venv", "venv"] {
+            if dir.join(venv).join("bin").join("tmuxp").is_file() {
+                return Some(ResolvedTmuxp {
+                    command: format!("./{}/bin/tmuxp", venv),
+                    source: "project virtualenv",
+                });
+            }
+        }
+    }
+
+    // pipx installs into PIPX_BIN_DIR, defaulting to ~/.local/bin
+    let pipx_bin = std::env::var_os("PIPX_BIN_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".local").join("bin")));
+    if let Some(bin) = pipx_bin.map(|b| b.join("tmuxp")).filter(|b| b.is_file()) {
+        return Some(ResolvedTmuxp {
+            command: bin.display().to_string(),
+            source: "pipx",
+        });
+    }
+
+    // Importable as a module by the system python
+    for python in ["python3", "python"] {
+        let importable = process::output(
+            std::process::Command::new(python).args(["-c", "import tmuxp"]),
+            process::QUICK_TIMEOUT,
+        )
+        .is_ok_and(|o| o.success());
+        if importable {
+            return Some(ResolvedTmuxp {
+                command: format!("{} -m tmuxp", python),
+                source: "python module",
+            });
+        }
+    }
+
+    None
+}
+
 /// Detect which shell is being used
 pub fn detect_shell() -> Option<String> {
     std::env::var("SHELL")
@@ -110,12 +306,10 @@ pub fn check_direnv_hook() -> Result<bool> {
         _ => None,
     };
 
-    if let Some(rc_path) = rc_file {
-        if rc_path.exists() {
-            let content = std::fs::read_to_string(&rc_path)?;
-            let hook_pattern = format!("direnv hook {}", shell);
-            return Ok(content.contains(&hook_pattern));
-        }
+    if let Some(rc_path) = rc_file.filter(|path| path.exists()) {
+        let content = std::fs::read_to_string(&rc_path)?;
+        let hook_pattern = format!("direnv hook {}", shell);
+        return Ok(content.contains(&hook_pattern));
     }
 
     Ok(false)
@@ -141,29 +335,209 @@ pub fn get_shell_rc_path() -> Option<String> {
     })
 }
 
+/// Whether prompts can be shown: dialoguer reads stdin and draws on stderr
+pub fn is_interactive() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Size of the current terminal as (columns, lines), or tmux's default size
+/// for sessions started without a client when there is no terminal
+pub fn terminal_size() -> (usize, usize) {
+    console::Term::stderr()
+        .size_checked()
+        .map(|(lines, columns)| (usize::from(columns), usize::from(lines)))
+        .unwrap_or(DEFAULT_TERMINAL_SIZE)
+}
+
+/// Fail with a clear message when prompts can't be shown (e.g. under make or CI)
+pub fn require_terminal(what: &str) -> Result<()> {
+    if is_interactive() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "{} requires a terminal, but stdin/stderr are not TTYs.\n\
+         Run it from an interactive shell, or preview with --dry-run. \
+         For scripts, use tmuxify generate or the other non-prompting commands \
+         (add-window, show, doctor).",
+        what
+    ))
+}
+
 /// Check if currently running inside a tmux session
 pub fn is_inside_tmux() -> bool {
     std::env::var("TMUX").is_ok()
 }
 
 /// Get the current tmux session name if inside tmux
-pub fn get_current_tmux_session() -> Option<String> {
+pub fn get_current_tmux_session() -> Result<Option<String>> {
     if !is_inside_tmux() {
+        return Ok(None);
+    }
+
+    let name = tmux::query(&["display-message", "-p", "#S"])?;
+    Ok(Some(name.trim().to_string()))
+}
+
+/// A remote-development environment that provides its own terminal multiplexing
+#[derive(Debug)]
+pub struct RemoteEnvironment {
+    pub name: &'static str,
+    pub env_var: &'static str,
+}
+
+/// Remote-development environments where auto-attaching to tmux is skipped.
+/// Keep in sync with the guard emitted by `Config::generate_envrc`.
+pub const REMOTE_ENVIRONMENTS: &[RemoteEnvironment] = &[
+    RemoteEnvironment {
+        name: "GitHub Codespaces",
+        env_var: "CODESPACES",
+    },
+    RemoteEnvironment {
+        name: "Gitpod",
+        env_var: "GITPOD_WORKSPACE_ID",
+    },
+    RemoteEnvironment {
+        name: "JetBrains Gateway",
+        env_var: "JETBRAINS_REMOTE_RUN",
+    },
+];
+
+/// Detect the remote-development environment we are running in, if any
+pub fn detect_remote_environment() -> Option<&'static RemoteEnvironment> {
+    REMOTE_ENVIRONMENTS
+        .iter()
+        .find(|env| std::env::var(env.env_var).is_ok_and(|v| !v.is_empty()))
+}
+
+/// Check if currently running under Windows Subsystem for Linux
+pub fn is_wsl() -> bool {
+    if std::env::var("WSL_DISTRO_NAME").is_ok() || std::env::var("WSL_INTEROP").is_ok() {
+        return true;
+    }
+
+    std::fs::read_to_string("/proc/version")
+        .map(|v| v.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+/// Translate a Windows-style path (e.g. `C:\Users\me\proj`) to its WSL mount
+/// point (`/mnt/c/Users/me/proj`). Returns `None` for non-Windows paths.
+pub fn translate_windows_path(path: &str) -> Option<PathBuf> {
+    let mut chars = path.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    if chars.next() != Some(':') {
         return None;
     }
 
-    // Try to get session name from tmux
-    std::process::Command::new("tmux")
-        .args(["display-message", "-p", "#S"])
-        .output()
-        .ok()
-        .and_then(|output| {
-            if output.status.success() {
-                String::from_utf8(output.stdout)
-                    .ok()
-                    .map(|s| s.trim().to_string())
-            } else {
-                None
-            }
-        })
+    let rest = chars.as_str();
+    if !(rest.is_empty() || rest.starts_with('\\') || rest.starts_with('/')) {
+        return None;
+    }
+
+    let rest = rest.replace('\\', "/");
+    let rest = rest.trim_start_matches('/');
+    let mut translated = PathBuf::from("/mnt").join(drive.to_ascii_lowercase().to_string());
+    if !rest.is_empty() {
+        translated.push(rest);
+    }
+    Some(translated)
+}
+
+/// Resolve a user-supplied path, translating Windows-style paths when under WSL
+pub fn resolve_user_path(path: &Path) -> PathBuf {
+    path.to_str()
+        .filter(|_| is_wsl())
+        .and_then(translate_windows_path)
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Check if a path lives on a Windows drive mounted into WSL (`/mnt/<drive>/...`)
+pub fn is_wsl_windows_mount(path: &Path) -> bool {
+    let mut components = path.components();
+    matches!(components.next(), Some(std::path::Component::RootDir))
+        && components.next().and_then(|c| c.as_os_str().to_str()) == Some("mnt")
+        && components
+            .next()
+            .and_then(|c| c.as_os_str().to_str())
+            .is_some_and(|d| d.len() == 1 && d.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_core_programs_are_required() {
+        let required: Vec<&str> = DEPENDENCIES
+            .iter()
+            .filter(|dep| dep.required)
+            .map(|dep| dep.binary)
+            .collect();
+        assert_eq!(required, ["tmux", "tmuxp", "direnv"]);
+        let git = DEPENDENCIES.iter().find(|dep| dep.binary == "git").unwrap();
+        assert!(!git.required && git.package_name == "git");
+        assert!(
+            DEPENDENCIES
+                .iter()
+                .all(|dep| dep.required || !dep.feature.is_empty())
+        );
+    }
+
+    #[test]
+    fn binary_lookups_are_cached() {
+        let missing = "tmuxify-definitely-not-a-real-binary";
+        assert!(!has_binary(missing));
+        assert!(!has_binary(missing));
+        assert_eq!(resolve_tmuxp(None), resolve_tmuxp(None));
+    }
+
+    #[test]
+    fn clearing_the_cache_probes_again() {
+        let missing = "tmuxify-not-installed-yet";
+        assert!(!has_binary(missing));
+        resolve_tmuxp(None);
+        clear_binary_cache();
+        assert!(
+            !BINARIES
+                .get()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .contains_key(missing)
+        );
+        assert!(!has_binary(missing));
+    }
+
+    #[test]
+    fn translate_windows_path_backslashes() {
+        let p = translate_windows_path(r"C:\Users\me\proj").unwrap();
+        assert_eq!(p, PathBuf::from("/mnt/c/Users/me/proj"));
+    }
+
+    #[test]
+    fn translate_windows_path_forward_slashes_and_root() {
+        assert_eq!(
+            translate_windows_path("D:/work").unwrap(),
+            PathBuf::from("/mnt/d/work")
+        );
+        assert_eq!(
+            translate_windows_path(r"E:\").unwrap(),
+            PathBuf::from("/mnt/e")
+        );
+    }
+
+    #[test]
+    fn translate_windows_path_ignores_unix_paths() {
+        assert!(translate_windows_path("/home/me/proj").is_none());
+        assert!(translate_windows_path("relative/dir").is_none());
+        assert!(translate_windows_path("C:relative").is_none());
+    }
+
+    #[test]
+    fn detects_wsl_windows_mount() {
+        assert!(is_wsl_windows_mount(Path::new("/mnt/c/Users/me")));
+        assert!(!is_wsl_windows_mount(Path::new("/mnt/data/me")));
+        assert!(!is_wsl_windows_mount(Path::new("/home/me")));
+    }
 }
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use console::style;
+use serde::Serialize;
+
+use crate::ops::json;
+
+/// Version and build metadata embedded by `build.rs`
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    pub version: &'static str,
+    pub git_sha: Option<&'static str>,
+    pub build_date: Option<String>,
+    pub rustc: Option<&'static str>,
+    pub target: Option<&'static str>,
+    pub features: Vec<&'static str>,
+}
+
+impl VersionInfo {
+    /// Metadata for the running binary
+    pub fn current() -> Self {
+        let non_empty = |s: &'static str| (!s.is_empty()).then_some(s);
+
+        let build_date = env!("TMUXIFY_BUILD_TIMESTAMP")
+            .parse::<i64>()
+            .ok()
+            .filter(|&secs| secs > 0)
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map(|dt| dt.to_rfc3339());
+
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: non_empty(env!("TMUXIFY_GIT_SHA")),
+            build_date,
+            rustc: non_empty(env!("TMUXIFY_RUSTC_VERSION")),
+            target: non_empty(env!("TMUXIFY_TARGET")),
+            features: env!("TMUXIFY_FEATURES")
+                .split(',')
+                .filter(|f| !f.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Print version and build metadata, as text or JSON
+pub fn run(as_json: bool) -> Result<()> {
+    let info = VersionInfo::current();
+
+    if as_json {
+        print!("{}", json::to_json_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("{} {}", style("tmuxify").bold().cyan(), info.version);
+    let unknown = || "unknown".to_string();
+    let rows = [
+        (
+            "commit",
+            info.git_sha.map(str::to_string).unwrap_or_else(unknown),
+        ),
+        ("built", info.build_date.clone().unwrap_or_else(unknown)),
+        (
+            "rustc",
+            info.rustc.map(str::to_string).unwrap_or_else(unknown),
+        ),
+        (
+            "target",
+            info.target.map(str::to_string).unwrap_or_else(unknown),
+        ),
+        (
+            "features",
+            if info.features.is_empty() {
+                "none".to_string()
+            } else {
+                info.features.join(", ")
+            },
+        ),
+    ];
+    for (label, value) in rows {
+        println!(
+            "  {} {}",
+            style(format!("{:<9}", label.to_string() + ":")).dim(),
+            value
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_includes_every_field() {
+        let json = json::to_json_pretty(&VersionInfo::current()).unwrap();
+        for key in [
+            "version",
+            "git_sha",
+            "build_date",
+            "rustc",
+            "target",
+            "features",
+        ] {
+            assert!(json.contains(&format!("\"{}\"", key)), "missing {}", key);
+        }
+        assert!(json.contains(env!("CARGO_PKG_VERSION")));
+    }
+}
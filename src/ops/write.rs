@@ -1,13 +1,24 @@
 use anyhow::{Context, Result};
+use console::style;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::model::{Config, TmuxpLocation};
+use crate::model::{Config, ConfigFormat, Driver, EnvrcOptions, Settings, TmuxpLocation};
+use crate::ops::history::{self, FileChange};
+use crate::ops::process::Runner;
+use crate::ops::{audit, diff, highlight, hook, interrupt, load, log, tmux, validate, zoxide};
 
 /// Options for writing files
+#[derive(Default)]
 pub struct WriteOptions {
     pub dry_run: bool,
     pub force: bool,
+    pub envrc: EnvrcOptions,
+    /// Don't append the write to the history log
+    pub skip_history: bool,
+    /// Format of the tmuxp config; `None` keeps an existing config's format
+    /// and writes YAML otherwise
+    pub format: Option<ConfigFormat>,
 }
 
 /// Result of a write operation
@@ -16,6 +27,8 @@ pub struct WriteResult {
     pub envrc_path: PathBuf,
     pub tmuxp_backed_up: bool,
     pub envrc_backed_up: bool,
+    /// Additional session configs written alongside the primary one
+    pub extra_paths: Vec<(PathBuf, bool)>,
 }
 
 impl WriteResult {
@@ -33,35 +46,372 @@ impl WriteResult {
         } else {
             println!("  {}", self.envrc_path.display());
         }
+
+        for (path, backed_up) in &self.extra_paths {
+            if *backed_up {
+                println!("  {} (backed up existing file)", path.display());
+            } else {
+                println!("  {}", path.display());
+            }
+        }
     }
 }
 
-/// Create a backup of a file if it exists
-fn backup_file(path: &Path, force: bool) -> Result<bool> {
+/// Create a backup of a file if it exists, returning the backup's path
+pub fn backup_file(path: &Path, force: bool) -> Result<Option<PathBuf>> {
     if !path.exists() {
-        return Ok(false);
+        return Ok(None);
     }
 
     if force {
         // Force mode: no backup, just overwrite
-        return Ok(false);
+        return Ok(None);
     }
 
-    // Create backup with timestamp, preserving original filename
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let backup_path = {
-        let file_name = path
+    // Create backup with timestamp, preserving original filename. Writes in
+    // the same second get a counter rather than replacing the earlier backup.
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let backup_name = |suffix: &str| -> Result<PathBuf> {
+        // Build the name as an OsString so non-UTF-8 file names survive
+        let mut file_name = path
             .file_name()
-            .and_then(|s| s.to_str())
-            .ok_or_else(|| anyhow::anyhow!("Invalid path for backup"))?;
+            .ok_or_else(|| anyhow::anyhow!("Invalid path for backup"))?
+            .to_os_string();
+        file_name.push(format!(".backup.{}{}", timestamp, suffix));
         let parent = path.parent().unwrap_or_else(|| Path::new("."));
-        parent.join(format!("{}.backup.{}", file_name, timestamp))
+        Ok(parent.join(file_name))
     };
+    let mut backup_path = backup_name("")?;
+    let mut counter = 1;
+    while fs::symlink_metadata(&backup_path).is_ok() {
+        backup_path = backup_name(&format!("_{}", counter))?;
+        counter += 1;
+    }
 
     fs::copy(path, &backup_path)
         .with_context(|| format!("Failed to create backup at {}", backup_path.display()))?;
 
-    Ok(true)
+    Ok(Some(backup_path))
+}
+
+/// A set of file writes (and removals) applied together: either every file
+/// is changed, or each one is put back the way it was
+#[derive(Default)]
+struct Transaction {
+    staged: Vec<(PathBuf, String)>,
+    removed: Vec<PathBuf>,
+}
+
+/// A file replaced during commit, kept so it can be restored
+struct Applied {
+    path: PathBuf,
+    /// Whether there was a file before, even one that couldn't be read
+    existed: bool,
+    previous: Option<Vec<u8>>,
+    backup: Option<PathBuf>,
+}
+
+impl Transaction {
+    fn stage(&mut self, path: PathBuf, content: String) {
+        self.staged.push((path, content));
+    }
+
+    /// Remove `path` once the staged files are written, e.g. a config that
+    /// moved to a new name
+    fn stage_removal(&mut self, path: PathBuf) {
+        self.removed.push(path);
+    }
+
+    /// Show each staged file: a diff against the file on disk when there is
+    /// one, otherwise the full content
+    fn print_dry_run(&self) {
+        for (path, content) in &self.staged {
+            if let Ok(on_disk) = fs::read_to_string(path) {
+                let label = path.display().to_string();
+                let changes = diff::unified(&on_disk, content, &label, &label);
+                if changes.is_empty() {
+                    println!("\n[DRY RUN] {} is unchanged", path.display());
+                } else {
+                    println!("\n[DRY RUN] Would update {}:", path.display());
+                    print!("{}", diff::colorize(&changes));
+                }
+                continue;
+            }
+            println!("\n[DRY RUN] Would write to: {}", path.display());
+            println!("---");
+            if audit::is_yaml(path) {
+                println!("{}", highlight::yaml(content));
+            } else {
+                println!("{}", content);
+            }
+            println!("---");
+        }
+        for path in &self.removed {
+            println!("\n[DRY RUN] Would remove: {}", path.display());
+        }
+    }
+
+    /// Write every staged file, rolling all of them back if any write fails
+    /// or the run is interrupted
+    fn commit(self, force: bool) -> Result<Vec<FileChange>> {
+        let _guard = interrupt::Guard::install();
+
+        // Write everything to temp files first, so a full disk or a permissions
+        // problem shows up before any real file changes
+        let mut temps = Vec::new();
+        for (path, content) in &self.staged {
+            let target = resolve_link(path);
+            let tmp = temp_path(&target);
+            temps.push(tmp.clone());
+            let written = tmp
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&tmp, content))
+                .and_then(|_| keep_permissions(&target, &tmp));
+            if let Err(e) = written {
+                remove_files(&temps);
+                return Err(e).with_context(|| {
+                    format!("Failed to write {}; no files were changed", path.display())
+                });
+            }
+        }
+
+        // Move each file into place (rename is atomic per file)
+        let mut applied = Vec::new();
+        let mut changes = Vec::new();
+        for ((path, content), tmp) in self.staged.iter().zip(&temps) {
+            match apply(path, content, tmp, force) {
+                Ok((entry, change)) => {
+                    applied.push(entry);
+                    changes.push(change);
+                }
+                Err(e) => {
+                    remove_files(&temps);
+                    let restored = rollback(applied);
+                    return Err(e.context(format!(
+                        "Write failed; restored {} file(s) to their previous state",
+                        restored
+                    )));
+                }
+            }
+
+            if interrupt::interrupted() {
+                remove_files(&temps);
+                let restored = rollback(applied);
+                anyhow::bail!(
+                    "Interrupted; restored {} file(s) to their previous state",
+                    restored
+                );
+            }
+        }
+
+        for path in &self.removed {
+            match remove(path, force) {
+                Ok(Some((entry, change))) => {
+                    applied.push(entry);
+                    changes.push(change);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let restored = rollback(applied);
+                    return Err(e.context(format!(
+                        "Write failed; restored {} file(s) to their previous state",
+                        restored
+                    )));
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+/// The file a write to `path` should replace: the target of a symlink (such
+/// as one made by `tmuxify sync --link`), so the link survives the write
+fn resolve_link(path: &Path) -> PathBuf {
+    let is_link = fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+    match is_link {
+        true => fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+        false => path.to_path_buf(),
+    }
+}
+
+/// Back up `path`, then rename the staged temp file over it (or over the
+/// file it links to)
+fn apply(path: &Path, content: &str, tmp: &Path, force: bool) -> Result<(Applied, FileChange)> {
+    let target = resolve_link(path);
+    let existed = fs::symlink_metadata(&target).is_ok();
+    let previous = fs::read(&target).ok();
+    let backup = backup_file(&target, force)?;
+
+    if let Err(e) = fs::rename(tmp, &target) {
+        if let Some(backup) = &backup {
+            let _ = fs::remove_file(backup);
+        }
+        return Err(e).with_context(|| format!("Failed to write {}", path.display()));
+    }
+
+    let old = match &previous {
+        Some(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+        None => existed.then(String::new),
+    };
+    let change = FileChange::new(path, old.as_deref(), content, backup.as_deref());
+    let applied = Applied {
+        path: target,
+        existed,
+        previous,
+        backup,
+    };
+    Ok((applied, change))
+}
+
+/// Back up and remove `path`, if it exists
+fn remove(path: &Path, force: bool) -> Result<Option<(Applied, FileChange)>> {
+    if fs::symlink_metadata(path).is_err() {
+        return Ok(None);
+    }
+    let previous = fs::read(path).ok();
+    let backup = backup_file(path, force)?;
+    if let Err(e) = fs::remove_file(path) {
+        if let Some(backup) = &backup {
+            let _ = fs::remove_file(backup);
+        }
+        return Err(e).with_context(|| format!("Failed to remove {}", path.display()));
+    }
+
+    let old = previous
+        .as_deref()
+        .map(String::from_utf8_lossy)
+        .unwrap_or_default();
+    let change = FileChange::new(path, Some(&old), "", backup.as_deref());
+    let applied = Applied {
+        path: path.to_path_buf(),
+        existed: true,
+        previous,
+        backup,
+    };
+    Ok(Some((applied, change)))
+}
+
+/// Put back previous contents (or remove new files) and drop this run's backups
+fn rollback(applied: Vec<Applied>) -> usize {
+    let mut restored = 0;
+    for entry in applied.into_iter().rev() {
+        let result = match (entry.previous, &entry.backup) {
+            (Some(content), _) => fs::write(&entry.path, content),
+            // It couldn't be read before the write, so the backup is the only copy
+            (None, Some(backup)) if entry.existed => fs::copy(backup, &entry.path).map(|_| ()),
+            (None, None) if entry.existed => Err(std::io::Error::other("no copy was kept")),
+            (None, _) => fs::remove_file(&entry.path),
+        };
+        match result {
+            Ok(()) => {
+                restored += 1;
+                if let Some(backup) = entry.backup {
+                    let _ = fs::remove_file(backup);
+                }
+            }
+            Err(e) => eprintln!(
+                "{} Could not restore {}: {}",
+                style("⚠").yellow().bold(),
+                entry.path.display(),
+                e
+            ),
+        }
+    }
+    restored
+}
+
+/// Give the temp file replacing `target` the same permissions, so a
+/// chmod'ed config keeps its mode
+fn keep_permissions(target: &Path, tmp: &Path) -> std::io::Result<()> {
+    match fs::metadata(target) {
+        Ok(metadata) => fs::set_permissions(tmp, metadata.permissions()),
+        Err(_) => Ok(()),
+    }
+}
+
+fn remove_files(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Temporary sibling used to replace `path` atomically, hidden with a
+/// leading dot (`.envrc` becomes `.envrc.tmuxify-tmp`)
+fn temp_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default();
+    let mut name = std::ffi::OsString::new();
+    if !file_name.as_encoded_bytes().starts_with(b".") {
+        name.push(".");
+    }
+    name.push(file_name);
+    name.push(".tmuxify-tmp");
+    path.with_file_name(name)
+}
+
+/// Commit a transaction (or print it in dry-run mode) and log it to the
+/// history of `project`
+fn apply_transaction(
+    tx: Transaction,
+    project: Option<&Path>,
+    options: &WriteOptions,
+) -> Result<Vec<FileChange>> {
+    if options.dry_run {
+        tx.print_dry_run();
+        return Ok(Vec::new());
+    }
+
+    let changes = tx.commit(options.force)?;
+    for change in &changes {
+        log::debug(format!(
+            "wrote {} (+{} -{}, backup: {})",
+            change.path,
+            change.added,
+            change.removed,
+            change.backup.as_deref().unwrap_or("none")
+        ));
+    }
+    if let Some(project) = project {
+        record_history(project, changes.clone(), options);
+    }
+    Ok(changes)
+}
+
+/// Whether the commit backed up an existing file at `path`
+fn backed_up(changes: &[FileChange], path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    changes.iter().any(|c| c.path == path && c.backup.is_some())
+}
+
+/// Append a write to the history log and tell zoxide about the project;
+/// failures only warn
+fn record_history(project: &Path, files: Vec<FileChange>, options: &WriteOptions) {
+    if options.skip_history {
+        return;
+    }
+    if let Err(e) = history::record(project, files) {
+        eprintln!(
+            "{} Could not record history: {}",
+            style("⚠").yellow().bold(),
+            e
+        );
+    }
+    if !project.as_os_str().is_empty() {
+        zoxide::add(project);
+    }
+}
+
+/// The project a config file written on its own belongs to: the directory
+/// of a project-local `.tmuxp.*`, else the config's `start_directory`
+fn project_of(config: &Config, path: &Path) -> PathBuf {
+    let local = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(".tmuxp."));
+    match path.parent() {
+        Some(dir) if local => dir.to_path_buf(),
+        _ => config.start_directory.clone(),
+    }
 }
 
 /// Write configuration files to disk
@@ -71,97 +421,333 @@ pub fn write_config(
     project_dir: &Path,
     options: &WriteOptions,
 ) -> Result<WriteResult> {
+    let mut tx = Transaction::default();
+    let (tmuxp_path, envrc_path) = stage_primary(config, location, project_dir, options, &mut tx)?;
+    let changes = apply_transaction(tx, Some(project_dir), options)?;
+
+    Ok(WriteResult {
+        tmuxp_backed_up: backed_up(&changes, &tmuxp_path),
+        envrc_backed_up: backed_up(&changes, &envrc_path),
+        tmuxp_path,
+        envrc_path,
+        extra_paths: Vec::new(),
+    })
+}
+
+/// Write `config` to its (new) path like [`write_config`], removing the file
+/// it was loaded from in the same transaction, as when a session is renamed
+pub fn move_config(
+    config: &Config,
+    location: TmuxpLocation,
+    project_dir: &Path,
+    old_path: &Path,
+    options: &WriteOptions,
+) -> Result<WriteResult> {
+    let mut tx = Transaction::default();
+    let (tmuxp_path, envrc_path) = stage_primary(config, location, project_dir, options, &mut tx)?;
+    if old_path != tmuxp_path {
+        tx.stage_removal(old_path.to_path_buf());
+    }
+    let changes = apply_transaction(tx, Some(project_dir), options)?;
+
+    Ok(WriteResult {
+        tmuxp_backed_up: backed_up(&changes, &tmuxp_path),
+        envrc_backed_up: backed_up(&changes, &envrc_path),
+        tmuxp_path,
+        envrc_path,
+        extra_paths: Vec::new(),
+    })
+}
+
+/// Stage the tmuxp config and `.envrc`, returning their paths
+fn stage_primary(
+    config: &Config,
+    location: TmuxpLocation,
+    project_dir: &Path,
+    options: &WriteOptions,
+    tx: &mut Transaction,
+) -> Result<(PathBuf, PathBuf)> {
     // Get file paths (centralized through Config)
-    let tmuxp_path = config.get_file_path(location, Some(project_dir))?;
+    let yaml_path = config.get_file_path(location, Some(project_dir))?;
+    let format = config_format(options, &yaml_path, project_dir)?;
+    let tmuxp_path = format.apply(&yaml_path);
+    config.validate_window_indexes(tmux::base_index)?;
+    // A copy in the other format would be stale; replace it along with the write
+    for other in [ConfigFormat::Yaml, ConfigFormat::Json].map(|f| f.apply(&tmuxp_path)) {
+        if other != tmuxp_path && other.is_file() && format != ConfigFormat::Toml {
+            tx.stage_removal(other);
+        }
+    }
 
     let envrc_path = project_dir.join(".envrc");
+    let envrc = EnvrcOptions {
+        format,
+        ..options.envrc.clone()
+    };
 
-    // Generate content
-    let tmuxp_content = config.to_yaml()?;
-    let envrc_content = config.generate_envrc(location);
+    tx.stage(tmuxp_path.clone(), config.render(format)?);
+    tx.stage(envrc_path.clone(), config.generate_envrc(location, &envrc));
 
-    if options.dry_run {
-        // Dry run: just print what would be written
-        println!("\n[DRY RUN] Would write to: {}", tmuxp_path.display());
-        println!("---");
-        println!("{}", tmuxp_content);
-        println!("---");
+    Ok((tmuxp_path, envrc_path))
+}
 
-        println!("\n[DRY RUN] Would write to: {}", envrc_path.display());
-        println!("---");
-        println!("{}", envrc_content);
-        println!("---");
+/// The format to write the config whose YAML path is `yaml_path` in: the
+/// one asked for, or that of the existing copy (see [`load::pick_config`])
+fn config_format(
+    options: &WriteOptions,
+    yaml_path: &Path,
+    project_dir: &Path,
+) -> Result<ConfigFormat> {
+    let format = options.format.unwrap_or_else(|| {
+        let copies = [yaml_path.to_path_buf(), ConfigFormat::Json.apply(yaml_path)];
+        load::pick_config(&copies, project_dir)
+            .map_or(ConfigFormat::Yaml, |path| ConfigFormat::from_path(&path))
+    });
+    require_loadable(format, options)?;
+    Ok(format)
+}
 
-        return Ok(WriteResult {
-            tmuxp_path,
-            envrc_path,
-            tmuxp_backed_up: false,
-            envrc_backed_up: false,
-        });
+/// Refuse to write a format tmuxp can't load; `--dry-run` may still print it
+fn require_loadable(format: ConfigFormat, options: &WriteOptions) -> Result<()> {
+    if format == ConfigFormat::Toml && !options.dry_run {
+        anyhow::bail!(
+            "tmuxp can't load TOML configs, so tmuxify only prints them; \
+             preview one with --dry-run or run `tmuxify show --toml`"
+        );
     }
+    Ok(())
+}
+
+/// The files `write_config` would write and their contents, without
+/// touching disk
+pub fn render_config(
+    config: &Config,
+    location: TmuxpLocation,
+    project_dir: &Path,
+    options: &WriteOptions,
+) -> Result<Vec<(PathBuf, String)>> {
+    let mut tx = Transaction::default();
+    stage_primary(config, location, project_dir, options, &mut tx)?;
+    Ok(tx.staged)
+}
 
-    // Ensure parent directories exist
-    if let Some(parent) = tmuxp_path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+/// `.envrc` options for a project: its tmuxp command and the user's preferred style
+pub fn envrc_options(project_dir: &Path, settings: &Settings) -> EnvrcOptions {
+    let tmuxp_command = validate::resolve_tmuxp(Some(project_dir))
+        .map(|t| t.command)
+        .unwrap_or_else(|| "tmuxp".to_string());
+    // The native driver's .envrc calls `tmuxify up` without the direnv library
+    let native = settings.driver == Driver::Native;
+    if settings.direnv_layout && !native && !hook::direnv_lib_installed() {
+        eprintln!(
+            "{} direnv_layout is set but the layout_tmux library is missing or outdated; \
+             run {}",
+            style("⚠").yellow().bold(),
+            style("tmuxify hook direnv").yellow()
+        );
+    }
+    EnvrcOptions {
+        tmuxp_command,
+        use_layout: settings.direnv_layout,
+        load_args: settings.tmuxp_load_args.clone(),
+        detach: settings.envrc_detach,
+        native,
+        ..Default::default()
     }
+}
 
-    // Backup existing files if needed
-    let tmuxp_backed_up = backup_file(&tmuxp_path, options.force)?;
-    let envrc_backed_up = backup_file(&envrc_path, options.force)?;
+/// Write a project's primary session plus any additional sessions.
+///
+/// `.envrc` auto-loads the primary session and lists the others in comments.
+pub fn write_sessions(
+    primary: &Config,
+    extras: &[Config],
+    location: TmuxpLocation,
+    project_dir: &Path,
+    options: &WriteOptions,
+) -> Result<WriteResult> {
+    let format = config_format(
+        options,
+        &primary.get_file_path(location, Some(project_dir))?,
+        project_dir,
+    )?;
+    let options = WriteOptions {
+        dry_run: options.dry_run,
+        force: options.force,
+        envrc: EnvrcOptions {
+            extra_sessions: extras
+                .iter()
+                .map(|c| c.secondary_load_path(location, format))
+                .collect(),
+            ..options.envrc.clone()
+        },
+        skip_history: options.skip_history,
+        format: Some(format),
+    };
 
-    // Write tmuxp config
-    fs::write(&tmuxp_path, tmuxp_content)
-        .with_context(|| format!("Failed to write {}", tmuxp_path.display()))?;
+    let mut tx = Transaction::default();
+    let (tmuxp_path, envrc_path) =
+        stage_primary(primary, location, project_dir, &options, &mut tx)?;
 
-    // Write .envrc
-    fs::write(&envrc_path, envrc_content)
-        .with_context(|| format!("Failed to write {}", envrc_path.display()))?;
+    let mut extra_paths = Vec::new();
+    for extra in extras {
+        let path = format.apply(&extra.secondary_file_path(location, Some(project_dir))?);
+        extra.validate_window_indexes(tmux::base_index)?;
+        tx.stage(path.clone(), extra.render(format)?);
+        extra_paths.push(path);
+    }
+
+    // One transaction and one history entry for the whole project
+    let changes = apply_transaction(tx, Some(project_dir), &options)?;
 
     Ok(WriteResult {
+        tmuxp_backed_up: backed_up(&changes, &tmuxp_path),
+        envrc_backed_up: backed_up(&changes, &envrc_path),
         tmuxp_path,
         envrc_path,
-        tmuxp_backed_up,
-        envrc_backed_up,
+        extra_paths: extra_paths
+            .into_iter()
+            .map(|path| {
+                let backed_up = backed_up(&changes, &path);
+                (path, backed_up)
+            })
+            .collect(),
     })
 }
 
-/// Run direnv allow in the project directory
-pub fn run_direnv_allow(project_dir: &Path) -> Result<()> {
+/// Rewrite a single tmuxp config file in place (backing it up unless forced)
+///
+/// Returns whether an existing file was backed up.
+pub fn save_config(config: &Config, path: &Path, options: &WriteOptions) -> Result<bool> {
+    config.validate_window_indexes(tmux::base_index)?;
+
+    let format = ConfigFormat::from_path(path);
+    require_loadable(format, options)?;
+    let mut tx = Transaction::default();
+    tx.stage(path.to_path_buf(), config.render(format)?);
+    let changes = apply_transaction(tx, Some(&project_of(config, path)), options)?;
+
+    Ok(backed_up(&changes, path))
+}
+
+/// Write a single file, backing up an existing one unless forced. Files
+/// generated for a project (such as exports) pass its directory for history.
+///
+/// Returns whether an existing file was backed up.
+pub fn write_file(
+    project: Option<&Path>,
+    path: &Path,
+    content: String,
+    options: &WriteOptions,
+) -> Result<bool> {
+    let mut tx = Transaction::default();
+    tx.stage(path.to_path_buf(), content);
+    let changes = apply_transaction(tx, project, options)?;
+
+    Ok(backed_up(&changes, path))
+}
+
+/// How long `direnv allow` may run; hooks that build things can be slow
+const DIRENV_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// Run direnv allow in the project directory, showing its stderr as it runs.
+/// Ctrl-C stops direnv and reports the cancellation.
+pub fn run_direnv_allow(runner: &dyn Runner, project_dir: &Path) -> Result<()> {
     use indicatif::{ProgressBar, ProgressStyle};
     use std::process::Command;
 
+    let mut allow = Command::new("direnv");
+    allow.arg("allow").current_dir(project_dir);
+    if runner.is_dry_run() {
+        return runner.run(&mut allow, DIRENV_TIMEOUT).map(|_| ());
+    }
+
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
+            .template("{spinner:.green} {msg} {elapsed:.dim}")
             .unwrap(),
     );
-    pb.set_message("Running direnv allow...");
+    pb.set_message("Running direnv allow (Ctrl-C to cancel)...");
     pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
-    let output = Command::new("direnv")
-        .arg("allow")
-        .current_dir(project_dir)
-        .output()
-        .context("Failed to execute direnv allow")?;
-
-    pb.finish_with_message("direnv allow completed");
+    let result = runner.run_streaming(&mut allow, DIRENV_TIMEOUT, &mut |line| {
+        pb.println(format!("  {}", style(line).dim()));
+    });
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("direnv allow failed: {}", stderr);
+    match &result {
+        Ok(_) => {
+            pb.set_message("direnv allow completed");
+            pb.finish();
+        }
+        Err(_) => pb.finish_and_clear(),
     }
-
-    Ok(())
+    result
+        .map(|_| ())
+        .context("direnv allow did not complete; run it manually in the project directory")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{Config, TmuxpLocation, Window};
+    use crate::model::{Config, Pane, TmuxpLocation, Window};
     use tempfile::tempdir;
 
+    #[test]
+    fn temp_names_have_one_leading_dot() {
+        assert_eq!(
+            temp_path(Path::new("/p/.envrc")),
+            Path::new("/p/.envrc.tmuxify-tmp")
+        );
+        assert_eq!(
+            temp_path(Path::new("/p/app.yaml")),
+            Path::new("/p/.app.yaml.tmuxify-tmp")
+        );
+    }
+
+    #[test]
+    fn json_format_writes_json_and_keeps_it_on_rewrite() {
+        let dir = tempdir().unwrap();
+        let cfg = Config::new(
+            "app".into(),
+            dir.path().to_path_buf(),
+            vec![Window::simple()],
+        );
+        let opts = WriteOptions {
+            skip_history: true,
+            format: Some(ConfigFormat::Json),
+            ..Default::default()
+        };
+        let res = write_config(&cfg, TmuxpLocation::Project, dir.path(), &opts).unwrap();
+        assert_eq!(res.tmuxp_path, dir.path().join(".tmuxp.json"));
+        assert!(
+            fs::read_to_string(&res.tmuxp_path)
+                .unwrap()
+                .starts_with('{')
+        );
+        let envrc = fs::read_to_string(dir.path().join(".envrc")).unwrap();
+        assert!(envrc.contains("./.tmuxp.json"));
+
+        // Without --format, a rewrite keeps the JSON config
+        let opts = WriteOptions {
+            skip_history: true,
+            force: true,
+            ..Default::default()
+        };
+        let res = write_config(&cfg, TmuxpLocation::Project, dir.path(), &opts).unwrap();
+        assert_eq!(res.tmuxp_path, dir.path().join(".tmuxp.json"));
+        assert!(!dir.path().join(".tmuxp.yaml").exists());
+
+        let toml = WriteOptions {
+            format: Some(ConfigFormat::Toml),
+            ..Default::default()
+        };
+        let err = write_config(&cfg, TmuxpLocation::Project, dir.path(), &toml)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("can't load TOML"));
+    }
+
     #[test]
     fn write_config_creates_backups_when_files_exist() {
         let dir = tempdir().unwrap();
@@ -173,8 +759,17 @@ mod tests {
         fs::write(&tmuxp_path, "existing tmuxp").unwrap();
         fs::write(&envrc_path, "existing envrc").unwrap();
 
-        let cfg = Config::new("sess".into(), project_dir.display().to_string(), vec![Window::simple()]);
-        let opts = WriteOptions { dry_run: false, force: false };
+        let cfg = Config::new(
+            "sess".into(),
+            project_dir.to_path_buf(),
+            vec![Window::simple()],
+        );
+        let opts = WriteOptions {
+            dry_run: false,
+            force: false,
+            skip_history: true,
+            ..Default::default()
+        };
 
         let res = write_config(&cfg, TmuxpLocation::Project, project_dir, &opts).unwrap();
 
@@ -188,11 +783,191 @@ mod tests {
             let entry = entry.unwrap();
             let name = entry.file_name();
             let s = name.to_string_lossy();
-            if s.starts_with(".tmuxp.yaml.backup.") { tmuxp_backup_found = true; }
-            if s.starts_with(".envrc.backup.") { envrc_backup_found = true; }
+            if s.starts_with(".tmuxp.yaml.backup.") {
+                tmuxp_backup_found = true;
+            }
+            if s.starts_with(".envrc.backup.") {
+                envrc_backup_found = true;
+            }
         }
 
         assert!(tmuxp_backup_found, "expected tmuxp backup file");
         assert!(envrc_backup_found, "expected envrc backup file");
     }
+
+    #[test]
+    fn direnv_allow_goes_through_the_runner() {
+        let dir = tempdir().unwrap();
+        let runner = crate::ops::process::RecordingRunner::default();
+        run_direnv_allow(&runner, dir.path()).unwrap();
+        assert_eq!(*runner.commands.borrow(), vec!["direnv allow".to_string()]);
+    }
+
+    #[test]
+    fn failed_commit_restores_every_file() {
+        let dir = tempdir().unwrap();
+        let envrc = dir.path().join(".envrc");
+        let blocked = dir.path().join("blocked");
+        fs::write(&envrc, "old").unwrap();
+        // A directory can't be replaced by a file, so the second write fails
+        fs::create_dir(&blocked).unwrap();
+
+        let mut tx = Transaction::default();
+        tx.stage(envrc.clone(), "new".into());
+        tx.stage(blocked.clone(), "content".into());
+        assert!(tx.commit(false).is_err());
+
+        assert_eq!(fs::read_to_string(&envrc).unwrap(), "old");
+        assert!(blocked.is_dir());
+        // No backups or temp files left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn failed_commit_restores_non_utf8_files() {
+        let dir = tempdir().unwrap();
+        let config = dir.path().join(".tmuxp.yaml");
+        let blocked = dir.path().join("blocked");
+        fs::write(&config, b"session_name: caf\xe9\n").unwrap();
+        fs::create_dir(&blocked).unwrap();
+
+        let mut tx = Transaction::default();
+        tx.stage(config.clone(), "session_name: app\n".into());
+        tx.stage(blocked.clone(), "content".into());
+        assert!(tx.commit(false).is_err());
+
+        assert_eq!(fs::read(&config).unwrap(), b"session_name: caf\xe9\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn switching_formats_removes_the_other_copy() {
+        let dir = tempdir().unwrap();
+        let yaml = dir.path().join(".tmuxp.yaml");
+        fs::write(&yaml, "session_name: app\nwindows: []\n").unwrap();
+        let config = Config::new(
+            "app".into(),
+            dir.path().to_path_buf(),
+            vec![Window::new(None, None, vec![Pane::empty()])],
+        );
+        let options = WriteOptions {
+            skip_history: true,
+            format: Some(ConfigFormat::Json),
+            ..Default::default()
+        };
+        let result = write_config(&config, TmuxpLocation::Project, dir.path(), &options).unwrap();
+        assert_eq!(result.tmuxp_path, dir.path().join(".tmuxp.json"));
+        assert!(!yaml.exists());
+        assert_eq!(load::local_config(dir.path()), Some(result.tmuxp_path));
+    }
+
+    #[test]
+    fn history_goes_to_the_project_not_the_start_directory() {
+        let config = Config::new("app".into(), "/src/app/web".into(), vec![]);
+        assert_eq!(
+            project_of(&config, Path::new("/src/app/.tmuxp.yaml")),
+            Path::new("/src/app")
+        );
+        assert_eq!(
+            project_of(&config, Path::new("/home/me/.tmuxp/app.yaml")),
+            Path::new("/src/app/web")
+        );
+    }
+
+    #[test]
+    fn removals_commit_and_roll_back_with_the_writes() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old.yaml");
+        let new = dir.path().join("new.yaml");
+        fs::write(&old, "session_name: old\n").unwrap();
+
+        let mut tx = Transaction::default();
+        tx.stage(new.clone(), "session_name: new\n".into());
+        tx.stage_removal(old.clone());
+        tx.stage_removal(dir.path().join("blocked"));
+        fs::create_dir(dir.path().join("blocked")).unwrap();
+        assert!(tx.commit(false).is_err());
+        assert_eq!(fs::read_to_string(&old).unwrap(), "session_name: old\n");
+        assert!(!new.exists());
+
+        let mut tx = Transaction::default();
+        tx.stage(new.clone(), "session_name: new\n".into());
+        tx.stage_removal(old.clone());
+        let changes = tx.commit(false).unwrap();
+        assert!(!old.exists());
+        assert_eq!(changes[1].removed, 1);
+        assert!(Path::new(changes[1].backup.as_deref().unwrap()).is_file());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commit_keeps_the_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".envrc");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let mut tx = Transaction::default();
+        tx.stage(path.clone(), "new".into());
+        tx.commit(true).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn commit_creates_new_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("app.yaml");
+
+        let mut tx = Transaction::default();
+        tx.stage(path.clone(), "session_name: app\n".into());
+        let changes = tx.commit(false).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "session_name: app\n");
+        assert!(changes[0].created);
+        assert!(!backed_up(&changes, &path));
+    }
+
+    #[test]
+    fn backups_in_the_same_second_are_all_kept() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".tmuxp.yaml");
+        let mut backups = Vec::new();
+        for content in ["first", "second", "third"] {
+            fs::write(&path, content).unwrap();
+            backups.push(backup_file(&path, false).unwrap().unwrap());
+        }
+        backups.dedup();
+        assert_eq!(backups.len(), 3, "{:?}", backups);
+        let kept: Vec<String> = backups
+            .iter()
+            .map(|b| fs::read_to_string(b).unwrap())
+            .collect();
+        assert_eq!(kept, ["first", "second", "third"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backup_keeps_non_utf8_file_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(OsStr::from_bytes(b"caf\xe9.yaml"));
+        fs::write(&path, "old").unwrap();
+
+        let backup = backup_file(&path, false).unwrap().unwrap();
+        assert!(
+            backup
+                .file_name()
+                .unwrap()
+                .as_bytes()
+                .starts_with(b"caf\xe9.yaml.backup.")
+        );
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "old");
+    }
 }
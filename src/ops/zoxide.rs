@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::ops::{log, process, validate};
+
+/// Whether zoxide is installed
+pub fn is_available() -> bool {
+    validate::has_binary("zoxide")
+}
+
+/// Add a project directory to zoxide's database; failures are only logged
+pub fn add(dir: &Path) {
+    if !is_available() {
+        return;
+    }
+    let result = process::run(
+        Command::new("zoxide").arg("add").arg(dir),
+        process::QUICK_TIMEOUT,
+    );
+    if let Err(e) = result {
+        log::debug(format!("zoxide add failed: {:#}", e));
+    }
+}
+
+/// Best match for `keywords` in zoxide's database
+pub fn query(keywords: &str) -> Option<PathBuf> {
+    if !is_available() {
+        return None;
+    }
+    let out = process::run(
+        Command::new("zoxide")
+            .arg("query")
+            .arg("--")
+            .args(keywords.split_whitespace()),
+        process::QUICK_TIMEOUT,
+    )
+    .ok()?;
+    let line = out.lines().next()?.trim();
+    (!line.is_empty()).then(|| PathBuf::from(line))
+}